@@ -37,10 +37,10 @@ fn main() {
         size,
         EpiStatComp {
             epi: EpiStat::S,
-            tick_changed: std::i32::MIN,
+            tick_changed: i32::MIN,
         },
     );
-    let mut epi = grid.get_mut(size / 2, size / 2);
+    let epi = grid.get_mut(size / 2, size / 2);
     epi.epi = EpiStat::I;
     epi.tick_changed = 0;
 
@@ -59,8 +59,9 @@ fn main() {
         .with_dimensions(600, 400)
         .with_position(560, 50)
         .with_data_limit(500)
-        .with_y_label("# Individuals x 1000")
-        .with_y_scale(0.001)
+        .with_y_label("Individuals")
+        .with_y_unit("ind")
+        .with_si_prefixes()
         .with_ylim(Some(0.0), None)
         .with_fps_skip(30.0)
         .add_series(Series::line("S", &BLUE))
@@ -125,11 +126,9 @@ fn infection_system(
                                 }
                             }
                         }
-                    } else if epi.epi == EpiStat::R {
-                        if tick > epi.tick_changed + ticks_immune {
-                            epi.epi = EpiStat::S;
-                            epi.tick_changed = std::i32::MIN;
-                        }
+                    } else if epi.epi == EpiStat::R && tick > epi.tick_changed + ticks_immune {
+                        epi.epi = EpiStat::S;
+                        epi.tick_changed = i32::MIN;
                     }
                 }
             }
@@ -195,8 +194,6 @@ fn draw_system(mut win: BufferWindow, step: u32) -> Box<dyn Runnable> {
                     let grid: &mut Grid<EpiStatComp> = grid;
                     for (i, epi) in grid.iter().enumerate() {
                         let (x, y) = grid.coord(i);
-                        let x = x as i32;
-                        let y = y as i32;
 
                         let col = match epi.epi {
                             EpiStat::S => &BLUE,
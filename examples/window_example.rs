@@ -44,23 +44,21 @@ fn main() {
 
 fn entity_creator_system(num_entities: i32) -> Box<dyn Schedulable> {
     let mut initialized = false;
-    let sys =
-        SystemBuilder::<()>::new("Creator").build(move |commands, _world, _resource, _queries| {
-            let mut rng: ThreadRng = rand::thread_rng();
-            if !initialized {
-                let entities: Vec<_> = (0..num_entities)
-                    .map(|_| {
-                        (Pos {
-                            x: rng.gen_range(0, SIZE) as i32,
-                            y: rng.gen_range(0, SIZE) as i32,
-                        },)
-                    })
-                    .collect();
-                commands.insert((), entities);
-                initialized = true;
-            }
-        });
-    sys
+    SystemBuilder::<()>::new("Creator").build(move |commands, _world, _resource, _queries| {
+        let mut rng: ThreadRng = rand::thread_rng();
+        if !initialized {
+            let entities: Vec<_> = (0..num_entities)
+                .map(|_| {
+                    (Pos {
+                        x: rng.gen_range(0, SIZE) as i32,
+                        y: rng.gen_range(0, SIZE) as i32,
+                    },)
+                })
+                .collect();
+            commands.insert((), entities);
+            initialized = true;
+        }
+    })
 }
 
 fn entity_mover_system() -> Box<dyn Schedulable> {
@@ -71,7 +69,7 @@ fn entity_mover_system() -> Box<dyn Schedulable> {
             for (_entity, mut pos) in queries.iter_entities(&mut *world) {
                 let px = pos.x + if rng.gen_bool(0.5) { 1 } else { -1 };
                 let py = pos.y + if rng.gen_bool(0.5) { 1 } else { -1 };
-                if px >= 0 && px < SIZE && py >= 0 && py < SIZE {
+                if (0..SIZE).contains(&px) && (0..SIZE).contains(&py) {
                     pos.x = px;
                     pos.y = py;
                 }
@@ -1,6 +1,11 @@
-use crate::color::style::{RGBColor, SimpleColor};
+use crate::color::css::CSS_COLORS;
+use crate::color::style::{HSLColor, RGBColor, SimpleColor};
 #[doc(no_inline)]
 pub use plotters::style;
+use std::error::Error;
+use std::fmt;
+
+mod css;
 
 pub trait ColorMap {
     fn get_color_norm(&self, value: f64) -> RGBColor;
@@ -9,17 +14,70 @@ pub trait ColorMap {
         self.get_color_norm((value - min) / range)
     }
 
-    fn lerp(lower: u8, upper: u8, frac: f64) -> u8 {
+    /// Returns the color map in reversed direction.
+    ///
+    /// # Example
+    /// ```
+    /// use easy_graph::color::{ColorMap, LinearColorMap};
+    /// use easy_graph::color::style::{Color, BLUE, RED};
+    ///
+    /// let map = LinearColorMap::new(&[&BLUE, &RED]).reversed();
+    /// assert_eq!(map.get_color_norm(0.0).rgb(), RED.rgb());
+    /// ```
+    fn reversed(self) -> Reversed<Self>
+    where
+        Self: Sized,
+    {
+        Reversed { map: self }
+    }
+    /// Returns the color map with a gamma adjustment, mapping normalized values `v` to `v^gamma`.
+    ///
+    /// Values of `gamma` below 1 stretch the lower end of the map, values above 1 the upper end.
+    /// Normalized values are clamped to the range 0 - 1.
+    fn with_gamma(self, gamma: f64) -> Gamma<Self>
+    where
+        Self: Sized,
+    {
+        Gamma { map: self, gamma }
+    }
+    /// Returns the color map as seen with a color vision deficiency, to check whether it remains readable.
+    ///
+    /// # Example
+    /// ```
+    /// use easy_graph::color::{ColorMap, ColorVisionDeficiency, LinearColorMap};
+    /// use easy_graph::color::style::{Color, BLUE, YELLOW};
+    ///
+    /// let map = LinearColorMap::new(&[&BLUE, &YELLOW]).simulate_cvd(ColorVisionDeficiency::Achromatopsia);
+    /// let (r, g, b) = map.get_color_norm(0.5).rgb();
+    /// assert!(r == g && g == b);
+    /// ```
+    fn simulate_cvd(self, cvd: ColorVisionDeficiency) -> SimulatedCvd<Self>
+    where
+        Self: Sized,
+    {
+        SimulatedCvd { map: self, cvd }
+    }
+
+    fn lerp(lower: u8, upper: u8, frac: f64) -> u8
+    where
+        Self: Sized,
+    {
         (lower as f64 + frac * (upper as i16 - lower as i16) as f64).round() as u8
     }
-    fn lerp_rgb(lower: (u8, u8, u8), upper: (u8, u8, u8), frac: f64) -> (u8, u8, u8) {
+    fn lerp_rgb(lower: (u8, u8, u8), upper: (u8, u8, u8), frac: f64) -> (u8, u8, u8)
+    where
+        Self: Sized,
+    {
         (
             Self::lerp(lower.0, upper.0, frac),
             Self::lerp(lower.1, upper.1, frac),
             Self::lerp(lower.2, upper.2, frac),
         )
     }
-    fn lerp_colors(lower: (u8, u8, u8), upper: (u8, u8, u8), frac: f64) -> RGBColor {
+    fn lerp_colors(lower: (u8, u8, u8), upper: (u8, u8, u8), frac: f64) -> RGBColor
+    where
+        Self: Sized,
+    {
         RGBColor(
             Self::lerp(lower.0, upper.0, frac),
             Self::lerp(lower.1, upper.1, frac),
@@ -28,6 +86,65 @@ pub trait ColorMap {
     }
 }
 
+/// A color map in reversed direction. See [`ColorMap::reversed()`](trait.ColorMap.html#method.reversed).
+pub struct Reversed<M: ColorMap> {
+    map: M,
+}
+impl<M: ColorMap> Reversed<M> {
+    /// The original color map.
+    pub fn inner(&self) -> &M {
+        &self.map
+    }
+}
+impl<M: ColorMap> ColorMap for Reversed<M> {
+    fn get_color_norm(&self, value: f64) -> RGBColor {
+        self.map.get_color_norm(1.0 - value)
+    }
+}
+
+/// A color map as seen with a color vision deficiency. See [`ColorMap::simulate_cvd()`](trait.ColorMap.html#method.simulate_cvd).
+pub struct SimulatedCvd<M: ColorMap> {
+    map: M,
+    cvd: ColorVisionDeficiency,
+}
+impl<M: ColorMap> SimulatedCvd<M> {
+    /// The original color map.
+    pub fn inner(&self) -> &M {
+        &self.map
+    }
+    /// The simulated color vision deficiency.
+    pub fn deficiency(&self) -> ColorVisionDeficiency {
+        self.cvd
+    }
+}
+impl<M: ColorMap> ColorMap for SimulatedCvd<M> {
+    fn get_color_norm(&self, value: f64) -> RGBColor {
+        simulate_cvd(&self.map.get_color_norm(value), self.cvd)
+    }
+}
+
+/// A color map with a gamma adjustment. See [`ColorMap::with_gamma()`](trait.ColorMap.html#method.with_gamma).
+pub struct Gamma<M: ColorMap> {
+    map: M,
+    gamma: f64,
+}
+impl<M: ColorMap> Gamma<M> {
+    /// The original color map.
+    pub fn inner(&self) -> &M {
+        &self.map
+    }
+    /// The gamma exponent.
+    pub fn gamma(&self) -> f64 {
+        self.gamma
+    }
+}
+impl<M: ColorMap> ColorMap for Gamma<M> {
+    fn get_color_norm(&self, value: f64) -> RGBColor {
+        self.map
+            .get_color_norm(value.clamp(0.0, 1.0).powf(self.gamma))
+    }
+}
+
 pub struct LinearColorMap {
     colors: Vec<(u8, u8, u8)>,
 }
@@ -55,11 +172,355 @@ impl ColorMap for LinearColorMap {
     }
 }
 
+/// A color map with colors at explicit, possibly non-uniform positions (breakpoints).
+///
+/// Colors are linearly interpolated between breakpoints.
+/// Normalized values (0 - 1) given to [`get_color_norm()`](trait.ColorMap.html#tymethod.get_color_norm)
+/// are mapped to the range from the first to the last breakpoint.
+pub struct BreakpointColorMap {
+    breakpoints: Vec<(f64, (u8, u8, u8))>,
+}
+impl BreakpointColorMap {
+    /// Creates a color map from (position, color) pairs.
+    ///
+    /// # Panics
+    /// Panics if no breakpoints are given, or if positions are not sorted in ascending order.
+    ///
+    /// # Example
+    /// ```
+    /// use easy_graph::color::{BreakpointColorMap, ColorMap};
+    /// use easy_graph::color::style::{BLUE, RED, WHITE};
+    ///
+    /// let map = BreakpointColorMap::new(&[(0.0, &BLUE), (0.3, &WHITE), (1.0, &RED)]);
+    /// let color = map.get_color_norm(0.3);
+    /// ```
+    pub fn new(breakpoints: &[(f64, &RGBColor)]) -> Self {
+        if breakpoints.is_empty() {
+            panic!("At least one breakpoint is required!");
+        }
+        if breakpoints.windows(2).any(|w| w[0].0 > w[1].0) {
+            panic!("Breakpoints must be sorted by position!");
+        }
+        BreakpointColorMap {
+            breakpoints: breakpoints.iter().map(|(v, c)| (*v, c.rgb())).collect(),
+        }
+    }
+    /// Returns the color at the given position, in the units of the breakpoints.
+    /// Values outside the breakpoints' range get the first or last color, respectively.
+    pub fn get_color_at(&self, value: f64) -> RGBColor {
+        let upper = self
+            .breakpoints
+            .iter()
+            .position(|(pos, _)| *pos > value)
+            .unwrap_or(self.breakpoints.len());
+        if upper == 0 || upper == self.breakpoints.len() {
+            let (r, g, b) = self.breakpoints[upper.max(1) - 1].1;
+            return RGBColor(r, g, b);
+        }
+        let (pos1, col1) = self.breakpoints[upper - 1];
+        let (pos2, col2) = self.breakpoints[upper];
+        Self::lerp_colors(col1, col2, (value - pos1) / (pos2 - pos1))
+    }
+}
+impl ColorMap for BreakpointColorMap {
+    fn get_color_norm(&self, value: f64) -> RGBColor {
+        let first = self.breakpoints[0].0;
+        let last = self.breakpoints[self.breakpoints.len() - 1].0;
+        self.get_color_at(first + value * (last - first))
+    }
+}
+
+/// Error returned by [`parse_color()`](fn.parse_color.html) for invalid color specs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorParseError {
+    spec: String,
+}
+impl fmt::Display for ColorParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid color spec: '{}'", self.spec)
+    }
+}
+impl Error for ColorParseError {}
+
+/// Parses a color from a hex code (`"#ff8800"` or `"#f80"`), an RGB spec (`"rgb(12, 34, 56)"`),
+/// or a named CSS color (`"steelblue"`). Case-insensitive.
+///
+/// # Example
+/// ```
+/// use easy_graph::color::parse_color;
+///
+/// assert_eq!(parse_color("#ff8800").unwrap().0, 255);
+/// assert_eq!(parse_color("rgb(12, 34, 56)").unwrap().1, 34);
+/// assert_eq!(parse_color("SteelBlue").unwrap().2, 180);
+/// assert!(parse_color("not a color").is_err());
+/// ```
+pub fn parse_color(spec: &str) -> Result<RGBColor, ColorParseError> {
+    let error = || ColorParseError {
+        spec: spec.to_string(),
+    };
+    let lower = spec.trim().to_lowercase();
+    let (r, g, b) = if let Some(hex) = lower.strip_prefix('#') {
+        let digits = hex
+            .chars()
+            .map(|c| c.to_digit(16).map(|d| d as u8))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(error)?;
+        match digits[..] {
+            [r, g, b] => (r * 17, g * 17, b * 17),
+            [r1, r2, g1, g2, b1, b2] => (r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2),
+            _ => return Err(error()),
+        }
+    } else if let Some(args) = lower
+        .strip_prefix("rgb(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        let values = args
+            .split(',')
+            .map(|v| v.trim().parse::<u8>().ok())
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(error)?;
+        match values[..] {
+            [r, g, b] => (r, g, b),
+            _ => return Err(error()),
+        }
+    } else {
+        let index = CSS_COLORS
+            .binary_search_by(|(name, _)| (*name).cmp(lower.as_str()))
+            .map_err(|_| error())?;
+        CSS_COLORS[index].1
+    };
+    Ok(RGBColor(r, g, b))
+}
+
+/// Categorical colors for data series, from the Tableau 10 palette.
+const CATEGORICAL: [(u8, u8, u8); 10] = [
+    (31, 119, 180),
+    (255, 127, 14),
+    (44, 160, 44),
+    (214, 39, 40),
+    (148, 103, 189),
+    (140, 86, 75),
+    (227, 119, 194),
+    (127, 127, 127),
+    (188, 189, 34),
+    (23, 190, 207),
+];
+
+/// The color-blind safe palette by Okabe & Ito (2008), without black.
+const OKABE_ITO: [(u8, u8, u8); 7] = [
+    (230, 159, 0),
+    (86, 180, 233),
+    (0, 158, 115),
+    (240, 228, 66),
+    (0, 114, 178),
+    (213, 94, 0),
+    (204, 121, 167),
+];
+
+/// The qualitative ColorBrewer palette "Dark2".
+const BREWER_DARK2: [(u8, u8, u8); 8] = [
+    (27, 158, 119),
+    (217, 95, 2),
+    (117, 112, 179),
+    (231, 41, 138),
+    (102, 166, 30),
+    (230, 171, 2),
+    (166, 118, 29),
+    (102, 102, 102),
+];
+
+/// The qualitative ColorBrewer palette "Set2".
+const BREWER_SET2: [(u8, u8, u8); 8] = [
+    (102, 194, 165),
+    (252, 141, 98),
+    (141, 160, 203),
+    (231, 138, 195),
+    (166, 216, 84),
+    (255, 217, 47),
+    (229, 196, 148),
+    (179, 179, 179),
+];
+
+/// The qualitative ColorBrewer palette "Paired".
+const BREWER_PAIRED: [(u8, u8, u8); 12] = [
+    (166, 206, 227),
+    (31, 120, 180),
+    (178, 223, 138),
+    (51, 160, 44),
+    (251, 154, 153),
+    (227, 26, 28),
+    (253, 191, 111),
+    (255, 127, 0),
+    (202, 178, 214),
+    (106, 61, 154),
+    (255, 255, 153),
+    (177, 89, 40),
+];
+
+fn to_colors(colors: &[(u8, u8, u8)]) -> Vec<RGBColor> {
+    colors.iter().map(|&(r, g, b)| RGBColor(r, g, b)).collect()
+}
+
+/// Generators for sets of visually distinct colors.
+///
+/// For color-blind safe charts, use [`okabe_ito()`](#method.okabe_ito), and check other palettes with [`simulate_cvd()`](fn.simulate_cvd.html).
+///
+/// # Example
+/// ```
+/// use easy_graph::color::Palette;
+/// use easy_graph::ui::chart::ChartBuilder;
+///
+/// let colors = Palette::okabe_ito();
+/// let colors: Vec<_> = colors.iter().collect();
+/// let builder = ChartBuilder::new().with_palette(&colors);
+/// ```
+pub struct Palette;
+impl Palette {
+    /// The 7 colors of the color-blind safe palette by Okabe & Ito, without black.
+    pub fn okabe_ito() -> Vec<RGBColor> {
+        to_colors(&OKABE_ITO)
+    }
+    /// The 8 colors of the ColorBrewer palette "Dark2". Safe for color-blind viewers for the first 3 colors.
+    pub fn brewer_dark2() -> Vec<RGBColor> {
+        to_colors(&BREWER_DARK2)
+    }
+    /// The 8 colors of the ColorBrewer palette "Set2". Safe for color-blind viewers for the first 3 colors.
+    pub fn brewer_set2() -> Vec<RGBColor> {
+        to_colors(&BREWER_SET2)
+    }
+    /// The 12 colors of the ColorBrewer palette "Paired", as light and dark pairs. Safe for color-blind viewers for the first 4 colors.
+    pub fn brewer_paired() -> Vec<RGBColor> {
+        to_colors(&BREWER_PAIRED)
+    }
+    /// Returns `n` visually distinct colors, e.g. for series.
+    ///
+    /// The first 10 colors are from the Tableau 10 palette. Further colors are generated by rotating hue by the golden angle.
+    pub fn categorical(n: usize) -> Vec<RGBColor> {
+        (0..n).map(Self::pick).collect()
+    }
+    /// Returns the color at the given index of the [`categorical()`](#method.categorical) palette.
+    pub fn pick(index: usize) -> RGBColor {
+        if let Some(&(r, g, b)) = CATEGORICAL.get(index) {
+            return RGBColor(r, g, b);
+        }
+        let i = index - CATEGORICAL.len();
+        let hue = (0.1 + i as f64 * 0.381_966).fract();
+        let lightness = if i % 2 == 0 { 0.45 } else { 0.6 };
+        let (r, g, b) = HSLColor(hue, 0.65, lightness).rgb();
+        RGBColor(r, g, b)
+    }
+}
+
+///
+/// Types of color vision deficiency, for previewing charts as seen by color-blind viewers.
+///
+/// See [`simulate_cvd()`](fn.simulate_cvd.html) and [`ColorMap::simulate_cvd()`](trait.ColorMap.html#method.simulate_cvd).
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorVisionDeficiency {
+    /// No functional red cones. Red appears dark, and is confused with green.
+    Protanopia,
+    /// No functional green cones, the most common type. Red and green are confused.
+    Deuteranopia,
+    /// No functional blue cones. Blue is confused with green, and yellow with violet.
+    Tritanopia,
+    /// No color vision at all, only brightness.
+    Achromatopsia,
+}
+
+impl ColorVisionDeficiency {
+    /// All types of color vision deficiency.
+    pub const ALL: [ColorVisionDeficiency; 4] = [
+        ColorVisionDeficiency::Protanopia,
+        ColorVisionDeficiency::Deuteranopia,
+        ColorVisionDeficiency::Tritanopia,
+        ColorVisionDeficiency::Achromatopsia,
+    ];
+
+    /// Simulation matrix in linear RGB, from Machado, Oliveira & Fernandes (2009) for full severity.
+    fn matrix(self) -> [[f64; 3]; 3] {
+        match self {
+            ColorVisionDeficiency::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            ColorVisionDeficiency::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            ColorVisionDeficiency::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+            ColorVisionDeficiency::Achromatopsia => {
+                let luma = [0.2126, 0.7152, 0.0722];
+                [luma, luma, luma]
+            }
+        }
+    }
+
+    /// Simulates how an sRGB color appears with the deficiency.
+    pub fn simulate(self, rgb: (u8, u8, u8)) -> (u8, u8, u8) {
+        let linear = [to_linear(rgb.0), to_linear(rgb.1), to_linear(rgb.2)];
+        let m = self.matrix();
+        let channel = |row: [f64; 3]| {
+            from_linear(row[0] * linear[0] + row[1] * linear[1] + row[2] * linear[2])
+        };
+        (channel(m[0]), channel(m[1]), channel(m[2]))
+    }
+}
+
+/// Converts an sRGB channel value to linear intensity (0 - 1).
+fn to_linear(value: u8) -> f64 {
+    let v = value as f64 / 255.0;
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts a linear intensity to an sRGB channel value, clamping to the valid range.
+fn from_linear(value: f64) -> u8 {
+    let v = value.clamp(0.0, 1.0);
+    let v = if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    };
+    (v * 255.0).round() as u8
+}
+
+/// Simulates how a color appears to viewers with a color vision deficiency.
+///
+/// See also [`ColorMap::simulate_cvd()`](trait.ColorMap.html#method.simulate_cvd) for color maps,
+/// and [`ChartBuilder::with_cvd_preview()`](../ui/chart/struct.ChartBuilder.html#method.with_cvd_preview) for entire charts.
+///
+/// # Example
+/// ```
+/// use easy_graph::color::{simulate_cvd, ColorVisionDeficiency};
+/// use easy_graph::color::style::{Color, RED};
+///
+/// let seen = simulate_cvd(&RED, ColorVisionDeficiency::Achromatopsia);
+/// let (r, g, b) = seen.rgb();
+/// assert!(r == g && g == b);
+/// ```
+pub fn simulate_cvd(color: &RGBColor, cvd: ColorVisionDeficiency) -> RGBColor {
+    let (r, g, b) = cvd.simulate(color.rgb());
+    RGBColor(r, g, b)
+}
+
 //#[cfg(test)]
 #[allow(unused_imports)]
 mod test {
     use crate::color::style::{Color, RGBColor, GREEN, RED, YELLOW};
-    use crate::color::{ColorMap, LinearColorMap};
+    use crate::color::{
+        parse_color, simulate_cvd, BreakpointColorMap, ColorMap, ColorVisionDeficiency,
+        LinearColorMap, Palette,
+    };
 
     #[test]
     fn color_map_test() {
@@ -71,4 +532,91 @@ mod test {
 
         assert_eq!(map.get_color_norm(0.25).rgb(), (128, 255, 0));
     }
+
+    #[test]
+    fn breakpoint_color_map_test() {
+        let map = BreakpointColorMap::new(&[(0.0, &GREEN), (0.2, &YELLOW), (1.0, &RED)]);
+
+        assert_eq!(map.get_color_norm(0.0).rgb(), (0, 255, 0));
+        assert_eq!(map.get_color_norm(0.1).rgb(), (128, 255, 0));
+        assert_eq!(map.get_color_norm(0.2).rgb(), (255, 255, 0));
+        assert_eq!(map.get_color_norm(0.6).rgb(), (255, 128, 0));
+        assert_eq!(map.get_color_norm(1.0).rgb(), (255, 0, 0));
+        assert_eq!(map.get_color_at(-1.0).rgb(), (0, 255, 0));
+        assert_eq!(map.get_color_at(2.0).rgb(), (255, 0, 0));
+    }
+
+    #[test]
+    fn color_map_adapters() {
+        let map = LinearColorMap::new(&[&GREEN, &RED]).reversed();
+        assert_eq!(map.get_color_norm(0.0).rgb(), (255, 0, 0));
+        assert_eq!(map.get_color_norm(1.0).rgb(), (0, 255, 0));
+
+        let map = LinearColorMap::new(&[&GREEN, &RED]).with_gamma(2.0);
+        assert_eq!(map.get_color_norm(0.5).rgb(), (64, 191, 0));
+        assert_eq!(map.get_color_norm(1.5).rgb(), (255, 0, 0));
+
+        let map = map.reversed();
+        assert_eq!(map.get_color_norm(0.5).rgb(), (64, 191, 0));
+        assert_eq!(map.get_color_norm(1.0).rgb(), (0, 255, 0));
+        assert_eq!(map.get_color(0.0, 10.0, 0.0).rgb(), (255, 0, 0));
+    }
+
+    #[test]
+    fn parse_color_test() {
+        assert_eq!(parse_color("#ff8800").unwrap().rgb(), (255, 136, 0));
+        assert_eq!(parse_color(" #F80 ").unwrap().rgb(), (255, 136, 0));
+        assert_eq!(parse_color("rgb(12, 34,56)").unwrap().rgb(), (12, 34, 56));
+        assert_eq!(parse_color("Green").unwrap().rgb(), (0, 128, 0));
+        assert_eq!(parse_color("aliceblue").unwrap().rgb(), (240, 248, 255));
+        assert_eq!(parse_color("yellowgreen").unwrap().rgb(), (154, 205, 50));
+        assert!(parse_color("#ff88").is_err());
+        assert!(parse_color("rgb(1,2,300)").is_err());
+        assert!(parse_color("greenish").is_err());
+    }
+
+    #[test]
+    fn categorical_palette() {
+        let colors = Palette::categorical(30);
+        assert_eq!(colors[0].rgb(), (31, 119, 180));
+        for (i, a) in colors.iter().enumerate() {
+            for b in &colors[i + 1..] {
+                assert_ne!(a.rgb(), b.rgb());
+            }
+        }
+    }
+
+    #[test]
+    fn color_blind_palettes() {
+        assert_eq!(Palette::okabe_ito().len(), 7);
+        assert_eq!(Palette::okabe_ito()[0].rgb(), (230, 159, 0));
+        assert_eq!(Palette::brewer_dark2().len(), 8);
+        assert_eq!(Palette::brewer_set2().len(), 8);
+        assert_eq!(Palette::brewer_paired().len(), 12);
+    }
+
+    #[test]
+    fn cvd_simulation() {
+        for cvd in ColorVisionDeficiency::ALL.iter() {
+            assert_eq!(cvd.simulate((0, 0, 0)), (0, 0, 0));
+            let (r, g, b) = cvd.simulate((255, 255, 255));
+            assert!(r >= 250 && g >= 250 && b >= 250, "{:?}", cvd);
+        }
+        let gray = simulate_cvd(&RGBColor(200, 50, 50), ColorVisionDeficiency::Achromatopsia);
+        assert!(gray.0 == gray.1 && gray.1 == gray.2);
+
+        // Red and green are hard to distinguish for deuteranopes
+        let cvd = ColorVisionDeficiency::Deuteranopia;
+        let (red, green) = (cvd.simulate((200, 60, 40)), cvd.simulate((60, 140, 40)));
+        let distance = |a: (u8, u8, u8), b: (u8, u8, u8)| {
+            (a.0 as i32 - b.0 as i32).abs()
+                + (a.1 as i32 - b.1 as i32).abs()
+                + (a.2 as i32 - b.2 as i32).abs()
+        };
+        assert!(distance(red, green) < 60, "{:?} {:?}", red, green);
+
+        let map = LinearColorMap::new(&[&GREEN, &RED]).simulate_cvd(cvd);
+        assert_eq!(map.deficiency(), cvd);
+        assert_eq!(map.get_color_norm(0.0).rgb(), cvd.simulate((0, 255, 0)));
+    }
 }
@@ -0,0 +1,249 @@
+//! Networks of nodes and undirected edges, and layout algorithms
+
+/// An undirected graph with data of type `N` attached to each node.
+///
+/// Nodes are identified by their index, in order of insertion.
+pub struct Graph<N> {
+    nodes: Vec<N>,
+    edges: Vec<(usize, usize)>,
+    adjacency: Vec<Vec<usize>>,
+}
+
+impl<N> Default for Graph<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N> Graph<N> {
+    /// Creates an empty graph.
+    pub fn new() -> Self {
+        Graph {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            adjacency: Vec::new(),
+        }
+    }
+
+    /// Adds a node and returns its index.
+    pub fn add_node(&mut self, node: N) -> usize {
+        self.nodes.push(node);
+        self.adjacency.push(Vec::new());
+        self.nodes.len() - 1
+    }
+
+    /// Adds an undirected edge between two nodes.
+    ///
+    /// # Panics
+    /// Panics if any of the indices is not in the range of node indices.
+    pub fn add_edge(&mut self, a: usize, b: usize) {
+        if a >= self.nodes.len() || b >= self.nodes.len() {
+            panic!("Node index out of range!");
+        }
+        self.edges.push((a, b));
+        self.adjacency[a].push(b);
+        if a != b {
+            self.adjacency[b].push(a);
+        }
+    }
+
+    /// Number of nodes.
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Number of edges.
+    pub fn num_edges(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Returns an immutable reference to the node at the given index.
+    pub fn node(&self, index: usize) -> &N {
+        &self.nodes[index]
+    }
+
+    /// Returns a mutable reference to the node at the given index.
+    pub fn node_mut(&mut self, index: usize) -> &mut N {
+        &mut self.nodes[index]
+    }
+
+    /// All nodes, in order of their indices.
+    pub fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+
+    /// All edges as pairs of node indices.
+    pub fn edges(&self) -> &[(usize, usize)] {
+        &self.edges
+    }
+
+    /// Indices of the nodes connected to the given node.
+    pub fn neighbors(&self, index: usize) -> &[usize] {
+        &self.adjacency[index]
+    }
+}
+
+/// Positions of a graph's nodes in the unit square, computed by a layout algorithm.
+#[derive(Clone, Debug)]
+pub struct GraphLayout {
+    positions: Vec<(f64, f64)>,
+}
+
+impl GraphLayout {
+    /// Places nodes evenly spaced on a circle.
+    pub fn circular<N>(graph: &Graph<N>) -> Self {
+        let n = graph.num_nodes();
+        let positions = (0..n)
+            .map(|i| {
+                let angle = 2.0 * std::f64::consts::PI * i as f64 / n as f64;
+                (0.5 + 0.5 * angle.cos(), 0.5 + 0.5 * angle.sin())
+            })
+            .collect();
+        GraphLayout { positions }
+    }
+
+    /// Computes a force-directed layout (Fruchterman-Reingold), starting from a circular layout.
+    ///
+    /// Connected nodes attract each other, while all nodes repel each other.
+    pub fn force_directed<N>(graph: &Graph<N>, iterations: usize) -> Self {
+        let mut layout = Self::circular(graph);
+        layout.relax(graph, iterations);
+        layout
+    }
+
+    /// Continues the force-directed layout from the current positions, e.g. after nodes or edges were added.
+    ///
+    /// Nodes added to the graph after the layout was created start at the center.
+    pub fn relax<N>(&mut self, graph: &Graph<N>, iterations: usize) {
+        let n = graph.num_nodes();
+        self.positions.resize(n, (0.5, 0.5));
+        if n < 2 {
+            return;
+        }
+        let k = (1.0 / n as f64).sqrt();
+        for iter in 0..iterations {
+            let temperature = 0.1 * (1.0 - iter as f64 / iterations as f64);
+            let mut disp = vec![(0.0, 0.0); n];
+            for i in 0..n {
+                for j in (i + 1)..n {
+                    let (dx, dy, dist) = self.delta(i, j);
+                    let force = k * k / dist;
+                    disp[i].0 += dx / dist * force;
+                    disp[i].1 += dy / dist * force;
+                    disp[j].0 -= dx / dist * force;
+                    disp[j].1 -= dy / dist * force;
+                }
+            }
+            for &(a, b) in graph.edges().iter().filter(|(a, b)| a != b) {
+                let (dx, dy, dist) = self.delta(a, b);
+                let force = dist * dist / k;
+                disp[a].0 -= dx / dist * force;
+                disp[a].1 -= dy / dist * force;
+                disp[b].0 += dx / dist * force;
+                disp[b].1 += dy / dist * force;
+            }
+            for (pos, (dx, dy)) in self.positions.iter_mut().zip(disp) {
+                let len = (dx * dx + dy * dy).sqrt().max(1e-9);
+                let step = len.min(temperature);
+                pos.0 += dx / len * step;
+                pos.1 += dy / len * step;
+            }
+        }
+        self.normalize();
+    }
+
+    /// Returns the position of a node.
+    pub fn position(&self, index: usize) -> (f64, f64) {
+        self.positions[index]
+    }
+
+    /// Returns the positions of all nodes.
+    pub fn positions(&self) -> &[(f64, f64)] {
+        &self.positions
+    }
+
+    /// Sets the position of a node, e.g. for nodes with a known location.
+    pub fn set_position(&mut self, index: usize, pos: (f64, f64)) {
+        self.positions[index] = pos;
+    }
+
+    /// Difference vector and distance between two nodes. The distance is never zero.
+    fn delta(&self, i: usize, j: usize) -> (f64, f64, f64) {
+        let (a, b) = (self.positions[i], self.positions[j]);
+        let (mut dx, mut dy) = (a.0 - b.0, a.1 - b.1);
+        if dx == 0.0 && dy == 0.0 {
+            // Separate coincident nodes in a deterministic direction.
+            dx = 1e-3 * (i as f64 + 1.0);
+            dy = 1e-3 * (j as f64 + 1.0);
+        }
+        (dx, dy, (dx * dx + dy * dy).sqrt())
+    }
+
+    /// Scales and shifts positions to fill the unit square, keeping the aspect ratio.
+    fn normalize(&mut self) {
+        let (mut min, mut max) = ((f64::MAX, f64::MAX), (f64::MIN, f64::MIN));
+        for &(x, y) in &self.positions {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+        let span = (max.0 - min.0).max(max.1 - min.1);
+        if span <= 0.0 {
+            return;
+        }
+        let offset = (
+            (span - (max.0 - min.0)) / 2.0,
+            (span - (max.1 - min.1)) / 2.0,
+        );
+        for pos in &mut self.positions {
+            pos.0 = (pos.0 - min.0 + offset.0) / span;
+            pos.1 = (pos.1 - min.1 + offset.1) / span;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::geom::graph::{Graph, GraphLayout};
+
+    #[test]
+    fn graph() {
+        let mut graph = Graph::new();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        graph.add_edge(a, b);
+        graph.add_edge(b, c);
+        assert_eq!(graph.num_nodes(), 3);
+        assert_eq!(graph.num_edges(), 2);
+        assert_eq!(graph.neighbors(b), &[a, c]);
+        assert_eq!(*graph.node(c), "c");
+    }
+
+    #[test]
+    fn layouts() {
+        let mut graph = Graph::new();
+        for i in 0..10 {
+            graph.add_node(i);
+        }
+        // Two separate cliques
+        for a in 0..5 {
+            for b in (a + 1)..5 {
+                graph.add_edge(a, b);
+                graph.add_edge(a + 5, b + 5);
+            }
+        }
+        let layout = GraphLayout::circular(&graph);
+        assert!((layout.position(0).0 - 1.0).abs() < 1e-9);
+
+        let layout = GraphLayout::force_directed(&graph, 100);
+        let dist = |a: usize, b: usize| {
+            let (p, q) = (layout.position(a), layout.position(b));
+            ((p.0 - q.0).powi(2) + (p.1 - q.1).powi(2)).sqrt()
+        };
+        assert!(dist(0, 1) < dist(0, 5));
+        assert!(dist(6, 7) < dist(2, 7));
+        for &(x, y) in layout.positions() {
+            assert!((0.0..=1.0).contains(&x) && (0.0..=1.0).contains(&y));
+        }
+    }
+}
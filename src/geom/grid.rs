@@ -1,27 +1,138 @@
 //! Generic grids
 
+use image::{DynamicImage, GenericImageView, ImageError};
+#[cfg(feature = "ndarray")]
+use ndarray::{Array2, ArrayView2};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::clone::Clone;
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
 use std::slice::{Iter, IterMut};
 
+/// Magic bytes at the start of binary grid files.
+const BINARY_MAGIC: &[u8; 8] = b"EGGRID01";
+
 /// A generic 2d grid.
 ///
 /// Flat representation in memory.
+///
+/// With feature `serde`, grids implement `Serialize` and `Deserialize`.
+/// Deserialization fails if the number of cells does not match width and height.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        try_from = "GridData<T>",
+        bound(deserialize = "T: Clone + Deserialize<'de>")
+    )
+)]
 pub struct Grid<T: Clone> {
     width: usize,
     height: usize,
     data: Vec<T>,
+    /// Modification record of cells, if change tracking is enabled.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    changes: Option<Changes>,
+}
+
+/// Serialized form of a grid, validated when converted to a grid.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct GridData<T> {
+    width: usize,
+    height: usize,
+    data: Vec<T>,
+}
+
+#[cfg(feature = "serde")]
+impl<T: Clone> TryFrom<GridData<T>> for Grid<T> {
+    type Error = String;
+
+    fn try_from(grid: GridData<T>) -> Result<Self, Self::Error> {
+        if grid.width.checked_mul(grid.height) != Some(grid.data.len()) {
+            return Err(format!(
+                "Grid data has {} cells, expected {} x {}",
+                grid.data.len(),
+                grid.width,
+                grid.height
+            ));
+        }
+        Ok(Grid::from_data(grid.width, grid.height, grid.data))
+    }
+}
+
+/// Modification record of grid cells, serving the change flags used by renderers
+/// as well as the dirty cells returned by `take_dirty()`.
+///
+/// Each modification stamps the cell with the current generation. Resetting the change flags and taking dirty cells
+/// start a new generation, so that both can be reset independently.
+#[derive(Clone)]
+struct Changes {
+    /// Generation of the last modification per cell, `0` for cells never modified.
+    stamps: Vec<u32>,
+    generation: u32,
+    /// Generation at the last reset of the change flags.
+    cleared: u32,
+    /// Generation at the last call to `take_dirty()`.
+    taken: u32,
+    /// Cells modified since the last call to `take_dirty()`, in order of first modification, if enabled.
+    dirty: Option<Vec<usize>>,
+}
+
+impl Changes {
+    fn new(len: usize) -> Self {
+        Changes {
+            stamps: vec![0; len],
+            generation: 1,
+            cleared: 1,
+            taken: 1,
+            dirty: None,
+        }
+    }
+    fn mark(&mut self, index: usize) {
+        if let Some(dirty) = &mut self.dirty {
+            if self.stamps[index] < self.taken {
+                dirty.push(index);
+            }
+        }
+        self.stamps[index] = self.generation;
+    }
+    /// Starts a new generation, and returns it.
+    fn next_generation(&mut self) -> u32 {
+        if self.generation == u32::MAX {
+            // Shift stamps down, keeping only the distinctions relevant for `cleared` and `taken`
+            let base = self.cleared.min(self.taken) - 1;
+            for stamp in &mut self.stamps {
+                *stamp = stamp.saturating_sub(base);
+            }
+            self.generation -= base;
+            self.cleared -= base;
+            self.taken -= base;
+        }
+        self.generation += 1;
+        self.generation
+    }
 }
 
 impl<T: Clone> Grid<T> {
     #[allow(dead_code)]
     pub fn new(width: usize, height: usize, default: T) -> Grid<T> {
-        let mut grid = Grid {
+        Grid::from_data(width, height, vec![default; width * height])
+    }
+
+    /// Creates a grid from data in memory order, without change tracking.
+    fn from_data(width: usize, height: usize, data: Vec<T>) -> Grid<T> {
+        debug_assert_eq!(data.len(), width * height);
+        Grid {
             width,
             height,
-            data: Vec::new(),
-        };
-        grid.data.resize(width * height, default);
-        grid
+            data,
+            changes: None,
+        }
     }
 
     /// Width (x dimension) of the grid in cells.
@@ -46,6 +157,7 @@ impl<T: Clone> Grid<T> {
     #[allow(dead_code)]
     pub fn get_mut(&mut self, x: usize, y: usize) -> &mut T {
         let idx = self.index(x, y);
+        self.mark_changed(idx);
         &mut self.data[idx]
     }
 
@@ -58,6 +170,7 @@ impl<T: Clone> Grid<T> {
     /// Returns a mutable reference to the cell at index (i.e. index in flat memory).
     #[allow(dead_code)]
     pub fn get_index_mut(&mut self, i: usize) -> &mut T {
+        self.mark_changed(i);
         &mut self.data[i]
     }
 
@@ -65,19 +178,21 @@ impl<T: Clone> Grid<T> {
     #[allow(dead_code)]
     pub fn set(&mut self, x: usize, y: usize, value: T) {
         let idx = self.index(x, y);
+        self.mark_changed(idx);
         self.data[idx] = value;
     }
 
     /// Sets the cell at index (i.e. index in flat memory).
     #[allow(dead_code)]
     pub fn set_index(&mut self, i: usize, value: T) {
+        self.mark_changed(i);
         self.data[i] = value;
     }
 
     /// Returns if the grid contains coordinate (x, y).
     #[allow(dead_code)]
     pub fn contains(&self, x: i32, y: i32) -> bool {
-        x >= 0 && y >= 0 && x < self.width as i32 && y < self.width as i32
+        x >= 0 && y >= 0 && x < self.width as i32 && y < self.height as i32
     }
 
     /// Calculates memory index from x, y coordinates.
@@ -96,6 +211,7 @@ impl<T: Clone> Grid<T> {
     where
         F: Fn(usize, usize) -> T,
     {
+        self.mark_all_changed();
         for x in 0..self.width {
             for y in 0..self.height {
                 let idx = self.index(x, y);
@@ -110,6 +226,7 @@ impl<T: Clone> Grid<T> {
     where
         F: Fn() -> T,
     {
+        self.mark_all_changed();
         for x in 0..self.width {
             for y in 0..self.height {
                 let idx = self.index(x, y);
@@ -119,14 +236,608 @@ impl<T: Clone> Grid<T> {
     }
 
     /// Returns an Iterator over all grid cells in memory order.
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         self.data.iter()
     }
 
     /// Returns a mutable Iterator over all grid cells in memory order.
-    pub fn iter_mut(&mut self) -> IterMut<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        self.mark_all_changed();
         self.data.iter_mut()
     }
+
+    /// Returns an immutable view of a rectangular region, with the region's upper left corner at x0, y0.
+    /// The view has the same accessors as the grid, in coordinates relative to the region.
+    ///
+    /// # Example
+    /// ```
+    /// use easy_graph::geom::grid::Grid;
+    ///
+    /// let mut grid = Grid::new(1000, 1000, 0);
+    /// grid.set(510, 520, 1);
+    /// let view = grid.view(500, 500, 50, 50);
+    /// assert_eq!(*view.get(10, 20), 1);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if the region exceeds the grid.
+    pub fn view(&self, x0: usize, y0: usize, width: usize, height: usize) -> GridView<'_, T> {
+        self.check_region(x0, y0, width, height);
+        GridView {
+            grid: self,
+            offset: (x0, y0),
+            size: (width, height),
+        }
+    }
+
+    /// Returns a mutable view of a rectangular region, with the region's upper left corner at x0, y0.
+    ///
+    /// # Panics
+    /// Panics if the region exceeds the grid.
+    pub fn view_mut(
+        &mut self,
+        x0: usize,
+        y0: usize,
+        width: usize,
+        height: usize,
+    ) -> GridViewMut<'_, T> {
+        self.check_region(x0, y0, width, height);
+        GridViewMut {
+            grid: self,
+            offset: (x0, y0),
+            size: (width, height),
+        }
+    }
+
+    /// Creates an owned copy of a rectangular region, with the region's upper left corner at x0, y0.
+    ///
+    /// # Panics
+    /// Panics if the region exceeds the grid.
+    pub fn crop(&self, x0: usize, y0: usize, width: usize, height: usize) -> Grid<T> {
+        self.view(x0, y0, width, height).to_grid()
+    }
+
+    /// Enables change tracking. From now on, all mutating accessors like [`set()`](#method.set), [`get_mut()`](#method.get_mut),
+    /// [`fill()`](#method.fill) or [`iter_mut()`](#method.iter_mut) record the indices of modified cells.
+    ///
+    /// Cells are recorded when accessed mutably, even if their value did not change.
+    ///
+    /// # Example
+    /// ```
+    /// use easy_graph::geom::grid::Grid;
+    ///
+    /// let mut grid = Grid::new(100, 100, 0);
+    /// grid.enable_tracking();
+    /// grid.set(10, 20, 1);
+    /// *grid.get_mut(30, 40) += 1;
+    /// grid.set(10, 20, 2);
+    /// assert_eq!(grid.take_dirty(), vec![grid.index(10, 20), grid.index(30, 40)]);
+    /// assert!(grid.take_dirty().is_empty());
+    /// ```
+    pub fn enable_tracking(&mut self) {
+        let len = self.data.len();
+        let changes = self.changes.get_or_insert_with(|| Changes::new(len));
+        if changes.dirty.is_none() {
+            changes.taken = changes.next_generation();
+            changes.dirty = Some(vec![]);
+        }
+    }
+
+    /// Disables change tracking and discards recorded changes.
+    pub fn disable_tracking(&mut self) {
+        if let Some(changes) = &mut self.changes {
+            changes.dirty = None;
+        }
+    }
+
+    /// Returns if change tracking is enabled.
+    pub fn is_tracking(&self) -> bool {
+        self.changes
+            .as_ref()
+            .is_some_and(|changes| changes.dirty.is_some())
+    }
+
+    /// Returns the memory indices of all cells modified since the last call, in order of their first modification,
+    /// and resets the record. Returns an empty vector if change tracking is not enabled.
+    ///
+    /// See [`enable_tracking()`](#method.enable_tracking). Use [`coord()`](#method.coord) to convert indices to coordinates.
+    pub fn take_dirty(&mut self) -> Vec<usize> {
+        match &mut self.changes {
+            Some(changes) if changes.dirty.is_some() => {
+                changes.taken = changes.next_generation();
+                changes.dirty.replace(vec![]).unwrap_or_default()
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Enables the change flags, with all cells unchanged. Cells are flagged by all mutating accessors.
+    pub(crate) fn track_changes(&mut self) {
+        let len = self.data.len();
+        self.changes.get_or_insert_with(|| Changes::new(len));
+    }
+
+    /// Returns if the change flags are enabled.
+    pub(crate) fn tracks_changes(&self) -> bool {
+        self.changes.is_some()
+    }
+
+    /// Returns if a cell changed since the last reset of the change flags. Always `false` if not enabled.
+    pub(crate) fn is_changed(&self, index: usize) -> bool {
+        self.changes
+            .as_ref()
+            .is_some_and(|changes| changes.stamps[index] >= changes.cleared)
+    }
+
+    /// Resets all change flags to unchanged.
+    pub(crate) fn clear_changes(&mut self) {
+        if let Some(changes) = &mut self.changes {
+            changes.cleared = changes.next_generation();
+        }
+    }
+
+    fn mark_changed(&mut self, index: usize) {
+        if let Some(changes) = &mut self.changes {
+            changes.mark(index);
+        }
+    }
+
+    fn mark_all_changed(&mut self) {
+        if let Some(changes) = &mut self.changes {
+            for index in 0..changes.stamps.len() {
+                changes.mark(index);
+            }
+        }
+    }
+
+    fn check_region(&self, x0: usize, y0: usize, width: usize, height: usize) {
+        if x0 + width > self.width || y0 + height > self.height {
+            panic!("Region exceeds the grid!");
+        }
+    }
+}
+
+///
+/// An immutable view of a rectangular region of a [`Grid`](struct.Grid.html), created by [`Grid::view()`](struct.Grid.html#method.view).
+///
+/// Coordinates are relative to the region's upper left corner.
+///
+pub struct GridView<'a, T: Clone> {
+    grid: &'a Grid<T>,
+    offset: (usize, usize),
+    size: (usize, usize),
+}
+
+impl<'a, T: Clone> GridView<'a, T> {
+    /// Width (x dimension) of the view in cells.
+    pub fn width(&self) -> i32 {
+        self.size.0 as i32
+    }
+
+    /// Height (y dimension) of the view in cells.
+    pub fn height(&self) -> i32 {
+        self.size.1 as i32
+    }
+
+    /// Position of the view's upper left corner in the grid.
+    pub fn offset(&self) -> (usize, usize) {
+        self.offset
+    }
+
+    /// Returns an immutable reference to the cell at x, y.
+    ///
+    /// # Panics
+    /// Panics if x, y is outside the view.
+    pub fn get(&self, x: usize, y: usize) -> &'a T {
+        if x >= self.size.0 || y >= self.size.1 {
+            panic!("Coordinates outside of grid view!");
+        }
+        self.grid.get(self.offset.0 + x, self.offset.1 + y)
+    }
+
+    /// Returns if the view contains coordinate (x, y).
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && x < self.size.0 as i32 && y < self.size.1 as i32
+    }
+
+    /// Returns an Iterator over all cells of the view in memory order (x-major).
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> {
+        let grid = self.grid;
+        let ((x0, y0), (width, height)) = (self.offset, self.size);
+        (x0..x0 + width).flat_map(move |x| {
+            let start = grid.index(x, y0);
+            grid.data[start..start + height].iter()
+        })
+    }
+
+    /// Creates an owned copy of the viewed region.
+    pub fn to_grid(&self) -> Grid<T> {
+        Grid::from_data(self.size.0, self.size.1, self.iter().cloned().collect())
+    }
+}
+
+///
+/// A mutable view of a rectangular region of a [`Grid`](struct.Grid.html), created by [`Grid::view_mut()`](struct.Grid.html#method.view_mut).
+///
+/// Coordinates are relative to the region's upper left corner.
+///
+pub struct GridViewMut<'a, T: Clone> {
+    grid: &'a mut Grid<T>,
+    offset: (usize, usize),
+    size: (usize, usize),
+}
+
+impl<'a, T: Clone> GridViewMut<'a, T> {
+    /// Width (x dimension) of the view in cells.
+    pub fn width(&self) -> i32 {
+        self.size.0 as i32
+    }
+
+    /// Height (y dimension) of the view in cells.
+    pub fn height(&self) -> i32 {
+        self.size.1 as i32
+    }
+
+    /// Returns an immutable reference to the cell at x, y.
+    ///
+    /// # Panics
+    /// Panics if x, y is outside the view.
+    pub fn get(&self, x: usize, y: usize) -> &T {
+        let (x, y) = self.grid_coord(x, y);
+        self.grid.get(x, y)
+    }
+
+    /// Returns a mutable reference to the cell at x, y.
+    ///
+    /// # Panics
+    /// Panics if x, y is outside the view.
+    pub fn get_mut(&mut self, x: usize, y: usize) -> &mut T {
+        let (x, y) = self.grid_coord(x, y);
+        self.grid.get_mut(x, y)
+    }
+
+    /// Sets the cell at x, y.
+    ///
+    /// # Panics
+    /// Panics if x, y is outside the view.
+    pub fn set(&mut self, x: usize, y: usize, value: T) {
+        *self.get_mut(x, y) = value;
+    }
+
+    /// Returns if the view contains coordinate (x, y).
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && x < self.size.0 as i32 && y < self.size.1 as i32
+    }
+
+    /// Fills the view using a closure with coordinates relative to the view as arguments.
+    pub fn fill_xy<F>(&mut self, f: F)
+    where
+        F: Fn(usize, usize) -> T,
+    {
+        for x in 0..self.size.0 {
+            for y in 0..self.size.1 {
+                self.set(x, y, f(x, y));
+            }
+        }
+    }
+
+    /// Returns an immutable view of the same region.
+    pub fn as_view(&self) -> GridView<'_, T> {
+        GridView {
+            grid: self.grid,
+            offset: self.offset,
+            size: self.size,
+        }
+    }
+
+    fn grid_coord(&self, x: usize, y: usize) -> (usize, usize) {
+        if x >= self.size.0 || y >= self.size.1 {
+            panic!("Coordinates outside of grid view!");
+        }
+        (self.offset.0 + x, self.offset.1 + y)
+    }
+}
+
+impl Grid<f64> {
+    /// Writes the grid in a compact binary format.
+    ///
+    /// The format is the 8 magic bytes `EGGRID01`, followed by width and height as little-endian `u64`,
+    /// followed by all values as little-endian `f64` in memory order (x-major).
+    /// In Python, it can be read with
+    /// `numpy.fromfile(path, dtype="<f8", offset=24).reshape(width, height)`, indexed as `[x, y]`.
+    pub fn write_binary<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(BINARY_MAGIC)?;
+        writer.write_all(&(self.width as u64).to_le_bytes())?;
+        writer.write_all(&(self.height as u64).to_le_bytes())?;
+        for value in &self.data {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Reads a grid in the binary format written by [`write_binary()`](#method.write_binary).
+    pub fn read_binary<R: Read>(reader: &mut R) -> io::Result<Grid<f64>> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != BINARY_MAGIC {
+            return Err(invalid("Not a binary grid file"));
+        }
+        let mut bytes = [0; 8];
+        reader.read_exact(&mut bytes)?;
+        let width = u64::from_le_bytes(bytes) as usize;
+        reader.read_exact(&mut bytes)?;
+        let height = u64::from_le_bytes(bytes) as usize;
+        let len = width
+            .checked_mul(height)
+            .ok_or_else(|| invalid("Invalid grid dimensions"))?;
+
+        let mut data = Vec::new();
+        for _ in 0..len {
+            reader.read_exact(&mut bytes)?;
+            data.push(f64::from_le_bytes(bytes));
+        }
+        Ok(Grid::from_data(width, height, data))
+    }
+
+    /// Saves the grid to a binary file. See [`write_binary()`](#method.write_binary) for the format.
+    pub fn save_binary<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_binary(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Loads a grid from a binary file written by [`save_binary()`](#method.save_binary).
+    pub fn load_binary<P: AsRef<Path>>(path: P) -> io::Result<Grid<f64>> {
+        Self::read_binary(&mut BufReader::new(File::open(path)?))
+    }
+}
+
+/// Image channels to load into a grid, see [`Grid::<f64>::from_image()`](struct.Grid.html#method.from_image).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageChannel {
+    /// The red channel.
+    Red,
+    /// The green channel.
+    Green,
+    /// The blue channel.
+    Blue,
+    /// The alpha channel. Fully opaque for images without transparency.
+    Alpha,
+    /// The brightness of the pixel.
+    Luma,
+}
+
+impl<T: Clone> Grid<T> {
+    /// Creates a grid from an image, with one cell per pixel. Image rows are grid rows, i.e. `y` increases downwards.
+    fn from_pixels<F>(image: &DynamicImage, f: F) -> Grid<T>
+    where
+        F: Fn(&DynamicImage, u32, u32) -> T,
+    {
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        let mut data = Vec::with_capacity(width * height);
+        for x in 0..image.width() {
+            for y in 0..image.height() {
+                data.push(f(image, x, y));
+            }
+        }
+        Grid::from_data(width, height, data)
+    }
+}
+
+impl Grid<f64> {
+    /// Loads a grid from a channel of an image file, e.g. for landscapes or masks painted in an image editor.
+    /// The format is derived from the file extension.
+    ///
+    /// Values are in the range 0 - 1. The grid has one cell per pixel, with `y` increasing downwards.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use easy_graph::geom::grid::{Grid, ImageChannel};
+    ///
+    /// let elevation = Grid::<f64>::from_image("elevation.png", ImageChannel::Luma).unwrap();
+    /// ```
+    pub fn from_image<P: AsRef<Path>>(
+        path: P,
+        channel: ImageChannel,
+    ) -> Result<Grid<f64>, ImageError> {
+        let image = image::open(path)?;
+        let image = match channel {
+            ImageChannel::Luma => DynamicImage::ImageLuma8(image.to_luma()),
+            _ => DynamicImage::ImageRgba8(image.to_rgba()),
+        };
+        let index = match channel {
+            ImageChannel::Red | ImageChannel::Luma => 0,
+            ImageChannel::Green => 1,
+            ImageChannel::Blue => 2,
+            ImageChannel::Alpha => 3,
+        };
+        Ok(Self::from_pixels(&image, |image, x, y| {
+            image.get_pixel(x, y).0[index] as f64 / 255.0
+        }))
+    }
+}
+
+impl Grid<(u8, u8, u8)> {
+    /// Loads a grid of RGB colors from an image file, e.g. for categorical maps painted in an image editor.
+    /// The format is derived from the file extension. Transparency is ignored.
+    ///
+    /// The grid has one cell per pixel, with `y` increasing downwards.
+    pub fn from_image<P: AsRef<Path>>(path: P) -> Result<Grid<(u8, u8, u8)>, ImageError> {
+        let image = image::open(path)?;
+        Ok(Self::from_pixels(&image, |image, x, y| {
+            let [r, g, b, _] = image.get_pixel(x, y).0;
+            (r, g, b)
+        }))
+    }
+}
+
+/// Interop with `ndarray`, enabled by feature `ndarray`. Arrays have shape `(width, height)` and are indexed as `[x, y]`.
+#[cfg(feature = "ndarray")]
+impl<T: Clone> Grid<T> {
+    /// Creates a grid from a 2d array of shape `(width, height)`, indexed as `[x, y]`.
+    ///
+    /// # Example
+    /// ```
+    /// use easy_graph::geom::grid::Grid;
+    /// use ndarray::Array2;
+    ///
+    /// let array = Array2::from_shape_fn((20, 10), |(x, y)| (x * y) as f64);
+    /// let grid = Grid::from_array2(array.view());
+    /// assert_eq!((grid.width(), grid.height()), (20, 10));
+    /// assert_eq!(*grid.get(3, 4), 12.0);
+    /// ```
+    pub fn from_array2(array: ArrayView2<T>) -> Grid<T> {
+        let (width, height) = array.dim();
+        Grid::from_data(width, height, array.iter().cloned().collect())
+    }
+    /// Copies the grid into a 2d array of shape `(width, height)`, indexed as `[x, y]`.
+    pub fn to_array2(&self) -> Array2<T> {
+        Array2::from_shape_vec((self.width, self.height), self.data.clone())
+            .expect("Grid data must match its dimensions")
+    }
+}
+
+/// Methods for resampling grids, see [`Grid::resample()`](struct.Grid.html#method.resample).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Resampling {
+    /// Value of the nearest source cell.
+    Nearest,
+    /// Bilinear interpolation between the four nearest source cells.
+    Bilinear,
+    /// Area-weighted average of all covered source cells. Best for downsampling.
+    Area,
+}
+
+impl<T: Clone> Grid<T> {
+    /// Creates a resampled copy of the grid, using the value of the nearest cell. Works for any cell type.
+    ///
+    /// Returns an empty grid if this grid or the requested size is empty.
+    pub fn resample_nearest(&self, width: usize, height: usize) -> Grid<T> {
+        if self.data.is_empty() || width == 0 || height == 0 {
+            return Grid::from_data(0, 0, Vec::new());
+        }
+        let mut data = Vec::with_capacity(width * height);
+        for x in 0..width {
+            let sx = (x * self.width / width).min(self.width - 1);
+            for y in 0..height {
+                let sy = (y * self.height / height).min(self.height - 1);
+                data.push(self.get(sx, sy).clone());
+            }
+        }
+        Grid::from_data(width, height, data)
+    }
+}
+
+impl Grid<f64> {
+    /// Creates a resampled copy of the grid with the given size.
+    ///
+    /// Returns an empty grid if this grid or the requested size is empty.
+    ///
+    /// # Example
+    /// ```
+    /// use easy_graph::geom::grid::{Grid, Resampling};
+    ///
+    /// let grid = Grid::new(4096, 4096, 1.0);
+    /// let small = grid.resample(512, 512, Resampling::Area);
+    /// assert_eq!(small.width(), 512);
+    /// ```
+    pub fn resample(&self, width: usize, height: usize, method: Resampling) -> Grid<f64> {
+        if self.data.is_empty() || width == 0 || height == 0 {
+            return Grid::from_data(0, 0, Vec::new());
+        }
+        match method {
+            Resampling::Nearest => self.resample_nearest(width, height),
+            Resampling::Bilinear => self.resample_bilinear(width, height),
+            Resampling::Area => self.resample_area(width, height),
+        }
+    }
+
+    /// Creates a pyramid of successively downsampled grids, halving the size per level (area-weighted).
+    ///
+    /// The first element is the first downsampled level, not the original grid.
+    /// Stops early when a dimension would drop below one cell.
+    pub fn downsample_pyramid(&self, levels: usize) -> Vec<Grid<f64>> {
+        let mut pyramid: Vec<Grid<f64>> = Vec::with_capacity(levels);
+        for _ in 0..levels {
+            let prev = pyramid.last().unwrap_or(self);
+            let (width, height) = (prev.width / 2, prev.height / 2);
+            if width == 0 || height == 0 {
+                break;
+            }
+            let next = prev.resample_area(width, height);
+            pyramid.push(next);
+        }
+        pyramid
+    }
+
+    fn resample_bilinear(&self, width: usize, height: usize) -> Grid<f64> {
+        let x_samples = Self::bilinear_samples(self.width, width);
+        let y_samples = Self::bilinear_samples(self.height, height);
+        let mut data = Vec::with_capacity(width * height);
+        for &(x0, x1, fx) in &x_samples {
+            for &(y0, y1, fy) in &y_samples {
+                let top = self.get(x0, y0) * (1.0 - fx) + self.get(x1, y0) * fx;
+                let bottom = self.get(x0, y1) * (1.0 - fx) + self.get(x1, y1) * fx;
+                data.push(top * (1.0 - fy) + bottom * fy);
+            }
+        }
+        Grid::from_data(width, height, data)
+    }
+
+    /// For each target cell along one axis, the two source cells and the interpolation fraction.
+    fn bilinear_samples(source: usize, target: usize) -> Vec<(usize, usize, f64)> {
+        let ratio = source as f64 / target as f64;
+        (0..target)
+            .map(|i| {
+                let pos = ((i as f64 + 0.5) * ratio - 0.5)
+                    .max(0.0)
+                    .min((source - 1) as f64);
+                let lower = pos.floor() as usize;
+                let upper = (lower + 1).min(source - 1);
+                (lower, upper, pos - lower as f64)
+            })
+            .collect()
+    }
+
+    fn resample_area(&self, width: usize, height: usize) -> Grid<f64> {
+        let x_weights = Self::area_weights(self.width, width);
+        let y_weights = Self::area_weights(self.height, height);
+        let mut data = Vec::with_capacity(width * height);
+        for xw in &x_weights {
+            for yw in &y_weights {
+                let mut sum = 0.0;
+                let mut total = 0.0;
+                for &(x, wx) in xw {
+                    for &(y, wy) in yw {
+                        sum += self.get(x, y) * wx * wy;
+                        total += wx * wy;
+                    }
+                }
+                data.push(sum / total);
+            }
+        }
+        Grid::from_data(width, height, data)
+    }
+
+    /// For each target cell along one axis, the covered source cells and their coverage.
+    fn area_weights(source: usize, target: usize) -> Vec<Vec<(usize, f64)>> {
+        let ratio = source as f64 / target as f64;
+        (0..target)
+            .map(|i| {
+                let (start, end) = (i as f64 * ratio, (i + 1) as f64 * ratio);
+                let first = start.floor() as usize;
+                let last = (end.ceil() as usize).min(source);
+                (first..last)
+                    .map(|s| {
+                        let cover = end.min((s + 1) as f64) - start.max(s as f64);
+                        (s, cover)
+                    })
+                    .filter(|(_, cover)| *cover > 0.0)
+                    .collect()
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -154,10 +865,193 @@ mod tests {
         assert_eq!(*grid.get(8, 3), 8 + 3);
     }
 
+    #[test]
+    fn change_bitmap() {
+        let mut grid = crate::geom::grid::Grid::new(4, 3, 0);
+        grid.set(1, 1, 1);
+        assert!(!grid.tracks_changes());
+        grid.track_changes();
+        grid.set(2, 1, 1);
+        *grid.view_mut(1, 1, 2, 2).get_mut(0, 1) = 2;
+        let changed: Vec<_> = (0..12).filter(|i| grid.is_changed(*i)).collect();
+        assert_eq!(changed, vec![grid.index(1, 2), grid.index(2, 1)]);
+        grid.clear_changes();
+        assert!(!(0..12).any(|i| grid.is_changed(i)));
+        grid.fill(|| 3);
+        assert!((0..12).all(|i| grid.is_changed(i)));
+    }
+
+    #[test]
+    fn dirty_tracking() {
+        let mut grid = crate::geom::grid::Grid::new(4, 3, 0);
+        grid.set(1, 1, 1);
+        assert!(!grid.is_tracking());
+        assert!(grid.take_dirty().is_empty());
+
+        grid.enable_tracking();
+        grid.track_changes();
+        grid.set_index(5, 1);
+        grid.view_mut(2, 0, 2, 2).set(1, 1, 2);
+        *grid.get_index_mut(5) = 3;
+        assert_eq!(grid.take_dirty(), vec![5, grid.index(3, 1)]);
+        assert!(grid.take_dirty().is_empty());
+        assert!(grid.is_changed(5));
+        grid.clear_changes();
+        assert!(!grid.is_changed(5));
+        grid.set_index(6, 1);
+        assert!(grid.is_changed(6));
+        assert_eq!(grid.take_dirty(), vec![6]);
+        assert!(grid.is_changed(6));
+
+        grid.set_index(5, 1);
+        grid.iter_mut().for_each(|v| *v += 1);
+        let dirty = grid.take_dirty();
+        assert_eq!(dirty.len(), 12);
+        assert_eq!(dirty[0], 5);
+
+        grid.disable_tracking();
+        grid.set(0, 0, 1);
+        assert!(grid.take_dirty().is_empty());
+    }
+
     #[test]
     fn contains() {
         let grid = crate::geom::grid::Grid::new(10, 10, 0);
         assert!(grid.contains(9, 9));
         assert!(!grid.contains(10, 10));
     }
+
+    #[test]
+    fn from_image() {
+        use crate::geom::grid::{Grid, ImageChannel};
+        use image::{Rgba, RgbaImage};
+        let path = std::env::temp_dir().join("easy_graph_grid_from_image.png");
+        let mut image = RgbaImage::new(3, 2);
+        image.put_pixel(2, 1, Rgba([255, 0, 51, 128]));
+        image.save(&path).unwrap();
+
+        let red = Grid::<f64>::from_image(&path, ImageChannel::Red).unwrap();
+        assert_eq!((red.width(), red.height()), (3, 2));
+        assert_eq!(*red.get(2, 1), 1.0);
+        assert_eq!(*red.get(1, 1), 0.0);
+        let blue = Grid::<f64>::from_image(&path, ImageChannel::Blue).unwrap();
+        assert_eq!(*blue.get(2, 1), 0.2);
+        let colors = Grid::<(u8, u8, u8)>::from_image(&path).unwrap();
+        assert_eq!(*colors.get(2, 1), (255, 0, 51));
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(Grid::<f64>::from_image("missing.png", ImageChannel::Luma).is_err());
+    }
+
+    #[test]
+    fn resample() {
+        use crate::geom::grid::{Grid, Resampling};
+        let mut grid = Grid::new(4, 2, 0.0);
+        grid.fill_xy(|x, y| (x + 4 * y) as f64);
+
+        let area = grid.resample(2, 1, Resampling::Area);
+        assert_eq!(*area.get(0, 0), (0.0 + 1.0 + 4.0 + 5.0) / 4.0);
+        assert_eq!(*area.get(1, 0), (2.0 + 3.0 + 6.0 + 7.0) / 4.0);
+
+        let nearest = grid.resample(8, 4, Resampling::Nearest);
+        assert_eq!(*nearest.get(3, 3), 5.0);
+
+        let bilinear = grid.resample(8, 2, Resampling::Bilinear);
+        assert_eq!(*bilinear.get(0, 0), 0.0);
+        assert_eq!(*bilinear.get(1, 0), 0.25);
+
+        let pyramid = grid.downsample_pyramid(3);
+        assert_eq!(pyramid.len(), 1);
+        assert_eq!(pyramid[0].width(), 2);
+
+        let empty = Grid::new(0, 3, 0.0);
+        for method in &[Resampling::Nearest, Resampling::Bilinear, Resampling::Area] {
+            assert_eq!(empty.resample(4, 4, *method).width(), 0);
+            assert_eq!(grid.resample(0, 4, *method).width(), 0);
+        }
+    }
+
+    #[test]
+    fn binary_roundtrip() {
+        use crate::geom::grid::Grid;
+        let mut grid = Grid::new(3, 2, 0.0);
+        grid.fill_xy(|x, y| x as f64 - 0.5 * y as f64);
+
+        let mut bytes = Vec::new();
+        grid.write_binary(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), 24 + 6 * 8);
+
+        let copy = Grid::read_binary(&mut &bytes[..]).unwrap();
+        assert_eq!(copy.width(), 3);
+        assert_eq!(copy.height(), 2);
+        assert_eq!(*copy.get(2, 1), 1.5);
+        assert!(Grid::read_binary(&mut &bytes[1..]).is_err());
+    }
+
+    #[test]
+    fn views() {
+        use crate::geom::grid::Grid;
+        let mut grid = Grid::new(10, 8, 0);
+        grid.fill_xy(|x, y| 10 * x + y);
+
+        let view = grid.view(2, 3, 4, 2);
+        assert_eq!((view.width(), view.height()), (4, 2));
+        assert_eq!(*view.get(0, 0), 23);
+        assert_eq!(*view.get(3, 1), 54);
+        assert!(view.contains(3, 1) && !view.contains(4, 0));
+        let cells: Vec<_> = view.iter().cloned().collect();
+        assert_eq!(cells, vec![23, 24, 33, 34, 43, 44, 53, 54]);
+
+        let crop = grid.crop(2, 3, 4, 2);
+        assert_eq!(*crop.get(3, 1), 54);
+
+        let mut view = grid.view_mut(8, 6, 2, 2);
+        view.fill_xy(|_, _| 0);
+        view.set(1, 1, 1);
+        assert_eq!(view.as_view().iter().sum::<usize>(), 1);
+        assert_eq!(*grid.get(9, 7), 1);
+        assert_eq!(*grid.get(8, 6), 0);
+        assert_eq!(*grid.get(7, 6), 76);
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn ndarray() {
+        use crate::geom::grid::Grid;
+        use ndarray::Array2;
+
+        let array = Array2::from_shape_fn((3, 2), |(x, y)| 10 * x + y);
+        let grid = Grid::from_array2(array.view());
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(*grid.get(2, 1), 21);
+        assert_eq!(grid.to_array2(), array);
+
+        // Non-standard memory layout
+        let transposed = Grid::from_array2(array.t());
+        assert_eq!((transposed.width(), transposed.height()), (2, 3));
+        assert_eq!(*transposed.get(1, 2), 21);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        use crate::geom::grid::Grid;
+
+        let mut grid = Grid::new(3, 2, 0);
+        grid.set(2, 1, 5);
+        grid.track_changes();
+        let json = serde_json::to_string(&grid).unwrap();
+        assert_eq!(json, r#"{"width":3,"height":2,"data":[0,0,0,0,0,5]}"#);
+        let copy: Grid<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!((copy.width(), copy.height()), (3, 2));
+        assert_eq!(*copy.get(2, 1), 5);
+        assert!(!copy.tracks_changes());
+
+        let result: Result<Grid<i32>, _> =
+            serde_json::from_str(r#"{"width":3,"height":2,"data":[0,0,0]}"#);
+        assert!(result.is_err());
+        let result: Result<Grid<i32>, _> =
+            serde_json::from_str(r#"{"width":18446744073709551615,"height":2,"data":[]}"#);
+        assert!(result.is_err());
+    }
 }
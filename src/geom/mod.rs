@@ -1,3 +1,5 @@
 //! Representations for spatial and geometric data types
 
+pub mod graph;
 pub mod grid;
+pub mod proj;
@@ -0,0 +1,190 @@
+//! Map projections from geographic coordinates (longitude, latitude) to pixels
+
+/// Latitude limit of the Mercator projection, in degrees. Beyond, projected values grow to infinity.
+pub const MERCATOR_MAX_LAT: f64 = 85.051_128_78;
+
+/// A projection from longitude and latitude (in degrees) to planar coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Projection {
+    /// Longitude and latitude are used directly as planar coordinates (plate carrée).
+    Equirectangular,
+    /// Conformal cylindrical projection, as used by web maps. Latitudes are clamped to ±[`MERCATOR_MAX_LAT`](constant.MERCATOR_MAX_LAT.html).
+    Mercator,
+}
+
+impl Projection {
+    /// Projects longitude and latitude to planar coordinates, with y pointing north.
+    pub fn project(&self, lon: f64, lat: f64) -> (f64, f64) {
+        match self {
+            Projection::Equirectangular => (lon, lat),
+            Projection::Mercator => {
+                let lat = lat.clamp(-MERCATOR_MAX_LAT, MERCATOR_MAX_LAT).to_radians();
+                let y = (std::f64::consts::FRAC_PI_4 + lat / 2.0).tan().ln();
+                (lon, y.to_degrees())
+            }
+        }
+    }
+
+    /// Converts planar coordinates back to longitude and latitude.
+    pub fn unproject(&self, x: f64, y: f64) -> (f64, f64) {
+        match self {
+            Projection::Equirectangular => (x, y),
+            Projection::Mercator => {
+                let lat = 2.0 * y.to_radians().exp().atan() - std::f64::consts::FRAC_PI_2;
+                (x, lat.to_degrees())
+            }
+        }
+    }
+}
+
+/// A geographic bounding box, in degrees.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingBox {
+    pub min_lon: f64,
+    pub min_lat: f64,
+    pub max_lon: f64,
+    pub max_lat: f64,
+}
+
+impl BoundingBox {
+    /// Creates a bounding box from its corners.
+    pub fn new(min_lon: f64, min_lat: f64, max_lon: f64, max_lat: f64) -> Self {
+        BoundingBox {
+            min_lon,
+            min_lat,
+            max_lon,
+            max_lat,
+        }
+    }
+
+    /// Creates the smallest bounding box containing all (lon, lat) points. Returns `None` if there are no points.
+    pub fn from_points<I: IntoIterator<Item = (f64, f64)>>(points: I) -> Option<Self> {
+        points.into_iter().fold(None, |bbox, (lon, lat)| {
+            Some(match bbox {
+                None => BoundingBox::new(lon, lat, lon, lat),
+                Some(b) => BoundingBox::new(
+                    b.min_lon.min(lon),
+                    b.min_lat.min(lat),
+                    b.max_lon.max(lon),
+                    b.max_lat.max(lat),
+                ),
+            })
+        })
+    }
+
+    /// Returns a copy, extended on each side by a fraction of the box's size.
+    pub fn padded(&self, fraction: f64) -> Self {
+        let dx = (self.max_lon - self.min_lon) * fraction;
+        let dy = (self.max_lat - self.min_lat) * fraction;
+        BoundingBox::new(
+            self.min_lon - dx,
+            self.min_lat - dy,
+            self.max_lon + dx,
+            self.max_lat + dy,
+        )
+    }
+
+    /// Returns if the box contains the given point.
+    pub fn contains(&self, lon: f64, lat: f64) -> bool {
+        lon >= self.min_lon && lon <= self.max_lon && lat >= self.min_lat && lat <= self.max_lat
+    }
+}
+
+/// Maps geographic coordinates to the pixels of an area, e.g. a [`BufferWindow`](../../ui/window/struct.BufferWindow.html).
+///
+/// # Example
+/// ```
+/// use easy_graph::geom::proj::{BoundingBox, MapView, Projection};
+///
+/// let cities = vec![(13.4, 52.5), (2.35, 48.86), (-0.13, 51.5)];
+/// let bbox = BoundingBox::from_points(cities.iter().cloned()).unwrap().padded(0.1);
+/// let view = MapView::fit(Projection::Mercator, &bbox, (600, 400));
+///
+/// let (x, y) = view.to_pixel(13.4, 52.5);
+/// assert!(x > 300 && y < 200);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MapView {
+    projection: Projection,
+    center: (f64, f64),
+    scale: f64,
+    size: (u32, u32),
+}
+
+impl MapView {
+    /// Creates a view centered on a bounding box, scaled to fit it into an area of the given size in pixels.
+    /// The aspect ratio of the projection is preserved.
+    pub fn fit(projection: Projection, bbox: &BoundingBox, size: (u32, u32)) -> Self {
+        let (x0, y0) = projection.project(bbox.min_lon, bbox.min_lat);
+        let (x1, y1) = projection.project(bbox.max_lon, bbox.max_lat);
+        let scale_x = size.0 as f64 / (x1 - x0).abs();
+        let scale_y = size.1 as f64 / (y1 - y0).abs();
+        let scale = scale_x.min(scale_y);
+        MapView {
+            projection,
+            center: ((x0 + x1) / 2.0, (y0 + y1) / 2.0),
+            scale: if scale.is_finite() { scale } else { 1.0 },
+            size,
+        }
+    }
+
+    /// The projection used by this view.
+    pub fn projection(&self) -> Projection {
+        self.projection
+    }
+
+    /// Pixels per planar unit (degree for the equirectangular projection).
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Converts longitude and latitude to sub-pixel coordinates.
+    pub fn to_pixel_f64(&self, lon: f64, lat: f64) -> (f64, f64) {
+        let (x, y) = self.projection.project(lon, lat);
+        (
+            self.size.0 as f64 / 2.0 + (x - self.center.0) * self.scale,
+            self.size.1 as f64 / 2.0 - (y - self.center.1) * self.scale,
+        )
+    }
+
+    /// Converts longitude and latitude to pixel coordinates.
+    pub fn to_pixel(&self, lon: f64, lat: f64) -> (i32, i32) {
+        let (x, y) = self.to_pixel_f64(lon, lat);
+        (x.floor() as i32, y.floor() as i32)
+    }
+
+    /// Converts pixel coordinates to longitude and latitude, e.g. for mouse positions.
+    pub fn to_lon_lat(&self, px: f64, py: f64) -> (f64, f64) {
+        let x = self.center.0 + (px - self.size.0 as f64 / 2.0) / self.scale;
+        let y = self.center.1 - (py - self.size.1 as f64 / 2.0) / self.scale;
+        self.projection.unproject(x, y)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::geom::proj::{BoundingBox, MapView, Projection};
+
+    #[test]
+    fn projections() {
+        let merc = Projection::Mercator;
+        let (x, y) = merc.project(10.0, 0.0);
+        assert!(x == 10.0 && y.abs() < 1e-9);
+        let (_, y) = merc.project(0.0, 60.0);
+        assert!(y > 60.0);
+        let (lon, lat) = merc.unproject(10.0, y);
+        assert!((lon - 10.0).abs() < 1e-9 && (lat - 60.0).abs() < 1e-9);
+        assert!(merc.project(0.0, 90.0).1.is_finite());
+    }
+
+    #[test]
+    fn map_view() {
+        let bbox = BoundingBox::from_points(vec![(0.0, 0.0), (20.0, 10.0)]).unwrap();
+        assert!(bbox.contains(5.0, 5.0));
+        let view = MapView::fit(Projection::Equirectangular, &bbox, (400, 400));
+        assert_eq!(view.scale(), 20.0);
+        assert_eq!(view.to_pixel_f64(0.0, 10.0), (0.0, 100.0));
+        assert_eq!(view.to_pixel_f64(20.0, 0.0), (400.0, 300.0));
+        assert_eq!(view.to_lon_lat(200.0, 200.0), (10.0, 5.0));
+    }
+}
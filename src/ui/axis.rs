@@ -0,0 +1,482 @@
+//!
+//! Axis coordinate types used by [`Chart`](../chart/struct.Chart.html).
+//!
+
+use plotters::coord::{Ranged, RangedCoordf64, Shift};
+use plotters::prelude::*;
+use std::ops::Range;
+
+const MINUTE: f64 = 60.0;
+const HOUR: f64 = 60.0 * MINUTE;
+const DAY: f64 = 24.0 * HOUR;
+
+/// Fraction of the axis length left empty at an axis break.
+const BREAK_GAP: f64 = 0.04;
+
+/// Time steps for ticks of time axes, in seconds.
+const TIME_STEPS: [f64; 19] = [
+    1.0,
+    2.0,
+    5.0,
+    10.0,
+    15.0,
+    30.0,
+    MINUTE,
+    2.0 * MINUTE,
+    5.0 * MINUTE,
+    10.0 * MINUTE,
+    15.0 * MINUTE,
+    30.0 * MINUTE,
+    HOUR,
+    2.0 * HOUR,
+    3.0 * HOUR,
+    6.0 * HOUR,
+    12.0 * HOUR,
+    DAY,
+    2.0 * DAY,
+];
+
+/// The kind of an axis, determining how values are mapped to pixels and where ticks are placed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum AxisKind {
+    /// Linear axis.
+    Linear,
+    /// Logarithmic axis.
+    Log,
+    /// Linear axis of time in seconds since the Unix epoch (UTC), with ticks at full time units.
+    Time,
+    /// Linear axis with ticks at whole numbers only, e.g. for generations or category indices.
+    Integer,
+}
+
+/// A chart axis over `f64` values.
+#[derive(Clone)]
+pub(crate) struct ChartAxis {
+    min: f64,
+    max: f64,
+    kind: AxisKind,
+    inverted: bool,
+    gap: Option<(f64, f64)>,
+}
+
+impl ChartAxis {
+    pub fn new(range: Range<f64>, kind: AxisKind) -> Self {
+        ChartAxis {
+            min: range.start,
+            max: range.end,
+            kind,
+            inverted: false,
+            gap: None,
+        }
+    }
+    /// Sets the axis direction to be inverted, i.e. from `max` to `min`. Affects data as well as ticks.
+    pub fn with_inverted(mut self, inverted: bool) -> Self {
+        self.inverted = inverted;
+        self
+    }
+    /// Breaks the axis, leaving out the values from `gap.0` to `gap.1`.
+    ///
+    /// Both remaining sub-ranges get half of the axis length, separated by a small gap.
+    pub fn with_break(mut self, gap: Option<(f64, f64)>) -> Self {
+        self.gap = gap.filter(|(lo, hi)| self.min < *lo && lo < hi && *hi < self.max);
+        self
+    }
+    /// The pixel positions of the lower and upper edge of the break, if the axis is broken.
+    pub fn break_pixels(&self, limit: (i32, i32)) -> Option<(i32, i32)> {
+        self.gap
+            .map(|(lo, hi)| (self.map(&lo, limit), self.map(&hi, limit)))
+    }
+
+    /// Maps a value to its relative position along the axis, in the range 0 - 1.
+    pub fn fraction(&self, value: f64) -> f64 {
+        let transform = |v: f64| match self.kind {
+            AxisKind::Log => v.max(self.min).ln(),
+            AxisKind::Linear | AxisKind::Time | AxisKind::Integer => v,
+        };
+        let (value, min, max) = (transform(value), transform(self.min), transform(self.max));
+        match self.gap {
+            None => (value - min) / (max - min),
+            Some((lo, hi)) => {
+                let (lo, hi) = (transform(lo), transform(hi));
+                let half = 0.5 * (1.0 - BREAK_GAP);
+                if value <= lo {
+                    half * (value - min) / (lo - min)
+                } else if value >= hi {
+                    1.0 - half + half * (value - hi) / (max - hi)
+                } else {
+                    0.5
+                }
+            }
+        }
+    }
+
+    fn time_key_points(&self, max_points: usize) -> Vec<f64> {
+        let span = self.max - self.min;
+        let step = TIME_STEPS
+            .iter()
+            .find(|step| span / **step < max_points as f64);
+        match step {
+            Some(step) => {
+                let mut points = vec![];
+                let mut value = (self.min / step).ceil() * step;
+                while value <= self.max {
+                    points.push(value);
+                    value += step;
+                }
+                points
+            }
+            None => RangedCoordf64::from((self.min / DAY)..(self.max / DAY))
+                .key_points(max_points)
+                .iter()
+                .map(|days| days.round() * DAY)
+                .collect(),
+        }
+    }
+
+    /// Key points at whole numbers, with steps of 1, 2 or 5 times a power of ten.
+    fn integer_key_points(&self, max_points: usize) -> Vec<f64> {
+        let span = self.max - self.min;
+        if max_points == 0 || !span.is_finite() || span < 0.0 {
+            return vec![];
+        }
+        let mut step = 1.0;
+        let mut factors = [2.0, 2.5, 2.0].iter().cycle();
+        while span / step >= max_points as f64 {
+            step *= factors.next().unwrap();
+        }
+        let mut points = vec![];
+        let mut value = (self.min / step).ceil() * step;
+        while value <= self.max {
+            points.push(value);
+            value += step;
+        }
+        points
+    }
+}
+
+impl From<Range<f64>> for ChartAxis {
+    fn from(range: Range<f64>) -> Self {
+        ChartAxis::new(range, AxisKind::Linear)
+    }
+}
+
+impl Ranged for ChartAxis {
+    type ValueType = f64;
+
+    fn map(&self, value: &f64, limit: (i32, i32)) -> i32 {
+        let limit = if self.inverted {
+            (limit.1, limit.0)
+        } else {
+            limit
+        };
+        if limit.0 == limit.1 {
+            return limit.1;
+        }
+        let pixels = (limit.1 - limit.0) as f64;
+        limit.0 + (self.fraction(*value) * pixels + 1e-3).floor() as i32
+    }
+
+    fn key_points(&self, max_points: usize) -> Vec<f64> {
+        if let Some((lo, hi)) = self.gap {
+            let lower = ChartAxis::new(self.min..lo, self.kind).key_points(max_points / 2);
+            let upper = ChartAxis::new(hi..self.max, self.kind).key_points(max_points / 2);
+            return lower.into_iter().chain(upper).collect();
+        }
+        match self.kind {
+            AxisKind::Linear => RangedCoordf64::from(self.min..self.max).key_points(max_points),
+            AxisKind::Log => {
+                plotters::coord::LogCoord::from(plotters::coord::LogRange(self.min..self.max))
+                    .key_points(max_points)
+            }
+            AxisKind::Time => self.time_key_points(max_points),
+            AxisKind::Integer => self.integer_key_points(max_points),
+        }
+    }
+
+    fn range(&self) -> Range<f64> {
+        self.min..self.max
+    }
+}
+
+/// Formats a time in seconds since the Unix epoch (UTC), with a precision appropriate for the visible time span.
+pub(crate) fn format_time(time: f64, span: f64) -> String {
+    let secs = time.round() as i64;
+    let days = secs.div_euclid(DAY as i64);
+    let secs_of_day = secs.rem_euclid(DAY as i64);
+    let (year, month, day) = civil_from_days(days);
+    let (hour, minute, second) = (
+        secs_of_day / 3600,
+        (secs_of_day % 3600) / 60,
+        secs_of_day % 60,
+    );
+    if span >= 5.0 * DAY {
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    } else if span >= DAY {
+        format!("{:02}-{:02} {:02}:{:02}", month, day, hour, minute)
+    } else if span >= 10.0 * MINUTE {
+        format!("{:02}:{:02}", hour, minute)
+    } else {
+        format!("{:02}:{:02}:{:02}", hour, minute, second)
+    }
+}
+
+/// SI prefixes with their decimal exponents.
+const SI_PREFIXES: [(i32, &str); 9] = [
+    (-12, "p"),
+    (-9, "n"),
+    (-6, "µ"),
+    (-3, "m"),
+    (0, ""),
+    (3, "k"),
+    (6, "M"),
+    (9, "G"),
+    (12, "T"),
+];
+
+/// Selects the SI prefix for values up to the given magnitude. Returns the factor to divide values by, and the prefix.
+pub(crate) fn si_prefix(magnitude: f64) -> (f64, &'static str) {
+    if !magnitude.is_normal() {
+        return (1.0, "");
+    }
+    let exponent = (magnitude.abs().log10() / 3.0).floor() as i32 * 3;
+    let (exponent, prefix) = SI_PREFIXES
+        .iter()
+        .find(|(exp, _)| *exp == exponent.clamp(-12, 12))
+        .unwrap();
+    (10f64.powi(*exponent), prefix)
+}
+
+/// Formats a tick value, suppressing floating point noise.
+pub(crate) fn format_tick(value: f64) -> String {
+    format!("{}", (value * 1e9).round() / 1e9)
+}
+
+/// Shortens a label with an ellipsis until it fits into the given width, in pixels.
+pub(crate) fn truncate_label(label: String, width: f64, font: &FontDesc) -> String {
+    let fits = |text: &str| {
+        font.box_size(text)
+            .map(|(w, _)| w as f64 <= width)
+            .unwrap_or(true)
+    };
+    if fits(&label) {
+        return label;
+    }
+    let mut chars: Vec<char> = label.chars().collect();
+    while chars.pop().is_some() {
+        let text: String = chars.iter().chain(std::iter::once(&'…')).collect();
+        if fits(&text) {
+            return text;
+        }
+    }
+    "…".to_string()
+}
+
+/// Extents of a label of the given size, rotated by an angle in degrees.
+///
+/// Returns the horizontal distance required between neighbouring labels to not overlap, and the vertical extent.
+pub(crate) fn rotated_extent((width, height): (f64, f64), degrees: f64) -> (f64, f64) {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let (sin, cos) = (sin.abs(), cos.abs());
+    let spacing = if sin < 1e-6 {
+        width
+    } else if cos < 1e-6 {
+        height
+    } else {
+        (width / cos).min(height / sin)
+    };
+    (spacing, width * sin + height * cos)
+}
+
+/// Draws text rotated counter-clockwise by an angle in degrees, hanging below `pos`.
+///
+/// For positive angles, the text ends at `pos`, for negative angles it starts there, like tick labels of a rotated axis.
+pub(crate) fn draw_rotated_text<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    text: &str,
+    font: &FontDesc,
+    color: &RGBColor,
+    pos: (i32, i32),
+    degrees: f64,
+) {
+    let ((x0, y0), (x1, y1)) = match font.layout_box(text) {
+        Ok(layout) => layout,
+        Err(_) => return,
+    };
+    let (w, h) = ((x1 - x0).max(0) as usize + 1, (y1 - y0).max(0) as usize + 1);
+    let mut coverage = vec![0.0_f32; w * h];
+    let _ = font.draw(text, (-x0, -y0), |x, y, v| {
+        if x >= 0 && y >= 0 && (x as usize) < w && (y as usize) < h {
+            let cell = &mut coverage[y as usize * w + x as usize];
+            *cell = cell.max(v);
+        }
+        Ok::<(), ()>(())
+    });
+    let sample = |u: f64, v: f64| {
+        let (u, v) = (u - 0.5, v - 0.5);
+        let (iu, iv) = (u.floor(), v.floor());
+        let (fu, fv) = (u - iu, v - iv);
+        let at = |du: f64, dv: f64| {
+            let (x, y) = (iu + du, iv + dv);
+            if x < 0.0 || y < 0.0 || x >= w as f64 || y >= h as f64 {
+                0.0
+            } else {
+                coverage[y as usize * w + x as usize] as f64
+            }
+        };
+        at(0.0, 0.0) * (1.0 - fu) * (1.0 - fv)
+            + at(1.0, 0.0) * fu * (1.0 - fv)
+            + at(0.0, 1.0) * (1.0 - fu) * fv
+            + at(1.0, 1.0) * fu * fv
+    };
+
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    // Anchor at the middle of the text's end (or start), relative to the text's top left corner
+    let anchor = (if degrees > 0.0 { w as f64 } else { 0.0 }, h as f64 / 2.0);
+    let to_screen = |(u, v): (f64, f64)| {
+        let (du, dv) = (u - anchor.0, v - anchor.1);
+        (du * cos + dv * sin, -du * sin + dv * cos)
+    };
+    let (mut left, mut right, mut top, mut bottom) = (0.0_f64, 0.0_f64, 0.0_f64, 0.0_f64);
+    for corner in &[
+        (0.0, 0.0),
+        (w as f64, 0.0),
+        (0.0, h as f64),
+        (w as f64, h as f64),
+    ] {
+        let (x, y) = to_screen(*corner);
+        left = left.min(x.floor());
+        right = right.max(x.ceil());
+        top = top.min(y.floor());
+        bottom = bottom.max(y.ceil());
+    }
+
+    let (area_width, area_height) = area.dim_in_pixel();
+    for sy in top as i32..=bottom as i32 {
+        for sx in left as i32..=right as i32 {
+            let (cx, cy) = (sx as f64 + 0.5, sy as f64 + 0.5);
+            let u = anchor.0 + cx * cos - cy * sin;
+            let v = anchor.1 + cx * sin + cy * cos;
+            let alpha = sample(u, v);
+            let px = (pos.0 + sx, pos.1 + sy - top as i32);
+            let inside =
+                px.0 >= 0 && px.1 >= 0 && px.0 < area_width as i32 && px.1 < area_height as i32;
+            if alpha > 0.0 && inside {
+                area.draw_pixel(px, &color.mix(alpha.min(1.0))).unwrap();
+            }
+        }
+    }
+}
+
+/// Converts days since the Unix epoch to a (year, month, day) date.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::axis::{
+        format_tick, format_time, rotated_extent, si_prefix, truncate_label, AxisKind, ChartAxis,
+        DAY, HOUR,
+    };
+    use plotters::coord::Ranged;
+    use plotters::style::IntoFont;
+
+    #[test]
+    fn time_format() {
+        // 2020-03-14 15:09:26 UTC
+        let t = 1_584_198_566.0;
+        assert_eq!(format_time(t, 10.0 * DAY), "2020-03-14");
+        assert_eq!(format_time(t, 2.0 * DAY), "03-14 15:09");
+        assert_eq!(format_time(t, HOUR), "15:09");
+        assert_eq!(format_time(t, 60.0), "15:09:26");
+        assert_eq!(format_time(0.0, 10.0 * DAY), "1970-01-01");
+    }
+
+    #[test]
+    fn time_key_points() {
+        let axis = ChartAxis::new(100.0..(100.0 + 6.0 * HOUR), AxisKind::Time);
+        let points = axis.key_points(10);
+        assert_eq!(points.len(), 6);
+        assert!(points.iter().all(|p| p % HOUR == 0.0));
+    }
+
+    #[test]
+    fn integer_key_points() {
+        let axis = ChartAxis::new(-0.5..3.5, AxisKind::Integer);
+        assert_eq!(axis.key_points(10), vec![0.0, 1.0, 2.0, 3.0]);
+        let axis = ChartAxis::new(0.2..1.8, AxisKind::Integer);
+        assert_eq!(axis.key_points(10), vec![1.0]);
+        let axis = ChartAxis::new(3.0..997.0, AxisKind::Integer);
+        let points = axis.key_points(12);
+        assert_eq!(points[..3], [100.0, 200.0, 300.0]);
+        let axis = ChartAxis::new(0.0..40.0, AxisKind::Integer);
+        assert_eq!(axis.key_points(5), vec![0.0, 10.0, 20.0, 30.0, 40.0]);
+        assert!(axis.key_points(0).is_empty());
+    }
+
+    #[test]
+    fn si_prefixes() {
+        assert_eq!(si_prefix(0.0), (1.0, ""));
+        assert_eq!(si_prefix(999.0), (1.0, ""));
+        assert_eq!(si_prefix(1500.0), (1e3, "k"));
+        assert_eq!(si_prefix(-2.5e7), (1e6, "M"));
+        assert_eq!(si_prefix(0.004), (1e-3, "m"));
+        assert_eq!(si_prefix(1e20), (1e12, "T"));
+        assert_eq!(format_tick(0.7 / 1e-3), "700");
+    }
+
+    #[test]
+    fn inverted() {
+        let axis = ChartAxis::new(0.0..10.0, AxisKind::Linear);
+        assert_eq!(axis.map(&2.0, (0, 100)), 20);
+        let axis = axis.with_inverted(true);
+        assert_eq!(axis.map(&2.0, (0, 100)), 80);
+        assert_eq!(axis.map(&10.0, (100, 0)), 100);
+        let axis = ChartAxis::new(1.0..100.0, AxisKind::Log).with_inverted(true);
+        assert_eq!(axis.map(&10.0, (0, 100)), 50);
+        assert_eq!(axis.map(&1.0, (0, 100)), 100);
+    }
+
+    #[test]
+    fn axis_break() {
+        let axis = ChartAxis::new(0.0..1000.0, AxisKind::Linear).with_break(Some((10.0, 900.0)));
+        assert_eq!(axis.map(&0.0, (0, 100)), 0);
+        assert_eq!(axis.map(&5.0, (0, 100)), 24);
+        assert_eq!(axis.map(&500.0, (0, 100)), 50);
+        assert_eq!(axis.map(&950.0, (0, 100)), 76);
+        assert_eq!(axis.map(&1000.0, (0, 100)), 100);
+        assert_eq!(axis.break_pixels((0, 100)), Some((48, 52)));
+
+        let points = axis.key_points(10);
+        assert!(points.iter().all(|p| *p <= 10.0 || *p >= 900.0));
+        assert!(points.iter().any(|p| *p <= 10.0) && points.iter().any(|p| *p >= 900.0));
+
+        let axis = ChartAxis::new(0.0..10.0, AxisKind::Linear).with_break(Some((5.0, 20.0)));
+        assert_eq!(axis.break_pixels((0, 100)), None);
+    }
+
+    #[test]
+    fn rotated_labels() {
+        let (spacing, extent) = rotated_extent((40.0, 10.0), 0.0);
+        assert_eq!((spacing, extent), (40.0, 10.0));
+        let (spacing, extent) = rotated_extent((40.0, 10.0), 90.0);
+        assert!((spacing - 10.0).abs() < 1e-9 && (extent - 40.0).abs() < 1e-9);
+        let (spacing, _) = rotated_extent((40.0, 10.0), -45.0);
+        assert!((spacing - 10.0 * 2f64.sqrt()).abs() < 1e-9);
+
+        let font = ("sans-serif", 12.0).into_font();
+        assert_eq!(truncate_label("abc".to_string(), 100.0, &font), "abc");
+        let label = truncate_label("a long label".to_string(), 30.0, &font);
+        assert!(label.ends_with('…'));
+        assert!(font.box_size(&label).unwrap().0 <= 30);
+    }
+}
@@ -0,0 +1,290 @@
+//!
+//! A pan and zoom camera for exploring large spatial simulations in a [`BufferWindow`](../window/struct.BufferWindow.html).
+//!
+//! The camera transforms drawing coordinates from world pixels to window pixels,
+//! so that drawing code stays unchanged. See [`BufferWindow::draw_camera()`](../window/struct.BufferWindow.html#method.draw_camera).
+//!
+//! # Example
+//! ```no_run
+//! use easy_graph::ui::window::WindowBuilder;
+//! use easy_graph::ui::drawing::IntoDrawingArea;
+//! use easy_graph::ui::element::Circle;
+//! use easy_graph::color::style::{BLACK, WHITE};
+//!
+//! let mut win = WindowBuilder::new()
+//!     .with_dimensions(600, 400)
+//!     .with_camera_keys() // Arrows to pan, +/- to zoom, Home to reset
+//!     .build();
+//!
+//! for _ in 0..10 { // change upper limit for longer run!
+//!     win.draw_camera(|b| {
+//!         let root = b.into_drawing_area();
+//!         root.fill(&WHITE).unwrap();
+//!         for i in 0..100 {
+//!             root.draw(&Circle::new((i * 50, i * 20), 10, &BLACK)).unwrap();
+//!         }
+//!     });
+//! }
+//! ```
+//!
+
+use plotters::drawing::backend::{BackendCoord, BackendStyle, DrawingErrorKind};
+use plotters::drawing::DrawingBackend;
+use plotters::style::{FontDesc, RGBAColor, TextStyle};
+
+/// Minimum zoom factor.
+const MIN_ZOOM: f64 = 0.01;
+/// Maximum zoom factor.
+const MAX_ZOOM: f64 = 100.0;
+
+///
+/// A camera transform: the world position shown at the window's upper left corner, and a zoom factor.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    offset: (f64, f64),
+    zoom: f64,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Camera {
+    /// Creates an identity camera, with no offset and zoom factor 1.
+    pub fn new() -> Self {
+        Camera {
+            offset: (0.0, 0.0),
+            zoom: 1.0,
+        }
+    }
+    /// The world position shown at the window's upper left corner.
+    pub fn offset(&self) -> (f64, f64) {
+        self.offset
+    }
+    /// The zoom factor. Values above 1 magnify.
+    pub fn zoom(&self) -> f64 {
+        self.zoom
+    }
+    /// Sets the world position shown at the window's upper left corner.
+    pub fn set_offset(&mut self, offset: (f64, f64)) {
+        self.offset = offset;
+    }
+    /// Sets the zoom factor, clamped to the range 0.01 - 100.
+    pub fn set_zoom(&mut self, zoom: f64) {
+        self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+    /// Moves the view by the given number of window pixels.
+    pub fn pan(&mut self, dx: f64, dy: f64) {
+        self.offset.0 += dx / self.zoom;
+        self.offset.1 += dy / self.zoom;
+    }
+    /// Zooms by a factor, keeping the world position under the given window pixel fixed.
+    pub fn zoom_at(&mut self, factor: f64, px: (f64, f64)) {
+        let world = (
+            self.offset.0 + px.0 / self.zoom,
+            self.offset.1 + px.1 / self.zoom,
+        );
+        self.set_zoom(self.zoom * factor);
+        self.offset = (world.0 - px.0 / self.zoom, world.1 - px.1 / self.zoom);
+    }
+    /// Transforms a world position to window pixels.
+    pub fn to_screen(&self, xy: BackendCoord) -> BackendCoord {
+        (
+            ((xy.0 as f64 - self.offset.0) * self.zoom).round() as i32,
+            ((xy.1 as f64 - self.offset.1) * self.zoom).round() as i32,
+        )
+    }
+    /// Transforms window pixels to a world position, e.g. for mouse input.
+    pub fn to_world(&self, px: BackendCoord) -> (f64, f64) {
+        (
+            self.offset.0 + px.0 as f64 / self.zoom,
+            self.offset.1 + px.1 as f64 / self.zoom,
+        )
+    }
+}
+
+///
+/// A drawing backend that applies a [`Camera`](struct.Camera.html) to all coordinates before drawing to the wrapped backend.
+///
+/// Positions and sizes of shapes are transformed. Line widths, font sizes and bitmaps are not scaled.
+/// World coordinates are expected to be non-negative, as plotters clamps coordinates to the drawing area.
+///
+pub struct CameraBackend<DB: DrawingBackend> {
+    inner: DB,
+    camera: Camera,
+}
+
+impl<DB: DrawingBackend> CameraBackend<DB> {
+    /// Wraps a backend.
+    pub fn new(inner: DB, camera: Camera) -> Self {
+        CameraBackend { inner, camera }
+    }
+    /// The camera of this backend.
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+    fn scale(&self, size: u32) -> u32 {
+        (size as f64 * self.camera.zoom).round() as u32
+    }
+}
+
+impl<DB: DrawingBackend> DrawingBackend for CameraBackend<DB> {
+    type ErrorType = DB::ErrorType;
+
+    /// The size of the world that can be drawn to: the visible part, plus a margin of one window size
+    /// to prevent shapes beyond the visible part from being clamped into it.
+    fn get_size(&self) -> (u32, u32) {
+        let (width, height) = self.inner.get_size();
+        let extent = |size: u32, offset: f64| {
+            (offset.max(0.0) + 2.0 * size as f64 / self.camera.zoom).ceil() as u32
+        };
+        (
+            extent(width, self.camera.offset.0),
+            extent(height, self.camera.offset.1),
+        )
+    }
+    fn ensure_prepared(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.ensure_prepared()
+    }
+    fn present(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.present()
+    }
+    fn draw_pixel(
+        &mut self,
+        point: BackendCoord,
+        color: &RGBAColor,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let from = self.camera.to_screen(point);
+        if self.camera.zoom <= 1.0 {
+            self.inner.draw_pixel(from, color)
+        } else {
+            let to = self.camera.to_screen((point.0 + 1, point.1 + 1));
+            self.inner
+                .draw_rect(from, (to.0 - 1, to.1 - 1), color, true)
+        }
+    }
+    fn draw_line<S: BackendStyle>(
+        &mut self,
+        from: BackendCoord,
+        to: BackendCoord,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let (from, to) = (self.camera.to_screen(from), self.camera.to_screen(to));
+        self.inner.draw_line(from, to, style)
+    }
+    fn draw_rect<S: BackendStyle>(
+        &mut self,
+        upper_left: BackendCoord,
+        bottom_right: BackendCoord,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let upper_left = self.camera.to_screen(upper_left);
+        let bottom_right = self.camera.to_screen(bottom_right);
+        self.inner.draw_rect(upper_left, bottom_right, style, fill)
+    }
+    fn draw_path<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        path: I,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let camera = self.camera;
+        self.inner
+            .draw_path(path.into_iter().map(|p| camera.to_screen(p)), style)
+    }
+    fn draw_circle<S: BackendStyle>(
+        &mut self,
+        center: BackendCoord,
+        radius: u32,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let center = self.camera.to_screen(center);
+        let radius = self.scale(radius);
+        self.inner.draw_circle(center, radius, style, fill)
+    }
+    fn fill_polygon<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        vert: I,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let camera = self.camera;
+        self.inner
+            .fill_polygon(vert.into_iter().map(|p| camera.to_screen(p)), style)
+    }
+    fn draw_text(
+        &mut self,
+        text: &str,
+        style: &TextStyle,
+        pos: BackendCoord,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let pos = self.camera.to_screen(pos);
+        self.inner.draw_text(text, style, pos)
+    }
+    fn estimate_text_size<'a>(
+        &self,
+        text: &str,
+        font: &FontDesc<'a>,
+    ) -> Result<(u32, u32), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.estimate_text_size(text, font)
+    }
+    fn blit_bitmap(
+        &mut self,
+        pos: BackendCoord,
+        size: (u32, u32),
+        src: &[u8],
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let pos = self.camera.to_screen(pos);
+        self.inner.blit_bitmap(pos, size, src)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::camera::{Camera, CameraBackend};
+    use plotters::prelude::*;
+
+    #[test]
+    fn camera_transform() {
+        let mut camera = Camera::new();
+        camera.set_offset((10.0, 20.0));
+        camera.set_zoom(2.0);
+        assert_eq!(camera.to_screen((15, 20)), (10, 0));
+        assert_eq!(camera.to_world((10, 0)), (15.0, 20.0));
+
+        camera.pan(20.0, 0.0);
+        assert_eq!(camera.offset(), (20.0, 20.0));
+
+        let world = camera.to_world((50, 50));
+        camera.zoom_at(2.0, (50.0, 50.0));
+        assert_eq!(camera.zoom(), 4.0);
+        assert_eq!(camera.to_world((50, 50)), world);
+
+        camera.set_zoom(1e6);
+        assert_eq!(camera.zoom(), 100.0);
+    }
+
+    #[test]
+    fn camera_backend() {
+        let (w, h) = (100, 100);
+        let mut buffer = vec![0; 3 * w * h];
+        let mut camera = Camera::new();
+        camera.set_offset((100.0, 100.0));
+        camera.set_zoom(2.0);
+        {
+            let backend = BitMapBackend::with_buffer(&mut buffer, (w as u32, h as u32));
+            let root = CameraBackend::new(backend, camera).into_drawing_area();
+            root.fill(&WHITE).unwrap();
+            root.draw(&Rectangle::new([(110, 110), (120, 120)], RED.filled()))
+                .unwrap();
+        }
+        let pixel = |x: usize, y: usize| &buffer[3 * (y * w + x)..3 * (y * w + x) + 3];
+        assert_eq!(pixel(30, 30), [255, 0, 0]);
+        assert_eq!(pixel(15, 15), [255, 255, 255]);
+        assert_eq!(pixel(45, 45), [255, 255, 255]);
+        assert_eq!(pixel(99, 99), [255, 255, 255]);
+    }
+}
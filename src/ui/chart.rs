@@ -28,10 +28,45 @@
 //! ```
 //!
 
-use crate::ui::window::BufferWindow;
-use minifb::Scale;
+use crate::color::Palette as ColorPalette;
+use crate::color::{ColorMap, ColorVisionDeficiency};
+use crate::ui::axis::{
+    draw_rotated_text, format_tick, format_time, rotated_extent, si_prefix, truncate_label,
+    AxisKind, ChartAxis,
+};
+use crate::ui::input::KeyEvent;
+use crate::ui::layer::ImageLayer;
+use crate::ui::layout::Positioned;
+use crate::ui::menu::{ContextMenu, MenuAction};
+use crate::ui::metadata::{ImageMetadata, MetadataFormat, SeriesMetadata};
+use crate::ui::pattern::{circle_polygon, Pattern, PatternRect, PATTERN_SPACING};
+#[cfg(feature = "profiling")]
+use crate::ui::profile::{Profile, Stage};
+use crate::ui::record::{ChartRecorder, PushEvent};
+use crate::ui::retention::RetentionFile;
+use crate::ui::source::DataSource;
+use crate::ui::store::{Bucket, SeriesStore};
+#[cfg(feature = "http")]
+use crate::ui::stream::{series_to_json, FrameServer};
+use crate::ui::summary::{ChartSummary, SeriesSummary};
+use crate::ui::window::{into_io_error, BufferWindow};
+use minifb::{Key, KeyRepeat, MouseButton, Scale};
+#[cfg(feature = "ndarray")]
+use ndarray::{ArrayView1, ArrayView2};
+use plotters::chart::SeriesAnno;
+use plotters::coord::{Ranged, RangedCoord, Shift};
+use plotters::drawing::bitmap_pixel::RGBPixel;
 use plotters::prelude::*;
-use std::collections::VecDeque;
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
 ///
 /// Builder for [`Chart`](struct.Chart.html). See [`chart`](index.html) module docs for an example.
@@ -41,18 +76,92 @@ pub struct ChartBuilder {
     dim: (usize, usize),
     position: Option<(isize, isize)>,
     data: Vec<Series>,
+    ensembles: Vec<EnsembleSeries>,
     data_limit: Option<usize>,
+    retention: Option<String>,
     x_label: String,
     y_label: String,
     x_scale: f64,
     y_scale: f64,
     y_log: bool,
+    x_axis: XAxisType,
+    x_inverted: bool,
+    y_inverted: bool,
+    y_break: Option<((f64, f64), (f64, f64))>,
+    minimap: bool,
+    x_label_rotation: f64,
+    max_label_width: Option<u32>,
+    legend_values: bool,
+    legend_position: LegendPosition,
+    line_tolerance: Option<f64>,
+    y_unit: String,
+    y_si: bool,
+    ui_scale: f64,
+    mesh: MeshStyle,
     limits: AxisLimits,
+    y_auto_range: AutoRange,
+    range_frames: usize,
+    non_finite: NonFinite,
     max_fps: Option<f64>,
     fps_skip: Option<f64>,
+    scale: Scale,
+    frame_budget: Option<Duration>,
+    threaded: bool,
+    toggle_keys: bool,
+    pause_keys: bool,
+    context_menu: bool,
+    screenshot_key: Option<Key>,
+    screenshot_dir: String,
+    key_broadcast: Option<Sender<KeyEvent>>,
+    cvd_preview: Option<ColorVisionDeficiency>,
+    screenshot_metadata: MetadataFormat,
+    source: Option<Box<dyn DataSource>>,
+    recorder: Option<ChartRecorder>,
+    background: Option<ImageLayer>,
+    palette: Vec<RGBColor>,
+    #[cfg(feature = "http")]
+    http_address: Option<String>,
+    #[cfg(feature = "profiling")]
+    profile_overlay: bool,
+}
+
+impl Default for ChartBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ChartBuilder {
+    /// Creates a chart builder with the options of a preset. Options can be changed by further builder calls.
+    ///
+    /// ```
+    ///# use easy_graph::ui::chart::{ChartBuilder, Preset};
+    /// let mut chart = ChartBuilder::preset(Preset::Monitoring)
+    ///     .with_x_window(300.0)
+    ///     .build();
+    /// ```
+    pub fn preset(preset: Preset) -> Self {
+        let builder = Self::new()
+            .with_fps_skip(30.0)
+            .with_pause_keys()
+            .with_toggle_keys();
+        match preset {
+            Preset::TimeSeries => builder
+                .with_labels("Time", "Value")
+                .with_legend_position(LegendPosition::UpperLeft),
+            Preset::Scatter => builder
+                .with_data_limit(10_000)
+                .with_legend_position(LegendPosition::UpperRight),
+            Preset::Monitoring => builder
+                .with_labels("Time", "Value")
+                .with_x_window(60.0)
+                .with_data_limit(100_000)
+                .with_fps_skip(10.0)
+                .with_y_auto_range(AutoRange::Smoothed(0.1))
+                .with_legend_values()
+                .with_legend_position(LegendPosition::UpperLeft),
+        }
+    }
     /// Creates a default chart builder.
     pub fn new() -> Self {
         ChartBuilder {
@@ -60,15 +169,53 @@ impl ChartBuilder {
             dim: (600, 400),
             position: None,
             data: Vec::new(),
+            ensembles: Vec::new(),
             data_limit: None,
+            retention: None,
             x_label: "X".to_string(),
             y_label: "Y".to_string(),
             x_scale: 1.0,
             y_scale: 1.0,
             y_log: false,
+            x_axis: XAxisType::Numeric,
+            x_inverted: false,
+            y_inverted: false,
+            y_break: None,
+            minimap: false,
+            x_label_rotation: 0.0,
+            max_label_width: None,
+            legend_values: false,
+            legend_position: LegendPosition::MiddleRight,
+            line_tolerance: None,
+            y_unit: String::new(),
+            y_si: false,
+            ui_scale: 1.0,
+            mesh: MeshStyle::new(),
             limits: AxisLimits::empty(),
+            y_auto_range: AutoRange::Exact,
+            range_frames: 0,
+            non_finite: NonFinite::Skip,
             max_fps: None,
             fps_skip: None,
+            scale: Scale::X1,
+            frame_budget: None,
+            threaded: false,
+            toggle_keys: false,
+            pause_keys: false,
+            context_menu: false,
+            screenshot_key: Some(Key::F12),
+            screenshot_dir: ".".to_string(),
+            key_broadcast: None,
+            cvd_preview: None,
+            screenshot_metadata: MetadataFormat::None,
+            source: None,
+            recorder: None,
+            background: None,
+            palette: Vec::new(),
+            #[cfg(feature = "http")]
+            http_address: None,
+            #[cfg(feature = "profiling")]
+            profile_overlay: false,
         }
     }
     /// Adds a [Series](struct.Series.html) to the chart.
@@ -76,6 +223,11 @@ impl ChartBuilder {
         self.data.push(series);
         self
     }
+    /// Adds an [EnsembleSeries](struct.EnsembleSeries.html) to the chart, drawn behind ordinary series.
+    pub fn add_ensemble(mut self, series: EnsembleSeries) -> Self {
+        self.ensembles.push(series);
+        self
+    }
     /// Sets the chart's title.
     pub fn with_title(mut self, title: &str) -> Self {
         self.title = title.to_string();
@@ -123,6 +275,41 @@ impl ChartBuilder {
         self.limits.y_max = max;
         self
     }
+    /// Sets how automatic y axis limits follow the data. Default: [`AutoRange::Exact`](enum.AutoRange.html#variant.Exact).
+    ///
+    /// Only applies to limits that are not fixed by [`with_ylim()`](#method.with_ylim).
+    /// ```
+    ///# use easy_graph::ui::chart::{AutoRange, ChartBuilder};
+    /// let mut chart = ChartBuilder::new().with_y_auto_range(AutoRange::Smoothed(0.1)).build();
+    /// ```
+    pub fn with_y_auto_range(mut self, mode: AutoRange) -> Self {
+        self.y_auto_range = mode;
+        self
+    }
+    /// Animates changes of axis limits over the given number of rendered frames, instead of jumping to the new limits.
+    /// Applies to automatic limits as well as to limits set through [`Chart::set_xlim()`](struct.Chart.html#method.set_xlim)
+    /// and [`Chart::set_ylim()`](struct.Chart.html#method.set_ylim). Default: 0 (no animation).
+    ///
+    /// The x range is not animated in follow mode, see [`with_x_window()`](#method.with_x_window).
+    /// ```
+    ///# use easy_graph::ui::chart::ChartBuilder;
+    /// let mut chart = ChartBuilder::new().with_range_animation(8).build();
+    /// ```
+    pub fn with_range_animation(mut self, frames: usize) -> Self {
+        self.range_frames = frames;
+        self
+    }
+    /// Sets how points with infinite coordinates are rendered. Default: [`NonFinite::Skip`](enum.NonFinite.html#variant.Skip).
+    ///
+    /// Non-finite values never affect automatic axis limits. NaN values are always skipped.
+    /// ```
+    ///# use easy_graph::ui::chart::{ChartBuilder, NonFinite};
+    /// let mut chart = ChartBuilder::new().with_non_finite(NonFinite::Clamp).build();
+    /// ```
+    pub fn with_non_finite(mut self, mode: NonFinite) -> Self {
+        self.non_finite = mode;
+        self
+    }
     /// Sets the chart's x axis scale.
     ///
     /// Data is multiplied by this factor before plotting.
@@ -131,11 +318,149 @@ impl ChartBuilder {
         self.x_scale = x_scale;
         self
     }
+    /// Sets the unit of the chart's y axis, shown in brackets after the y label, like `Y [ind]`.
+    pub fn with_y_unit(mut self, unit: &str) -> Self {
+        self.y_unit = unit.to_string();
+        self
+    }
+    /// Scales y tick labels with SI prefixes (k, M, G, ...) chosen from the visible range, and adds the prefix to the y unit.
+    /// E.g. a range up to 25000 with unit "ind" is labeled 0 - 25 with axis label `Y [kind]`.
+    pub fn with_si_prefixes(mut self) -> Self {
+        self.y_si = true;
+        self
+    }
+    /// Sets the style of the chart's mesh, like grid line colors and label fonts.
+    ///
+    /// See [`MeshStyle`](struct.MeshStyle.html).
+    pub fn with_mesh_style(mut self, style: MeshStyle) -> Self {
+        self.mesh = style;
+        self
+    }
     /// Sets the chart's y axis to logarithmic.
     pub fn with_y_log(mut self) -> Self {
         self.y_log = true;
         self
     }
+    /// Sets the chart's x axis to a time axis.
+    ///
+    /// X values (after applying the x scale) are interpreted as seconds since the Unix epoch (UTC).
+    /// Ticks are placed at full time units, and labels are formatted as dates and/or times,
+    /// depending on the visible time span.
+    ///
+    /// E.g., for x values in days since the epoch:
+    /// ```
+    ///# use easy_graph::ui::chart::ChartBuilder;
+    /// let mut chart = ChartBuilder::new()
+    ///     .with_x_time()
+    ///     .with_x_scale(24.0 * 60.0 * 60.0)
+    ///     .build();
+    /// ```
+    pub fn with_x_time(mut self) -> Self {
+        self.x_axis = XAxisType::Time;
+        self
+    }
+    /// Sets the chart's x axis to integer ticks, e.g. for generations or simulation steps.
+    ///
+    /// Ticks are placed at whole numbers only, and labels are formatted without fractional digits.
+    pub fn with_x_integer(mut self) -> Self {
+        self.x_axis = XAxisType::Integer;
+        self
+    }
+    /// Sets the chart's x axis to discrete categories.
+    ///
+    /// X values are interpreted as indices into the categories, which are used as tick labels.
+    /// Also sets the x axis limits to show all categories, which can be overwritten by [`with_xlim()`](#method.with_xlim).
+    ///
+    /// E.g., for the population sizes of three species:
+    /// ```
+    ///# use easy_graph::ui::chart::{ChartBuilder, Series};
+    ///# use easy_graph::color::style::RED;
+    /// let mut chart = ChartBuilder::new()
+    ///     .with_x_categories(&["Wolves", "Sheep", "Grass"])
+    ///     .add_series(Series::point("Count", &RED))
+    ///     .build_headless();
+    /// chart.push_xy(0, (0.0, 12.0));
+    /// chart.push_xy(0, (1.0, 240.0));
+    /// chart.push_xy(0, (2.0, 1800.0));
+    /// ```
+    pub fn with_x_categories(mut self, categories: &[&str]) -> Self {
+        self.limits.x_min = Some(-0.5);
+        self.limits.x_max = Some(categories.len() as f64 - 0.5);
+        self.x_axis = XAxisType::Categories(categories.iter().map(|c| c.to_string()).collect());
+        self
+    }
+    /// Sets the type of the chart's x axis. See also [`with_x_time()`](#method.with_x_time),
+    /// [`with_x_integer()`](#method.with_x_integer) and [`with_x_categories()`](#method.with_x_categories).
+    pub fn with_x_axis_type(mut self, axis: XAxisType) -> Self {
+        self.x_axis = axis;
+        self
+    }
+    /// Inverts the chart's x axis, with values increasing to the left.
+    pub fn with_x_inverted(mut self) -> Self {
+        self.x_inverted = true;
+        self
+    }
+    /// Inverts the chart's y axis, with values increasing downwards, e.g. for depth profiles.
+    /// ```
+    ///# use easy_graph::ui::chart::ChartBuilder;
+    /// let mut chart = ChartBuilder::new()
+    ///     .with_labels("Temperature", "Depth")
+    ///     .with_y_inverted()
+    ///     .build();
+    /// ```
+    pub fn with_y_inverted(mut self) -> Self {
+        self.y_inverted = true;
+        self
+    }
+    /// Breaks the chart's y axis into a lower and an upper range, separated by a break marker.
+    ///
+    /// Both ranges get half of the plotting area's height, and the y axis range is fixed to cover both.
+    /// Useful for data with a cluster of small values and a few large outliers.
+    /// ```
+    ///# use easy_graph::ui::chart::ChartBuilder;
+    /// let mut chart = ChartBuilder::new()
+    ///     .with_y_break((0.0, 10.0), (900.0, 1000.0))
+    ///     .build();
+    /// ```
+    pub fn with_y_break(mut self, low_range: (f64, f64), high_range: (f64, f64)) -> Self {
+        self.y_break = Some((low_range, high_range));
+        self
+    }
+    /// Shows an overview inset of all data, with a rectangle for the visible range, while not all data is visible,
+    /// e.g. due to axis limits or [`with_x_window()`](#method.with_x_window).
+    ///
+    /// Clicking or dragging in the inset moves the visible range there, with fixed x limits.
+    pub fn with_minimap(mut self) -> Self {
+        self.minimap = true;
+        self
+    }
+    /// Rotates the x tick labels counter-clockwise by the given angle in degrees, e.g. 45 for long timestamps.
+    ///
+    /// With positive angles, labels end at their tick, with negative angles they start there.
+    /// The label area grows to fit the rotated labels. Ticks are thinned out when labels would overlap.
+    pub fn with_x_label_rotation(mut self, degrees: f64) -> Self {
+        self.x_label_rotation = degrees;
+        self
+    }
+    /// Sets the maximum width of x tick labels in pixels. Longer labels are shortened with an ellipsis.
+    ///
+    /// Ticks are thinned out when labels would overlap, so limiting long labels allows for denser ticks.
+    pub fn with_max_label_width(mut self, width: u32) -> Self {
+        self.max_label_width = Some(width);
+        self
+    }
+    /// Shows the latest value of each series in its legend entry, e.g. "Infected: 1,204".
+    ///
+    /// Series with an own legend format keep it, see [`Series::with_legend_format()`](struct.Series.html#method.with_legend_format).
+    pub fn with_legend_values(mut self) -> Self {
+        self.legend_values = true;
+        self
+    }
+    /// Sets the position of the legend in the plotting area. Default: [`LegendPosition::MiddleRight`](enum.LegendPosition.html#variant.MiddleRight).
+    pub fn with_legend_position(mut self, position: LegendPosition) -> Self {
+        self.legend_position = position;
+        self
+    }
     /// Sets the chart's y axis scale.
     ///
     /// Data is multiplied by this factor before plotting.
@@ -144,6 +469,11 @@ impl ChartBuilder {
         self.y_scale = y_scale;
         self
     }
+    /// Scales font sizes, marker sizes, line widths and label areas, e.g. for high-resolution screens. Default 1.0.
+    pub fn with_ui_scale(mut self, scale: f64) -> Self {
+        self.ui_scale = scale;
+        self
+    }
     /// Sets the chart's FPS limit. Slows down the process updating the chart.
     ///
     /// The chart's update() method will block to achieve the FPS limit.
@@ -158,6 +488,124 @@ impl ChartBuilder {
         self.fps_skip = Some(max_fps);
         self
     }
+    /// Renders the chart on a background thread.
+    ///
+    /// The chart's update() method only takes a snapshot of the data and hands it over to the render thread,
+    /// and presents the most recently finished frame. Thus, the displayed frame may lag one update behind.
+    /// New frames are only submitted when the previous one is finished.
+    pub fn with_threaded_rendering(mut self) -> Self {
+        self.threaded = true;
+        self
+    }
+    /// Enables adaptive rendering quality. When rendering a frame takes longer than the budget,
+    /// the chart draws fewer points of line and point series and ensembles, and finally omits mesh lines.
+    /// Full quality is restored when rendering is fast again.
+    ///
+    /// See [`Chart::quality_level()`](struct.Chart.html#method.quality_level).
+    pub fn with_frame_budget(mut self, budget: Duration) -> Self {
+        self.frame_budget = Some(budget);
+        self
+    }
+    /// Simplifies line series before drawing, dropping points that deviate less than the tolerance (in pixels) from the drawn line.
+    ///
+    /// Speeds up drawing of dense series with many sub-pixel segments. A tolerance of about 0.5 is visually lossless.
+    /// Stored data is not changed. Line series with a color map are not simplified.
+    pub fn with_line_simplification(mut self, tolerance: f64) -> Self {
+        self.line_tolerance = Some(tolerance);
+        self
+    }
+    /// Enables toggling series visibility with the number keys 1-9 and 0 (for the first 10 series).
+    pub fn with_toggle_keys(mut self) -> Self {
+        self.toggle_keys = true;
+        self
+    }
+    /// Enables pausing and resuming with the space key, and single-stepping with the right arrow key.
+    ///
+    /// See [`Chart::pause()`](struct.Chart.html#method.pause).
+    pub fn with_pause_keys(mut self) -> Self {
+        self.pause_keys = true;
+        self
+    }
+    /// Enables a context menu, opened by right-clicking into the chart's window, with the actions
+    /// "Save PNG", "Export CSV", "Clear data" and "Reset zoom".
+    ///
+    /// PNG and CSV files are saved to the screenshot directory, see [`with_screenshot_dir()`](#method.with_screenshot_dir).
+    /// Failures to save are reported by [`Chart::take_error()`](struct.Chart.html#method.take_error).
+    /// Add own actions with [`Chart::add_menu_action()`](struct.Chart.html#method.add_menu_action).
+    pub fn with_context_menu(mut self) -> Self {
+        self.context_menu = true;
+        self
+    }
+    /// Sets the hotkey for saving screenshots. Use `None` to disable. Default: `F12`.
+    ///
+    /// See [`BufferWindow::save_screenshot()`](../window/struct.BufferWindow.html#method.save_screenshot).
+    /// Failures to save are reported by [`Chart::take_error()`](struct.Chart.html#method.take_error).
+    pub fn with_screenshot_key(mut self, key: Option<Key>) -> Self {
+        self.screenshot_key = key;
+        self
+    }
+    /// Sets the directory to save screenshots to. Default: the current working directory.
+    pub fn with_screenshot_dir(mut self, dir: &str) -> Self {
+        self.screenshot_dir = dir.to_string();
+        self
+    }
+    /// Writes metadata with screenshots, like axis ranges and series names, as a JSON sidecar and/or PNG text chunks.
+    /// Default: `MetadataFormat::None`.
+    ///
+    /// See module [`metadata`](../metadata/index.html) and [`Chart::image_metadata()`](struct.Chart.html#method.image_metadata).
+    pub fn with_screenshot_metadata(mut self, format: MetadataFormat) -> Self {
+        self.screenshot_metadata = format;
+        self
+    }
+    /// Sends all key presses in the chart's window to a shared channel, e.g. of an [`InputGroup`](../input/struct.InputGroup.html).
+    ///
+    /// See [`WindowBuilder::with_key_broadcast()`](../window/struct.WindowBuilder.html#method.with_key_broadcast).
+    pub fn with_key_broadcast(mut self, sender: Sender<KeyEvent>) -> Self {
+        self.key_broadcast = Some(sender);
+        self
+    }
+    /// Previews the chart as seen with a color vision deficiency, e.g. to check whether series remain distinguishable.
+    ///
+    /// See [`BufferWindow::set_cvd_preview()`](../window/struct.BufferWindow.html#method.set_cvd_preview).
+    pub fn with_cvd_preview(mut self, cvd: ColorVisionDeficiency) -> Self {
+        self.cvd_preview = Some(cvd);
+        self
+    }
+    /// Sets the palette for series without an explicit color, like [`Series::line_auto()`](struct.Series.html#method.line_auto).
+    /// Colors are assigned in the order series are added, cycling through the palette.
+    ///
+    /// Default: [`Palette::categorical()`](../../color/struct.Palette.html#method.categorical).
+    ///
+    /// # Panics
+    /// Panics if the palette is empty.
+    pub fn with_palette(mut self, colors: &[&RGBColor]) -> Self {
+        if colors.is_empty() {
+            panic!("Palette requires at least one color!");
+        }
+        self.palette = colors.iter().map(|c| RGBColor(c.0, c.1, c.2)).collect();
+        self
+    }
+    /// Sets an image drawn as background of the chart's plotting area, scaled to fit.
+    ///
+    /// For best quality, provide an image that is already resized to the plotting area.
+    pub fn with_background_image(mut self, image: ImageLayer) -> Self {
+        self.background = Some(image);
+        self
+    }
+    /// Sets a data source that is polled on each update of the chart, adding a time series entry per poll.
+    ///
+    /// See module [`source`](../source/index.html) for details.
+    pub fn with_data_source<S: DataSource + 'static>(mut self, source: S) -> Self {
+        self.source = Some(Box::new(source));
+        self
+    }
+    /// Sets a recorder that logs all data pushed into the chart, for replaying it later.
+    ///
+    /// See module [`record`](../record/index.html) for details.
+    pub fn with_recorder(mut self, recorder: ChartRecorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
     /// Sets the chart's data limit.
     /// For each series, when the given number of enties is exceeded, entries are dropped from the front of the series.
     ///
@@ -166,6 +614,51 @@ impl ChartBuilder {
         self.data_limit = Some(max_values);
         self
     }
+    /// Retains entries trimmed by data limits in a file per series in the given directory, instead of dropping them.
+    /// The directory is created if it does not exist.
+    ///
+    /// See module [`retention`](../retention/index.html) for details.
+    ///
+    /// # Panics
+    /// [`build()`](#method.build) panics if the retention files can't be created.
+    pub fn with_retention(mut self, dir: &str) -> Self {
+        self.retention = Some(dir.to_string());
+        self
+    }
+    /// Makes the x axis follow the data: the visible x range is always the last `width` units of data, and the y range is fitted to the visible data.
+    ///
+    /// In contrast to [`with_data_limit()`](#method.with_data_limit), older data is retained, e.g. for export.
+    /// Overrides the lower x limit. Assumes that x values increase within each series.
+    pub fn with_x_window(mut self, width: f64) -> Self {
+        self.limits.x_window = Some(width);
+        self
+    }
+    /// Serves the chart's frames and series data over HTTP at the given address, e.g. `"127.0.0.1:8080"`.
+    ///
+    /// Requires feature `http`. See module [`stream`](../stream/index.html) for details.
+    ///
+    /// # Panics
+    /// [`build()`](#method.build) panics if the server can't listen on the address.
+    #[cfg(feature = "http")]
+    pub fn with_http_server(mut self, address: &str) -> Self {
+        self.http_address = Some(address.to_string());
+        self
+    }
+    /// Shows the timings of the chart's update stages in the upper left corner of the plotting area.
+    ///
+    /// Shows the timings of the previous frame. Requires feature `profiling`. See module [`profile`](../profile/index.html) for details.
+    #[cfg(feature = "profiling")]
+    pub fn with_profile_overlay(mut self) -> Self {
+        self.profile_overlay = true;
+        self
+    }
+    /// Sets the chart window's scale factor. The window's size is the chart's dimensions times the scale factor.
+    ///
+    /// Marker sizes are given in screen pixels, and are divided by the scale factor to prevent blocky markers.
+    pub fn with_scale(mut self, scale: Scale) -> Self {
+        self.scale = scale;
+        self
+    }
     /// Sets the dimensions of the chart in screen pixels.
     pub fn with_dimensions(mut self, width: usize, height: usize) -> Self {
         self.dim = (width, height);
@@ -178,32 +671,409 @@ impl ChartBuilder {
     }
     /// Builds the chart.
     pub fn build(self) -> Chart {
-        let mut win = Chart::new(
+        let window = BufferWindow::new(
             &self.title,
             self.dim,
-            self.data,
             self.max_fps,
             self.fps_skip,
+            self.scale,
+            true,
         );
-        win.x_scale = self.x_scale;
-        win.y_scale = self.y_scale;
-        win.y_log = self.y_log;
-        win.x_label = self.x_label;
-        win.y_label = self.y_label;
+        self.build_with(window)
+    }
+    /// Builds a headless chart, which renders only to its window's buffer, without opening a platform window.
+    ///
+    /// E.g. for embedding charts into other applications, see field [`BufferWindow::buffer_u8`](../window/struct.BufferWindow.html#structfield.buffer_u8).
+    /// Headless charts have no keyboard or mouse input.
+    pub fn build_headless(self) -> Chart {
+        let window = BufferWindow::headless(&self.title, self.dim, self.fps_skip);
+        self.build_with(window)
+    }
+    fn build_with(self, window: BufferWindow) -> Chart {
+        let mut win = Chart::new(window, self.data);
+        win.config.x_scale = self.x_scale;
+        win.config.y_scale = self.y_scale;
+        win.config.y_log = self.y_log;
+        win.config.non_finite = self.non_finite;
+        win.config.x_axis = self.x_axis;
+        win.config.x_inverted = self.x_inverted;
+        win.config.y_inverted = self.y_inverted;
+        win.config.y_break = self.y_break;
+        win.config.minimap = self.minimap;
+        win.config.x_label_rotation = self.x_label_rotation;
+        win.config.max_label_width = self.max_label_width;
+        win.config.legend_values = self.legend_values;
+        win.config.legend_position = self.legend_position;
+        win.config.line_tolerance = self.line_tolerance;
+        win.config.y_unit = self.y_unit;
+        win.config.y_si = self.y_si;
+        win.config.ui_scale = self.ui_scale;
+        win.config.mesh = self.mesh;
+        win.config.x_follow = self.limits.x_window.is_some();
+        win.palette = self.palette;
+        for index in 0..win.data.len() {
+            win.assign_color(index);
+        }
+        win.config.x_label = self.x_label;
+        win.config.y_label = self.y_label;
+        win.config.background = self.background.map(Arc::new);
+        if let Some(source) = self.source {
+            win.source = Some((source, Instant::now()));
+        }
+        win.recorder = self.recorder;
+        if self.threaded {
+            win.renderer = Some(RenderThread::new(self.dim));
+        }
+        win.ensembles = self.ensembles;
         win.data_limit = self.data_limit;
+        win.frame_budget = self.frame_budget;
+        win.toggle_keys = self.toggle_keys;
+        win.pause_keys = self.pause_keys;
+        win.set_context_menu(self.context_menu);
+        win.home_limits = self.limits.clone();
         win.limits = self.limits;
+        win.y_auto_range = self.y_auto_range;
+        win.range_frames = self.range_frames;
+        if let Some(dir) = &self.retention {
+            win.set_retention(Some(dir.as_str())).unwrap_or_else(|err| {
+                panic!("Unable to create retention files: {}", err);
+            });
+        }
 
         if let Some(pos) = self.position {
             win.window.set_position(pos);
         }
+        win.window.set_screenshot_key(self.screenshot_key);
+        win.window.set_screenshot_dir(&self.screenshot_dir);
+        win.window.set_screenshot_metadata(self.screenshot_metadata);
+        win.window.set_key_broadcast(self.key_broadcast);
+        win.window.set_cvd_preview(self.cvd_preview);
+        #[cfg(feature = "http")]
+        {
+            win.server = self.http_address.map(|address| {
+                FrameServer::start(&address[..]).unwrap_or_else(|e| {
+                    panic!("Unable to start HTTP server at {}: {}", address, e);
+                })
+            });
+        }
+        #[cfg(feature = "profiling")]
+        win.set_profile_overlay(self.profile_overlay);
         win
     }
 }
 
 /// The type of [`Series`](struct.Series.html) for [`Chart`](struct.Chart.html)s, like Point or Line. Different types can be mixed in the same chart.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum SeriesType {
     Point,
     Line,
+    /// Line segments between pairs of points, see [`Chart::push_segment()`](struct.Chart.html#method.push_segment).
+    Segment,
+    /// Like `Segment`, with an arrow head at the end of each segment.
+    Arrow,
+}
+
+///
+/// Styling of a chart's mesh: grid lines, axis lines and tick labels. See [`ChartBuilder::with_mesh_style()`](struct.ChartBuilder.html#method.with_mesh_style).
+///
+/// # Example
+/// ```no_run
+/// use easy_graph::ui::chart::{ChartBuilder, MeshStyle};
+/// use easy_graph::color::style::{BLUE, RGBColor};
+///
+/// let mut chart = ChartBuilder::new()
+///     .with_mesh_style(
+///         MeshStyle::new()
+///             .without_light_lines()
+///             .with_bold_lines(&BLUE, 0.3)
+///             .with_axis_color(&RGBColor(80, 80, 80))
+///             .with_label_font("serif", 14.0),
+///     )
+///     .build();
+/// ```
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeshStyle {
+    bold: Option<((u8, u8, u8), f64)>,
+    light: Option<((u8, u8, u8), f64)>,
+    axis: (u8, u8, u8),
+    label_color: (u8, u8, u8),
+    label_font: Option<(String, f64)>,
+}
+
+impl Default for MeshStyle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MeshStyle {
+    /// Creates the default mesh style: black grid lines with opacity 0.2 (bold) and 0.1 (light), black axes and labels.
+    pub fn new() -> Self {
+        MeshStyle {
+            bold: Some(((0, 0, 0), 0.2)),
+            light: Some(((0, 0, 0), 0.1)),
+            axis: (0, 0, 0),
+            label_color: (0, 0, 0),
+            label_font: None,
+        }
+    }
+    /// Sets the color and opacity (0 - 1) of bold grid lines, at labeled ticks.
+    pub fn with_bold_lines(mut self, color: &RGBColor, alpha: f64) -> Self {
+        self.bold = Some(((color.0, color.1, color.2), alpha));
+        self
+    }
+    /// Sets the color and opacity (0 - 1) of light grid lines, between labeled ticks.
+    pub fn with_light_lines(mut self, color: &RGBColor, alpha: f64) -> Self {
+        self.light = Some(((color.0, color.1, color.2), alpha));
+        self
+    }
+    /// Omits light grid lines, for a less noisy mesh.
+    pub fn without_light_lines(mut self) -> Self {
+        self.light = None;
+        self
+    }
+    /// Omits all grid lines. Axes and labels are still drawn.
+    pub fn without_lines(mut self) -> Self {
+        self.bold = None;
+        self.light = None;
+        self
+    }
+    /// Sets the color of axis lines.
+    pub fn with_axis_color(mut self, color: &RGBColor) -> Self {
+        self.axis = (color.0, color.1, color.2);
+        self
+    }
+    /// Sets the color of tick labels and axis descriptions.
+    pub fn with_label_color(mut self, color: &RGBColor) -> Self {
+        self.label_color = (color.0, color.1, color.2);
+        self
+    }
+    /// Sets the font family (like "sans-serif" or "serif") and size in pixels of tick labels.
+    /// Default: sans-serif, size relative to the chart.
+    pub fn with_label_font(mut self, family: &str, size: f64) -> Self {
+        self.label_font = Some((family.to_string(), size));
+        self
+    }
+}
+
+/// Smoothing of line series, see [`Series::with_smoothing()`](struct.Series.html#method.with_smoothing).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Smoothing {
+    /// Straight segments between points.
+    None,
+    /// Catmull-Rom spline through all points. May overshoot between points.
+    CatmullRom,
+    /// Monotone cubic interpolation (Fritsch-Carlson). Does not overshoot, but requires increasing x values.
+    /// Falls back to straight segments otherwise.
+    Monotone,
+}
+
+/// Shape of density cells, see [`Density`](struct.Density.html).
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DensityShape {
+    Square,
+    Hex,
+}
+
+///
+/// Thumbnails of rendered frames, see [`Chart::snapshot_every()`](struct.Chart.html#method.snapshot_every).
+struct Snapshots {
+    every: usize,
+    width: u32,
+    updates: usize,
+    images: Vec<image::RgbImage>,
+}
+impl Snapshots {
+    fn new(every: usize) -> Self {
+        Snapshots {
+            every,
+            width: 240,
+            updates: 0,
+            images: Vec::new(),
+        }
+    }
+}
+
+/// Gap between thumbnails of a contact sheet, in pixels.
+const CONTACT_SHEET_GAP: u32 = 4;
+
+/// Tiles images row by row into one image, on white background. `None` if there are no images.
+fn contact_sheet(images: &[image::RgbImage], columns: usize) -> Option<image::RgbImage> {
+    let columns = columns.clamp(1, images.len().max(1));
+    let rows = images.len().div_ceil(columns);
+    let width = images.iter().map(|img| img.width()).max()?;
+    let height = images.iter().map(|img| img.height()).max()?;
+    let gap = CONTACT_SHEET_GAP;
+    let mut sheet = image::RgbImage::from_pixel(
+        columns as u32 * (width + gap) + gap,
+        rows as u32 * (height + gap) + gap,
+        image::Rgb([255, 255, 255]),
+    );
+    for (i, img) in images.iter().enumerate() {
+        let x = gap + (i % columns) as u32 * (width + gap);
+        let y = gap + (i / columns) as u32 * (height + gap);
+        image::imageops::replace(&mut sheet, img, x, y);
+    }
+    Some(sheet)
+}
+
+/// Values and color map of a line series colored per segment, see [`Series::with_color_map()`](struct.Series.html#method.with_color_map).
+#[derive(Clone)]
+struct SegmentColors {
+    color_map: Arc<dyn ColorMap + Send + Sync>,
+    range: Option<(f64, f64)>,
+    values: VecDeque<f64>,
+}
+impl SegmentColors {
+    /// The value range mapped to colors: the fixed range if set, otherwise the range of finite values.
+    fn value_range(&self) -> (f64, f64) {
+        if let Some(range) = self.range {
+            return range;
+        }
+        let (min, max) = self
+            .values
+            .iter()
+            .filter(|v| v.is_finite())
+            .fold((f64::MAX, f64::MIN), |(min, max), v| {
+                (min.min(*v), max.max(*v))
+            });
+        if min > max {
+            (0.0, 1.0)
+        } else if min == max {
+            (min - 0.5, max + 0.5)
+        } else {
+            (min, max)
+        }
+    }
+    /// Maps a value to a color, clamped to the range.
+    fn color(&self, value: f64, range: (f64, f64)) -> RGBColor {
+        let norm = ((value - range.0) / (range.1 - range.0)).clamp(0.0, 1.0);
+        self.color_map.get_color_norm(norm)
+    }
+}
+
+/// Density rendering of point series, see [`Series::with_density()`](struct.Series.html#method.with_density).
+///
+/// Bins points into a grid of cells in screen space, and colors cells by their point count, relative to the fullest cell.
+/// Use e.g. [`ColorMap::with_gamma()`](../../color/trait.ColorMap.html#method.with_gamma) to emphasize sparse cells.
+///
+/// # Example
+/// ```
+/// use easy_graph::ui::chart::{Density, Series};
+/// use easy_graph::color::{ColorMap, LinearColorMap};
+/// use easy_graph::color::style::{BLUE, RED, WHITE};
+///
+/// let map = LinearColorMap::new(&[&WHITE, &BLUE, &RED]).with_gamma(0.5);
+/// let series = Series::point("Particles", &BLUE)
+///     .with_density(Density::hexbin(map).with_cell_size(10).with_threshold(5000));
+/// ```
+#[derive(Clone)]
+pub struct Density {
+    shape: DensityShape,
+    cell_size: u32,
+    threshold: usize,
+    color_map: Arc<dyn ColorMap + Send + Sync>,
+}
+
+impl Density {
+    /// Square cells, i.e. a 2D histogram.
+    pub fn histogram<M: ColorMap + Send + Sync + 'static>(color_map: M) -> Self {
+        Self::new(DensityShape::Square, color_map)
+    }
+    /// Hexagonal cells.
+    pub fn hexbin<M: ColorMap + Send + Sync + 'static>(color_map: M) -> Self {
+        Self::new(DensityShape::Hex, color_map)
+    }
+    fn new<M: ColorMap + Send + Sync + 'static>(shape: DensityShape, color_map: M) -> Self {
+        Density {
+            shape,
+            cell_size: 8,
+            threshold: 10_000,
+            color_map: Arc::new(color_map),
+        }
+    }
+    /// Sets the width of cells in screen pixels. Default: 8.
+    ///
+    /// Scaled by the chart's UI scale, and divided by the window's scale factor, like marker radii.
+    pub fn with_cell_size(mut self, size: u32) -> Self {
+        self.cell_size = size.max(1);
+        self
+    }
+    /// Sets the number of visible points above which density rendering is used, instead of markers. Default: 10 000.
+    pub fn with_threshold(mut self, points: usize) -> Self {
+        self.threshold = points;
+        self
+    }
+
+    /// The cell of a pixel, for the given cell width in pixels.
+    fn cell(&self, px: (i32, i32), size: f64) -> (i32, i32) {
+        let (x, y) = (px.0 as f64, px.1 as f64);
+        match self.shape {
+            DensityShape::Square => ((x / size).floor() as i32, (y / size).floor() as i32),
+            DensityShape::Hex => {
+                // Axial coordinates of pointy-top hexagons, rounded via cube coordinates
+                let radius = size / 3_f64.sqrt();
+                let q = (3_f64.sqrt() / 3.0 * x - y / 3.0) / radius;
+                let r = (2.0 / 3.0 * y) / radius;
+                let s = -q - r;
+                let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+                let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+                if dq > dr && dq > ds {
+                    rq = -rr - rs;
+                } else if dr > ds {
+                    rr = -rq - rs;
+                }
+                (rq as i32, rr as i32)
+            }
+        }
+    }
+    /// The outline of a cell in pixels, for the given cell width in pixels.
+    fn outline(&self, cell: (i32, i32), size: f64) -> Vec<(i32, i32)> {
+        match self.shape {
+            DensityShape::Square => {
+                let (x0, y0) = (cell.0 as f64 * size, cell.1 as f64 * size);
+                let corners = [
+                    (x0, y0),
+                    (x0 + size, y0),
+                    (x0 + size, y0 + size),
+                    (x0, y0 + size),
+                ];
+                corners
+                    .iter()
+                    .map(|(x, y)| (x.round() as i32, y.round() as i32))
+                    .collect()
+            }
+            DensityShape::Hex => {
+                let radius = size / 3_f64.sqrt();
+                let (q, r) = (cell.0 as f64, cell.1 as f64);
+                let cx = size * (q + r / 2.0);
+                let cy = radius * 1.5 * r;
+                (0..6)
+                    .map(|i| {
+                        let angle = (60.0 * i as f64 - 30.0).to_radians();
+                        (
+                            (cx + radius * angle.cos()).round() as i32,
+                            (cy + radius * angle.sin()).round() as i32,
+                        )
+                    })
+                    .collect()
+            }
+        }
+    }
+    /// Counts points per cell. Points outside the plotting area are ignored.
+    fn bin<I: Iterator<Item = (i32, i32)>>(
+        &self,
+        pixels: I,
+        area: (i32, i32),
+        size: f64,
+    ) -> HashMap<(i32, i32), usize> {
+        let mut cells = HashMap::new();
+        for px in pixels.filter(|px| px.0 >= 0 && px.1 >= 0 && px.0 < area.0 && px.1 < area.1) {
+            *cells.entry(self.cell(px, size)).or_insert(0) += 1;
+        }
+        cells
+    }
 }
 
 ///
@@ -215,7 +1085,27 @@ pub struct Series {
     name: String,
     color: RGBColor,
     series_type: SeriesType,
-    data: VecDeque<(f64, f64)>,
+    smoothing: Smoothing,
+    data: SeriesStore,
+    visible: bool,
+    group: Option<String>,
+    limit: Option<usize>,
+    stride: usize,
+    offered: usize,
+    held: Option<(f64, f64)>,
+    radius: Option<u32>,
+    auto_color: bool,
+    transform: Option<Arc<YTransform>>,
+    density: Option<Density>,
+    colors: Option<SegmentColors>,
+    latest: Option<f64>,
+    legend: Option<Arc<LegendFormat>>,
+    unit: Option<String>,
+    meta: Vec<(String, String)>,
+    pattern: Pattern,
+    retention: Option<Arc<Mutex<RetentionFile>>>,
+    #[cfg(feature = "profiling")]
+    trim_time: Duration,
 }
 impl Series {
     fn new<T: Color>(name: &str, color: &T, series_type: SeriesType) -> Self {
@@ -224,7 +1114,27 @@ impl Series {
             name: name.to_string(),
             color: RGBColor(r, g, b),
             series_type,
-            data: VecDeque::new(),
+            smoothing: Smoothing::None,
+            data: SeriesStore::new(),
+            visible: true,
+            group: None,
+            limit: None,
+            stride: 1,
+            offered: 0,
+            held: None,
+            radius: None,
+            auto_color: false,
+            transform: None,
+            density: None,
+            colors: None,
+            latest: None,
+            legend: None,
+            unit: None,
+            meta: Vec::new(),
+            pattern: Pattern::Solid,
+            retention: None,
+            #[cfg(feature = "profiling")]
+            trim_time: Duration::default(),
         }
     }
     /// Creates an empty point series.
@@ -237,368 +1147,6198 @@ impl Series {
         Self::new(name, color, SeriesType::Line)
     }
 
-    /// Pushes an xy entry to the back (end) of the series.
-    /// Preferably use [`Chart`'s](struct.Chart.html) methods to add or change data.
-    pub fn push(&mut self, xy: (f64, f64)) {
-        self.data.push_back(xy);
+    /// Creates an empty point series, colored from the chart's palette when added to a chart.
+    ///
+    /// See [`ChartBuilder::with_palette()`](struct.ChartBuilder.html#method.with_palette).
+    pub fn point_auto(name: &str) -> Self {
+        let mut series = Self::new(name, &BLACK, SeriesType::Point);
+        series.auto_color = true;
+        series
     }
-    /// Drops entries from the front of the series until the series has `targ_len` entries.
-    pub fn drop_front(&mut self, targ_len: usize) {
-        let mut drop = self.data.len() as i32 - targ_len as i32;
-        while drop > 0 {
-            let _ = self.data.pop_front();
-            drop -= 1;
-        }
+
+    /// Creates an empty line series, colored from the chart's palette when added to a chart.
+    ///
+    /// See [`ChartBuilder::with_palette()`](struct.ChartBuilder.html#method.with_palette).
+    pub fn line_auto(name: &str) -> Self {
+        let mut series = Self::new(name, &BLACK, SeriesType::Line);
+        series.auto_color = true;
+        series
     }
-    /// Drops entries from the back (end) of the series until the series has `targ_len` entries.
-    pub fn drop_back(&mut self, targ_len: usize) {
-        let mut drop = self.data.len() - targ_len;
-        while drop > 0 {
-            let _ = self.data.pop_back();
-            drop -= 1;
-        }
+
+    /// Creates an empty segment series, e.g. for displacement vectors.
+    ///
+    /// Entries are pairs of start and end point, see [`Chart::push_segment()`](struct.Chart.html#method.push_segment).
+    pub fn segment(name: &str, color: &RGBColor) -> Self {
+        Self::new(name, color, SeriesType::Segment)
     }
-    /// Clears the data of the series. Name and style are not affected.
-    pub fn clear(&mut self) {
-        self.data.clear();
+
+    /// Creates an empty segment series, with arrow heads at segment ends.
+    pub fn arrow(name: &str, color: &RGBColor) -> Self {
+        Self::new(name, color, SeriesType::Arrow)
     }
-}
 
-struct AxisLimits {
-    x_min: Option<f64>,
-    x_max: Option<f64>,
-    y_min: Option<f64>,
-    y_max: Option<f64>,
-}
-impl AxisLimits {
-    pub fn empty() -> Self {
-        AxisLimits {
-            x_min: None,
-            x_max: None,
-            y_min: None,
-            y_max: None,
-        }
+    /// Creates a line series by sampling a function at `n` evenly spaced x values over the range (inclusive).
+    pub fn from_fn<F: Fn(f64) -> f64>(
+        name: &str,
+        color: &RGBColor,
+        range: (f64, f64),
+        n: usize,
+        f: F,
+    ) -> Self {
+        let mut series = Self::line(name, color);
+        series.data.extend(sample_fn(range, n, &f));
+        series
     }
-}
 
-///
-/// A window for easy plotting. Construct using [`ChartBuilder`](struct.ChartBuilder.html).
-///
-/// See [`chart`](index.html) module docs for an example.
-///
-#[allow(dead_code)]
-pub struct Chart {
-    window: BufferWindow,
-    data: Vec<Series>,
-    data_limit: Option<usize>,
-    x_label: String,
-    y_label: String,
-    x_scale: f64,
-    y_scale: f64,
-    y_log: bool,
-    limits: AxisLimits,
-}
+    /// Assigns the series to a named group, for toggling the visibility of multiple series at once.
+    ///
+    /// See [`Chart::set_group_visible()`](struct.Chart.html#method.set_group_visible).
+    pub fn with_group(mut self, group: &str) -> Self {
+        self.group = Some(group.to_string());
+        self
+    }
 
-impl Chart {
-    fn new(
-        title: &str,
-        dim: (usize, usize),
-        series: Vec<Series>,
-        max_fps: Option<f64>,
-        fps_skip: Option<f64>,
-    ) -> Self {
-        let window = BufferWindow::new(title, dim, max_fps, fps_skip, Scale::X1, true);
+    /// Sets the data limit of the series, overriding the chart's data limit.
+    ///
+    /// See [`ChartBuilder::with_data_limit()`](struct.ChartBuilder.html#method.with_data_limit).
+    pub fn with_limit(mut self, max_values: usize) -> Self {
+        self.limit = Some(max_values);
+        self
+    }
 
-        Chart {
-            window,
-            data: series,
-            data_limit: None,
-            x_label: "X".to_string(),
-            y_label: "Y".to_string(),
-            x_scale: 1.0,
-            y_scale: 1.0,
-            y_log: false,
-            limits: AxisLimits::empty(),
-        }
+    /// Sets the decimation stride of the series. Only every `stride`-th entry pushed through
+    /// [`Chart`'s](struct.Chart.html) methods is stored. Default: 1 (store all entries).
+    ///
+    /// Combined with a data limit, this allows to keep a longer history for slowly varying series.
+    pub fn with_decimation(mut self, stride: usize) -> Self {
+        self.stride = stride.max(1);
+        self
     }
 
-    /// Returns if the chart's window is open.
-    pub fn is_open(&self) -> bool {
-        self.window.is_open()
+    /// Sets the smoothing of a line series, for a smooth curve through the points. Default: [`Smoothing::None`](enum.Smoothing.html#variant.None).
+    ///
+    /// Has no effect on point series.
+    pub fn with_smoothing(mut self, smoothing: Smoothing) -> Self {
+        self.smoothing = smoothing;
+        self
     }
 
-    /// Returns the underlying [`BufferWindow`](../window/struct.BufferWindow.html).
-    pub fn window(&mut self) -> &mut BufferWindow {
-        &mut self.window
+    /// Sets the marker radius of point series, in screen pixels. Default: 2.
+    ///
+    /// Scaled by the chart's UI scale, and divided by the window's scale factor.
+    pub fn with_radius(mut self, radius: u32) -> Self {
+        self.radius = Some(radius);
+        self
     }
 
-    /// Returns the number of series in the chart.
-    pub fn num_series(&self) -> usize {
-        self.data.len()
+    /// Sets density rendering for point series with many points, see [`Density`](struct.Density.html).
+    ///
+    /// Has no effect on other series types.
+    pub fn with_density(mut self, density: Density) -> Self {
+        self.density = Some(density);
+        self
     }
-    /// Pushes a data row to the chart as a time series entry.
+
+    /// Colors each segment of a line series by a value, through a color map, e.g. a trajectory colored by speed.
     ///
-    /// # Arguments
-    /// * `t` - Time or x value for all y values.
-    /// * `y` - Slice of y values, one per series.
+    /// Values are pushed with the points, see [`Chart::push_xyv()`](struct.Chart.html#method.push_xyv).
+    /// Each segment gets the color of its end point's value. Segments ending in points without a finite value,
+    /// e.g. pushed through other methods, are drawn in the series' color. Smoothing is not applied.
     ///
-    /// # Panics
-    /// Panics if the length of `y` does not equal the number of series in the chart.
-    pub fn push_time_series(&mut self, t: f64, y: &[f64]) {
-        if !self.window.is_open() {
-            return;
-        }
-        if self.data.len() != y.len() {
-            panic!("Length of y must be equaltu number of series!");
-        }
-        for (ser, value) in self.data.iter_mut().zip(y) {
-            ser.push((t, *value));
-            if let Some(lim) = self.data_limit {
-                ser.drop_front(lim);
-            }
+    /// Has no effect on other series types.
+    ///
+    /// # Example
+    /// ```
+    /// use easy_graph::ui::chart::{ChartBuilder, Series};
+    /// use easy_graph::color::LinearColorMap;
+    /// use easy_graph::color::style::{BLACK, BLUE, RED};
+    ///
+    /// let mut chart = ChartBuilder::new()
+    ///     .add_series(Series::line("Trajectory", &BLACK).with_color_map(LinearColorMap::new(&[&BLUE, &RED])))
+    ///     .build_headless();
+    /// for t in 0..100 {
+    ///     let t = t as f64 * 0.1;
+    ///     let speed = t.cos().abs();
+    ///     chart.push_xyv(0, (t.sin(), t.cos() * t.sin()), speed);
+    /// }
+    /// ```
+    pub fn with_color_map<M: ColorMap + Send + Sync + 'static>(mut self, color_map: M) -> Self {
+        self.colors = Some(SegmentColors {
+            color_map: Arc::new(color_map),
+            range: self.colors.and_then(|colors| colors.range),
+            values: VecDeque::new(),
+        });
+        self.sync_values();
+        self
+    }
+    /// Sets a fixed value range for the series' color map. Default: the range of all values of the series.
+    ///
+    /// Has no effect without a color map, see [`with_color_map()`](#method.with_color_map).
+    pub fn with_color_range(mut self, min: f64, max: f64) -> Self {
+        if let Some(colors) = &mut self.colors {
+            colors.range = Some((min, max));
         }
+        self
     }
 
-    /// Pushes an xy entry to a certain series.
+    /// Sets a transform applied to y values when drawing, e.g. `|y| y.abs().ln()` for a log view.
     ///
-    /// # Arguments
-    /// * `index` - Index of the series to push to.
-    /// * `xy` - Data point as a tuple of (x, y).
+    /// Stored data is not changed. Axis ranges and tooltips use transformed values.
+    pub fn with_transform<F>(mut self, transform: F) -> Self
+    where
+        F: Fn(f64) -> f64 + Send + Sync + 'static,
+    {
+        self.transform = Some(Arc::new(transform));
+        self
+    }
+
+    /// Shows the series' latest value in its legend entry, e.g. "Infected: 1,204".
     ///
-    /// # Panics
-    /// Panics if the index is not in the range of series indices.
-    pub fn push_xy(&mut self, index: usize, xy: (f64, f64)) {
-        let ser = &mut self.data[index];
-        ser.push(xy);
-        if let Some(lim) = self.data_limit {
-            ser.drop_front(lim);
-        }
+    /// The value is the latest pushed one, even if it was not stored due to decimation or deadband pushes.
+    /// See also [`ChartBuilder::with_legend_values()`](struct.ChartBuilder.html#method.with_legend_values) to enable it for all series.
+    pub fn with_legend_value(self) -> Self {
+        self.with_legend_format(|name, value| format!("{}: {}", name, format_legend_value(value)))
+    }
+    /// Sets a custom format for the series' legend entry, from the series' name and its latest value.
+    ///
+    /// # Example
+    /// ```
+    ///# use easy_graph::ui::chart::Series;
+    ///# use easy_graph::color::style::RED;
+    /// let series = Series::line("Temperature", &RED)
+    ///     .with_legend_format(|name, value| format!("{} ({:.1} °C)", name, value));
+    /// ```
+    pub fn with_legend_format<F>(mut self, format: F) -> Self
+    where
+        F: Fn(&str, f64) -> String + Send + Sync + 'static,
+    {
+        self.legend = Some(Arc::new(format));
+        self
     }
 
-    /// Replaces the data of a certain series.
+    /// Sets the unit of the series' y values, e.g. `"kg"`.
     ///
-    /// # Arguments
-    /// * `index` - Index of the series to replace.
-    /// * `data` - Slice of tuples of (x, y).
+    /// The unit is shown in the legend, in [`Chart::tooltip()`](struct.Chart.html#method.tooltip),
+    /// in CSV headers and in [`Chart::summary()`](struct.Chart.html#method.summary).
+    pub fn with_unit(mut self, unit: &str) -> Self {
+        self.unit = Some(unit.to_string());
+        self
+    }
+
+    /// Attaches a metadata entry to the series, e.g. the data source or parameters of a simulation run.
+    /// Replaces the value of an existing entry with the same key.
     ///
-    /// # Panics
-    /// Panics if the index is not in the range of series indices.
-    pub fn replace_series(&mut self, index: usize, data: &[(f64, f64)]) {
-        let ser = &mut self.data[index];
-        ser.clear();
-        for xy in data {
-            ser.push(*xy);
+    /// Metadata is written to CSV exports, see [`Chart::write_csv()`](struct.Chart.html#method.write_csv),
+    /// and is available in [`Chart::summary()`](struct.Chart.html#method.summary).
+    ///
+    /// ```
+    ///# use easy_graph::ui::chart::Series;
+    ///# use easy_graph::color::style::RED;
+    /// let series = Series::line("Biomass", &RED)
+    ///     .with_unit("kg")
+    ///     .with_meta("source", "field survey 2020");
+    /// assert_eq!(series.meta("source"), Some("field survey 2020"));
+    /// ```
+    pub fn with_meta(mut self, key: &str, value: &str) -> Self {
+        match self.meta.iter_mut().find(|(k, _)| k == key) {
+            Some(entry) => entry.1 = value.to_string(),
+            None => self.meta.push((key.to_string(), value.to_string())),
         }
+        self
     }
 
-    /// Render the graph
-    pub fn update(&mut self) {
-        let data = &self.data;
-        let x_label = &self.x_label;
-        let y_label = &self.y_label;
-        let x_scale = self.x_scale;
-        let y_scale = self.y_scale;
-        let y_log = self.y_log;
-        let (xlim, ylim) = self.calc_axis_ranges();
-        self.window.draw(|b| {
-            let root = b.into_drawing_area();
-            root.fill(&WHITE).unwrap();
-            if y_log {
-                let mut cc: ChartContext<_, _> = plotters::chart::ChartBuilder::on(&root)
-                    .margin(10)
-                    .x_label_area_size(40)
-                    .y_label_area_size(60)
-                    .build_ranged(
-                        (xlim.0 * x_scale)..(xlim.1 * x_scale),
-                        LogRange((ylim.0 * y_scale)..(ylim.1 * y_scale)),
-                    )
-                    .unwrap();
+    /// Sets the fill pattern of point markers and the legend entry. Default: [`Pattern::Solid`](../pattern/enum.Pattern.html#variant.Solid).
+    ///
+    /// Markers with a pattern other than `Solid` are drawn with an outline. See module [`pattern`](../pattern/index.html).
+    pub fn with_pattern(mut self, pattern: Pattern) -> Self {
+        self.pattern = pattern;
+        self
+    }
 
-                cc.configure_mesh()
-                    .x_label_formatter(&|x| format!("{}", *x))
-                    .y_label_formatter(&|y| format!("{}", *y))
-                    .x_labels(15)
-                    .y_labels(8)
-                    .x_desc(x_label)
-                    .y_desc(y_label)
-                    .axis_desc_style(("sans-serif", 15).into_font())
-                    .draw()
-                    .unwrap();
+    /// Sets the series initially hidden.
+    pub fn hidden(mut self) -> Self {
+        self.visible = false;
+        self
+    }
 
-                for (_, series) in (0..).zip(data.iter()) {
-                    let draw = match &series.series_type {
-                        SeriesType::Line => cc.draw_series(LineSeries::new(
-                            series.data.iter().map(|(a, b)| {
-                                (
-                                    *a * x_scale,
-                                    if y_log && *b <= 0.0 {
-                                        std::f64::NAN
-                                    } else {
-                                        *b * y_scale
-                                    },
-                                )
-                            }),
-                            ShapeStyle::from(&series.color),
-                        )),
-                        SeriesType::Point => cc.draw_series(series.data.iter().map(|(a, b)| {
-                            Circle::new(
-                                (*a * x_scale, *b * y_scale),
-                                2,
-                                ShapeStyle::from(&series.color).filled(),
-                            )
-                        })),
-                    };
-                    draw.unwrap().label(&series.name).legend(move |(x, y)| {
-                        Rectangle::new(
-                            [(x - 5, y - 5), (x + 5, y + 5)],
-                            ShapeStyle::from(&series.color).filled(),
-                        )
-                    });
-                }
+    /// Returns the series' name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
 
-                cc.configure_series_labels()
-                    .background_style(&WHITE.mix(0.8))
-                    .border_style(&BLACK)
-                    .draw()
-                    .unwrap();
-            } else {
-                let mut cc: ChartContext<_, _> = plotters::chart::ChartBuilder::on(&root)
-                    .margin(10)
-                    .x_label_area_size(40)
-                    .y_label_area_size(60)
-                    .build_ranged(
-                        (xlim.0 * x_scale)..(xlim.1 * x_scale),
-                        (ylim.0 * y_scale)..(ylim.1 * y_scale),
-                    )
-                    .unwrap();
+    /// Returns if the series is visible.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
 
-                cc.configure_mesh()
-                    .x_label_formatter(&|x| format!("{}", *x))
-                    .y_label_formatter(&|y| format!("{}", *y))
-                    .x_labels(15)
-                    .y_labels(8)
-                    .x_desc(x_label)
-                    .y_desc(y_label)
-                    .axis_desc_style(("sans-serif", 15).into_font())
-                    .draw()
-                    .unwrap();
+    /// Returns the unit of the series' y values, if any.
+    pub fn unit(&self) -> Option<&str> {
+        self.unit.as_deref()
+    }
 
-                for (_, series) in (0..).zip(data.iter()) {
-                    let draw = match &series.series_type {
-                        SeriesType::Line => cc.draw_series(LineSeries::new(
-                            series.data.iter().map(|(a, b)| {
-                                (
-                                    *a * x_scale,
-                                    if y_log && *b <= 0.0 {
-                                        std::f64::NAN
-                                    } else {
-                                        *b * y_scale
-                                    },
-                                )
-                            }),
-                            ShapeStyle::from(&series.color),
-                        )),
-                        SeriesType::Point => cc.draw_series(series.data.iter().map(|(a, b)| {
-                            Circle::new(
-                                (*a * x_scale, *b * y_scale),
-                                2,
-                                ShapeStyle::from(&series.color).filled(),
-                            )
-                        })),
-                    };
-                    draw.unwrap().label(&series.name).legend(move |(x, y)| {
-                        Rectangle::new(
-                            [(x - 5, y - 5), (x + 5, y + 5)],
-                            ShapeStyle::from(&series.color).filled(),
-                        )
-                    });
-                }
+    /// Returns the value of a metadata entry, if any.
+    pub fn meta(&self, key: &str) -> Option<&str> {
+        self.meta
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| &value[..])
+    }
 
-                cc.configure_series_labels()
-                    .background_style(&WHITE.mix(0.8))
-                    .border_style(&BLACK)
-                    .draw()
-                    .unwrap();
+    /// Returns all metadata entries as (key, value) pairs, in the order they were added.
+    pub fn metadata(&self) -> &[(String, String)] {
+        &self.meta
+    }
+
+    /// The series' name, followed by the unit in brackets if there is one, e.g. "Biomass [kg]".
+    fn label(&self) -> String {
+        match &self.unit {
+            Some(unit) => format!("{} [{}]", self.name, unit),
+            None => self.name.clone(),
+        }
+    }
+    /// A y value followed by the series' unit, if any.
+    fn with_unit_suffix(&self, value: String) -> String {
+        match &self.unit {
+            Some(unit) => format!("{} {}", value, unit),
+            None => value,
+        }
+    }
+
+    /// Returns the y value of the latest pushed entry, including entries not stored due to decimation or deadband pushes.
+    pub fn latest_value(&self) -> Option<f64> {
+        self.latest.or_else(|| self.data.back().map(|xy| xy.1))
+    }
+    /// The label of the series' legend entry. Without an own format, `values` shows the latest value in the default format.
+    fn legend_label(&self, values: bool) -> String {
+        match (&self.legend, self.latest_value()) {
+            (Some(format), Some(value)) => format(&self.name, value),
+            (None, Some(value)) if values => {
+                let value = self.with_unit_suffix(format_legend_value(value));
+                format!("{}: {}", self.name, value)
             }
-        });
+            _ => self.label(),
+        }
     }
 
-    fn calc_axis_ranges(&self) -> ((f64, f64), (f64, f64)) {
-        (self.calc_axis_range(true), self.calc_axis_range(false))
+    /// Returns the entries with x values from `x0` to `x1` (inclusive).
+    ///
+    /// Uses binary search, and thus assumes increasing x values, as for time series.
+    pub fn range(&self, x0: f64, x1: f64) -> impl Iterator<Item = (f64, f64)> + '_ {
+        let start = self.data.partition_point(|xy| xy.0 < x0);
+        let end = self.data.partition_point(|xy| xy.0 <= x1).max(start);
+        self.data.range(start..end).cloned()
     }
-    fn calc_axis_range(&self, is_x: bool) -> (f64, f64) {
-        let (min, max) = if is_x {
-            (self.limits.x_min, self.limits.x_max)
+    /// Summarizes the entries with x values from `x0` to `x1` (inclusive) in at least `count` buckets,
+    /// if there are at least `16 * count` entries. Otherwise, returns one bucket per entry.
+    ///
+    /// Uses binary search, and thus assumes increasing x values, as for time series.
+    pub fn buckets(&self, x0: f64, x1: f64, count: usize) -> Vec<Bucket> {
+        let start = self.data.partition_point(|xy| xy.0 < x0);
+        let end = self.data.partition_point(|xy| xy.0 <= x1).max(start);
+        self.data.buckets(start..end, count)
+    }
+
+    /// Pushes an xy entry to the back (end) of the series.
+    /// Preferably use [`Chart`'s](struct.Chart.html) methods to add or change data.
+    pub fn push(&mut self, xy: (f64, f64)) {
+        self.data.push_back(xy);
+        self.latest = Some(xy.1);
+        self.sync_values();
+    }
+    /// Pushes a segment from one point to another to the back (end) of a segment or arrow series.
+    /// Preferably use [`Chart`'s](struct.Chart.html) methods to add or change data.
+    pub fn push_segment(&mut self, from: (f64, f64), to: (f64, f64)) {
+        self.data.push_back(from);
+        self.data.push_back(to);
+    }
+    /// Pushes a segment, respecting the series' decimation stride and data limit. Limits count segments.
+    fn append_segment(&mut self, from: (f64, f64), to: (f64, f64), default_limit: Option<usize>) {
+        let keep = self.offered % self.stride == 0;
+        self.offered += 1;
+        if keep {
+            self.push_segment(from, to);
+            if let Some(lim) = self.limit.or(default_limit) {
+                self.drop_front(2 * lim);
+            }
+        }
+    }
+    /// Pushes an xy entry, respecting the series' decimation stride and data limit.
+    /// `default_limit` is used if the series has no own data limit.
+    fn append(&mut self, xy: (f64, f64), default_limit: Option<usize>) {
+        let keep = self.offered % self.stride == 0;
+        self.offered += 1;
+        self.latest = Some(xy.1);
+        if keep {
+            self.push(xy);
+            if let Some(lim) = self.limit.or(default_limit) {
+                self.drop_front(lim);
+            }
+        }
+    }
+    /// Appends many entries, respecting the decimation stride, and trims to the data limit once at the end.
+    fn extend<I: IntoIterator<Item = (f64, f64)>>(
+        &mut self,
+        entries: I,
+        default_limit: Option<usize>,
+    ) {
+        for xy in entries {
+            if self.offered % self.stride == 0 {
+                self.data.push_back(xy);
+            }
+            self.offered += 1;
+            self.latest = Some(xy.1);
+        }
+        self.sync_values();
+        if let Some(lim) = self.limit.or(default_limit) {
+            self.drop_front(lim);
+        }
+    }
+    /// Pushes an xy entry with a value for the color map, respecting the series' decimation stride and data limit.
+    fn append_valued(&mut self, xy: (f64, f64), value: f64, default_limit: Option<usize>) {
+        let keep = self.offered % self.stride == 0;
+        self.offered += 1;
+        self.latest = Some(xy.1);
+        if keep {
+            self.data.push_back(xy);
+            if let Some(colors) = &mut self.colors {
+                colors.values.push_back(value);
+            }
+            self.sync_values();
+            if let Some(lim) = self.limit.or(default_limit) {
+                self.drop_front(lim);
+            }
+        }
+    }
+    /// Keeps color map values aligned with the data: drops values of removed entries, and adds `NaN` for entries without a value.
+    fn sync_values(&mut self) {
+        if let Some(colors) = &mut self.colors {
+            while colors.values.len() > self.data.len() {
+                colors.values.pop_front();
+            }
+            while colors.values.len() < self.data.len() {
+                colors.values.push_back(f64::NAN);
+            }
+        }
+    }
+    /// Appends an entry only if its y value differs from the last stored one by more than `epsilon`.
+    /// Otherwise, the entry is held back, and stored before the next change or on [`flush()`](#method.flush).
+    /// Storing the last unchanged entry before a change keeps the shape of lines. Returns if the entry was stored.
+    fn append_if_changed(
+        &mut self,
+        xy: (f64, f64),
+        epsilon: f64,
+        default_limit: Option<usize>,
+    ) -> bool {
+        // Also true for non-finite values
+        let changed = self
+            .data
+            .back()
+            .map(|last| {
+                let diff = (xy.1 - last.1).abs();
+                diff > epsilon || diff.is_nan()
+            })
+            .unwrap_or(true);
+        if changed {
+            self.flush(default_limit);
+            self.append(xy, default_limit);
         } else {
-            (self.limits.y_min, self.limits.y_max)
+            self.held = Some(xy);
+            self.latest = Some(xy.1);
+        }
+        changed
+    }
+    /// Stores the entry held back by deadband pushes, if any.
+    fn flush(&mut self, default_limit: Option<usize>) {
+        if let Some(xy) = self.held.take() {
+            self.append(xy, default_limit);
+        }
+    }
+    /// Drops entries from the front of the series until the series has `targ_len` entries.
+    ///
+    /// With retention enabled, dropped entries are appended to the series' retention file.
+    pub fn drop_front(&mut self, targ_len: usize) {
+        #[cfg(feature = "profiling")]
+        let start = Instant::now();
+        if let Some(retention) = &self.retention {
+            let dropped = self.data.len().saturating_sub(targ_len);
+            retention
+                .lock()
+                .unwrap()
+                .append_or_record(self.data.range(..dropped));
+        }
+        self.data
+            .drop_front(self.data.len().saturating_sub(targ_len));
+        self.sync_values();
+        #[cfg(feature = "profiling")]
+        {
+            self.trim_time += start.elapsed();
+        }
+    }
+    /// Drops entries from the back (end) of the series until the series has `targ_len` entries.
+    pub fn drop_back(&mut self, targ_len: usize) {
+        self.data.truncate(targ_len);
+        if let Some(colors) = &mut self.colors {
+            colors.values.truncate(targ_len);
+        }
+        self.latest = None;
+    }
+    /// Clears the data of the series. Name and style are not affected.
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.held = None;
+        self.latest = None;
+        if let Some(colors) = &mut self.colors {
+            colors.values.clear();
+        }
+    }
+}
+impl Clone for Series {
+    fn clone(&self) -> Self {
+        Series {
+            name: self.name.clone(),
+            color: RGBColor(self.color.0, self.color.1, self.color.2),
+            series_type: self.series_type,
+            smoothing: self.smoothing,
+            data: self.data.clone(),
+            visible: self.visible,
+            group: self.group.clone(),
+            limit: self.limit,
+            stride: self.stride,
+            offered: self.offered,
+            held: self.held,
+            radius: self.radius,
+            auto_color: self.auto_color,
+            transform: self.transform.clone(),
+            density: self.density.clone(),
+            colors: self.colors.clone(),
+            latest: self.latest,
+            legend: self.legend.clone(),
+            unit: self.unit.clone(),
+            meta: self.meta.clone(),
+            pattern: self.pattern,
+            // Clones don't write to the retention file of the original
+            retention: None,
+            #[cfg(feature = "profiling")]
+            trim_time: self.trim_time,
+        }
+    }
+}
+
+impl Series {
+    /// Applies the series' y transform to a data point.
+    fn transformed(&self, xy: &(f64, f64)) -> (f64, f64) {
+        match &self.transform {
+            Some(transform) => (xy.0, transform(xy.1)),
+            None => *xy,
+        }
+    }
+}
+
+/// Samples a function at `n` evenly spaced x values over the range (inclusive).
+fn sample_fn(range: (f64, f64), n: usize, f: &dyn Fn(f64) -> f64) -> Vec<(f64, f64)> {
+    let step = if n > 1 {
+        (range.1 - range.0) / (n - 1) as f64
+    } else {
+        0.0
+    };
+    (0..n)
+        .map(|i| {
+            let x = range.0 + i as f64 * step;
+            (x, f(x))
+        })
+        .collect()
+}
+
+/// A function plotted into a series, re-sampled when the x limits change.
+struct FnPlot {
+    index: usize,
+    n: usize,
+    range: (f64, f64),
+    f: Box<dyn Fn(f64) -> f64>,
+}
+
+/// Derivation of a series from another one, see [`Chart::derive_series()`](struct.Chart.html#method.derive_series).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Derivation {
+    /// Finite difference between consecutive entries, i.e. the rate of change.
+    Derivative,
+    /// Cumulative sum of y values, i.e. the running total. Non-finite values are skipped.
+    Cumulative,
+}
+
+/// A series derived from another one, extended when the source grows.
+struct DerivedPlot {
+    index: usize,
+    source: usize,
+    derivation: Derivation,
+    last: Option<(f64, f64)>,
+    sum: f64,
+}
+
+impl DerivedPlot {
+    fn new(index: usize, source: usize, derivation: Derivation) -> Self {
+        DerivedPlot {
+            index,
+            source,
+            derivation,
+            last: None,
+            sum: 0.0,
+        }
+    }
+    /// Returns if the source was reset since the last update, e.g. cleared or replaced.
+    fn is_reset(&self, source: &VecDeque<(f64, f64)>) -> bool {
+        match (self.last, source.back()) {
+            (Some(last), Some(back)) => back.0 < last.0,
+            (Some(_), None) => true,
+            _ => false,
+        }
+    }
+    /// Derives entries for source entries added since the last update.
+    fn derive(&mut self, source: &VecDeque<(f64, f64)>) -> Vec<(f64, f64)> {
+        let new = match self.last {
+            Some(last) => source.iter().rev().take_while(|xy| xy.0 > last.0).count(),
+            None => source.len(),
         };
-        if min.is_some() && max.is_some() {
-            (min.unwrap(), max.unwrap())
-        } else {
-            let find_min = min.is_none();
-            let find_max = max.is_none();
-            let mut v_min = std::f64::MAX;
-            let mut v_max = std::f64::MIN;
-            for ser in &self.data {
-                for xy in &ser.data {
-                    let v = if is_x { xy.0 } else { xy.1 };
-                    if find_min && v < v_min {
-                        v_min = v;
+        let mut result = Vec::with_capacity(new);
+        for &(x, y) in source.range(source.len() - new..) {
+            match self.derivation {
+                Derivation::Derivative => {
+                    if let Some(last) = self.last {
+                        result.push((x, (y - last.1) / (x - last.0)));
                     }
-                    if find_max && v > v_max {
-                        v_max = v;
+                }
+                Derivation::Cumulative => {
+                    if y.is_finite() {
+                        self.sum += y;
                     }
+                    result.push((x, self.sum));
                 }
             }
-            (min.unwrap_or(v_min), max.unwrap_or(v_max))
+            self.last = Some((x, y));
         }
+        result
     }
 }
 
-#[cfg(test)]
-#[allow(unused_imports)]
-mod test {
-    use crate::ui::chart::{Chart, ChartBuilder, Series};
-    use plotters::style::{BLUE, GREEN, RED};
-    use rand::Rng;
+/// The envelope drawn around the mean of an [`EnsembleSeries`](struct.EnsembleSeries.html).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Envelope {
+    /// Range between minimum and maximum of the replicates.
+    MinMax,
+    /// Range between a lower and an upper percentile (0 - 100) of the replicates.
+    Percentile(f64, f64),
+}
 
-    #[test]
-    fn time_series_plot() {
-        let mut chart = ChartBuilder::new()
-            .with_title("Test")
-            .with_labels("some x", "some y")
-            .with_dimensions(800, 400)
-            .with_data_limit(100)
+///
+/// A data series with multiple y values (one per replicate run) for each x.
+///
+/// Drawn as the mean line with a shaded envelope. Add to charts using [`ChartBuilder::add_ensemble()`](struct.ChartBuilder.html#method.add_ensemble).
+///
+pub struct EnsembleSeries {
+    name: String,
+    color: RGBColor,
+    envelope: Envelope,
+    data: VecDeque<(f64, Vec<f64>)>,
+    /// `(x, mean, lower, upper)` per entry of `data`, calculated when pushing.
+    summaries: VecDeque<(f64, f64, f64, f64)>,
+    visible: bool,
+    limit: Option<usize>,
+    pattern: Pattern,
+}
+impl EnsembleSeries {
+    /// Creates an empty ensemble series with a min-max envelope.
+    pub fn new(name: &str, color: &RGBColor) -> Self {
+        EnsembleSeries {
+            name: name.to_string(),
+            color: RGBColor(color.0, color.1, color.2),
+            envelope: Envelope::MinMax,
+            data: VecDeque::new(),
+            summaries: VecDeque::new(),
+            visible: true,
+            limit: None,
+            pattern: Pattern::Solid,
+        }
+    }
+
+    /// Sets the envelope type.
+    pub fn with_envelope(mut self, envelope: Envelope) -> Self {
+        self.envelope = envelope;
+        self.summaries = self
+            .data
+            .iter()
+            .map(|(x, values)| summarize(*x, values, envelope))
+            .collect();
+        self
+    }
+
+    /// Limits the number of x values of this series, overriding the chart's data limit.
+    pub fn with_limit(mut self, max_values: usize) -> Self {
+        self.limit = Some(max_values);
+        self
+    }
+
+    /// Sets the fill pattern of the envelope and the legend entry. Default: [`Pattern::Solid`](../pattern/enum.Pattern.html#variant.Solid).
+    ///
+    /// Envelopes with a pattern other than `Solid` are drawn lightly shaded, with the pattern on top.
+    /// See module [`pattern`](../pattern/index.html).
+    pub fn with_pattern(mut self, pattern: Pattern) -> Self {
+        self.pattern = pattern;
+        self
+    }
+
+    /// Hides the series initially.
+    pub fn hidden(mut self) -> Self {
+        self.visible = false;
+        self
+    }
+
+    /// Returns the series' name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Adds the replicate values for an x value. Empty slices are ignored.
+    pub fn push(&mut self, x: f64, values: &[f64]) {
+        self.append(x, values, None);
+    }
+
+    fn append(&mut self, x: f64, values: &[f64], default_limit: Option<usize>) {
+        if values.is_empty() {
+            return;
+        }
+        self.data.push_back((x, values.to_vec()));
+        self.summaries
+            .push_back(summarize(x, values, self.envelope));
+        if let Some(limit) = self.limit.or(default_limit) {
+            self.trim(limit);
+        }
+    }
+
+    /// Drops x values from the front until the series has at most `limit` x values.
+    fn trim(&mut self, limit: usize) {
+        while self.data.len() > limit {
+            self.data.pop_front();
+            self.summaries.pop_front();
+        }
+    }
+
+    /// Removes all data.
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.summaries.clear();
+    }
+
+    /// Returns `(x, mean, lower, upper)` for each x value, with lower and upper bounds according to the envelope type.
+    ///
+    /// Non-finite replicate values are ignored. All values are `NaN` for x values without finite replicates.
+    pub fn summary(&self) -> Vec<(f64, f64, f64, f64)> {
+        self.summaries.iter().cloned().collect()
+    }
+}
+
+/// Calculates `(x, mean, lower, upper)` of the finite replicate values of an x value.
+fn summarize(x: f64, values: &[f64], envelope: Envelope) -> (f64, f64, f64, f64) {
+    let mut finite: Vec<_> = values.iter().cloned().filter(|v| v.is_finite()).collect();
+    if finite.is_empty() {
+        return (x, f64::NAN, f64::NAN, f64::NAN);
+    }
+    let mean = finite.iter().sum::<f64>() / finite.len() as f64;
+    let (lower, upper) = match envelope {
+        Envelope::MinMax => finite
+            .iter()
+            .fold((f64::MAX, f64::MIN), |(lo, hi), v| (lo.min(*v), hi.max(*v))),
+        Envelope::Percentile(lower, upper) => {
+            finite.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            (percentile(&finite, lower), percentile(&finite, upper))
+        }
+    };
+    (x, mean, lower, upper)
+}
+impl Clone for EnsembleSeries {
+    fn clone(&self) -> Self {
+        EnsembleSeries {
+            name: self.name.clone(),
+            color: RGBColor(self.color.0, self.color.1, self.color.2),
+            envelope: self.envelope,
+            data: self.data.clone(),
+            summaries: self.summaries.clone(),
+            visible: self.visible,
+            limit: self.limit,
+            pattern: self.pattern,
+        }
+    }
+}
+
+/// Linearly interpolated percentile (0 - 100) of sorted values.
+fn percentile(sorted: &[f64], percent: f64) -> f64 {
+    let pos = (percent / 100.0).clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let lower = pos.floor() as usize;
+    let upper = pos.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (pos - lower as f64)
+}
+
+/// Modes for automatic axis limits, see [`ChartBuilder::with_y_auto_range()`](struct.ChartBuilder.html#method.with_y_auto_range).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AutoRange {
+    /// Limits exactly fit the current data. May jump from frame to frame.
+    Exact,
+    /// Limits only grow to fit new extrema, but never shrink.
+    Growing,
+    /// Limits grow immediately to fit new extrema, but shrink smoothly towards the data.
+    ///
+    /// The argument is the fraction (0 - 1) by which limits approach the data range per update.
+    Smoothed(f64),
+}
+impl AutoRange {
+    /// Calculates the new range, given the previous range and the exact range of the data.
+    fn apply(&self, prev: Option<(f64, f64)>, target: (f64, f64)) -> (f64, f64) {
+        let prev = match prev {
+            Some(prev) if target.0 <= target.1 => prev,
+            _ => return target,
+        };
+        match self {
+            AutoRange::Exact => target,
+            AutoRange::Growing => (prev.0.min(target.0), prev.1.max(target.1)),
+            AutoRange::Smoothed(alpha) => (
+                if target.0 < prev.0 {
+                    target.0
+                } else {
+                    prev.0 + alpha * (target.0 - prev.0)
+                },
+                if target.1 > prev.1 {
+                    target.1
+                } else {
+                    prev.1 + alpha * (target.1 - prev.1)
+                },
+            ),
+        }
+    }
+}
+
+/// Rendering of points with non-finite coordinates, see [`ChartBuilder::with_non_finite()`](struct.ChartBuilder.html#method.with_non_finite).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NonFinite {
+    /// Non-finite points are not drawn. Lines are interrupted at these points.
+    Skip,
+    /// Infinite coordinates are clamped to the edges of the plotting area. NaN points are skipped.
+    Clamp,
+}
+
+/// Condition on a series' latest value for alerts, see [`Chart::add_alert()`](struct.Chart.html#method.add_alert).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Threshold {
+    /// Values above the given level.
+    Above(f64),
+    /// Values below the given level.
+    Below(f64),
+    /// Values outside the given range (min, max).
+    Outside(f64, f64),
+}
+impl Threshold {
+    /// Returns if the value crosses the threshold. Never true for NaN.
+    pub fn is_crossed(&self, value: f64) -> bool {
+        match *self {
+            Threshold::Above(level) => value > level,
+            Threshold::Below(level) => value < level,
+            Threshold::Outside(min, max) => value < min || value > max,
+        }
+    }
+    /// The threshold's levels, drawn as horizontal lines.
+    fn levels(&self) -> Vec<f64> {
+        match *self {
+            Threshold::Above(level) | Threshold::Below(level) => vec![level],
+            Threshold::Outside(min, max) => vec![min, max],
+        }
+    }
+}
+
+/// An alert registered by [`Chart::add_alert()`](struct.Chart.html#method.add_alert).
+struct Alert {
+    series: usize,
+    threshold: Threshold,
+    hook: Option<Box<AlertHook>>,
+    active: bool,
+}
+
+/// An accessor pulling a series' values from a user state, see [`Chart::bind_series()`](struct.Chart.html#method.bind_series).
+struct Binding {
+    series: usize,
+    accessor: Box<BindingAccessor>,
+}
+
+/// An alert's threshold and state, passed to the renderer.
+#[derive(Clone)]
+struct AlertMark {
+    series: usize,
+    levels: Vec<f64>,
+    active: bool,
+}
+
+/// Types of the chart's x axis, see [`ChartBuilder::with_x_axis_type()`](struct.ChartBuilder.html#method.with_x_axis_type).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum XAxisType {
+    /// Continuous values.
+    #[default]
+    Numeric,
+    /// Whole numbers, with ticks at integers only and labels without fractional digits.
+    Integer,
+    /// Time in seconds since the Unix epoch (UTC), see [`ChartBuilder::with_x_time()`](struct.ChartBuilder.html#method.with_x_time).
+    Time,
+    /// Discrete categories, with x values as indices into the category names.
+    Categories(Vec<String>),
+}
+
+/// Positions of the legend in the plotting area, see [`ChartBuilder::with_legend_position()`](struct.ChartBuilder.html#method.with_legend_position).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LegendPosition {
+    /// In the upper left corner.
+    UpperLeft,
+    /// In the upper right corner.
+    UpperRight,
+    /// Centered at the right edge.
+    #[default]
+    MiddleRight,
+    /// In the lower left corner.
+    LowerLeft,
+    /// In the lower right corner.
+    LowerRight,
+}
+impl LegendPosition {
+    fn to_plotters(self) -> SeriesLabelPosition {
+        match self {
+            LegendPosition::UpperLeft => SeriesLabelPosition::UpperLeft,
+            LegendPosition::UpperRight => SeriesLabelPosition::UpperRight,
+            LegendPosition::MiddleRight => SeriesLabelPosition::MiddleRight,
+            LegendPosition::LowerLeft => SeriesLabelPosition::LowerLeft,
+            LegendPosition::LowerRight => SeriesLabelPosition::LowerRight,
+        }
+    }
+}
+
+/// Bundles of builder options for common use cases, see [`ChartBuilder::preset()`](struct.ChartBuilder.html#method.preset).
+///
+/// All presets skip frames above 30 FPS, and enable pause and toggle keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Preset {
+    /// Time series with the full history, e.g. of a simulation run. Legend in the upper left corner.
+    TimeSeries,
+    /// Point clouds, limited to 10,000 points per series. Legend in the upper right corner.
+    Scatter,
+    /// Live monitoring of long-running processes. Follows the last 60 x units of data, limited to 100,000 entries per series.
+    /// Skips frames above 10 FPS, smooths y axis limits and shows the latest values in the legend.
+    Monitoring,
+}
+
+#[derive(Clone)]
+struct AxisLimits {
+    x_min: Option<f64>,
+    x_max: Option<f64>,
+    y_min: Option<f64>,
+    y_max: Option<f64>,
+    x_window: Option<f64>,
+}
+impl AxisLimits {
+    pub fn empty() -> Self {
+        AxisLimits {
+            x_min: None,
+            x_max: None,
+            y_min: None,
+            y_max: None,
+            x_window: None,
+        }
+    }
+}
+
+/// Transition of axis ranges, see [`ChartBuilder::with_range_animation()`](struct.ChartBuilder.html#method.with_range_animation).
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct RangeAnimation {
+    from: ((f64, f64), (f64, f64)),
+    to: ((f64, f64), (f64, f64)),
+    frame: usize,
+}
+impl RangeAnimation {
+    /// The ranges at the current frame of a transition over `frames` frames, with ease-out.
+    fn ranges(&self, frames: usize) -> ((f64, f64), (f64, f64)) {
+        let t = ((self.frame + 1) as f64 / frames.max(1) as f64).min(1.0);
+        let f = 1.0 - (1.0 - t).powi(3);
+        let lerp = |a: (f64, f64), b: (f64, f64)| (a.0 + f * (b.0 - a.0), a.1 + f * (b.1 - a.1));
+        (lerp(self.from.0, self.to.0), lerp(self.from.1, self.to.1))
+    }
+}
+
+///
+/// A window for easy plotting. Construct using [`ChartBuilder`](struct.ChartBuilder.html).
+///
+/// See [`chart`](index.html) module docs for an example.
+///
+#[allow(dead_code)]
+pub struct Chart {
+    window: BufferWindow,
+    data: Vec<Series>,
+    ensembles: Vec<EnsembleSeries>,
+    data_limit: Option<usize>,
+    retention: Option<(PathBuf, usize)>,
+    error: Option<io::Error>,
+    config: ChartConfig,
+    limits: AxisLimits,
+    home_limits: AxisLimits,
+    y_auto_range: AutoRange,
+    y_range: Option<(f64, f64)>,
+    range_frames: usize,
+    range_animation: Option<RangeAnimation>,
+    renderer: Option<RenderThread>,
+    toggle_keys: bool,
+    pause_keys: bool,
+    frame_budget: Option<Duration>,
+    paused: bool,
+    step_pending: bool,
+    source: Option<(Box<dyn DataSource>, Instant)>,
+    recorder: Option<ChartRecorder>,
+    update_hook: Option<Box<UpdateHook>>,
+    range_hook: Option<Box<RangeHook>>,
+    hooked_range: Option<(f64, f64)>,
+    alerts: Vec<Alert>,
+    bindings: Vec<Binding>,
+    functions: Vec<FnPlot>,
+    derived: Vec<DerivedPlot>,
+    dirty: bool,
+    drawn_ranges: Option<((f64, f64), (f64, f64))>,
+    coords: Option<ChartCoords>,
+    mesh_cache: MeshCache,
+    palette: Vec<RGBColor>,
+    auto_colors: usize,
+    snapshots: Option<Snapshots>,
+    context_menu: Option<ContextMenu>,
+    menu_actions: Vec<(String, Box<UpdateHook>)>,
+    screenshot_pending: bool,
+    #[cfg(feature = "http")]
+    server: Option<FrameServer>,
+    #[cfg(feature = "profiling")]
+    profile: Profile,
+}
+
+impl Positioned for Chart {
+    fn screen_size(&self) -> (usize, usize) {
+        self.window.screen_size()
+    }
+    fn set_position(&mut self, pos: (isize, isize)) {
+        self.window.set_position(pos);
+    }
+    fn position(&self) -> Option<(isize, isize)> {
+        self.window.position()
+    }
+    fn title(&self) -> &str {
+        self.window.title()
+    }
+    fn set_title(&mut self, title: &str) {
+        self.window.set_title(title);
+    }
+}
+
+/// Transform of y values of a series, see [`Series::with_transform()`](struct.Series.html#method.with_transform).
+pub type YTransform = dyn Fn(f64) -> f64 + Send + Sync;
+
+/// Format of a series' legend entry from its name and latest value, see [`Series::with_legend_format()`](struct.Series.html#method.with_legend_format).
+pub type LegendFormat = dyn Fn(&str, f64) -> String + Send + Sync;
+
+/// Custom drawing code run by a chart on its plotting area. See [`Chart::pre_draw()`](struct.Chart.html#method.pre_draw).
+pub type DrawHook =
+    dyn Fn(&DrawingArea<BitMapBackend<RGBPixel>, Shift>, &ChartCoords) + Send + Sync;
+
+///
+/// Maps data coordinates to pixels of a chart's plotting area. Passed to draw hooks.
+///
+#[derive(Clone)]
+pub struct ChartCoords {
+    x_axis: ChartAxis,
+    y_axis: ChartAxis,
+    x_scale: f64,
+    y_scale: f64,
+    size: (i32, i32),
+    origin: (i32, i32),
+    minimap: Option<Minimap>,
+    /// Time spent on drawing the mesh, and the series.
+    draw_times: (Duration, Duration),
+}
+impl ChartCoords {
+    /// Converts a data point (before axis scaling) to pixel coordinates relative to the plotting area.
+    pub fn to_pixel(&self, xy: (f64, f64)) -> (i32, i32) {
+        (
+            self.x_axis.map(&(xy.0 * self.x_scale), (0, self.size.0)),
+            self.y_axis.map(&(xy.1 * self.y_scale), (self.size.1, 0)),
+        )
+    }
+    /// The visible x range, in data coordinates.
+    pub fn x_range(&self) -> (f64, f64) {
+        let range = self.x_axis.range();
+        (range.start / self.x_scale, range.end / self.x_scale)
+    }
+    /// The visible y range, in data coordinates.
+    pub fn y_range(&self) -> (f64, f64) {
+        let range = self.y_axis.range();
+        (range.start / self.y_scale, range.end / self.y_scale)
+    }
+    /// The size of the plotting area in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        (self.size.0 as u32, self.size.1 as u32)
+    }
+    /// The position of the plotting area's upper left corner in window pixels.
+    pub fn origin(&self) -> (i32, i32) {
+        self.origin
+    }
+}
+
+/// Placement and data ranges of a chart's overview inset.
+#[derive(Clone)]
+struct Minimap {
+    origin: (i32, i32),
+    size: (i32, i32),
+    x_range: (f64, f64),
+    y_range: (f64, f64),
+    inverted: (bool, bool),
+}
+impl Minimap {
+    /// Fraction of the plotting area's width and height covered by the inset.
+    const FRACTION: f64 = 0.25;
+
+    /// Places the inset in the lower right corner of the plotting area, if not all data is visible.
+    fn new(
+        config: &ChartConfig,
+        data: &[Series],
+        view: ((f64, f64), (f64, f64)),
+        size: (i32, i32),
+    ) -> Option<Self> {
+        if !config.minimap {
+            return None;
+        }
+        let (mut x_range, mut y_range) = ((f64::MAX, f64::MIN), (f64::MAX, f64::MIN));
+        let points = data
+            .iter()
+            .filter(|ser| ser.visible)
+            .flat_map(|ser| ser.data.iter().map(move |xy| ser.transformed(xy)));
+        for (x, y) in points.filter(|(x, y)| x.is_finite() && y.is_finite()) {
+            x_range = (x_range.0.min(x), x_range.1.max(x));
+            y_range = (y_range.0.min(y), y_range.1.max(y));
+        }
+        let ((x0, x1), (y0, y1)) = view;
+        if x_range.0 >= x0 && x_range.1 <= x1 && y_range.0 >= y0 && y_range.1 <= y1 {
+            return None;
+        }
+        let margin = (10.0 * config.ui_scale) as i32;
+        let width = (size.0 as f64 * Self::FRACTION) as i32;
+        let height = (size.1 as f64 * Self::FRACTION) as i32;
+        Some(Minimap {
+            origin: (size.0 - width - margin, size.1 - height - margin),
+            size: (width, height),
+            x_range: valid_range((x_range.0.min(x0), x_range.1.max(x1))),
+            y_range: valid_range((y_range.0.min(y0), y_range.1.max(y1))),
+            inverted: (config.x_inverted, config.y_inverted),
+        })
+    }
+
+    /// Converts a data point to pixel coordinates relative to the plotting area.
+    fn to_pixel(&self, xy: (f64, f64)) -> (i32, i32) {
+        let mut fx = (xy.0 - self.x_range.0) / (self.x_range.1 - self.x_range.0);
+        let mut fy = (xy.1 - self.y_range.0) / (self.y_range.1 - self.y_range.0);
+        if self.inverted.0 {
+            fx = 1.0 - fx;
+        }
+        if !self.inverted.1 {
+            fy = 1.0 - fy;
+        }
+        (
+            self.origin.0 + (fx * self.size.0 as f64).round() as i32,
+            self.origin.1 + (fy * self.size.1 as f64).round() as i32,
+        )
+    }
+    /// Converts pixel coordinates relative to the plotting area to a data point, if within the inset.
+    fn to_data(&self, px: (i32, i32)) -> Option<(f64, f64)> {
+        let (x, y) = (px.0 - self.origin.0, px.1 - self.origin.1);
+        if x < 0 || y < 0 || x > self.size.0 || y > self.size.1 {
+            return None;
+        }
+        let mut fx = x as f64 / self.size.0 as f64;
+        let mut fy = y as f64 / self.size.1 as f64;
+        if self.inverted.0 {
+            fx = 1.0 - fx;
+        }
+        if !self.inverted.1 {
+            fy = 1.0 - fy;
+        }
+        Some((
+            self.x_range.0 + fx * (self.x_range.1 - self.x_range.0),
+            self.y_range.0 + fy * (self.y_range.1 - self.y_range.0),
+        ))
+    }
+
+    /// Draws the inset with all series as thin lines or dots, and the visible range as a rectangle.
+    fn draw<DB: DrawingBackend>(
+        &self,
+        area: &DrawingArea<DB, Shift>,
+        data: &[Series],
+        view: ((f64, f64), (f64, f64)),
+    ) {
+        let corner = (self.origin.0 + self.size.0, self.origin.1 + self.size.1);
+        area.draw(&Rectangle::new([self.origin, corner], WHITE.filled()))
+            .unwrap();
+        for series in data.iter().filter(|ser| ser.visible) {
+            let width = self.size.0.max(1) as usize;
+            let entries: Vec<_> = if series.series_type == SeriesType::Line {
+                series.data.reduced(0..series.data.len(), width)
+            } else {
+                let stride = (series.data.len() / (2 * width)).max(1);
+                series.data.iter().step_by(stride).cloned().collect()
+            };
+            let points = entries
+                .iter()
+                .map(|xy| series.transformed(xy))
+                .filter(|(x, y)| x.is_finite() && y.is_finite())
+                .map(|xy| self.to_pixel(xy));
+            if series.series_type == SeriesType::Line {
+                area.draw(&PathElement::new(points.collect::<Vec<_>>(), &series.color))
+                    .unwrap();
+            } else {
+                for px in points {
+                    area.draw_pixel(px, &series.color).unwrap();
+                }
+            }
+        }
+        let ((x0, x1), (y0, y1)) = view;
+        let rect = [self.to_pixel((x0, y1)), self.to_pixel((x1, y0))];
+        area.draw(&Rectangle::new(rect, BLACK.mix(0.15).filled()))
+            .unwrap();
+        area.draw(&Rectangle::new(rect, &BLUE)).unwrap();
+        area.draw(&Rectangle::new([self.origin, corner], &BLACK))
+            .unwrap();
+    }
+}
+
+///
+/// Statistics of a single chart update, returned by [`Chart::update_stats()`](struct.Chart.html#method.update_stats).
+///
+/// If neither `rendered` nor `skipped` is set, nothing changed since the last frame.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct UpdateStats {
+    /// A newly rendered frame was displayed.
+    pub rendered: bool,
+    /// The chart changed, but rendering was skipped due to the FPS skip, a busy render thread or a closed window.
+    pub skipped: bool,
+    /// The chart is paused.
+    pub paused: bool,
+    /// Time spent rendering the displayed frame. With threaded rendering, measured on the render thread.
+    pub render_time: Duration,
+    /// Number of data points of visible series and ensembles in the displayed frame.
+    pub points: usize,
+}
+
+/// Counts the data points of visible series and ensembles.
+fn count_points(data: &[Series], ensembles: &[EnsembleSeries]) -> usize {
+    let series: usize = data
+        .iter()
+        .filter(|s| s.visible)
+        .map(|s| s.data.len())
+        .sum();
+    let ensembles: usize = ensembles
+        .iter()
+        .filter(|e| e.visible)
+        .map(|e| e.data.iter().map(|(_, values)| values.len()).sum::<usize>())
+        .sum();
+    series + ensembles
+}
+
+type UpdateHook = dyn FnMut(&mut Chart);
+type RangeHook = dyn FnMut(&mut Chart, (f64, f64));
+type AlertHook = dyn FnMut(&mut Chart, f64);
+/// Accessor of a series binding, returning `None` for states of other types.
+type BindingAccessor = dyn Fn(&dyn Any) -> Option<(f64, f64)>;
+
+/// Publishes the window's frame and series data to the HTTP server, at most once per the server's interval.
+#[cfg(feature = "http")]
+fn publish(server: &mut Option<FrameServer>, window: &BufferWindow, data: &[Series]) {
+    if let Some(server) = server {
+        if server.is_due() {
+            let (width, height) = window.dimensions();
+            server.publish_frame(&window.buffer_u8, width as u32, height as u32);
+            server.publish_json(series_to_json(data.iter().map(|s| (&s.name[..], &s.data))));
+        }
+    }
+}
+
+/// Dispatches draw hooks for a rendering stage, independent of the concrete backend.
+type StageHook<'h, DB> = dyn Fn(HookStage, &DrawingArea<DB, Shift>, &ChartCoords) + 'h;
+
+/// The point in chart rendering at which a draw hook runs.
+#[derive(Clone, Copy)]
+enum HookStage {
+    PreDraw,
+    PostDraw,
+}
+
+impl Chart {
+    fn new(window: BufferWindow, series: Vec<Series>) -> Self {
+        Chart {
+            window,
+            data: series,
+            ensembles: Vec::new(),
+            data_limit: None,
+            retention: None,
+            error: None,
+            config: ChartConfig::new(),
+            limits: AxisLimits::empty(),
+            home_limits: AxisLimits::empty(),
+            y_auto_range: AutoRange::Exact,
+            y_range: None,
+            range_frames: 0,
+            range_animation: None,
+            renderer: None,
+            toggle_keys: false,
+            pause_keys: false,
+            frame_budget: None,
+            paused: false,
+            step_pending: false,
+            source: None,
+            recorder: None,
+            update_hook: None,
+            range_hook: None,
+            hooked_range: None,
+            alerts: Vec::new(),
+            bindings: Vec::new(),
+            functions: Vec::new(),
+            derived: Vec::new(),
+            dirty: true,
+            drawn_ranges: None,
+            coords: None,
+            mesh_cache: MeshCache::new(),
+            palette: Vec::new(),
+            auto_colors: 0,
+            snapshots: None,
+            context_menu: None,
+            menu_actions: Vec::new(),
+            screenshot_pending: false,
+            #[cfg(feature = "http")]
+            server: None,
+            #[cfg(feature = "profiling")]
+            profile: Profile::new(),
+        }
+    }
+
+    /// Returns if the chart's window is open.
+    pub fn is_open(&self) -> bool {
+        self.window.is_open()
+    }
+
+    /// Returns if closing the chart's window was handled, after [`request_close()`](#method.request_close),
+    /// or after an update detected that the user closed the window.
+    ///
+    /// See [`BufferWindow::close_requested()`](../window/struct.BufferWindow.html#method.close_requested).
+    pub fn close_requested(&self) -> bool {
+        self.window.close_requested()
+    }
+
+    /// Requests the chart's window to close.
+    ///
+    /// See [`BufferWindow::request_close()`](../window/struct.BufferWindow.html#method.request_close).
+    pub fn request_close(&mut self) {
+        self.window.request_close();
+    }
+
+    /// Sets the chart's title.
+    pub fn set_title(&mut self, title: &str) {
+        self.window.set_title(title);
+    }
+
+    /// Sets the chart's x and y axis label.
+    pub fn set_labels(&mut self, x_label: &str, y_label: &str) {
+        self.dirty = true;
+        self.config.x_label = x_label.to_string();
+        self.config.y_label = y_label.to_string();
+    }
+
+    /// Sets the chart's x axis label.
+    pub fn set_x_label(&mut self, x_label: &str) {
+        self.dirty = true;
+        self.config.x_label = x_label.to_string();
+    }
+
+    /// Sets the chart's y axis label.
+    pub fn set_y_label(&mut self, y_label: &str) {
+        self.dirty = true;
+        self.config.y_label = y_label.to_string();
+    }
+
+    /// Sets the style of the chart's mesh. See [`MeshStyle`](struct.MeshStyle.html).
+    pub fn set_mesh_style(&mut self, style: MeshStyle) {
+        self.dirty = true;
+        self.config.mesh = style;
+    }
+
+    /// Sets the unit of the chart's y axis. See [`ChartBuilder::with_y_unit()`](struct.ChartBuilder.html#method.with_y_unit).
+    pub fn set_y_unit(&mut self, unit: &str) {
+        self.dirty = true;
+        self.config.y_unit = unit.to_string();
+    }
+
+    /// Enables or disables SI prefixes for y tick labels. See [`ChartBuilder::with_si_prefixes()`](struct.ChartBuilder.html#method.with_si_prefixes).
+    pub fn set_si_prefixes(&mut self, si: bool) {
+        self.dirty = true;
+        self.config.y_si = si;
+    }
+
+    /// Sets the chart's x axis limits. Use `None` for automatic limit(s).
+    ///
+    /// See [`ChartBuilder::with_xlim()`](struct.ChartBuilder.html#method.with_xlim).
+    pub fn set_xlim(&mut self, min: Option<f64>, max: Option<f64>) {
+        self.dirty = true;
+        self.limits.x_min = min;
+        self.limits.x_max = max;
+    }
+
+    /// Sets the width of the visible x range in follow mode. Use `None` to disable follow mode.
+    ///
+    /// See [`ChartBuilder::with_x_window()`](struct.ChartBuilder.html#method.with_x_window).
+    pub fn set_x_window(&mut self, width: Option<f64>) {
+        self.dirty = true;
+        self.limits.x_window = width;
+        self.config.x_follow = width.is_some();
+    }
+
+    /// Sets the chart's y axis limits. Use `None` for automatic limit(s).
+    ///
+    /// See [`ChartBuilder::with_ylim()`](struct.ChartBuilder.html#method.with_ylim).
+    pub fn set_ylim(&mut self, min: Option<f64>, max: Option<f64>) {
+        self.dirty = true;
+        self.limits.y_min = min;
+        self.limits.y_max = max;
+        self.y_range = None;
+    }
+
+    /// Sets the chart's x and y axis scales.
+    ///
+    /// See [`ChartBuilder::with_x_scale()`](struct.ChartBuilder.html#method.with_x_scale).
+    pub fn set_scales(&mut self, x_scale: f64, y_scale: f64) {
+        self.dirty = true;
+        self.config.x_scale = x_scale;
+        self.config.y_scale = y_scale;
+    }
+
+    /// Sets the type of the chart's x axis. Does not change axis limits.
+    ///
+    /// See [`ChartBuilder::with_x_axis_type()`](struct.ChartBuilder.html#method.with_x_axis_type).
+    pub fn set_x_axis_type(&mut self, axis: XAxisType) {
+        self.dirty = true;
+        self.config.x_axis = axis;
+    }
+
+    /// Sets the chart's x and y axes inverted or not.
+    ///
+    /// See [`ChartBuilder::with_y_inverted()`](struct.ChartBuilder.html#method.with_y_inverted).
+    pub fn set_inverted(&mut self, x_inverted: bool, y_inverted: bool) {
+        self.dirty = true;
+        self.config.x_inverted = x_inverted;
+        self.config.y_inverted = y_inverted;
+    }
+
+    /// Breaks the chart's y axis into a lower and an upper range. Use `None` for an unbroken axis.
+    ///
+    /// See [`ChartBuilder::with_y_break()`](struct.ChartBuilder.html#method.with_y_break).
+    pub fn set_y_break(&mut self, ranges: Option<((f64, f64), (f64, f64))>) {
+        self.dirty = true;
+        self.config.y_break = ranges;
+    }
+
+    /// Shows or hides the overview inset.
+    ///
+    /// See [`ChartBuilder::with_minimap()`](struct.ChartBuilder.html#method.with_minimap).
+    pub fn set_minimap(&mut self, minimap: bool) {
+        self.dirty = true;
+        self.config.minimap = minimap;
+    }
+
+    /// Sets the rotation of x tick labels in degrees, counter-clockwise.
+    ///
+    /// See [`ChartBuilder::with_x_label_rotation()`](struct.ChartBuilder.html#method.with_x_label_rotation).
+    pub fn set_x_label_rotation(&mut self, degrees: f64) {
+        self.dirty = true;
+        self.config.x_label_rotation = degrees;
+    }
+    /// Sets the maximum width of x tick labels in pixels. Use `None` for unlimited width.
+    ///
+    /// See [`ChartBuilder::with_max_label_width()`](struct.ChartBuilder.html#method.with_max_label_width).
+    pub fn set_max_label_width(&mut self, width: Option<u32>) {
+        self.dirty = true;
+        self.config.max_label_width = width;
+    }
+
+    /// Previews the chart as seen with a color vision deficiency. Use `None` to show true colors.
+    ///
+    /// See [`ChartBuilder::with_cvd_preview()`](struct.ChartBuilder.html#method.with_cvd_preview).
+    pub fn set_cvd_preview(&mut self, cvd: Option<ColorVisionDeficiency>) {
+        self.dirty = true;
+        self.window.set_cvd_preview(cvd);
+    }
+
+    /// Shows or hides the latest value of each series in its legend entry.
+    ///
+    /// See [`ChartBuilder::with_legend_values()`](struct.ChartBuilder.html#method.with_legend_values).
+    pub fn set_legend_values(&mut self, values: bool) {
+        self.dirty = true;
+        self.config.legend_values = values;
+    }
+
+    /// Sets the position of the legend in the plotting area.
+    pub fn set_legend_position(&mut self, position: LegendPosition) {
+        self.dirty = true;
+        self.config.legend_position = position;
+    }
+
+    /// Sets the chart's y axis to logarithmic or linear.
+    pub fn set_y_log(&mut self, y_log: bool) {
+        self.dirty = true;
+        self.config.y_log = y_log;
+    }
+
+    /// Sets how automatic y axis limits follow the data.
+    ///
+    /// See [`ChartBuilder::with_y_auto_range()`](struct.ChartBuilder.html#method.with_y_auto_range).
+    pub fn set_y_auto_range(&mut self, mode: AutoRange) {
+        self.dirty = true;
+        self.y_auto_range = mode;
+        self.y_range = None;
+    }
+
+    /// Sets the number of rendered frames over which changes of axis limits are animated. Use 0 to disable animation.
+    ///
+    /// See [`ChartBuilder::with_range_animation()`](struct.ChartBuilder.html#method.with_range_animation).
+    pub fn set_range_animation(&mut self, frames: usize) {
+        self.range_frames = frames;
+        self.range_animation = None;
+    }
+
+    /// Sets how points with infinite coordinates are rendered.
+    ///
+    /// See [`ChartBuilder::with_non_finite()`](struct.ChartBuilder.html#method.with_non_finite).
+    pub fn set_non_finite(&mut self, mode: NonFinite) {
+        self.dirty = true;
+        self.config.non_finite = mode;
+    }
+
+    /// Sets the chart's data limit. Use `None` for no limit.
+    /// Series exceeding the new limit are trimmed immediately.
+    ///
+    /// See [`ChartBuilder::with_data_limit()`](struct.ChartBuilder.html#method.with_data_limit).
+    pub fn set_data_limit(&mut self, max_values: Option<usize>) {
+        self.dirty = true;
+        self.data_limit = max_values;
+        if let Some(lim) = max_values {
+            for ser in &mut self.data {
+                let lim = ser.limit.unwrap_or(lim);
+                match ser.series_type {
+                    SeriesType::Segment | SeriesType::Arrow => ser.drop_front(2 * lim),
+                    _ => ser.drop_front(lim),
+                }
+            }
+            for ser in &mut self.ensembles {
+                ser.trim(ser.limit.unwrap_or(lim));
+            }
+        }
+    }
+
+    /// Enables retention of trimmed entries in a file per series in the given directory, or disables it with `None`.
+    /// The directory is created if it does not exist. Disabling keeps existing files.
+    ///
+    /// See [`ChartBuilder::with_retention()`](struct.ChartBuilder.html#method.with_retention).
+    pub fn set_retention(&mut self, dir: Option<&str>) -> io::Result<()> {
+        for ser in &mut self.data {
+            ser.retention = None;
+        }
+        self.retention = None;
+        if let Some(dir) = dir {
+            fs::create_dir_all(dir)?;
+            self.retention = Some((PathBuf::from(dir), 0));
+            for index in 0..self.data.len() {
+                self.attach_retention(index)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates the retention file of a series, if retention is enabled.
+    fn attach_retention(&mut self, index: usize) -> io::Result<()> {
+        if let Some((dir, files)) = &mut self.retention {
+            let name: String = self.data[index]
+                .name
+                .chars()
+                .map(|c| if c.is_alphanumeric() { c } else { '_' })
+                .collect();
+            let file = RetentionFile::create(dir.join(format!("{}_{}.bin", files, name)))?;
+            *files += 1;
+            self.data[index].retention = Some(Arc::new(Mutex::new(file)));
+        }
+        Ok(())
+    }
+
+    /// Sets a data source that is polled on each update of the chart. Use `None` to remove the data source.
+    ///
+    /// See [`ChartBuilder::with_data_source()`](struct.ChartBuilder.html#method.with_data_source).
+    pub fn set_data_source(&mut self, source: Option<Box<dyn DataSource>>) {
+        self.source = source.map(|source| (source, Instant::now()));
+    }
+
+    /// Sets a recorder that logs all data pushed into the chart. Use `None` to stop recording.
+    ///
+    /// See [`ChartBuilder::with_recorder()`](struct.ChartBuilder.html#method.with_recorder).
+    pub fn set_recorder(&mut self, recorder: Option<ChartRecorder>) {
+        self.recorder = recorder;
+    }
+
+    /// Removes the recorder and returns it, e.g. to [`finish()`](../record/struct.ChartRecorder.html#method.finish) the recording.
+    pub fn take_recorder(&mut self) -> Option<ChartRecorder> {
+        self.recorder.take()
+    }
+
+    /// Records a push call, if a recorder is set. The event is only created when recording.
+    fn record<F: FnOnce() -> PushEvent>(&mut self, event: F) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(&event());
+        }
+    }
+
+    /// Polls the data source, if any, and pushes the sample.
+    fn poll_source(&mut self) {
+        let sample = match &self.source {
+            Some((source, start)) => {
+                let t = start.elapsed().as_secs_f64();
+                (t, source.sample(t))
+            }
+            None => return,
+        };
+        if !sample.1.is_empty() {
+            self.push_time_series(sample.0, &sample.1);
+        }
+    }
+
+    /// Scales font sizes, marker sizes, line widths and label areas.
+    ///
+    /// See [`ChartBuilder::with_ui_scale()`](struct.ChartBuilder.html#method.with_ui_scale).
+    pub fn set_ui_scale(&mut self, scale: f64) {
+        self.dirty = true;
+        self.config.ui_scale = scale;
+    }
+
+    /// Sets an image drawn as background of the chart's plotting area. Use `None` to remove it.
+    ///
+    /// See [`ChartBuilder::with_background_image()`](struct.ChartBuilder.html#method.with_background_image).
+    pub fn set_background_image(&mut self, image: Option<ImageLayer>) {
+        self.dirty = true;
+        self.config.background = image.map(Arc::new);
+    }
+
+    /// Returns the underlying [`BufferWindow`](../window/struct.BufferWindow.html).
+    pub fn window(&mut self) -> &mut BufferWindow {
+        &mut self.window
+    }
+
+    /// Returns the number of series in the chart.
+    pub fn num_series(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns the index of the first series with the given name, if any.
+    pub fn series_index(&self, name: &str) -> Option<usize> {
+        self.data.iter().position(|series| series.name == name)
+    }
+
+    /// Adds a series to the chart while running, e.g. when new species appear in a simulation. Returns the index of the series.
+    ///
+    /// If the series' retention file can't be created, the series is added without retention,
+    /// and the error is returned by [`take_error()`](#method.take_error).
+    ///
+    /// Note that [`push_time_series()`](#method.push_time_series) requires a value for each series not derived from another one.
+    pub fn add_series(&mut self, series: Series) -> usize {
+        self.dirty = true;
+        self.data.push(series);
+        self.assign_color(self.data.len() - 1);
+        if let Err(err) = self.attach_retention(self.data.len() - 1) {
+            self.error = Some(err);
+        }
+        self.data.len() - 1
+    }
+
+    /// Assigns the next palette color to a series, if it has no explicit color.
+    fn assign_color(&mut self, index: usize) {
+        if self.data[index].auto_color {
+            self.data[index].color = palette_color(&self.palette, self.auto_colors);
+            self.data[index].auto_color = false;
+            self.auto_colors += 1;
+        }
+    }
+
+    /// Removes a series from the chart and returns it. Indices of all following series are shifted down by one.
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of series indices.
+    pub fn remove_series(&mut self, index: usize) -> Series {
+        self.record(|| PushEvent::RemoveSeries { index });
+        self.dirty = true;
+        self.functions.retain(|plot| plot.index != index);
+        for plot in self.functions.iter_mut().filter(|plot| plot.index > index) {
+            plot.index -= 1;
+        }
+        self.derived
+            .retain(|plot| plot.index != index && plot.source != index);
+        for plot in &mut self.derived {
+            if plot.index > index {
+                plot.index -= 1;
+            }
+            if plot.source > index {
+                plot.source -= 1;
+            }
+        }
+        self.alerts.retain(|alert| alert.series != index);
+        for alert in self.alerts.iter_mut().filter(|alert| alert.series > index) {
+            alert.series -= 1;
+        }
+        self.bindings.retain(|binding| binding.series != index);
+        for binding in self.bindings.iter_mut().filter(|b| b.series > index) {
+            binding.series -= 1;
+        }
+        self.data.remove(index)
+    }
+
+    /// Returns the entries of a series with x values from `x0` to `x1` (inclusive), e.g. for exporting the visible data.
+    ///
+    /// See [`Series::range()`](struct.Series.html#method.range) and [`visible_ranges()`](#method.visible_ranges).
+    ///
+    /// # Example
+    /// ```
+    /// use easy_graph::ui::chart::{ChartBuilder, Series};
+    /// use easy_graph::color::style::RED;
+    ///
+    /// let mut chart = ChartBuilder::new()
+    ///     .add_series(Series::line("A", &RED))
+    ///     .build_headless();
+    /// for t in 0..10 {
+    ///     chart.push_time_series(t as f64, &[(t * t) as f64]);
+    /// }
+    /// let data: Vec<_> = chart.slice_series(0, 2.0, 4.0).collect();
+    /// assert_eq!(data, vec![(2.0, 4.0), (3.0, 9.0), (4.0, 16.0)]);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of series indices.
+    pub fn slice_series(
+        &self,
+        index: usize,
+        x0: f64,
+        x1: f64,
+    ) -> impl Iterator<Item = (f64, f64)> + '_ {
+        self.data[index].range(x0, x1)
+    }
+
+    /// Summarizes the entries of a series with x values from `x0` to `x1` (inclusive) by their minimum, maximum and mean y values
+    /// in at least `count` buckets, taken from the series' pre-aggregated levels.
+    /// If there are fewer than `16 * count` entries, returns one bucket per entry.
+    ///
+    /// See [`Series::buckets()`](struct.Series.html#method.buckets) and module [`store`](../store/index.html).
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of series indices.
+    pub fn series_buckets(&self, index: usize, x0: f64, x1: f64, count: usize) -> Vec<Bucket> {
+        self.data[index].buckets(x0, x1, count)
+    }
+
+    /// The x and y axis ranges of the last rendered frame, if any.
+    pub fn visible_ranges(&self) -> Option<((f64, f64), (f64, f64))> {
+        self.drawn_ranges
+    }
+
+    /// Statistics of all series, like number of values, range, mean and slope, e.g. for end-of-run reports.
+    ///
+    /// Statistics are computed from the data retained by the series. See [`summary`](../summary/index.html) module docs for an example.
+    pub fn summary(&self) -> ChartSummary {
+        self.summary_slope(None)
+    }
+
+    /// Statistics of all series, like [`summary()`](#method.summary), but with slopes computed only from the entries
+    /// with x values within `window` from the last entry of each series, e.g. for the recent trend of a time series.
+    pub fn summary_with_slope_window(&self, window: f64) -> ChartSummary {
+        self.summary_slope(Some(window))
+    }
+
+    fn summary_slope(&self, slope_window: Option<f64>) -> ChartSummary {
+        ChartSummary {
+            series: self
+                .data
+                .iter()
+                .map(|ser| {
+                    let mut summary = SeriesSummary::new(&ser.name, &ser.data, slope_window);
+                    summary.unit = ser.unit.clone();
+                    summary.meta = ser.meta.clone();
+                    summary
+                })
+                .collect(),
+        }
+    }
+
+    /// Writes the data of all series as CSV, with an x and a y column per series.
+    ///
+    /// Headers are the series' names, with the unit in brackets for y columns, e.g. `Biomass x,Biomass [kg]`.
+    /// Series metadata is written before the header, as comment lines like `# Biomass: source = field survey`.
+    /// Cells of series shorter than the longest one are left empty.
+    pub fn write_csv<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        for series in &self.data {
+            for (key, value) in &series.meta {
+                writeln!(writer, "# {}: {} = {}", series.name, key, value)?;
+            }
+        }
+        let header: Vec<_> = self
+            .data
+            .iter()
+            .flat_map(|ser| vec![csv_cell(&format!("{} x", ser.name)), csv_cell(&ser.label())])
+            .collect();
+        writeln!(writer, "{}", header.join(","))?;
+        let rows = self
+            .data
+            .iter()
+            .map(|ser| ser.data.len())
+            .max()
+            .unwrap_or(0);
+        for row in 0..rows {
+            let cells: Vec<_> = self
+                .data
+                .iter()
+                .flat_map(|ser| match ser.data.get(row) {
+                    Some((x, y)) => vec![x.to_string(), y.to_string()],
+                    None => vec![String::new(), String::new()],
+                })
+                .collect();
+            writeln!(writer, "{}", cells.join(","))?;
+        }
+        Ok(())
+    }
+
+    /// Saves the data of all series to a CSV file. See [`write_csv()`](#method.write_csv) for the format.
+    pub fn save_csv<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_csv(&mut writer)?;
+        writer.flush()
+    }
+
+    /// The path of a series' retention file, if retention is enabled. See module [`retention`](../retention/index.html).
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of series indices.
+    pub fn retention_path(&self, index: usize) -> Option<PathBuf> {
+        self.data[index]
+            .retention
+            .as_ref()
+            .map(|file| file.lock().unwrap().path().to_path_buf())
+    }
+    /// The number of entries of a series retained on disk after trimming. 0 without retention.
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of series indices.
+    pub fn retained_len(&self, index: usize) -> usize {
+        self.data[index]
+            .retention
+            .as_ref()
+            .map_or(0, |file| file.lock().unwrap().len())
+    }
+    /// Reads the retained entries of a series in a range of indices, where index 0 is the oldest entry.
+    /// The range is clamped to the retained entries.
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of series indices.
+    pub fn retained(&self, index: usize, range: Range<usize>) -> io::Result<Vec<(f64, f64)>> {
+        match &self.data[index].retention {
+            Some(file) => file.lock().unwrap().read(range),
+            None => Ok(Vec::new()),
+        }
+    }
+    /// Reads the retained entries of a series with x values from `x0` to `x1` (inclusive).
+    ///
+    /// Uses binary search, and thus assumes increasing x values, as for time series.
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of series indices.
+    pub fn retained_between(&self, index: usize, x0: f64, x1: f64) -> io::Result<Vec<(f64, f64)>> {
+        match &self.data[index].retention {
+            Some(file) => file.lock().unwrap().read_between(x0, x1),
+            None => Ok(Vec::new()),
+        }
+    }
+    /// All entries of a series over the full run: retained entries, followed by the entries in memory.
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of series indices.
+    pub fn full_data(&self, index: usize) -> io::Result<Vec<(f64, f64)>> {
+        let mut data = self.retained(index, 0..usize::MAX)?;
+        data.extend(self.data[index].data.iter().cloned());
+        Ok(data)
+    }
+    /// Returns and clears the last error of an operation that does not report errors directly,
+    /// like writing trimmed entries to retention files, saving screenshots by the hotkey, or saving and exporting from the context menu.
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error
+            .take()
+            .or_else(|| self.window.take_error().map(into_io_error))
+            .or_else(|| {
+                self.data
+                    .iter()
+                    .filter_map(|ser| ser.retention.as_ref())
+                    .find_map(|file| file.lock().unwrap().take_error())
+            })
+    }
+
+    /// Metadata of the current frame, as written with saved images: title, visible axis ranges, and names and data counts of all series.
+    ///
+    /// Axis ranges are `None` before the chart was drawn. See module [`metadata`](../metadata/index.html).
+    pub fn image_metadata(&self) -> ImageMetadata {
+        let mut metadata = ImageMetadata::new(self.window.title());
+        if let Some(coords) = &self.coords {
+            metadata.x_range = Some(coords.x_range());
+            metadata.y_range = Some(coords.y_range());
+        }
+        metadata.series = self
+            .data
+            .iter()
+            .map(|series| SeriesMetadata {
+                name: series.name.clone(),
+                unit: series.unit.clone(),
+                count: series.data.len(),
+            })
+            .chain(self.ensembles.iter().map(|ensemble| SeriesMetadata {
+                name: ensemble.name.clone(),
+                unit: None,
+                count: ensemble.data.len(),
+            }))
+            .collect();
+        metadata
+    }
+
+    /// Saves the chart's current frame to an image file, with metadata in the given format.
+    /// The image format is derived from the file extension.
+    ///
+    /// See module [`metadata`](../metadata/index.html) for an example.
+    pub fn save_image<P: AsRef<Path>>(
+        &self,
+        path: P,
+        format: MetadataFormat,
+    ) -> Result<(), image::ImageError> {
+        self.window
+            .save_buffer_with_metadata(path, &self.image_metadata(), format)
+    }
+
+    /// Renames a series.
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of series indices.
+    pub fn rename_series(&mut self, index: usize, name: &str) {
+        self.dirty = true;
+        self.data[index].name = name.to_string();
+    }
+
+    /// Shows or hides a series. Hidden series keep their data, are shown hollow in the legend,
+    /// and are ignored for automatic axis limits.
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of series indices.
+    pub fn set_series_visible(&mut self, index: usize, visible: bool) {
+        self.dirty = true;
+        self.data[index].visible = visible;
+    }
+
+    /// Returns if a series is visible.
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of series indices.
+    pub fn is_series_visible(&self, index: usize) -> bool {
+        self.data[index].visible
+    }
+
+    /// Toggles the visibility of a series.
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of series indices.
+    pub fn toggle_series(&mut self, index: usize) {
+        self.dirty = true;
+        let ser = &mut self.data[index];
+        ser.visible = !ser.visible;
+    }
+
+    /// Shows or hides all series of a group. See [`Series::with_group()`](struct.Series.html#method.with_group).
+    pub fn set_group_visible(&mut self, group: &str, visible: bool) {
+        self.dirty = true;
+        for ser in self
+            .data
+            .iter_mut()
+            .filter(|ser| ser.group.as_deref() == Some(group))
+        {
+            ser.visible = visible;
+        }
+    }
+
+    /// Pauses the chart. The display is frozen, and neither the update hook nor the data source are polled,
+    /// until [`resume()`](#method.resume) or [`step()`](#method.step) is called.
+    /// Window events are still processed by [`update()`](#method.update).
+    ///
+    /// Data pushed while paused is stored and shown after resuming.
+    /// To halt a simulation as well, call [`wait_while_paused()`](#method.wait_while_paused) in its loop.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Resumes a paused chart.
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.step_pending = false;
+    }
+
+    /// Returns if the chart is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Pauses the chart, and lets the next [`update()`](#method.update) run as if it was not paused.
+    pub fn step(&mut self) {
+        self.paused = true;
+        self.step_pending = true;
+    }
+
+    /// Blocks while the chart is paused, processing window events, until it is resumed, stepped, or closed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use easy_graph::ui::chart::{ChartBuilder, Series};
+    /// use easy_graph::color::style::RED;
+    ///
+    /// let mut chart = ChartBuilder::new()
+    ///     .add_series(Series::line("A", &RED))
+    ///     .with_pause_keys()
+    ///     .build();
+    ///
+    /// for tick in 0..1000 {
+    ///     chart.push_time_series(tick as f64, &[(tick as f64).sqrt()]);
+    ///     chart.update();
+    ///     chart.wait_while_paused();
+    /// }
+    /// ```
+    pub fn wait_while_paused(&mut self) {
+        while self.paused && !self.step_pending && self.window.is_open() {
+            self.update();
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// Handles space (pause/resume) and right arrow (step) keys pressed in the chart's window.
+    fn handle_pause_keys(&mut self) {
+        for key in self.window.keys_pressed(KeyRepeat::No) {
+            match key {
+                Key::Space if self.paused => self.resume(),
+                Key::Space => self.pause(),
+                Key::Right => self.step(),
+                _ => {}
+            }
+        }
+    }
+
+    /// Moves the visible range to the position clicked in the overview inset, if any.
+    fn handle_minimap(&mut self) {
+        if !self.window.is_mouse_down(MouseButton::Left) {
+            return;
+        }
+        if let Some(pos) = self.window.mouse_pos() {
+            self.minimap_jump(pos);
+        }
+    }
+    /// Centers the visible range on the data point under the pixel in the overview inset.
+    fn minimap_jump(&mut self, px: (i32, i32)) {
+        let (coords, ((x0, x1), (y0, y1))) = match (&self.coords, self.drawn_ranges) {
+            (Some(coords), Some(ranges)) => (coords, ranges),
+            _ => return,
+        };
+        let local = (px.0 - coords.origin.0, px.1 - coords.origin.1);
+        let (x, y) = match coords.minimap.as_ref().and_then(|m| m.to_data(local)) {
+            Some(xy) => xy,
+            None => return,
+        };
+        let half_width = 0.5 * (x1 - x0);
+        self.set_x_window(None);
+        self.set_xlim(Some(x - half_width), Some(x + half_width));
+        if let (Some(_), Some(_)) = (self.limits.y_min, self.limits.y_max) {
+            let half_height = 0.5 * (y1 - y0);
+            self.set_ylim(Some(y - half_height), Some(y + half_height));
+        }
+    }
+
+    /// Opens, closes and dispatches the context menu by mouse clicks in the chart's window.
+    fn handle_context_menu(&mut self) {
+        let mouse = self.window.mouse_pos();
+        let buttons = (
+            self.window.is_mouse_down(MouseButton::Left),
+            self.window.is_mouse_down(MouseButton::Right),
+        );
+        let dim = self.window.dimensions();
+        let (changed, action) = match &mut self.context_menu {
+            Some(menu) => menu.update(mouse, buttons, dim, self.config.ui_scale),
+            None => return,
+        };
+        self.dirty |= changed;
+        match action {
+            Some(MenuAction::SavePng) => self.screenshot_pending = true,
+            Some(MenuAction::ExportCsv) => {
+                let path = self.window.screenshot_path("data", "csv");
+                let result = fs::create_dir_all(path.parent().unwrap_or(&path))
+                    .and_then(|_| self.save_csv(&path));
+                if let Err(err) = result {
+                    self.error = Some(err);
+                }
+            }
+            Some(MenuAction::ClearData) => self.clear_data(),
+            Some(MenuAction::ResetZoom) => self.reset_zoom(),
+            Some(MenuAction::Custom(index)) => {
+                let mut callback =
+                    std::mem::replace(&mut self.menu_actions[index].1, Box::new(|_| {}));
+                callback(self);
+                self.menu_actions[index].1 = callback;
+            }
+            None => {}
+        }
+    }
+
+    /// Enables or disables the context menu.
+    ///
+    /// See [`ChartBuilder::with_context_menu()`](struct.ChartBuilder.html#method.with_context_menu).
+    pub fn set_context_menu(&mut self, enabled: bool) {
+        self.dirty = true;
+        self.context_menu = None;
+        if enabled {
+            let mut menu = ContextMenu::new();
+            for (index, (label, _)) in self.menu_actions.iter().enumerate() {
+                menu.add_item(label, MenuAction::Custom(index));
+            }
+            self.context_menu = Some(menu);
+        }
+    }
+
+    /// Adds an action to the context menu, which calls the given callback when selected.
+    ///
+    /// The action is shown once the context menu is enabled, see [`ChartBuilder::with_context_menu()`](struct.ChartBuilder.html#method.with_context_menu).
+    /// ```no_run
+    ///# use easy_graph::ui::chart::ChartBuilder;
+    /// let mut chart = ChartBuilder::new().with_context_menu().build();
+    /// chart.add_menu_action("Log scale", |chart| chart.set_y_log(true));
+    /// ```
+    pub fn add_menu_action<F>(&mut self, label: &str, callback: F)
+    where
+        F: FnMut(&mut Chart) + 'static,
+    {
+        let index = self.menu_actions.len();
+        self.menu_actions
+            .push((label.to_string(), Box::new(callback)));
+        if let Some(menu) = &mut self.context_menu {
+            menu.add_item(label, MenuAction::Custom(index));
+        }
+    }
+
+    /// Removes the data of all series and ensembles. Names and styles are not affected.
+    pub fn clear_data(&mut self) {
+        self.record(|| PushEvent::ClearData);
+        self.dirty = true;
+        for ser in &mut self.data {
+            ser.clear();
+        }
+        for ser in &mut self.ensembles {
+            ser.clear();
+        }
+        self.y_range = None;
+    }
+
+    /// Restores the axis limits the chart was built with, e.g. after jumping through the overview inset.
+    pub fn reset_zoom(&mut self) {
+        self.dirty = true;
+        self.limits = self.home_limits.clone();
+        self.config.x_follow = self.limits.x_window.is_some();
+        self.y_range = None;
+    }
+
+    /// Toggles series visibility by number keys pressed in the chart's window.
+    fn handle_toggle_keys(&mut self) {
+        for key in self.window.keys_pressed(KeyRepeat::No) {
+            let index = match key {
+                Key::Key1 => 0,
+                Key::Key2 => 1,
+                Key::Key3 => 2,
+                Key::Key4 => 3,
+                Key::Key5 => 4,
+                Key::Key6 => 5,
+                Key::Key7 => 6,
+                Key::Key8 => 7,
+                Key::Key9 => 8,
+                Key::Key0 => 9,
+                _ => continue,
+            };
+            if index < self.data.len() {
+                self.toggle_series(index);
+            }
+        }
+    }
+    /// Pushes a data row to the chart as a time series entry.
+    ///
+    /// # Arguments
+    /// * `t` - Time or x value for all y values.
+    /// * `y` - Slice of y values, one per series, skipping derived series (see [`derive_series()`](#method.derive_series)).
+    ///
+    /// # Panics
+    /// Panics if the length of `y` does not equal the number of series in the chart that are not derived.
+    pub fn push_time_series(&mut self, t: f64, y: &[f64]) {
+        self.record(|| PushEvent::TimeSeries { t, y: y.to_vec() });
+        self.dirty = true;
+        if !self.window.is_open() {
+            return;
+        }
+        if self.num_pushed_series() != y.len() {
+            panic!("Length of y must be equal to number of non-derived series!");
+        }
+        let limit = self.data_limit;
+        for (ser, value) in self.pushed_series().zip(y) {
+            ser.append((t, *value), limit);
+        }
+    }
+
+    /// Pushes many time series entries at once. Faster than pushing entries one by one.
+    ///
+    /// # Arguments
+    /// * `t` - Time or x values.
+    /// * `y` - One slice of y values per series, skipping derived series, each with one value per time.
+    ///
+    /// # Panics
+    /// Panics if the length of `y` does not equal the number of series in the chart that are not derived,
+    /// or if a slice in `y` has a different length than `t`.
+    pub fn push_time_series_batch(&mut self, t: &[f64], y: &[&[f64]]) {
+        self.record(|| PushEvent::TimeSeriesBatch {
+            t: t.to_vec(),
+            y: y.iter().map(|values| values.to_vec()).collect(),
+        });
+        self.dirty = true;
+        if !self.window.is_open() {
+            return;
+        }
+        if self.num_pushed_series() != y.len() {
+            panic!("Length of y must be equal to number of non-derived series!");
+        }
+        if y.iter().any(|values| values.len() != t.len()) {
+            panic!("Each slice in y must have the same length as t!");
+        }
+        let limit = self.data_limit;
+        for (ser, values) in self.pushed_series().zip(y) {
+            ser.extend(t.iter().cloned().zip(values.iter().cloned()), limit);
+        }
+    }
+
+    /// Pushes many xy entries to a certain series at once. Faster than pushing entries one by one.
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of series indices.
+    pub fn extend_series(&mut self, index: usize, data: &[(f64, f64)]) {
+        self.record(|| PushEvent::ExtendSeries {
+            index,
+            data: data.to_vec(),
+        });
+        self.dirty = true;
+        self.data[index].extend(data.iter().cloned(), self.data_limit);
+    }
+
+    /// Pushes an xy entry per item of an iterator to a certain series, using an accessor to extract the entry from each item.
+    ///
+    /// # Example
+    /// ```
+    /// use easy_graph::ui::chart::{ChartBuilder, Series};
+    /// use easy_graph::color::style::RED;
+    ///
+    /// struct Sample {
+    ///     time: f64,
+    ///     population: usize,
+    /// }
+    /// let samples = vec![Sample { time: 0.0, population: 10 }, Sample { time: 1.0, population: 12 }];
+    ///
+    /// let mut chart = ChartBuilder::new()
+    ///     .add_series(Series::line("Population", &RED))
+    ///     .build_headless();
+    /// chart.extend_series_from(0, &samples, |s| (s.time, s.population as f64));
+    /// let data: Vec<_> = chart.slice_series(0, 0.0, 1.0).collect();
+    /// assert_eq!(data, vec![(0.0, 10.0), (1.0, 12.0)]);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of series indices.
+    pub fn extend_series_from<I, T, F>(&mut self, index: usize, items: I, accessor: F)
+    where
+        I: IntoIterator<Item = T>,
+        F: Fn(T) -> (f64, f64),
+    {
+        let data: Vec<_> = items.into_iter().map(accessor).collect();
+        self.extend_series(index, &data);
+    }
+
+    /// Binds a series to an accessor, which extracts an xy entry from a user state, e.g. a simulation or ECS resource.
+    ///
+    /// On each call of [`update_from()`](#method.update_from) with a state of type `S`, the entry is pushed to the series.
+    /// A series can have multiple bindings, and bindings for different state types can be mixed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use easy_graph::ui::chart::{ChartBuilder, Series};
+    /// use easy_graph::color::style::{BLUE, RED};
+    ///
+    /// struct Population {
+    ///     time: f64,
+    ///     prey: usize,
+    ///     predators: usize,
+    /// }
+    ///
+    /// let mut chart = ChartBuilder::new()
+    ///     .add_series(Series::line("Prey", &BLUE))
+    ///     .add_series(Series::line("Predators", &RED))
+    ///     .build();
+    /// chart.bind_series(0, |p: &Population| (p.time, p.prey as f64));
+    /// chart.bind_series(1, |p: &Population| (p.time, p.predators as f64));
+    ///
+    /// let mut state = Population { time: 0.0, prey: 100, predators: 10 };
+    /// while chart.is_open() {
+    ///     state.time += 1.0;
+    ///     // ... simulate
+    ///     chart.update_from(&state);
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of series indices.
+    pub fn bind_series<S, F>(&mut self, index: usize, accessor: F)
+    where
+        S: 'static,
+        F: Fn(&S) -> (f64, f64) + 'static,
+    {
+        if index >= self.data.len() {
+            panic!("Series index {} out of range!", index);
+        }
+        self.bindings.push(Binding {
+            series: index,
+            accessor: Box::new(move |state| state.downcast_ref::<S>().map(&accessor)),
+        });
+    }
+
+    /// Returns the number of series bindings, see [`bind_series()`](#method.bind_series).
+    pub fn num_bindings(&self) -> usize {
+        self.bindings.len()
+    }
+
+    /// Removes all series bindings.
+    pub fn clear_bindings(&mut self) {
+        self.bindings.clear();
+    }
+
+    /// Pushes the entries of all series bound to states of type `S`, without rendering. See [`bind_series()`](#method.bind_series).
+    /// Returns the number of pushed entries.
+    pub fn push_from<S: 'static>(&mut self, state: &S) -> usize {
+        let entries: Vec<_> = self
+            .bindings
+            .iter()
+            .filter_map(|binding| (binding.accessor)(state).map(|xy| (binding.series, xy)))
+            .collect();
+        for &(index, xy) in &entries {
+            self.push_xy(index, xy);
+        }
+        entries.len()
+    }
+
+    /// Pushes the entries of all series bound to states of type `S`, and renders the chart like [`update()`](#method.update).
+    /// See [`bind_series()`](#method.bind_series).
+    pub fn update_from<S: 'static>(&mut self, state: &S) {
+        self.push_from(state);
+        self.update();
+    }
+
+    /// Pushes a time step with an array of values, one per series, like [`push_time_series()`](#method.push_time_series).
+    ///
+    /// Requires feature `ndarray`.
+    ///
+    /// # Panics
+    /// Panics if the length of `y` does not equal the number of series in the chart.
+    #[cfg(feature = "ndarray")]
+    pub fn push_time_series_array(&mut self, t: f64, y: ArrayView1<f64>) {
+        self.push_time_series(t, &y.to_vec());
+    }
+
+    /// Pushes many time series entries at once from arrays, like [`push_time_series_batch()`](#method.push_time_series_batch).
+    /// `y` has one row per series, and one column per time.
+    ///
+    /// Requires feature `ndarray`.
+    ///
+    /// # Panics
+    /// Panics if the number of rows of `y` does not equal the number of series in the chart,
+    /// or if the number of columns of `y` does not equal the length of `t`.
+    #[cfg(feature = "ndarray")]
+    pub fn push_time_series_arrays(&mut self, t: ArrayView1<f64>, y: ArrayView2<f64>) {
+        let t = t.to_vec();
+        let rows: Vec<Vec<f64>> = y.outer_iter().map(|row| row.to_vec()).collect();
+        let rows: Vec<&[f64]> = rows.iter().map(|row| &row[..]).collect();
+        self.push_time_series_batch(&t, &rows);
+    }
+
+    /// Pushes many xy entries to a certain series at once from arrays of x and y values,
+    /// like [`extend_series()`](#method.extend_series).
+    ///
+    /// Requires feature `ndarray`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use easy_graph::ui::chart::{ChartBuilder, Series};
+    /// use easy_graph::color::style::RED;
+    /// use ndarray::Array1;
+    ///
+    /// let x = Array1::linspace(0.0, 10.0, 100);
+    /// let y = x.mapv(f64::sin);
+    /// let mut chart = ChartBuilder::new()
+    ///     .add_series(Series::line("sin", &RED))
+    ///     .build();
+    /// chart.extend_series_arrays(0, x.view(), y.view());
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of series indices, or if `x` and `y` differ in length.
+    #[cfg(feature = "ndarray")]
+    pub fn extend_series_arrays(&mut self, index: usize, x: ArrayView1<f64>, y: ArrayView1<f64>) {
+        if x.len() != y.len() {
+            panic!("Arrays x and y must have the same length!");
+        }
+        let data: Vec<_> = x.iter().cloned().zip(y.iter().cloned()).collect();
+        self.extend_series(index, &data);
+    }
+
+    /// Pushes a data row to the chart as a time series entry, like [`push_time_series()`](#method.push_time_series),
+    /// but only stores values that differ from the last stored value of their series by more than `epsilon`.
+    ///
+    /// Reduces memory and rendering time for slowly varying signals. Unchanged values are held back, and the last of them is
+    /// stored before the next change, so that lines keep their shape. Call [`flush()`](#method.flush) to store held back values,
+    /// e.g. at the end of a simulation.
+    ///
+    /// # Panics
+    /// Panics if the length of `y` does not equal the number of series in the chart that are not derived.
+    pub fn push_time_series_if_changed(&mut self, t: f64, y: &[f64], epsilon: f64) {
+        self.record(|| PushEvent::TimeSeriesIfChanged {
+            t,
+            y: y.to_vec(),
+            epsilon,
+        });
+        if !self.window.is_open() {
+            return;
+        }
+        if self.num_pushed_series() != y.len() {
+            panic!("Length of y must be equal to number of non-derived series!");
+        }
+        let limit = self.data_limit;
+        let mut changed = false;
+        for (ser, value) in self.pushed_series().zip(y) {
+            changed |= ser.append_if_changed((t, *value), epsilon, limit);
+        }
+        if changed {
+            self.dirty = true;
+        }
+    }
+
+    /// Stores values held back by [`push_time_series_if_changed()`](#method.push_time_series_if_changed).
+    pub fn flush(&mut self) {
+        self.record(|| PushEvent::Flush);
+        for ser in &mut self.data {
+            if ser.held.is_some() {
+                ser.flush(self.data_limit);
+                self.dirty = true;
+            }
+        }
+    }
+
+    /// Pushes an xy entry to a certain series.
+    ///
+    /// # Arguments
+    /// * `index` - Index of the series to push to.
+    /// * `xy` - Data point as a tuple of (x, y).
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of series indices.
+    pub fn push_xy(&mut self, index: usize, xy: (f64, f64)) {
+        self.record(|| PushEvent::Xy { index, xy });
+        self.dirty = true;
+        self.data[index].append(xy, self.data_limit);
+    }
+
+    /// Pushes an xy data point with a value to a line series colored per segment.
+    ///
+    /// See [`Series::with_color_map()`](struct.Series.html#method.with_color_map) for an example.
+    /// For series without a color map, the value is ignored.
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of series indices.
+    pub fn push_xyv(&mut self, index: usize, xy: (f64, f64), value: f64) {
+        self.record(|| PushEvent::Xyv { index, xy, value });
+        self.dirty = true;
+        self.data[index].append_valued(xy, value, self.data_limit);
+    }
+
+    /// Pushes a segment from one point to another to a [segment](struct.Series.html#method.segment) or
+    /// [arrow](struct.Series.html#method.arrow) series. Data limits of these series count segments rather than points.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use easy_graph::ui::chart::{ChartBuilder, Series};
+    /// use easy_graph::color::style::RED;
+    ///
+    /// let mut chart = ChartBuilder::new()
+    ///     .add_series(Series::arrow("Displacement", &RED))
+    ///     .build();
+    /// chart.push_segment(0, (0.0, 0.0), (1.0, 0.5));
+    /// chart.update();
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of series indices.
+    pub fn push_segment(&mut self, index: usize, from: (f64, f64), to: (f64, f64)) {
+        self.record(|| PushEvent::Segment { index, from, to });
+        self.dirty = true;
+        self.data[index].append_segment(from, to, self.data_limit);
+    }
+
+    /// Pushes the replicate values for an x value to an [EnsembleSeries](struct.EnsembleSeries.html).
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of ensemble series indices.
+    pub fn push_ensemble(&mut self, index: usize, x: f64, values: &[f64]) {
+        self.record(|| PushEvent::Ensemble {
+            index,
+            x,
+            values: values.to_vec(),
+        });
+        self.dirty = true;
+        self.ensembles[index].append(x, values, self.data_limit);
+    }
+
+    /// Returns the number of ensemble series in the chart.
+    pub fn num_ensembles(&self) -> usize {
+        self.ensembles.len()
+    }
+
+    /// Pushes long-format records of `(series_name, x, y)`.
+    ///
+    /// Line series are created for unseen names, with colors picked from a palette.
+    /// Like with [`add_series()`](#method.add_series), failures to create their retention files
+    /// are reported by [`take_error()`](#method.take_error).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use easy_graph::ui::chart::ChartBuilder;
+    ///
+    /// let mut chart = ChartBuilder::new().build();
+    /// let groups = vec![("wolves", 12.0), ("sheep", 140.0)];
+    /// chart.push_records(groups.iter().map(|(name, count)| (*name, 0.0, *count)));
+    /// assert_eq!(chart.num_series(), 2);
+    /// ```
+    pub fn push_records<I, S>(&mut self, records: I)
+    where
+        I: IntoIterator<Item = (S, f64, f64)>,
+        S: AsRef<str>,
+    {
+        if let Some(recorder) = &mut self.recorder {
+            let records: Vec<_> = records
+                .into_iter()
+                .map(|(name, x, y)| (name.as_ref().to_string(), x, y))
+                .collect();
+            recorder.record(&PushEvent::Records(records.clone()));
+            self.append_records(records);
+        } else {
+            self.append_records(records);
+        }
+    }
+    fn append_records<I, S>(&mut self, records: I)
+    where
+        I: IntoIterator<Item = (S, f64, f64)>,
+        S: AsRef<str>,
+    {
+        self.dirty = true;
+        for (name, x, y) in records {
+            let name = name.as_ref();
+            let index = match self.data.iter().position(|ser| ser.name == name) {
+                Some(index) => index,
+                None => {
+                    self.data.push(Series::line_auto(name));
+                    self.assign_color(self.data.len() - 1);
+                    if let Err(err) = self.attach_retention(self.data.len() - 1) {
+                        self.error = Some(err);
+                    }
+                    self.data.len() - 1
+                }
+            };
+            self.data[index].append((x, y), self.data_limit);
+        }
+    }
+
+    /// Replaces the data of a certain series.
+    ///
+    /// # Arguments
+    /// * `index` - Index of the series to replace.
+    /// * `data` - Slice of tuples of (x, y).
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of series indices.
+    pub fn replace_series(&mut self, index: usize, data: &[(f64, f64)]) {
+        self.record(|| PushEvent::ReplaceSeries {
+            index,
+            data: data.to_vec(),
+        });
+        self.reset_series(index, data);
+    }
+
+    /// Replaces the data of a series, and removes function plots and derivations targeting it. Not recorded.
+    fn reset_series(&mut self, index: usize, data: &[(f64, f64)]) {
+        self.dirty = true;
+        self.functions.retain(|plot| plot.index != index);
+        self.derived.retain(|plot| plot.index != index);
+        self.replace_data(index, data);
+    }
+
+    fn replace_data(&mut self, index: usize, data: &[(f64, f64)]) {
+        let ser = &mut self.data[index];
+        ser.clear();
+        for xy in data {
+            ser.push(*xy);
+        }
+    }
+
+    /// Replaces a series by samples of a function at `n` evenly spaced x values over the range (inclusive).
+    ///
+    /// If both x limits are set (see [`set_xlim()`](#method.set_xlim)), the function is re-sampled over the limits instead,
+    /// whenever they change.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use easy_graph::ui::chart::{ChartBuilder, Series};
+    /// use easy_graph::color::style::{RED, BLUE};
+    ///
+    /// let mut chart = ChartBuilder::new()
+    ///     .add_series(Series::point("Simulation", &RED))
+    ///     .add_series(Series::line("Analytical", &BLUE))
+    ///     .build();
+    /// chart.plot_fn(1, (0.0, 10.0), 100, |t| 100.0 * (-0.5 * t).exp());
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of series indices.
+    pub fn plot_fn<F: Fn(f64) -> f64 + 'static>(
+        &mut self,
+        index: usize,
+        range: (f64, f64),
+        n: usize,
+        f: F,
+    ) {
+        self.reset_series(index, &sample_fn(range, n, &f));
+        self.functions.push(FnPlot {
+            index,
+            n,
+            range,
+            f: Box::new(f),
+        });
+    }
+
+    /// Makes a series a derivative or cumulative sum of another series, replacing its data.
+    ///
+    /// The derived series is extended on each update when the source grows, and recomputed if the source is cleared.
+    /// Data limits of the derived series apply independently, so cumulative sums include entries dropped from the source.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use easy_graph::ui::chart::{ChartBuilder, Derivation, Series};
+    /// use easy_graph::color::style::{RED, BLUE};
+    ///
+    /// let mut chart = ChartBuilder::new()
+    ///     .add_series(Series::line("Births", &RED))
+    ///     .add_series(Series::line("Total births", &BLUE))
+    ///     .build();
+    /// chart.derive_series(1, 0, Derivation::Cumulative);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if an index is not in the range of series indices, or if both are equal.
+    pub fn derive_series(&mut self, index: usize, source: usize, derivation: Derivation) {
+        assert!(
+            index != source && source < self.data.len(),
+            "Invalid source series {} for derived series {}",
+            source,
+            index
+        );
+        self.reset_series(index, &[]);
+        self.derived
+            .push(DerivedPlot::new(index, source, derivation));
+    }
+
+    /// Returns if a series is derived from another one.
+    fn is_derived(&self, index: usize) -> bool {
+        self.derived.iter().any(|plot| plot.index == index)
+    }
+
+    /// Returns the number of series that take values pushed as time series, i.e. all series that are not derived.
+    fn num_pushed_series(&self) -> usize {
+        (0..self.data.len())
+            .filter(|i| !self.is_derived(*i))
+            .count()
+    }
+
+    /// Iterates over the series that take values pushed as time series, in order.
+    fn pushed_series(&mut self) -> impl Iterator<Item = &mut Series> {
+        let derived = &self.derived;
+        self.data
+            .iter_mut()
+            .enumerate()
+            .filter(move |(i, _)| !derived.iter().any(|plot| plot.index == *i))
+            .map(|(_, ser)| ser)
+    }
+
+    /// Extends derived series by entries added to their sources.
+    fn update_derived(&mut self) {
+        for plot in &mut self.derived {
+            let source = &self.data[plot.source].data;
+            if plot.is_reset(source) {
+                *plot = DerivedPlot::new(plot.index, plot.source, plot.derivation);
+                self.data[plot.index].clear();
+                self.dirty = true;
+            }
+            let source = &self.data[plot.source].data;
+            let entries = plot.derive(source);
+            if !entries.is_empty() {
+                self.data[plot.index].extend(entries, self.data_limit);
+                self.dirty = true;
+            }
+        }
+    }
+
+    /// Re-samples plotted functions if the x limits changed.
+    fn resample_functions(&mut self) {
+        let range = match (self.limits.x_min, self.limits.x_max) {
+            (Some(min), Some(max)) => (min, max),
+            _ => return,
+        };
+        let mut samples = vec![];
+        for plot in self.functions.iter_mut().filter(|plot| plot.range != range) {
+            plot.range = range;
+            samples.push((plot.index, sample_fn(range, plot.n, &plot.f)));
+        }
+        for (index, data) in samples {
+            self.dirty = true;
+            self.replace_data(index, &data);
+        }
+    }
+
+    /// Registers a callback run at the start of every [`update()`](#method.update), e.g. to push data.
+    pub fn on_update<F: FnMut(&mut Chart) + 'static>(&mut self, hook: F) {
+        self.update_hook = Some(Box::new(hook));
+    }
+
+    /// Registers a callback run during [`update()`](#method.update) when the visible x range changed,
+    /// e.g. through limits, follow mode or the overview inset. It is also run on the first update.
+    ///
+    /// The callback receives the new x range, and can load or decimate data for it on demand,
+    /// e.g. from disk or a database, instead of keeping all data in memory.
+    /// Axis ranges are re-calculated after the callback, so data replaced by the callback is shown in the same frame.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use easy_graph::ui::chart::{ChartBuilder, Series};
+    /// use easy_graph::color::style::RED;
+    ///
+    /// let mut chart = ChartBuilder::new()
+    ///     .add_series(Series::line("A", &RED))
+    ///     .with_xlim(Some(0.0), Some(100.0))
+    ///     .build();
+    /// chart.on_x_range_change(|chart, (x0, x1)| {
+    ///     // Load 200 points for the visible range, e.g. from a database
+    ///     let data: Vec<_> = (0..=200)
+    ///         .map(|i| x0 + (x1 - x0) * i as f64 / 200.0)
+    ///         .map(|x| (x, x.sin()))
+    ///         .collect();
+    ///     chart.replace_series(0, &data);
+    /// });
+    /// while chart.is_open() {
+    ///     chart.update();
+    /// }
+    /// ```
+    pub fn on_x_range_change<F: FnMut(&mut Chart, (f64, f64)) + 'static>(&mut self, hook: F) {
+        self.range_hook = Some(Box::new(hook));
+        self.hooked_range = None;
+    }
+
+    /// Runs the range hook if the x range changed since it was last run. Returns if the hook was run.
+    fn notify_x_range(&mut self, x_range: (f64, f64)) -> bool {
+        if self.hooked_range == Some(x_range) {
+            return false;
+        }
+        match self.range_hook.take() {
+            Some(mut hook) => {
+                self.hooked_range = Some(x_range);
+                hook(self, x_range);
+                if self.range_hook.is_none() {
+                    self.range_hook = Some(hook);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Registers an alert on a series' values. Returns the index of the alert.
+    ///
+    /// On each update, the threshold is checked against the series' latest pushed value.
+    /// When the value starts crossing the threshold, the callback is invoked with the chart and the value.
+    /// The alert is re-armed when the value no longer crosses the threshold.
+    ///
+    /// Threshold levels are drawn as lines in the series' color. While an alert is active,
+    /// its series is drawn with a thicker line, and the plotting area is tinted in the series' color.
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of series indices.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use easy_graph::ui::chart::{ChartBuilder, Series, Threshold};
+    /// use easy_graph::color::style::RED;
+    ///
+    /// let mut chart = ChartBuilder::new()
+    ///     .add_series(Series::line("Temperature", &RED))
+    ///     .build();
+    /// chart.add_alert(0, Threshold::Above(0.9), |chart, value| {
+    ///     println!("Temperature at {}, saving checkpoint", value);
+    ///     chart.save_csv("checkpoint.csv").unwrap();
+    /// });
+    /// let mut t = 0.0;
+    /// while chart.is_open() {
+    ///     t += 0.01;
+    ///     chart.push_time_series(t, &[t.sin()]);
+    ///     chart.update();
+    /// }
+    /// ```
+    pub fn add_alert<F: FnMut(&mut Chart, f64) + 'static>(
+        &mut self,
+        series: usize,
+        threshold: Threshold,
+        hook: F,
+    ) -> usize {
+        if series >= self.data.len() {
+            panic!("Series index {} out of range!", series);
+        }
+        self.dirty = true;
+        self.alerts.push(Alert {
+            series,
+            threshold,
+            hook: Some(Box::new(hook)),
+            active: false,
+        });
+        self.alerts.len() - 1
+    }
+
+    /// Returns if an alert's series currently crosses its threshold.
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of alert indices.
+    pub fn is_alert_active(&self, index: usize) -> bool {
+        self.alerts[index].active
+    }
+
+    /// Returns the number of registered alerts.
+    pub fn num_alerts(&self) -> usize {
+        self.alerts.len()
+    }
+
+    /// Removes all alerts.
+    pub fn clear_alerts(&mut self) {
+        self.dirty = true;
+        self.alerts.clear();
+    }
+
+    /// Checks all alerts against the latest values of their series, and runs the callbacks of newly crossed alerts.
+    fn check_alerts(&mut self) {
+        let mut index = 0;
+        while index < self.alerts.len() {
+            let alert = &mut self.alerts[index];
+            let value = self
+                .data
+                .get(alert.series)
+                .and_then(|series| series.latest_value());
+            let active = value.is_some_and(|v| alert.threshold.is_crossed(v));
+            if active != alert.active {
+                alert.active = active;
+                self.dirty = true;
+                let hook = if active { alert.hook.take() } else { None };
+                if let Some(mut hook) = hook {
+                    hook(self, value.unwrap());
+                    if let Some(alert) = self.alerts.get_mut(index) {
+                        if alert.hook.is_none() {
+                            alert.hook = Some(hook);
+                        }
+                    }
+                }
+            }
+            index += 1;
+        }
+        self.config.alerts = self
+            .alerts
+            .iter()
+            .map(|alert| AlertMark {
+                series: alert.series,
+                levels: alert.threshold.levels(),
+                active: alert.active,
+            })
+            .collect();
+    }
+
+    /// Registers custom drawing code run on the plotting area after the mesh, but before the series are drawn.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use easy_graph::ui::chart::{ChartBuilder, Series};
+    /// use easy_graph::ui::element::Circle;
+    /// use easy_graph::color::style::{RED, BLUE};
+    ///
+    /// let mut chart = ChartBuilder::new()
+    ///     .add_series(Series::line("A", &BLUE))
+    ///     .build();
+    /// // Mark the origin below the series
+    /// chart.pre_draw(|area, coords| {
+    ///     let center = coords.to_pixel((0.0, 0.0));
+    ///     area.draw(&Circle::new(center, 5, &RED)).unwrap();
+    /// });
+    /// ```
+    pub fn pre_draw<F>(&mut self, hook: F)
+    where
+        F: Fn(&DrawingArea<BitMapBackend<RGBPixel>, Shift>, &ChartCoords) + Send + Sync + 'static,
+    {
+        self.dirty = true;
+        self.config.pre_draw.push(Arc::new(hook));
+    }
+
+    /// Registers custom drawing code run on the plotting area after series and legend are drawn.
+    ///
+    /// See [`pre_draw()`](#method.pre_draw) for an example.
+    pub fn post_draw<F>(&mut self, hook: F)
+    where
+        F: Fn(&DrawingArea<BitMapBackend<RGBPixel>, Shift>, &ChartCoords) + Send + Sync + 'static,
+    {
+        self.dirty = true;
+        self.config.post_draw.push(Arc::new(hook));
+    }
+
+    /// Removes all registered update, range and draw hooks.
+    pub fn clear_hooks(&mut self) {
+        self.dirty = true;
+        self.update_hook = None;
+        self.range_hook = None;
+        self.config.pre_draw.clear();
+        self.config.post_draw.clear();
+    }
+
+    /// Sets the frame budget for adaptive rendering quality. Use `None` to always render in full quality.
+    ///
+    /// See [`ChartBuilder::with_frame_budget()`](struct.ChartBuilder.html#method.with_frame_budget).
+    pub fn set_frame_budget(&mut self, budget: Option<Duration>) {
+        self.dirty = true;
+        self.frame_budget = budget;
+        if budget.is_none() {
+            self.config.quality = 0;
+        }
+    }
+
+    /// Sets the tolerance for simplifying line series before drawing, in pixels. Use `None` to draw all points.
+    ///
+    /// See [`ChartBuilder::with_line_simplification()`](struct.ChartBuilder.html#method.with_line_simplification).
+    pub fn set_line_simplification(&mut self, tolerance: Option<f64>) {
+        self.dirty = true;
+        self.config.line_tolerance = tolerance;
+    }
+
+    /// The current level of reduced rendering quality due to the frame budget. 0 is full quality.
+    ///
+    /// At level `n`, only every `2^n`-th point of line and point series is drawn. Starting at level 4, mesh lines are omitted.
+    pub fn quality_level(&self) -> usize {
+        self.config.quality
+    }
+
+    /// Finds the data point nearest to the given window pixel coordinates, using the axes of the last rendered frame.
+    /// Returns the series index, the point's index in the series, and the point itself.
+    ///
+    /// Only visible series are considered. Returns `None` if no frame was rendered yet,
+    /// the pixel is outside the plotting area, or there is no data.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use easy_graph::ui::chart::{ChartBuilder, Series};
+    /// use easy_graph::color::style::RED;
+    ///
+    /// let mut chart = ChartBuilder::new().add_series(Series::point("A", &RED)).build();
+    /// chart.push_xy(0, (1.0, 2.0));
+    /// chart.update();
+    /// if let Some((series, index, xy)) = chart.mouse_pos().and_then(|pos| chart.pick(pos)) {
+    ///     println!("Series {}, point {}: {:?}", series, index, xy);
+    /// }
+    /// ```
+    pub fn pick(&self, px: (i32, i32)) -> Option<(usize, usize, (f64, f64))> {
+        pick_nearest(&self.data, self.coords.as_ref()?, px)
+    }
+
+    /// A tooltip text for the data point nearest to the given window pixel coordinates, e.g. "Biomass: (12, 3.5 kg)".
+    ///
+    /// See [`pick()`](#method.pick) for how the point is found.
+    pub fn tooltip(&self, px: (i32, i32)) -> Option<String> {
+        let (index, _, (x, y)) = self.pick(px)?;
+        let series = &self.data[index];
+        let y = series.with_unit_suffix(format_legend_value(y));
+        Some(format!(
+            "{}: ({}, {})",
+            series.name,
+            self.config.x_value_label(x),
+            y
+        ))
+    }
+
+    /// The mouse position in window pixels, if the mouse is over the window.
+    pub fn mouse_pos(&self) -> Option<(i32, i32)> {
+        self.window.mouse_pos()
+    }
+
+    /// Runs a computation on a worker thread and keeps the chart's window responsive until it finishes.
+    ///
+    /// See [`BufferWindow::run_responsive()`](../window/struct.BufferWindow.html#method.run_responsive).
+    pub fn run_responsive<F, R>(&mut self, compute: F) -> R
+    where
+        F: FnOnce() -> R + Send,
+        R: Send,
+    {
+        self.window.run_responsive(compute)
+    }
+
+    /// Draws the chart onto a drawing area owned by someone else, e.g. an inset in a simulation window.
+    ///
+    /// Polls the data source, resamples function plots and extends derived series like [`update()`](#method.update),
+    /// but neither draws to nor handles events of the chart's own window. For charts used only this way,
+    /// use [`ChartBuilder::build_headless()`](struct.ChartBuilder.html#method.build_headless) to not open a window at all.
+    ///
+    /// Hooks added with [`pre_draw()`](#method.pre_draw) and [`post_draw()`](#method.post_draw) are skipped,
+    /// as they draw to the chart's bitmap only.
+    ///
+    /// Returns the coordinate mapping of the plotting area, with the origin relative to `area`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use easy_graph::ui::chart::{ChartBuilder, Series};
+    /// use easy_graph::ui::window::WindowBuilder;
+    /// use easy_graph::color::style::{RED, WHITE};
+    /// use plotters::prelude::*;
+    ///
+    /// let mut win = WindowBuilder::new().with_dimensions(800, 600).build();
+    /// let mut chart = ChartBuilder::new()
+    ///     .add_series(Series::line("A", &RED))
+    ///     .build_headless();
+    /// chart.push_xy(0, (0.0, 1.0));
+    ///
+    /// win.draw(|b| {
+    ///     let root = b.into_drawing_area();
+    ///     root.fill(&WHITE).unwrap();
+    ///     // ... draw the simulation
+    ///     let inset = root.shrink((500, 0), (300, 200));
+    ///     chart.draw_onto(&inset);
+    /// });
+    /// ```
+    pub fn draw_onto<DB: DrawingBackend>(&mut self, area: &DrawingArea<DB, Shift>) -> ChartCoords {
+        self.poll_source();
+        self.resample_functions();
+        self.update_derived();
+        let (xlim, ylim) = self.calc_axis_ranges();
+        draw_chart(
+            area,
+            &self.config,
+            &self.data,
+            &self.ensembles,
+            xlim,
+            ylim,
+            &|_, _, _| {},
+        )
+    }
+
+    /// Draws the chart onto a drawing area like [`draw_onto()`](#method.draw_onto), with fonts, markers, line widths
+    /// and label areas scaled by `scale` in addition to the chart's UI scale, e.g. for exporting high-resolution images.
+    pub fn draw_onto_scaled<DB: DrawingBackend>(
+        &mut self,
+        area: &DrawingArea<DB, Shift>,
+        scale: f64,
+    ) -> ChartCoords {
+        let ui_scale = self.config.ui_scale;
+        self.config.ui_scale *= scale;
+        let coords = self.draw_onto(area);
+        self.config.ui_scale = ui_scale;
+        coords
+    }
+
+    /// Renders the graph, even if nothing changed since the last frame.
+    pub fn force_update(&mut self) {
+        self.dirty = true;
+        self.update();
+    }
+
+    /// Render the graph
+    ///
+    /// Rendering is skipped if no data, limits or styles changed since the last frame.
+    /// Window events are processed anyway. Use [`force_update()`](#method.force_update) to always render.
+    /// The background with axes, mesh and tick labels is cached, and only re-rendered when axis limits, labels or styles change.
+    ///
+    /// See [`update_stats()`](#method.update_stats) for information on what happened during the update.
+    pub fn update(&mut self) {
+        self.update_stats();
+    }
+
+    /// Same as [`update()`](#method.update), but returns statistics about the update,
+    /// e.g. to adapt logging or simulation speed to the actual frame rate.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use easy_graph::ui::chart::ChartBuilder;
+    ///
+    /// let mut chart = ChartBuilder::new().with_fps_skip(10.0).build();
+    /// let mut frames = 0;
+    /// for i in 0..1000 {
+    ///     chart.push_time_series(i as f64, &[]);
+    ///     let stats = chart.update_stats();
+    ///     if stats.rendered {
+    ///         frames += 1;
+    ///         println!("{} points in {:?}", stats.points, stats.render_time);
+    ///     }
+    /// }
+    /// ```
+    pub fn update_stats(&mut self) -> UpdateStats {
+        let mut stats = UpdateStats::default();
+        if self.pause_keys {
+            self.handle_pause_keys();
+        }
+        if self.toggle_keys {
+            self.handle_toggle_keys();
+        }
+        if self.config.minimap
+            && !self
+                .context_menu
+                .as_ref()
+                .is_some_and(|m| m.captures_mouse())
+        {
+            self.handle_minimap();
+        }
+        self.handle_context_menu();
+        if self.paused {
+            if !self.step_pending {
+                self.window.update_events_only();
+                stats.paused = true;
+                return stats;
+            }
+            self.step_pending = false;
+            self.dirty = true;
+        }
+        if let Some(mut hook) = self.update_hook.take() {
+            hook(self);
+            if self.update_hook.is_none() {
+                self.update_hook = Some(hook);
+            }
+        }
+        self.poll_source();
+        self.resample_functions();
+        self.update_derived();
+        self.check_alerts();
+        let pixel_scale = self.window.scale_factor();
+        if (pixel_scale - self.config.pixel_scale).abs() > f64::EPSILON {
+            self.config.pixel_scale = pixel_scale;
+            self.dirty = true;
+        }
+        #[cfg(feature = "profiling")]
+        let start = Instant::now();
+        let mut ranges = self.calc_axis_ranges();
+        #[cfg(feature = "profiling")]
+        self.profile.record(Stage::RangeCalc, start.elapsed());
+        if self.notify_x_range(ranges.0) {
+            ranges = self.calc_axis_ranges();
+        }
+        let (xlim, ylim) = self.animate_ranges(ranges);
+        if self.window.screenshot_metadata() != MetadataFormat::None {
+            // Screenshots are taken when presenting, before coords of the new frame are available
+            let mut metadata = self.image_metadata();
+            metadata.x_range = Some(xlim);
+            metadata.y_range = Some(ylim);
+            self.window.set_image_metadata(Some(metadata));
+        }
+        let changed = self.dirty || self.drawn_ranges != Some((xlim, ylim));
+        if let Some(renderer) = &mut self.renderer {
+            if let Some((frame, points)) = renderer.poll() {
+                let mut buffer = frame.buffer;
+                let (width, height) = renderer.dim;
+                draw_context_menu(
+                    &self.context_menu,
+                    &mut buffer,
+                    (width as u32, height as u32),
+                    self.config.ui_scale,
+                );
+                let buffer = self.window.present(buffer);
+                renderer.recycle(buffer);
+                self.coords = Some(frame.coords);
+                stats.rendered = true;
+                stats.render_time = frame.render_time;
+                stats.points = points;
+                #[cfg(feature = "http")]
+                publish(&mut self.server, &self.window, &self.data);
+            } else if !changed || renderer.is_busy() {
+                self.window.update_events_only();
+            }
+            if changed && !renderer.is_busy() && self.window.is_open() && !self.window.skip_frame()
+            {
+                renderer.submit(ChartFrame {
+                    config: self.config.clone(),
+                    data: self.data.clone(),
+                    ensembles: self.ensembles.clone(),
+                    xlim,
+                    ylim,
+                });
+                self.dirty = false;
+                self.drawn_ranges = Some((xlim, ylim));
+                self.advance_animation();
+            } else if changed {
+                stats.skipped = true;
+            }
+        } else if !changed {
+            self.window.update_events_only();
+        } else {
+            let config = &self.config;
+            let data = &self.data;
+            let ensembles = &self.ensembles;
+            let mesh_cache = &mut self.mesh_cache;
+            let menu = &self.context_menu;
+            let start = Instant::now();
+            let mut coords = None;
+            let drawn = self.window.draw_rgb(|buffer, dim| {
+                coords = Some(mesh_cache.draw_chart(
+                    buffer,
+                    dim,
+                    config,
+                    data,
+                    ensembles,
+                    xlim,
+                    ylim,
+                    &|stage, area, coords| config.run_hooks(stage, area, coords),
+                ));
+                draw_context_menu(menu, buffer, dim, config.ui_scale);
+            });
+            if drawn {
+                self.coords = coords;
+                self.dirty = false;
+                self.drawn_ranges = Some((xlim, ylim));
+                self.advance_animation();
+                stats.rendered = true;
+                stats.render_time = start.elapsed();
+                stats.points = count_points(&self.data, &self.ensembles);
+                #[cfg(feature = "http")]
+                publish(&mut self.server, &self.window, &self.data);
+            } else {
+                stats.skipped = true;
+            }
+        }
+        #[cfg(feature = "profiling")]
+        if stats.rendered {
+            self.record_profile();
+        }
+        if stats.rendered && self.screenshot_pending {
+            // Saved after the menu was closed, to exclude it from the screenshot
+            self.screenshot_pending = false;
+            if let Err(err) = self.window.save_screenshot() {
+                self.error = Some(into_io_error(err));
+            }
+        }
+        self.take_snapshot();
+        if let (Some(budget), true) = (self.frame_budget, stats.rendered) {
+            self.config.quality = adapt_quality(self.config.quality, stats.render_time, budget);
+        }
+        stats
+    }
+
+    /// Takes a thumbnail of the current frame every `updates` calls to [`update()`](#method.update), for a contact sheet.
+    /// Use 0 to stop taking thumbnails. Existing thumbnails are kept.
+    ///
+    /// Thumbnails are 240 pixels wide by default, see [`set_snapshot_width()`](#method.set_snapshot_width).
+    ///
+    /// # Example
+    /// ```
+    /// use easy_graph::ui::chart::{ChartBuilder, Series};
+    /// use easy_graph::color::style::RED;
+    ///
+    /// let mut chart = ChartBuilder::new()
+    ///     .add_series(Series::line("Population", &RED))
+    ///     .build_headless();
+    /// chart.snapshot_every(100);
+    /// for t in 0..1000 {
+    ///     chart.push_time_series(t as f64, &[(t as f64 * 0.01).sin()]);
+    ///     chart.update();
+    /// }
+    /// assert_eq!(chart.num_snapshots(), 10);
+    /// // chart.save_contact_sheet("evolution.png", 5).unwrap();
+    /// ```
+    pub fn snapshot_every(&mut self, updates: usize) {
+        match (&mut self.snapshots, updates) {
+            (Some(snapshots), _) => {
+                snapshots.every = updates;
+                snapshots.updates = 0;
+            }
+            (None, 0) => {}
+            (None, _) => self.snapshots = Some(Snapshots::new(updates)),
+        }
+    }
+    /// Sets the width of thumbnails taken from now on, in pixels. The height follows from the chart's aspect ratio.
+    pub fn set_snapshot_width(&mut self, width: u32) {
+        let snapshots = self.snapshots.get_or_insert_with(|| Snapshots::new(0));
+        snapshots.width = width.max(1);
+    }
+    /// The number of thumbnails taken.
+    pub fn num_snapshots(&self) -> usize {
+        self.snapshots.as_ref().map_or(0, |s| s.images.len())
+    }
+    /// Removes all thumbnails taken so far.
+    pub fn clear_snapshots(&mut self) {
+        if let Some(snapshots) = &mut self.snapshots {
+            snapshots.images.clear();
+        }
+    }
+    /// Saves all thumbnails tiled into one image, row by row with `columns` thumbnails per row.
+    ///
+    /// See [`snapshot_every()`](#method.snapshot_every). The format is derived from the file extension.
+    /// Fails if no thumbnails were taken.
+    pub fn save_contact_sheet<P: AsRef<Path>>(
+        &self,
+        path: P,
+        columns: usize,
+    ) -> Result<(), image::ImageError> {
+        let images = self.snapshots.as_ref().map_or(&[][..], |s| &s.images[..]);
+        contact_sheet(images, columns)
+            .ok_or_else(|| {
+                image::ImageError::IoError(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "No snapshots taken for the contact sheet",
+                ))
+            })?
+            .save(path)
+    }
+
+    /// Takes a thumbnail of the current frame, if due.
+    fn take_snapshot(&mut self) {
+        let snapshots = match &mut self.snapshots {
+            Some(snapshots) if snapshots.every > 0 => snapshots,
+            _ => return,
+        };
+        snapshots.updates += 1;
+        if snapshots.updates < snapshots.every || self.coords.is_none() {
+            return;
+        }
+        snapshots.updates = 0;
+        let (width, height) = self.window.dimensions();
+        let frame =
+            image::RgbImage::from_raw(width as u32, height as u32, self.window.buffer_u8.clone())
+                .expect("Window buffer size does not match its dimensions.");
+        let thumb_height = (snapshots.width as f64 * height as f64 / width as f64)
+            .round()
+            .max(1.0) as u32;
+        snapshots.images.push(image::imageops::resize(
+            &frame,
+            snapshots.width,
+            thumb_height,
+            image::imageops::FilterType::Triangle,
+        ));
+    }
+
+    /// The address of the chart's HTTP server, if any. Requires feature `http`.
+    #[cfg(feature = "http")]
+    pub fn http_address(&self) -> Option<std::net::SocketAddr> {
+        self.server.as_ref().map(|server| server.address())
+    }
+
+    /// Timings of the chart's update stages. Requires feature `profiling`.
+    ///
+    /// See module [`profile`](../profile/index.html) for details.
+    #[cfg(feature = "profiling")]
+    pub fn profile(&self) -> &Profile {
+        &self.profile
+    }
+    /// Removes all timings, e.g. after a warm-up phase. Requires feature `profiling`.
+    #[cfg(feature = "profiling")]
+    pub fn reset_profile(&mut self) {
+        self.profile.reset();
+    }
+    /// Shows or hides the timings overlay. Requires feature `profiling`.
+    ///
+    /// See [`ChartBuilder::with_profile_overlay()`](struct.ChartBuilder.html#method.with_profile_overlay).
+    #[cfg(feature = "profiling")]
+    pub fn set_profile_overlay(&mut self, overlay: bool) {
+        self.dirty = true;
+        self.config.profile = if overlay { Some(Profile::new()) } else { None };
+    }
+
+    /// Records the timings of a rendered frame.
+    #[cfg(feature = "profiling")]
+    fn record_profile(&mut self) {
+        let trim = self
+            .data
+            .iter_mut()
+            .map(|ser| std::mem::take(&mut ser.trim_time))
+            .sum();
+        self.profile.record(Stage::DataTrim, trim);
+        if let Some(coords) = &self.coords {
+            self.profile.record(Stage::MeshDraw, coords.draw_times.0);
+            self.profile.record(Stage::SeriesDraw, coords.draw_times.1);
+        }
+        let (transfer, present) = self.window.present_times();
+        self.profile.record(Stage::BufferTransfer, transfer);
+        self.profile.record(Stage::Present, present);
+        if self.config.profile.is_some() {
+            self.config.profile = Some(self.profile.clone());
+        }
+    }
+
+    /// The axis ranges to draw: a transition from the drawn ranges to the target ranges if range animation is enabled.
+    fn animate_ranges(&mut self, target: ((f64, f64), (f64, f64))) -> ((f64, f64), (f64, f64)) {
+        let mut drawn = match self.drawn_ranges {
+            Some(drawn) if self.range_frames > 1 && drawn != target => drawn,
+            _ => {
+                self.range_animation = None;
+                return target;
+            }
+        };
+        if self.limits.x_window.is_some() {
+            drawn.0 = target.0;
+        }
+        match &self.range_animation {
+            Some(animation) if animation.to == target => {}
+            _ => {
+                self.range_animation = Some(RangeAnimation {
+                    from: drawn,
+                    to: target,
+                    frame: 0,
+                })
+            }
+        }
+        self.range_animation
+            .map_or(target, |animation| animation.ranges(self.range_frames))
+    }
+    /// Advances the range animation after a frame was drawn.
+    fn advance_animation(&mut self) {
+        if let Some(animation) = &mut self.range_animation {
+            animation.frame += 1;
+            if animation.frame >= self.range_frames {
+                self.range_animation = None;
+            }
+        }
+    }
+
+    fn calc_axis_ranges(&mut self) -> ((f64, f64), (f64, f64)) {
+        let mut x_range = valid_range(self.calc_axis_range(true, None));
+        if let Some(width) = self.limits.x_window {
+            x_range.0 = x_range.1 - width;
+        }
+        let x_visible = self.limits.x_window.map(|_| x_range);
+        let y_data = self.calc_axis_range(false, x_visible);
+        let mut y_range = self.y_auto_range.apply(self.y_range, y_data);
+        if let Some(min) = self.limits.y_min {
+            y_range.0 = min;
+        }
+        if let Some(max) = self.limits.y_max {
+            y_range.1 = max;
+        }
+        self.y_range = Some(y_range);
+        if let Some((low, high)) = self.config.y_break {
+            y_range = (low.0, high.1);
+        }
+        (x_range, valid_range(y_range))
+    }
+    /// Calculates the range of x or y values, optionally only for data within an x range.
+    ///
+    /// Non-finite values are ignored. Without any finite values, the range is empty, i.e. `(MAX, MIN)`.
+    fn calc_axis_range(&self, is_x: bool, x_visible: Option<(f64, f64)>) -> (f64, f64) {
+        let (min, max) = if is_x {
+            (self.limits.x_min, self.limits.x_max)
+        } else {
+            (self.limits.y_min, self.limits.y_max)
+        };
+        if let (Some(min), Some(max)) = (min, max) {
+            (min, max)
+        } else {
+            let find_min = min.is_none();
+            let find_max = max.is_none();
+            let mut v_min = f64::MAX;
+            let mut v_max = f64::MIN;
+            let series = self.data.iter().filter(|ser| ser.visible);
+            let values = series.flat_map(|ser| {
+                let range = match x_visible {
+                    Some(x_range) => visible_range(&ser.data, x_range),
+                    None => 0..ser.data.len(),
+                };
+                ser.data.range(range).map(move |xy| {
+                    let xy = ser.transformed(xy);
+                    if is_x {
+                        (xy.0, xy.0)
+                    } else {
+                        (xy.1, xy.1)
+                    }
+                })
+            });
+            let ensembles = self.ensembles.iter().filter(|ser| ser.visible);
+            let envelopes = ensembles
+                .flat_map(|ser| ser.summaries.iter())
+                .filter(|s| x_visible.map(|r| s.0 >= r.0 && s.0 <= r.1).unwrap_or(true))
+                .map(|s| if is_x { (s.0, s.0) } else { (s.2, s.3) });
+            for (lower, upper) in values.chain(envelopes) {
+                if find_min && lower.is_finite() && lower < v_min {
+                    v_min = lower;
+                }
+                if find_max && upper.is_finite() && upper > v_max {
+                    v_max = upper;
+                }
+            }
+            (min.unwrap_or(v_min), max.unwrap_or(v_max))
+        }
+    }
+}
+
+/// Visual configuration of a chart, shared by all rendering paths.
+#[derive(Clone)]
+struct ChartConfig {
+    x_label: String,
+    y_label: String,
+    x_scale: f64,
+    y_scale: f64,
+    y_log: bool,
+    non_finite: NonFinite,
+    x_axis: XAxisType,
+    x_inverted: bool,
+    y_inverted: bool,
+    y_break: Option<((f64, f64), (f64, f64))>,
+    minimap: bool,
+    x_label_rotation: f64,
+    max_label_width: Option<u32>,
+    legend_values: bool,
+    legend_position: LegendPosition,
+    line_tolerance: Option<f64>,
+    y_unit: String,
+    y_si: bool,
+    ui_scale: f64,
+    pixel_scale: f64,
+    x_follow: bool,
+    quality: usize,
+    mesh: MeshStyle,
+    background: Option<Arc<ImageLayer>>,
+    alerts: Vec<AlertMark>,
+    pre_draw: Vec<Arc<DrawHook>>,
+    post_draw: Vec<Arc<DrawHook>>,
+    #[cfg(feature = "profiling")]
+    profile: Option<Profile>,
+}
+impl ChartConfig {
+    fn new() -> Self {
+        ChartConfig {
+            x_label: "X".to_string(),
+            y_label: "Y".to_string(),
+            x_scale: 1.0,
+            y_scale: 1.0,
+            y_log: false,
+            non_finite: NonFinite::Skip,
+            x_axis: XAxisType::Numeric,
+            x_inverted: false,
+            y_inverted: false,
+            y_break: None,
+            minimap: false,
+            x_label_rotation: 0.0,
+            max_label_width: None,
+            legend_values: false,
+            legend_position: LegendPosition::MiddleRight,
+            line_tolerance: None,
+            y_unit: String::new(),
+            y_si: false,
+            ui_scale: 1.0,
+            pixel_scale: 1.0,
+            x_follow: false,
+            quality: 0,
+            mesh: MeshStyle::new(),
+            background: None,
+            alerts: Vec::new(),
+            pre_draw: Vec::new(),
+            post_draw: Vec::new(),
+            #[cfg(feature = "profiling")]
+            profile: None,
+        }
+    }
+    /// The y axis label with unit and SI prefix, and the factor to divide tick values by.
+    fn y_desc(&self, y_range: (f64, f64)) -> (String, f64) {
+        let (factor, prefix) = if self.y_si {
+            si_prefix(y_range.0.abs().max(y_range.1.abs()))
+        } else {
+            (1.0, "")
+        };
+        if prefix.is_empty() && self.y_unit.is_empty() {
+            (self.y_label.clone(), factor)
+        } else {
+            (
+                format!("{} [{}{}]", self.y_label, prefix, self.y_unit),
+                factor,
+            )
+        }
+    }
+    /// The font of tick labels.
+    fn label_font(&self) -> FontDesc<'_> {
+        let (family, size) = match &self.mesh.label_font {
+            Some((family, size)) => (&family[..], *size),
+            None => ("sans-serif", 12.0),
+        };
+        (family, size * self.ui_scale).into_font()
+    }
+    /// Formats an x tick label, shortened to the maximum label width.
+    fn x_tick_label(&self, font: &FontDesc, x: f64, span: f64) -> String {
+        let label = match &self.x_axis {
+            XAxisType::Numeric => format!("{}", x),
+            XAxisType::Integer => format!("{}", x.round()),
+            XAxisType::Time => format_time(x, span),
+            XAxisType::Categories(names) => category(names, x).unwrap_or_default().to_string(),
+        };
+        match self.max_label_width {
+            Some(width) => truncate_label(label, width as f64 * self.ui_scale, font),
+            None => label,
+        }
+    }
+    /// Formats an x value for tooltips.
+    fn x_value_label(&self, x: f64) -> String {
+        match &self.x_axis {
+            XAxisType::Integer => format_legend_value(x.round()),
+            XAxisType::Categories(names) => match category(names, x) {
+                Some(name) => name.to_string(),
+                None => format_legend_value(x),
+            },
+            _ => format_legend_value(x),
+        }
+    }
+    fn run_hooks(
+        &self,
+        stage: HookStage,
+        area: &DrawingArea<BitMapBackend<RGBPixel>, Shift>,
+        coords: &ChartCoords,
+    ) {
+        let hooks = match stage {
+            HookStage::PreDraw => &self.pre_draw,
+            HookStage::PostDraw => &self.post_draw,
+        };
+        for hook in hooks {
+            hook(area, coords);
+        }
+    }
+}
+
+/// The category at an x value, if the value is an index into the categories.
+fn category(names: &[String], x: f64) -> Option<&str> {
+    if (x - x.round()).abs() > 1e-6 || x < -0.5 {
+        return None;
+    }
+    names.get(x.round() as usize).map(|name| &name[..])
+}
+
+/// A snapshot of everything required to render a chart frame independent of the chart itself.
+struct ChartFrame {
+    config: ChartConfig,
+    data: Vec<Series>,
+    ensembles: Vec<EnsembleSeries>,
+    xlim: (f64, f64),
+    ylim: (f64, f64),
+}
+
+/// The index range of entries with x values within the range, plus one entry on each side to continue lines to the border.
+/// Assumes increasing x values.
+fn visible_range(data: &VecDeque<(f64, f64)>, x_range: (f64, f64)) -> Range<usize> {
+    let start = data.partition_point(|xy| xy.0 < x_range.0);
+    let end = data.partition_point(|xy| xy.0 <= x_range.1);
+    start.saturating_sub(1)..(end + 1).min(data.len())
+}
+
+/// Replaces an empty or zero-width axis range, which can't be drawn, e.g. for charts without data or with a single value.
+fn valid_range(range: (f64, f64)) -> (f64, f64) {
+    let (lower, upper) = range;
+    if !lower.is_finite() || !upper.is_finite() || lower > upper {
+        (0.0, 1.0)
+    } else if lower == upper {
+        let pad = if lower == 0.0 { 1.0 } else { 0.1 * lower.abs() };
+        (lower - pad, upper + pad)
+    } else {
+        range
+    }
+}
+
+/// Clamps infinite coordinates to the given x and y ranges. NaN values are kept.
+fn clamp_infinite(xy: (f64, f64), x_range: (f64, f64), y_range: (f64, f64)) -> (f64, f64) {
+    let clamp = |v: f64, (a, b): (f64, f64)| {
+        if v.is_infinite() {
+            v.max(a.min(b)).min(a.max(b))
+        } else {
+            v
+        }
+    };
+    (clamp(xy.0, x_range), clamp(xy.1, y_range))
+}
+
+/// The palette color for the given index, cycling through the palette. Uses the categorical palette if empty.
+fn palette_color(palette: &[RGBColor], index: usize) -> RGBColor {
+    if palette.is_empty() {
+        ColorPalette::pick(index)
+    } else {
+        let c = &palette[index % palette.len()];
+        RGBColor(c.0, c.1, c.2)
+    }
+}
+
+/// Blanks the gap of a broken axis across the plotting area, and marks its edges with diagonal strokes.
+fn draw_axis_break<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    gap: (i32, i32),
+    width: i32,
+    ui_scale: f64,
+) {
+    area.draw(&Rectangle::new(
+        [(0, gap.0), (width, gap.1)],
+        WHITE.filled(),
+    ))
+    .unwrap();
+    let dx = (6.0 * ui_scale) as i32;
+    let dy = (3.0 * ui_scale) as i32;
+    for x in &[0, width - dx] {
+        for y in &[gap.0, gap.1] {
+            area.draw(&PathElement::new(
+                vec![(*x, y + dy), (x + dx, y - dy)],
+                &BLACK,
+            ))
+            .unwrap();
+        }
+    }
+}
+
+/// Draws a complete chart onto a drawing area. Returns the coordinate mapping of the plotting area.
+fn draw_chart<'a, DB: DrawingBackend + 'a>(
+    root: &'a DrawingArea<DB, Shift>,
+    config: &'a ChartConfig,
+    data: &'a [Series],
+    ensembles: &'a [EnsembleSeries],
+    xlim: (f64, f64),
+    ylim: (f64, f64),
+    hook: &StageHook<'_, DB>,
+) -> ChartCoords {
+    let start = Instant::now();
+    let labels = x_label_area(root, config, xlim);
+    let (mut cc, mut coords) = chart_layout(root, config, data, xlim, ylim, labels.1);
+    draw_chart_background(root, &mut cc, config, &coords, labels.0);
+    let mesh_time = start.elapsed();
+    let series_time = draw_chart_content(cc, config, data, ensembles, &coords, hook);
+    coords.draw_times = (mesh_time, series_time);
+    coords
+}
+
+/// The x and y axes of a chart, in scaled coordinates.
+fn chart_axes(config: &ChartConfig, xlim: (f64, f64), ylim: (f64, f64)) -> (ChartAxis, ChartAxis) {
+    let x_axis = ChartAxis::new(
+        (xlim.0 * config.x_scale)..(xlim.1 * config.x_scale),
+        match config.x_axis {
+            XAxisType::Numeric => AxisKind::Linear,
+            XAxisType::Time => AxisKind::Time,
+            XAxisType::Integer | XAxisType::Categories(_) => AxisKind::Integer,
+        },
+    )
+    .with_inverted(config.x_inverted);
+    let y_axis = ChartAxis::new(
+        (ylim.0 * config.y_scale)..(ylim.1 * config.y_scale),
+        if config.y_log {
+            AxisKind::Log
+        } else {
+            AxisKind::Linear
+        },
+    )
+    .with_inverted(config.y_inverted)
+    .with_break(
+        config
+            .y_break
+            .map(|(low, high)| (low.1 * config.y_scale, high.0 * config.y_scale)),
+    );
+    (x_axis, y_axis)
+}
+
+/// The number of x tick labels, and the height of the area below the plot reserved for them.
+fn x_label_area<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    config: &ChartConfig,
+    xlim: (f64, f64),
+) -> (usize, f64) {
+    let (x_axis, _) = chart_axes(config, xlim, (0.0, 1.0));
+    let (root_width, root_height) = root.dim_in_pixel();
+    let plot_width = root_width as f64 - 80.0 * config.ui_scale;
+    let (x_labels, label_size) = x_label_layout(config, &x_axis, &config.label_font(), plot_width);
+    let mut area = 40.0 * config.ui_scale;
+    if config.x_label_rotation != 0.0 {
+        let (_, extent) = rotated_extent(label_size, config.x_label_rotation);
+        area = (area + extent - label_size.1).min(root_height as f64 / 3.0);
+    }
+    (x_labels, area)
+}
+
+/// Splits a drawing area into plotting and label areas, without drawing anything.
+/// Returns the chart context, and the coordinate mapping of the plotting area.
+fn chart_layout<'a, DB: DrawingBackend + 'a>(
+    root: &'a DrawingArea<DB, Shift>,
+    config: &ChartConfig,
+    data: &[Series],
+    xlim: (f64, f64),
+    ylim: (f64, f64),
+    x_label_area: f64,
+) -> (
+    ChartContext<'a, DB, RangedCoord<ChartAxis, ChartAxis>>,
+    ChartCoords,
+) {
+    let (x_axis, y_axis) = chart_axes(config, xlim, ylim);
+    let mut coords = ChartCoords {
+        x_axis: x_axis.clone(),
+        y_axis: y_axis.clone(),
+        x_scale: config.x_scale,
+        y_scale: config.y_scale,
+        size: (0, 0),
+        origin: (0, 0),
+        minimap: None,
+        draw_times: (Duration::default(), Duration::default()),
+    };
+    let cc = plotters::chart::ChartBuilder::on(root)
+        .margin((10.0 * config.ui_scale) as u32)
+        .x_label_area_size(x_label_area as u32)
+        .y_label_area_size((60.0 * config.ui_scale) as u32)
+        .build_ranged(x_axis, y_axis)
+        .unwrap();
+    let (width, height) = cc.plotting_area().dim_in_pixel();
+    coords.size = (width as i32, height as i32);
+    let (x0, y0) = cc.plotting_area().get_base_pixel();
+    let (rx, ry) = root.get_base_pixel();
+    coords.origin = (x0 - rx, y0 - ry);
+    coords.minimap = Minimap::new(config, data, (xlim, ylim), coords.size);
+    (cc, coords)
+}
+
+///
+/// Keys of everything the chart background depends on, see [`MeshCache`](struct.MeshCache.html).
+///
+#[derive(Clone, PartialEq)]
+struct MeshKey {
+    dim: (u32, u32),
+    xlim: (f64, f64),
+    ylim: (f64, f64),
+    x_label: String,
+    y_label: String,
+    scales: (f64, f64),
+    y_log: bool,
+    x_axis: XAxisType,
+    inverted: (bool, bool),
+    y_break: Option<((f64, f64), (f64, f64))>,
+    x_label_rotation: f64,
+    max_label_width: Option<u32>,
+    y_unit: String,
+    y_si: bool,
+    ui_scale: f64,
+    mesh: MeshStyle,
+    mesh_disabled: bool,
+}
+impl MeshKey {
+    fn new(config: &ChartConfig, dim: (u32, u32), xlim: (f64, f64), ylim: (f64, f64)) -> Self {
+        MeshKey {
+            dim,
+            xlim,
+            ylim,
+            x_label: config.x_label.clone(),
+            y_label: config.y_label.clone(),
+            scales: (config.x_scale, config.y_scale),
+            y_log: config.y_log,
+            x_axis: config.x_axis.clone(),
+            inverted: (config.x_inverted, config.y_inverted),
+            y_break: config.y_break,
+            x_label_rotation: config.x_label_rotation,
+            max_label_width: config.max_label_width,
+            y_unit: config.y_unit.clone(),
+            y_si: config.y_si,
+            ui_scale: config.ui_scale,
+            mesh: config.mesh.clone(),
+            mesh_disabled: config.quality >= MESH_QUALITY_LEVEL,
+        }
+    }
+}
+
+///
+/// The rendered background of a chart (fill, background image, mesh, axes and tick labels),
+/// reused as long as axis ranges, labels and styles don't change.
+///
+/// Series, draw hooks and the legend are drawn on top of the cached background in every frame.
+///
+struct MeshCache {
+    key: Option<(MeshKey, Option<Arc<ImageLayer>>)>,
+    buffer: Vec<u8>,
+    x_labels: (usize, f64),
+}
+impl MeshCache {
+    fn new() -> Self {
+        MeshCache {
+            key: None,
+            buffer: Vec::new(),
+            x_labels: (0, 0.0),
+        }
+    }
+    /// If the cached background matches the key and background image.
+    fn is_valid(&self, key: &MeshKey, background: &Option<Arc<ImageLayer>>) -> bool {
+        match (&self.key, background) {
+            (Some((cached, None)), None) => cached == key,
+            (Some((cached, Some(image))), Some(other)) => {
+                cached == key && Arc::ptr_eq(image, other)
+            }
+            _ => false,
+        }
+    }
+    /// Draws a complete chart into an RGB buffer, like [`draw_chart()`](fn.draw_chart.html),
+    /// but copies the background from the cache if possible. Returns the coordinate mapping of the plotting area.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_chart<'b>(
+        &mut self,
+        buffer: &'b mut [u8],
+        dim: (u32, u32),
+        config: &ChartConfig,
+        data: &[Series],
+        ensembles: &[EnsembleSeries],
+        xlim: (f64, f64),
+        ylim: (f64, f64),
+        hook: &StageHook<'_, BitMapBackend<'b, RGBPixel>>,
+    ) -> ChartCoords {
+        let start = Instant::now();
+        let key = MeshKey::new(config, dim, xlim, ylim);
+        if self.is_valid(&key, &config.background) {
+            buffer.copy_from_slice(&self.buffer);
+        } else {
+            {
+                let root = BitMapBackend::with_buffer(buffer, dim).into_drawing_area();
+                self.x_labels = x_label_area(&root, config, xlim);
+                let (mut cc, coords) =
+                    chart_layout(&root, config, data, xlim, ylim, self.x_labels.1);
+                draw_chart_background(&root, &mut cc, config, &coords, self.x_labels.0);
+            }
+            self.buffer.clear();
+            self.buffer.extend_from_slice(buffer);
+            self.key = Some((key, config.background.clone()));
+        }
+        let mesh_time = start.elapsed();
+        let root = BitMapBackend::with_buffer(buffer, dim).into_drawing_area();
+        let (cc, mut coords) = chart_layout(&root, config, data, xlim, ylim, self.x_labels.1);
+        let series_time = draw_chart_content(cc, config, data, ensembles, &coords, hook);
+        coords.draw_times = (mesh_time, series_time);
+        coords
+    }
+}
+
+/// Default number of x tick labels, reduced when labels would overlap.
+const X_LABELS: usize = 15;
+/// Minimum horizontal gap between x tick labels, in pixels.
+const LABEL_GAP: f64 = 5.0;
+
+/// Number of x tick labels that fit into the plot width without overlapping, and the size of the largest label.
+fn x_label_layout(
+    config: &ChartConfig,
+    axis: &ChartAxis,
+    font: &FontDesc,
+    width: f64,
+) -> (usize, (f64, f64)) {
+    let range = axis.range();
+    let span = range.end - range.start;
+    let gap = LABEL_GAP * config.ui_scale;
+    let mut count = X_LABELS;
+    loop {
+        let points = axis.key_points(count);
+        let size = points
+            .iter()
+            .map(|x| font.box_size(&config.x_tick_label(font, *x, span)))
+            .fold((0.0_f64, 0.0_f64), |(w, h), size| {
+                let (bw, bh) = size.unwrap_or((0, 0));
+                (w.max(bw as f64), h.max(bh as f64))
+            });
+        let (spacing, _) = rotated_extent(size, config.x_label_rotation);
+        let needed = spacing + gap;
+        let pixels: Vec<_> = points
+            .iter()
+            .map(|x| axis.map(x, (0, width as i32)))
+            .collect();
+        let distance = pixels
+            .windows(2)
+            .map(|p| (p[1] - p[0]).abs() as f64)
+            .fold(f64::INFINITY, f64::min);
+        if distance >= needed || count <= 2 {
+            return (count, size);
+        }
+        count = ((count as f64 * distance / needed) as usize)
+            .min(count - 1)
+            .max(2);
+    }
+}
+
+/// Draws the x tick labels below the plotting area, rotated by the configured angle.
+fn draw_rotated_x_labels<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    config: &ChartConfig,
+    coords: &ChartCoords,
+    count: usize,
+    font: &FontDesc,
+) {
+    let range = coords.x_axis.range();
+    let span = range.end - range.start;
+    let color = RGBColor(
+        config.mesh.label_color.0,
+        config.mesh.label_color.1,
+        config.mesh.label_color.2,
+    );
+    // Same distance from the axis as plotters uses: twice the tick mark size
+    let tick = (0.05 * coords.size.0.min(coords.size.1) as f64).min(5.0) as i32;
+    let y = coords.origin.1 + coords.size.1 + 2 * tick;
+    for x in coords.x_axis.key_points(count) {
+        let px = coords.origin.0 + coords.x_axis.map(&x, (0, coords.size.0));
+        let label = config.x_tick_label(font, x, span);
+        draw_rotated_text(root, &label, font, &color, (px, y), config.x_label_rotation);
+    }
+}
+
+/// Draws the chart's background: fill, background image, mesh, axes and tick labels.
+fn draw_chart_background<'a, DB>(
+    root: &DrawingArea<DB, Shift>,
+    cc: &mut ChartContext<'a, DB, RangedCoord<ChartAxis, ChartAxis>>,
+    config: &ChartConfig,
+    coords: &ChartCoords,
+    x_labels: usize,
+) where
+    DB: DrawingBackend + 'a,
+{
+    root.fill(&WHITE).unwrap();
+    let x_range = cc.x_range();
+    let x_span = x_range.end - x_range.start;
+    let label_font = config.label_font();
+    let x_rotated = config.x_label_rotation != 0.0;
+    let x_formatter = |x: &f64| {
+        if x_rotated {
+            String::new()
+        } else {
+            config.x_tick_label(&label_font, *x, x_span)
+        }
+    };
+
+    if let Some(image) = &config.background {
+        let area = cc.plotting_area().strip_coord_spec();
+        image.draw_scaled(&area, (0, 0), area.dim_in_pixel());
+    }
+
+    let ui_scale = config.ui_scale;
+    let y_range = cc.y_range();
+    let (y_desc, y_factor) = config.y_desc((y_range.start, y_range.end));
+    let y_formatter = move |y: &f64| {
+        if config.y_si {
+            format_tick(*y / y_factor)
+        } else {
+            format!("{}", *y)
+        }
+    };
+
+    let style = &config.mesh;
+    let rgb = |c: (u8, u8, u8)| RGBColor(c.0, c.1, c.2);
+    let label_color = rgb(style.label_color);
+    let (font_family, font_size) = match &style.label_font {
+        Some((family, size)) => (&family[..], *size),
+        None => ("sans-serif", 12.0),
+    };
+    let mut mesh = cc.configure_mesh();
+    mesh.x_label_formatter(&x_formatter)
+        .y_label_formatter(&y_formatter)
+        .x_labels(x_labels)
+        .y_labels(8)
+        .x_desc(&config.x_label)
+        .y_desc(y_desc)
+        .axis_style(&rgb(style.axis))
+        .axis_desc_style(
+            (font_family, 15.0 * ui_scale)
+                .into_font()
+                .color(&label_color),
+        );
+    if (ui_scale - 1.0).abs() > f64::EPSILON
+        || style.label_font.is_some()
+        || style.label_color != (0, 0, 0)
+    {
+        mesh.label_style(
+            (font_family, font_size * ui_scale)
+                .into_font()
+                .color(&label_color),
+        );
+    }
+    match (style.bold, style.light) {
+        (None, None) => {
+            mesh.disable_mesh();
+        }
+        (bold, light) => {
+            let line = |l: Option<((u8, u8, u8), f64)>| {
+                l.map(|(c, alpha)| rgb(c).mix(alpha))
+                    .unwrap_or_else(|| BLACK.mix(0.0))
+            };
+            mesh.line_style_1(&line(bold)).line_style_2(&line(light));
+        }
+    }
+    if config.quality >= MESH_QUALITY_LEVEL {
+        mesh.disable_mesh();
+    }
+    mesh.draw().unwrap();
+    if x_rotated {
+        draw_rotated_x_labels(root, config, coords, x_labels, &label_font);
+    }
+}
+
+/// Draws series, draw hooks and the legend. Returns the time spent on drawing.
+fn draw_chart_content<'a, DB>(
+    mut cc: ChartContext<'a, DB, RangedCoord<ChartAxis, ChartAxis>>,
+    config: &'a ChartConfig,
+    data: &'a [Series],
+    ensembles: &'a [EnsembleSeries],
+    coords: &ChartCoords,
+    hook: &StageHook<'_, DB>,
+) -> Duration
+where
+    DB: DrawingBackend + 'a,
+{
+    let start = Instant::now();
+    let x_scale = config.x_scale;
+    let y_scale = config.y_scale;
+    let y_log = config.y_log;
+    let ui_scale = config.ui_scale;
+    let legend_size = (5.0 * ui_scale).round() as i32;
+    let line_width = ui_scale.round().max(1.0) as u32;
+    let marker_size = |radius: Option<u32>| {
+        (radius.unwrap_or(DEFAULT_MARKER_RADIUS) as f64 * ui_scale / config.pixel_scale)
+            .round()
+            .max(1.0) as i32
+    };
+    let spacing = (PATTERN_SPACING * ui_scale / config.pixel_scale)
+        .round()
+        .max(2.0) as i32;
+    let stride = 1 << config.quality;
+    let (x_visible, y_visible) = (coords.x_range(), coords.y_range());
+    let finite = move |xy: (f64, f64)| match config.non_finite {
+        NonFinite::Skip => xy,
+        NonFinite::Clamp => clamp_infinite(xy, x_visible, y_visible),
+    };
+    let is_finite = |xy: &(f64, f64)| xy.0.is_finite() && xy.1.is_finite();
+
+    let area = cc.plotting_area().strip_coord_spec();
+    let visible_alerts = config
+        .alerts
+        .iter()
+        .filter(|alert| data.get(alert.series).is_some_and(|s| s.visible));
+    for alert in visible_alerts.clone().filter(|alert| alert.active) {
+        let color = &data[alert.series].color;
+        area.fill(&color.mix(0.12)).unwrap();
+    }
+    for alert in visible_alerts {
+        let color = data[alert.series].color.mix(0.6);
+        let (x0, x1) = coords.x_range();
+        for level in &alert.levels {
+            let (a, b) = (coords.to_pixel((x0, *level)), coords.to_pixel((x1, *level)));
+            area.draw(&PathElement::new(
+                vec![a, b],
+                ShapeStyle::from(&color).stroke_width(line_width),
+            ))
+            .unwrap();
+        }
+    }
+    hook(HookStage::PreDraw, &area, coords);
+
+    for ensemble in ensembles.iter() {
+        let color = &ensemble.color;
+        if !ensemble.visible {
+            legend_entry(&mut cc)
+                .label(&ensemble.name)
+                .legend(move |(x, y)| {
+                    let s = legend_size;
+                    Rectangle::new([(x - s, y - s), (x + s, y + s)], color)
+                });
+            continue;
+        }
+        let (x0, x1) = coords.x_range();
+        let summary: Vec<_> = ensemble
+            .summaries
+            .iter()
+            .filter(|s| s.1.is_finite() && (!config.x_follow || (s.0 >= x0 && s.0 <= x1)))
+            .cloned()
+            .collect();
+        let summary = thin(summary, stride);
+        let envelope: Vec<_> = summary
+            .iter()
+            .map(|s| (s.0 * x_scale, s.3 * y_scale))
+            .chain(summary.iter().rev().map(|s| (s.0 * x_scale, s.2 * y_scale)))
+            .collect();
+        if ensemble.pattern == Pattern::Solid {
+            cc.draw_series(std::iter::once(Polygon::new(envelope, &color.mix(0.3))))
+                .unwrap();
+        } else {
+            cc.draw_series(std::iter::once(Polygon::new(envelope, &color.mix(0.15))))
+                .unwrap();
+            let outline: Vec<_> = summary
+                .iter()
+                .map(|s| coords.to_pixel((s.0, s.3)))
+                .chain(summary.iter().rev().map(|s| coords.to_pixel((s.0, s.2))))
+                .collect();
+            ensemble
+                .pattern
+                .fill_polygon(&area, &outline, color, spacing);
+        }
+        let pattern = ensemble.pattern;
+        cc.draw_series(LineSeries::new(
+            summary.iter().map(|s| (s.0 * x_scale, s.1 * y_scale)),
+            ShapeStyle::from(color).stroke_width(line_width),
+        ))
+        .unwrap()
+        .label(&ensemble.name)
+        .legend(move |(x, y)| {
+            let s = legend_size;
+            PatternRect::new([(x - s, y - s), (x + s, y + s)], pattern, color, spacing)
+        });
+    }
+
+    for (index, series) in data.iter().enumerate() {
+        let line_width = if config
+            .alerts
+            .iter()
+            .any(|alert| alert.active && alert.series == index)
+        {
+            2 * line_width
+        } else {
+            line_width
+        };
+        if !series.visible {
+            legend_entry(&mut cc)
+                .label(series.legend_label(config.legend_values))
+                .legend(move |(x, y)| {
+                    let s = legend_size;
+                    Rectangle::new([(x - s, y - s), (x + s, y + s)], &series.color)
+                });
+            continue;
+        }
+        // Skip data outside the visible x range, which would be clamped to the plotting area
+        let visible = if config.x_follow {
+            visible_range(&series.data, coords.x_range())
+        } else {
+            0..series.data.len()
+        };
+        let line_point = |xy: &(f64, f64)| {
+            let (a, b) = finite(series.transformed(xy));
+            (
+                a * x_scale,
+                if y_log && b <= 0.0 {
+                    f64::NAN
+                } else {
+                    b * y_scale
+                },
+            )
+        };
+        match &series.series_type {
+            SeriesType::Line if series.colors.is_some() => {
+                let colors = series.colors.as_ref().unwrap();
+                let range = colors.value_range();
+                let points: Vec<_> = series
+                    .data
+                    .range(visible.clone())
+                    .zip(colors.values.range(visible))
+                    .step_by(stride)
+                    .map(|(xy, value)| (line_point(xy), *value))
+                    .collect();
+                let segments = points
+                    .windows(2)
+                    .filter(|seg| is_finite(&seg[0].0) && is_finite(&seg[1].0));
+                cc.draw_series(segments.map(|seg| {
+                    let color = if seg[1].1.is_finite() {
+                        colors.color(seg[1].1, range)
+                    } else {
+                        RGBColor(series.color.0, series.color.1, series.color.2)
+                    };
+                    PathElement::new(
+                        vec![seg[0].0, seg[1].0],
+                        ShapeStyle::from(&color).stroke_width(line_width),
+                    )
+                }))
+                .unwrap();
+            }
+            SeriesType::Line => {
+                // Long zoomed-out series are reduced to the extremes of pre-aggregated buckets, about one per pixel
+                let points: Vec<_> = series
+                    .data
+                    .reduced(visible.clone(), coords.size.0 as usize)
+                    .iter()
+                    .map(line_point)
+                    .collect();
+                // Draw runs of finite points separately, to get gaps instead of lines to the border
+                let line = smooth(&thin(points, stride), series.smoothing);
+                let runs = line
+                    .split(|xy| !is_finite(xy))
+                    .filter(|run| !run.is_empty());
+                let to_pixel = |xy: &(f64, f64)| {
+                    (
+                        coords.x_axis.fraction(xy.0) * coords.size.0 as f64,
+                        coords.y_axis.fraction(xy.1) * coords.size.1 as f64,
+                    )
+                };
+                let runs: Vec<_> = match config.line_tolerance {
+                    Some(tolerance) => runs.map(|run| simplify(run, to_pixel, tolerance)).collect(),
+                    None => runs.map(|run| run.to_vec()).collect(),
+                };
+                cc.draw_series(runs.into_iter().map(|run| {
+                    PathElement::new(
+                        run,
+                        ShapeStyle::from(&series.color).stroke_width(line_width),
+                    )
+                }))
+                .unwrap();
+            }
+            SeriesType::Point if is_dense(series, visible.len()) => {
+                let density = series.density.as_ref().unwrap();
+                let size = (density.cell_size as f64 * ui_scale / config.pixel_scale).max(1.0);
+                let pixels = series
+                    .data
+                    .range(visible)
+                    .map(|xy| finite(series.transformed(xy)))
+                    .filter(is_finite)
+                    .map(|xy| coords.to_pixel(xy));
+                let cells = density.bin(pixels, coords.size, size);
+                let max = cells.values().max().cloned().unwrap_or(1) as f64;
+                for (cell, count) in cells {
+                    let color = density.color_map.get_color_norm(count as f64 / max);
+                    area.draw(&Polygon::new(density.outline(cell, size), color.filled()))
+                        .unwrap();
+                }
+            }
+            SeriesType::Point if series.pattern != Pattern::Solid => {
+                let radius = marker_size(series.radius);
+                let points = series.data.range(visible).step_by(stride);
+                for xy in points
+                    .map(|xy| finite(series.transformed(xy)))
+                    .filter(is_finite)
+                {
+                    let center = coords.to_pixel(xy);
+                    let outline = circle_polygon(center, radius as f64);
+                    series
+                        .pattern
+                        .fill_polygon(&area, &outline, &series.color, spacing);
+                    area.draw(&Circle::new(center, radius, &series.color))
+                        .unwrap();
+                }
+            }
+            SeriesType::Point => {
+                let marker_size = marker_size(series.radius);
+                let points = series.data.range(visible).step_by(stride);
+                cc.draw_series(
+                    points
+                        .map(|xy| finite(series.transformed(xy)))
+                        .filter(is_finite)
+                        .map(|(a, b)| {
+                            Circle::new(
+                                (a * x_scale, b * y_scale),
+                                marker_size,
+                                ShapeStyle::from(&series.color).filled(),
+                            )
+                        }),
+                )
+                .unwrap();
+            }
+            SeriesType::Segment | SeriesType::Arrow => {
+                let points: Vec<_> = series
+                    .data
+                    .iter()
+                    .map(|xy| finite(series.transformed(xy)))
+                    .collect();
+                let segments = points
+                    .chunks_exact(2)
+                    .filter(|seg| is_finite(&seg[0]) && is_finite(&seg[1]));
+                if series.series_type == SeriesType::Arrow {
+                    let head = 8.0 * ui_scale;
+                    for seg in segments.clone() {
+                        let (from, to) = (coords.to_pixel(seg[0]), coords.to_pixel(seg[1]));
+                        if from != to {
+                            area.draw(&Polygon::new(
+                                arrow_head(from, to, head),
+                                series.color.filled(),
+                            ))
+                            .unwrap();
+                        }
+                    }
+                }
+                cc.draw_series(segments.map(|seg| {
+                    PathElement::new(
+                        vec![
+                            (seg[0].0 * x_scale, seg[0].1 * y_scale),
+                            (seg[1].0 * x_scale, seg[1].1 * y_scale),
+                        ],
+                        ShapeStyle::from(&series.color).stroke_width(line_width),
+                    )
+                }))
+                .unwrap();
+            }
+        }
+        let label = series.legend_label(config.legend_values);
+        legend_entry(&mut cc).label(label).legend(move |(x, y)| {
+            let s = legend_size;
+            PatternRect::new(
+                [(x - s, y - s), (x + s, y + s)],
+                series.pattern,
+                &series.color,
+                spacing,
+            )
+        });
+    }
+
+    if let Some((lo, hi)) = coords.y_axis.break_pixels((coords.size.1, 0)) {
+        draw_axis_break(&area, (lo.min(hi), lo.max(hi)), coords.size.0, ui_scale);
+    }
+    if let Some(minimap) = &coords.minimap {
+        let view = (coords.x_range(), coords.y_range());
+        minimap.draw(&area, data, view);
+    }
+
+    cc.configure_series_labels()
+        .position(config.legend_position.to_plotters())
+        .label_font(("sans-serif", 12.0 * ui_scale).into_font())
+        .margin((10.0 * ui_scale) as u32)
+        .legend_area_size((30.0 * ui_scale) as u32)
+        .background_style(&WHITE.mix(0.8))
+        .border_style(&BLACK)
+        .draw()
+        .unwrap();
+
+    hook(HookStage::PostDraw, &area, coords);
+    #[cfg(feature = "profiling")]
+    if let Some(profile) = &config.profile {
+        profile.draw(&area, ui_scale);
+    }
+    start.elapsed()
+}
+
+/// Adds an entry to the legend, to be labeled by the caller.
+/// Draws nothing, as plotters only creates legend entries for drawn series.
+fn legend_entry<'a, 'b, DB: DrawingBackend>(
+    cc: &'b mut ChartContext<'a, DB, RangedCoord<ChartAxis, ChartAxis>>,
+) -> &'b mut SeriesAnno<'a, DB> {
+    cc.draw_series(std::iter::empty::<Circle<(f64, f64), i32>>())
+        .unwrap()
+}
+
+/// Draws the context menu over a rendered frame, if it is open.
+fn draw_context_menu(
+    menu: &Option<ContextMenu>,
+    buffer: &mut [u8],
+    dim: (u32, u32),
+    ui_scale: f64,
+) {
+    if let Some(menu) = menu.as_ref().filter(|menu| menu.is_open()) {
+        let root = BitMapBackend::with_buffer(buffer, dim).into_drawing_area();
+        menu.draw(&root, ui_scale);
+    }
+}
+
+/// Finds the visible data point nearest to the pixel, if the pixel is within the plotting area.
+fn pick_nearest(
+    data: &[Series],
+    coords: &ChartCoords,
+    px: (i32, i32),
+) -> Option<(usize, usize, (f64, f64))> {
+    let (x, y) = (px.0 - coords.origin.0, px.1 - coords.origin.1);
+    if x < 0 || y < 0 || x >= coords.size.0 || y >= coords.size.1 {
+        return None;
+    }
+    let mut nearest = None;
+    let mut min_dist = i64::MAX;
+    for (s, series) in data.iter().enumerate().filter(|(_, s)| s.visible) {
+        for (i, xy) in series.data.iter().enumerate() {
+            let xy = series.transformed(xy);
+            let (px, py) = coords.to_pixel(xy);
+            let dist = (px - x) as i64 * (px - x) as i64 + (py - y) as i64 * (py - y) as i64;
+            if dist < min_dist {
+                min_dist = dist;
+                nearest = Some((s, i, xy));
+            }
+        }
+    }
+    nearest
+}
+
+/// Quotes a CSV cell if it contains separators, quotes or line breaks.
+fn csv_cell(text: &str) -> String {
+    if text.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Formats a value for legend entries, with thousands separators and up to three decimals.
+fn format_legend_value(value: f64) -> String {
+    if !value.is_finite() {
+        return format!("{}", value);
+    }
+    let decimals = if value.abs() >= 1000.0 { 0 } else { 3 };
+    let mut text = format!("{:.*}", decimals, value);
+    if text.contains('.') {
+        text = text.trim_end_matches('0').trim_end_matches('.').to_string();
+    }
+    let (sign, text) = match text.strip_prefix('-') {
+        Some(rest) if rest.chars().any(|c| c != '0' && c != '.') => ("-", rest),
+        Some(rest) => ("", rest),
+        None => ("", &text[..]),
+    };
+    let (int, frac) = match text.find('.') {
+        Some(pos) => text.split_at(pos),
+        None => (text, ""),
+    };
+    let mut grouped = String::new();
+    for (i, c) in int.chars().enumerate() {
+        if i > 0 && (int.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    format!("{}{}{}", sign, grouped, frac)
+}
+
+/// Default radius of point markers, in screen pixels.
+const DEFAULT_MARKER_RADIUS: u32 = 2;
+
+/// Highest level of reduced rendering quality.
+const MAX_QUALITY_LEVEL: usize = 6;
+/// Quality level from which on mesh lines are omitted.
+const MESH_QUALITY_LEVEL: usize = 4;
+
+/// Adapts the rendering quality level to the last frame's render time.
+/// Quality is reduced when over budget, and restored when well below, to avoid oscillation.
+fn adapt_quality(level: usize, render_time: Duration, budget: Duration) -> usize {
+    if render_time > budget {
+        (level + 1).min(MAX_QUALITY_LEVEL)
+    } else if render_time * 3 < budget {
+        level.saturating_sub(1)
+    } else {
+        level
+    }
+}
+
+/// Keeps only every `stride`-th entry, plus the last one.
+fn thin<T: Copy>(mut items: Vec<T>, stride: usize) -> Vec<T> {
+    if stride <= 1 || items.len() < 2 {
+        return items;
+    }
+    let last = items[items.len() - 1];
+    let keep_last = (items.len() - 1) % stride != 0;
+    let mut i = 0_usize;
+    items.retain(|_| {
+        i += 1;
+        (i - 1) % stride == 0
+    });
+    if keep_last {
+        items.push(last);
+    }
+    items
+}
+
+/// Simplifies a polyline for drawing, given a mapping of points to pixels. Returns the kept points.
+///
+/// Drops points closer than `tolerance` pixels to the previously kept point (radial distance),
+/// followed by Ramer–Douglas–Peucker simplification with the same tolerance. End points are always kept.
+fn simplify<F>(points: &[(f64, f64)], to_pixel: F, tolerance: f64) -> Vec<(f64, f64)>
+where
+    F: Fn(&(f64, f64)) -> (f64, f64),
+{
+    if points.len() <= 2 || tolerance <= 0.0 {
+        return points.to_vec();
+    }
+    let pixels: Vec<_> = points.iter().map(to_pixel).collect();
+    let tolerance_sq = tolerance * tolerance;
+
+    let last = points.len() - 1;
+    let mut radial = vec![0];
+    for i in 1..last {
+        let prev = pixels[radial[radial.len() - 1]];
+        if distance_sq(pixels[i], prev) > tolerance_sq {
+            radial.push(i);
+        }
+    }
+    radial.push(last);
+
+    let mut keep = vec![false; radial.len()];
+    keep[0] = true;
+    keep[radial.len() - 1] = true;
+    let mut stack = vec![(0, radial.len() - 1)];
+    while let Some((first, last)) = stack.pop() {
+        let (a, b) = (pixels[radial[first]], pixels[radial[last]]);
+        let mut farthest = (0.0, first);
+        for (k, i) in radial.iter().enumerate().take(last).skip(first + 1) {
+            let dist = segment_distance_sq(pixels[*i], a, b);
+            if dist > farthest.0 {
+                farthest = (dist, k);
+            }
+        }
+        if farthest.0 > tolerance_sq {
+            keep[farthest.1] = true;
+            stack.push((first, farthest.1));
+            stack.push((farthest.1, last));
+        }
+    }
+    radial
+        .iter()
+        .zip(keep)
+        .filter(|(_, keep)| *keep)
+        .map(|(i, _)| points[*i])
+        .collect()
+}
+
+/// Squared distance between two points.
+fn distance_sq(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)
+}
+
+/// Squared distance of a point from the line segment from `a` to `b`.
+fn segment_distance_sq(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let len_sq = dx * dx + dy * dy;
+    if len_sq == 0.0 {
+        return distance_sq(p, a);
+    }
+    let t = (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / len_sq).clamp(0.0, 1.0);
+    distance_sq(p, (a.0 + t * dx, a.1 + t * dy))
+}
+
+/// Returns if a series is drawn in density mode, for the given number of visible points.
+fn is_dense(series: &Series, points: usize) -> bool {
+    match &series.density {
+        Some(density) => points > density.threshold,
+        None => false,
+    }
+}
+
+/// Triangle of an arrow head at the end of a segment, in pixels.
+fn arrow_head(from: (i32, i32), to: (i32, i32), size: f64) -> Vec<(i32, i32)> {
+    let (dx, dy) = ((to.0 - from.0) as f64, (to.1 - from.1) as f64);
+    let len = (dx * dx + dy * dy).sqrt();
+    let (ux, uy) = (dx / len, dy / len);
+    let (bx, by) = (to.0 as f64 - ux * size, to.1 as f64 - uy * size);
+    let half = size * 0.4;
+    vec![
+        to,
+        (
+            (bx - uy * half).round() as i32,
+            (by + ux * half).round() as i32,
+        ),
+        (
+            (bx + uy * half).round() as i32,
+            (by - ux * half).round() as i32,
+        ),
+    ]
+}
+
+/// Number of interpolated segments between two points of a smoothed line.
+const SMOOTHING_STEPS: usize = 8;
+
+/// Interpolates a smooth curve through the points. Runs of finite points are smoothed separately.
+fn smooth(points: &[(f64, f64)], smoothing: Smoothing) -> Vec<(f64, f64)> {
+    if smoothing == Smoothing::None {
+        return points.to_vec();
+    }
+    let mut result = Vec::with_capacity(points.len() * SMOOTHING_STEPS);
+    for run in points.split(|(x, y)| !x.is_finite() || !y.is_finite()) {
+        if !result.is_empty() {
+            result.push((f64::NAN, f64::NAN));
+        }
+        match smoothing {
+            Smoothing::CatmullRom => catmull_rom(run, &mut result),
+            Smoothing::Monotone => monotone_cubic(run, &mut result),
+            Smoothing::None => result.extend_from_slice(run),
+        }
+    }
+    result
+}
+
+/// Uniform Catmull-Rom spline, with end points duplicated as outer control points.
+fn catmull_rom(points: &[(f64, f64)], out: &mut Vec<(f64, f64)>) {
+    if points.len() < 3 {
+        out.extend_from_slice(points);
+        return;
+    }
+    let last = points.len() - 1;
+    for i in 0..last {
+        let p0 = points[i.saturating_sub(1)];
+        let (p1, p2) = (points[i], points[i + 1]);
+        let p3 = points[(i + 2).min(last)];
+        for step in 0..SMOOTHING_STEPS {
+            let t = step as f64 / SMOOTHING_STEPS as f64;
+            let (t2, t3) = (t * t, t * t * t);
+            let interp = |a: f64, b: f64, c: f64, d: f64| {
+                0.5 * (2.0 * b
+                    + (c - a) * t
+                    + (2.0 * a - 5.0 * b + 4.0 * c - d) * t2
+                    + (3.0 * b - a - 3.0 * c + d) * t3)
+            };
+            out.push((
+                interp(p0.0, p1.0, p2.0, p3.0),
+                interp(p0.1, p1.1, p2.1, p3.1),
+            ));
+        }
+    }
+    out.push(points[last]);
+}
+
+/// Monotone cubic Hermite interpolation (Fritsch-Carlson) of y over x.
+fn monotone_cubic(points: &[(f64, f64)], out: &mut Vec<(f64, f64)>) {
+    let n = points.len();
+    if n < 3 || points.windows(2).any(|w| w[1].0 <= w[0].0) {
+        out.extend_from_slice(points);
+        return;
+    }
+    let secants: Vec<_> = points
+        .windows(2)
+        .map(|w| (w[1].1 - w[0].1) / (w[1].0 - w[0].0))
+        .collect();
+    let mut tangents = Vec::with_capacity(n);
+    tangents.push(secants[0]);
+    for w in secants.windows(2) {
+        tangents.push(if w[0] * w[1] <= 0.0 {
+            0.0
+        } else {
+            (w[0] + w[1]) / 2.0
+        });
+    }
+    tangents.push(secants[n - 2]);
+    for (i, &d) in secants.iter().enumerate() {
+        if d == 0.0 {
+            tangents[i] = 0.0;
+            tangents[i + 1] = 0.0;
+            continue;
+        }
+        let (a, b) = (tangents[i] / d, tangents[i + 1] / d);
+        let s = a * a + b * b;
+        if s > 9.0 {
+            let tau = 3.0 / s.sqrt();
+            tangents[i] = tau * a * d;
+            tangents[i + 1] = tau * b * d;
+        }
+    }
+    for i in 0..n - 1 {
+        let ((x0, y0), (x1, y1)) = (points[i], points[i + 1]);
+        let h = x1 - x0;
+        for step in 0..SMOOTHING_STEPS {
+            let t = step as f64 / SMOOTHING_STEPS as f64;
+            let (t2, t3) = (t * t, t * t * t);
+            let y = (2.0 * t3 - 3.0 * t2 + 1.0) * y0
+                + (t3 - 2.0 * t2 + t) * h * tangents[i]
+                + (-2.0 * t3 + 3.0 * t2) * y1
+                + (t3 - t2) * h * tangents[i + 1];
+            out.push((x0 + t * h, y));
+        }
+    }
+    out.push(points[n - 1]);
+}
+
+/// A frame finished by a `RenderThread`.
+struct RenderedFrame {
+    buffer: Vec<u8>,
+    render_time: Duration,
+    coords: ChartCoords,
+}
+
+/// Renders chart frames on a background thread.
+///
+/// Holds at most one frame in flight. Rendered buffers are sent back to be presented by the window's thread.
+struct RenderThread {
+    dim: (usize, usize),
+    jobs: Option<Sender<(ChartFrame, Vec<u8>)>>,
+    frames: Receiver<RenderedFrame>,
+    spare: Option<Vec<u8>>,
+    busy: bool,
+    points: usize,
+    handle: Option<JoinHandle<()>>,
+}
+impl RenderThread {
+    fn new(dim: (usize, usize)) -> Self {
+        let (job_sender, job_receiver) = channel::<(ChartFrame, Vec<u8>)>();
+        let (frame_sender, frame_receiver) = channel();
+        let handle = thread::spawn(move || {
+            let mut mesh_cache = MeshCache::new();
+            for (frame, mut buffer) in job_receiver {
+                let start = Instant::now();
+                let config = &frame.config;
+                let coords = mesh_cache.draw_chart(
+                    &mut buffer,
+                    (dim.0 as u32, dim.1 as u32),
+                    config,
+                    &frame.data,
+                    &frame.ensembles,
+                    frame.xlim,
+                    frame.ylim,
+                    &|stage, area, coords| config.run_hooks(stage, area, coords),
+                );
+                let rendered = RenderedFrame {
+                    buffer,
+                    render_time: start.elapsed(),
+                    coords,
+                };
+                if frame_sender.send(rendered).is_err() {
+                    break;
+                }
+            }
+        });
+        RenderThread {
+            dim,
+            jobs: Some(job_sender),
+            frames: frame_receiver,
+            spare: None,
+            busy: false,
+            points: 0,
+            handle: Some(handle),
+        }
+    }
+    fn is_busy(&self) -> bool {
+        self.busy
+    }
+    fn submit(&mut self, frame: ChartFrame) {
+        let buffer = self
+            .spare
+            .take()
+            .unwrap_or_else(|| vec![0; 3 * self.dim.0 * self.dim.1]);
+        self.points = count_points(&frame.data, &frame.ensembles);
+        if let Some(jobs) = &self.jobs {
+            self.busy = jobs.send((frame, buffer)).is_ok();
+        }
+    }
+    /// Returns a finished frame and its number of points, if any.
+    fn poll(&mut self) -> Option<(RenderedFrame, usize)> {
+        let frame = self.frames.try_recv().ok()?;
+        self.busy = false;
+        Some((frame, self.points))
+    }
+    fn recycle(&mut self, buffer: Vec<u8>) {
+        self.spare = Some(buffer);
+    }
+}
+impl Drop for RenderThread {
+    fn drop(&mut self) {
+        self.jobs = None;
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_imports)]
+mod test {
+    use crate::color::LinearColorMap;
+    use crate::color::Palette as ColorPalette;
+    use crate::ui::axis::{AxisKind, ChartAxis};
+    use crate::ui::chart::{
+        adapt_quality, arrow_head, clamp_infinite, draw_chart, format_legend_value, palette_color,
+        pick_nearest, simplify, smooth, thin, valid_range, visible_range, x_label_layout,
+        AutoRange, Chart, ChartBuilder, ChartConfig, Density, Derivation, EnsembleSeries, Envelope,
+        LegendPosition, MeshCache, NonFinite, Preset, RenderThread, Series, Smoothing, Threshold,
+        XAxisType,
+    };
+    use crate::ui::metadata::{read_png_text, sidecar_path, MetadataFormat};
+    use plotters::style::{Color, IntoFont, RGBColor, BLUE, GREEN, RED};
+    use rand::Rng;
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+
+    #[test]
+    fn time_series_plot() {
+        let mut chart = ChartBuilder::new()
+            .with_title("Test")
+            .with_labels("some x", "some y")
+            .with_dimensions(800, 400)
+            .with_data_limit(100)
             .with_fps_limit(30.0)
             .add_series(Series::point("A", &RED))
             .add_series(Series::line("A", &BLUE))
             .build();
 
-        for i in 1..5 {
-            chart.push_time_series(i as f64, &[(i as f64).log10(), (i as f64).sqrt()]);
-            chart.update();
+        for i in 1..5 {
+            chart.push_time_series(i as f64, &[(i as f64).log10(), (i as f64).sqrt()]);
+            chart.update();
+        }
+    }
+
+    #[test]
+    fn scatter_plot() {
+        let mut rng = rand::thread_rng();
+        let mut chart = ChartBuilder::new()
+            .with_title("Scatter")
+            .with_labels("some x", "some y")
+            .with_dimensions(400, 400)
+            .with_xlim(Some(0.0), Some(1.0))
+            .with_ylim(Some(0.0), Some(1.0))
+            .with_fps_limit(30.0)
+            .add_series(Series::point("A", &RED))
+            .build();
+
+        for _i in 1..5 {
+            let points: Vec<_> = (0..100)
+                .map(|_| (rng.gen_range(0.0, 1.0), rng.gen_range(0.0, 1.0)))
+                .collect();
+            chart.replace_series(0, &points);
+            chart.update();
+        }
+    }
+
+    #[test]
+    fn auto_range() {
+        let target = (1.0, 2.0);
+        assert_eq!(AutoRange::Exact.apply(Some((0.0, 3.0)), target), target);
+        assert_eq!(AutoRange::Growing.apply(None, target), target);
+        assert_eq!(
+            AutoRange::Growing.apply(Some((0.0, 1.5)), target),
+            (0.0, 2.0)
+        );
+        assert_eq!(
+            AutoRange::Smoothed(0.5).apply(Some((0.0, 1.5)), target),
+            (0.5, 2.0)
+        );
+    }
+
+    #[test]
+    fn render_thread() {
+        let dim = (200, 100);
+        let mut series = Series::line("A", &RED);
+        series.push((0.0, 0.0));
+        series.push((1.0, 1.0));
+        let mut renderer = RenderThread::new(dim);
+        renderer.submit(super::ChartFrame {
+            config: ChartConfig::new(),
+            data: vec![series],
+            ensembles: vec![],
+            xlim: (0.0, 1.0),
+            ylim: (0.0, 1.0),
+        });
+        assert!(renderer.is_busy());
+        let frame = renderer.frames.recv().unwrap();
+        let (buffer, render_time) = (frame.buffer, frame.render_time);
+        assert_eq!(buffer.len(), 3 * dim.0 * dim.1);
+        assert!(buffer.chunks(3).any(|px| px == [255, 0, 0]));
+        assert!(render_time > std::time::Duration::from_secs(0));
+        assert_eq!(renderer.points, 2);
+    }
+
+    #[test]
+    fn series_limit_and_decimation() {
+        let mut series = Series::line("A", &RED).with_limit(3).with_decimation(2);
+        for i in 0..10 {
+            series.append((i as f64, 0.0), Some(100));
+        }
+        let x: Vec<_> = series.data.iter().map(|xy| xy.0).collect();
+        assert_eq!(x, vec![4.0, 6.0, 8.0]);
+
+        let mut series = Series::line("B", &RED);
+        for i in 0..10 {
+            series.append((i as f64, 0.0), Some(5));
+        }
+        assert_eq!(series.data.len(), 5);
+    }
+
+    #[test]
+    fn set_data_limit() {
+        let mut chart = ChartBuilder::new()
+            .add_series(Series::line("A", &RED).with_limit(5))
+            .add_series(Series::line("B", &RED))
+            .add_series(Series::segment("C", &RED))
+            .build_headless();
+        for i in 0..10 {
+            let x = i as f64;
+            chart.data[0].push((x, 0.0));
+            chart.data[1].push((x, 0.0));
+            chart.data[2].push_segment((x, 0.0), (x, 1.0));
+        }
+        chart.set_data_limit(Some(3));
+        assert_eq!(chart.data[0].data.len(), 5);
+        assert_eq!(chart.data[1].data.len(), 3);
+        assert_eq!(chart.data[2].data.len(), 6);
+        assert_eq!(chart.data[2].data.front(), Some(&(7.0, 0.0)));
+    }
+
+    #[test]
+    fn draw_hooks() {
+        use plotters::prelude::*;
+        use std::sync::Arc;
+
+        let dim = (200, 100);
+        let mut config = ChartConfig::new();
+        config.post_draw.push(Arc::new(|area, coords| {
+            assert_eq!(coords.to_pixel((0.0, 0.0)), (0, coords.size().1 as i32));
+            assert_eq!(coords.x_range(), (0.0, 1.0));
+            area.fill(&GREEN).unwrap();
+        }));
+        let mut renderer = RenderThread::new(dim);
+        renderer.submit(super::ChartFrame {
+            config,
+            data: vec![],
+            ensembles: vec![],
+            xlim: (0.0, 1.0),
+            ylim: (0.0, 1.0),
+        });
+        let buffer = renderer.frames.recv().unwrap().buffer;
+        assert!(buffer.chunks(3).any(|px| px == [0, 255, 0]));
+    }
+
+    #[test]
+    fn ensemble_summary() {
+        let mut series = EnsembleSeries::new("A", &RED);
+        series.push(0.0, &[1.0, 2.0, 3.0, 6.0]);
+        series.push(1.0, &[]);
+        assert_eq!(series.summary(), vec![(0.0, 3.0, 1.0, 6.0)]);
+
+        let mut series = series.with_envelope(Envelope::Percentile(25.0, 75.0));
+        series.push(1.0, &[0.0, 10.0, 20.0, 30.0, 40.0]);
+        assert_eq!(series.summary()[1], (1.0, 20.0, 10.0, 30.0));
+
+        let dim = (600, 400);
+        let mut renderer = RenderThread::new(dim);
+        renderer.submit(super::ChartFrame {
+            config: ChartConfig::new(),
+            data: vec![],
+            ensembles: vec![series],
+            xlim: (0.0, 1.0),
+            ylim: (0.0, 40.0),
+        });
+        let buffer = renderer.frames.recv().unwrap().buffer;
+        assert!(buffer.chunks(3).any(|px| px == [255, 0, 0]));
+        assert!(buffer
+            .chunks(3)
+            .any(|px| px[0] == 255 && px[1] > 150 && px[1] < 200));
+    }
+
+    #[test]
+    fn ensemble_non_finite() {
+        let series = EnsembleSeries::new("A", &RED).with_envelope(Envelope::Percentile(0.0, 100.0));
+        let mut chart = ChartBuilder::new()
+            .with_dimensions(200, 100)
+            .add_ensemble(series)
+            .build_headless();
+        chart.push_ensemble(0, 0.0, &[1.0, f64::NAN, 3.0]);
+        chart.push_ensemble(0, 1.0, &[f64::NAN]);
+        chart.push_ensemble(0, 2.0, &[f64::INFINITY, 4.0]);
+        chart.update();
+
+        let summary = chart.ensembles[0].summary();
+        assert_eq!(summary[0], (0.0, 2.0, 1.0, 3.0));
+        assert!(summary[1].1.is_nan() && summary[1].2.is_nan());
+        assert_eq!(summary[2], (2.0, 4.0, 4.0, 4.0));
+    }
+
+    #[test]
+    fn ui_scale() {
+        let dim = (800, 600);
+        let mut renderer = RenderThread::new(dim);
+        let mut red_pixels = vec![];
+        for &scale in &[1.0, 2.0] {
+            let mut series = Series::point("A", &RED);
+            series.push((0.5, 0.5));
+            let mut config = ChartConfig::new();
+            config.ui_scale = scale;
+            renderer.submit(super::ChartFrame {
+                config,
+                data: vec![series],
+                ensembles: vec![],
+                xlim: (0.0, 1.0),
+                ylim: (0.0, 1.0),
+            });
+            let buffer = renderer.frames.recv().unwrap().buffer;
+            red_pixels.push(buffer.chunks(3).filter(|px| px == &[255, 0, 0]).count());
+            renderer.recycle(buffer);
+            renderer.busy = false;
+        }
+        assert!(red_pixels[1] > 3 * red_pixels[0]);
+    }
+
+    #[test]
+    fn series_from_fn() {
+        let series = Series::from_fn("A", &RED, (0.0, 2.0), 3, |x| x * x);
+        let data: Vec<_> = series.data.iter().cloned().collect();
+        assert_eq!(data, vec![(0.0, 0.0), (1.0, 1.0), (2.0, 4.0)]);
+    }
+
+    #[test]
+    fn smoothing() {
+        let points = vec![(0.0, 0.0), (1.0, 1.0), (2.0, 1.0), (3.0, 3.0)];
+        let linear = smooth(&points, Smoothing::None);
+        assert_eq!(linear, points);
+
+        for method in &[Smoothing::CatmullRom, Smoothing::Monotone] {
+            let curve = smooth(&points, *method);
+            assert_eq!(curve.len(), 3 * 8 + 1);
+            for (i, p) in points.iter().enumerate() {
+                assert!((curve[8 * i].0 - p.0).abs() < 1e-9);
+                assert!((curve[8 * i].1 - p.1).abs() < 1e-9);
+            }
+        }
+        // Monotone interpolation does not overshoot the flat segment
+        let curve = smooth(&points, Smoothing::Monotone);
+        assert!(curve[8..16]
+            .iter()
+            .all(|p| p.1 >= 1.0 - 1e-9 && p.1 <= 1.0 + 1e-9));
+        let curve = smooth(&points, Smoothing::CatmullRom);
+        assert!(curve[8..16].iter().any(|p| p.1 < 1.0 - 1e-3));
+
+        let gap = vec![
+            (0.0, 0.0),
+            (1.0, f64::NAN),
+            (2.0, 1.0),
+            (3.0, 2.0),
+            (4.0, 2.0),
+        ];
+        let curve = smooth(&gap, Smoothing::CatmullRom);
+        assert_eq!(curve.len(), 1 + 1 + 2 * 8 + 1);
+        assert!(curve[1].1.is_nan());
+    }
+
+    #[test]
+    fn segments() {
+        use plotters::prelude::*;
+        let mut series = Series::arrow("A", &RED).with_limit(2);
+        for i in 0..3 {
+            let x = i as f64;
+            series.append_segment((x, 0.0), (x, 1.0), None);
+        }
+        let data: Vec<_> = series.data.iter().cloned().collect();
+        assert_eq!(data, vec![(1.0, 0.0), (1.0, 1.0), (2.0, 0.0), (2.0, 1.0)]);
+
+        assert_eq!(
+            arrow_head((0, 0), (10, 0), 5.0),
+            vec![(10, 0), (5, 2), (5, -2)]
+        );
+
+        let (w, h) = (200, 200);
+        let mut buffer = vec![0; 3 * w * h];
+        {
+            let root =
+                BitMapBackend::with_buffer(&mut buffer, (w as u32, h as u32)).into_drawing_area();
+            draw_chart(
+                &root,
+                &ChartConfig::new(),
+                &[series],
+                &[],
+                (0.0, 3.0),
+                (0.0, 1.0),
+                &|_, _, _| {},
+            );
         }
+        let red = buffer.chunks(3).filter(|px| *px == [255, 0, 0]).count();
+        assert!(red > 2 * 50);
     }
 
     #[test]
-    fn scatter_plot() {
+    fn y_units() {
+        let mut config = ChartConfig::new();
+        assert_eq!(config.y_desc((0.0, 2e4)), ("Y".to_string(), 1.0));
+        config.y_unit = "ind".to_string();
+        assert_eq!(config.y_desc((0.0, 2e4)), ("Y [ind]".to_string(), 1.0));
+        config.y_si = true;
+        assert_eq!(config.y_desc((0.0, 2e4)), ("Y [kind]".to_string(), 1e3));
+        assert_eq!(config.y_desc((-3e6, 0.0)), ("Y [Mind]".to_string(), 1e6));
+    }
+
+    #[test]
+    fn adaptive_quality() {
+        use std::time::Duration;
+        let budget = Duration::from_millis(10);
+        let ms = Duration::from_millis;
+        assert_eq!(adapt_quality(0, ms(20), budget), 1);
+        assert_eq!(adapt_quality(6, ms(20), budget), 6);
+        assert_eq!(adapt_quality(2, ms(5), budget), 2);
+        assert_eq!(adapt_quality(2, ms(1), budget), 1);
+        assert_eq!(adapt_quality(0, ms(1), budget), 0);
+
+        assert_eq!(thin(vec![0, 1, 2, 3, 4], 2), vec![0, 2, 4]);
+        assert_eq!(thin(vec![0, 1, 2, 3, 4, 5], 2), vec![0, 2, 4, 5]);
+        assert_eq!(thin(vec![0, 1, 2], 1), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn pick() {
+        use plotters::prelude::*;
+        let mut series = Series::point("A", &RED);
+        series.push((0.0, 0.0));
+        series.push((1.0, 1.0));
+        let hidden = Series::point("B", &BLUE).hidden();
+        let data = vec![series, hidden];
+
+        let (w, h) = (300, 200);
+        let mut buffer = vec![0; 3 * w * h];
+        let coords = {
+            let root =
+                BitMapBackend::with_buffer(&mut buffer, (w as u32, h as u32)).into_drawing_area();
+            let (_, right) = root.split_horizontally(50);
+            draw_chart(
+                &right,
+                &ChartConfig::new(),
+                &data,
+                &[],
+                (0.0, 1.0),
+                (0.0, 1.0),
+                &|_, _, _| {},
+            )
+        };
+        assert!(coords.origin().0 > 50);
+        let (x, y) = coords.to_pixel((1.0, 1.0));
+        let (ox, oy) = coords.origin();
+        assert_eq!(
+            pick_nearest(&data, &coords, (ox + x - 3, oy + y + 2)),
+            Some((0, 1, (1.0, 1.0)))
+        );
+        assert_eq!(pick_nearest(&data, &coords, (0, 0)), None);
+    }
+
+    #[test]
+    fn mesh_style() {
+        use crate::ui::chart::MeshStyle;
+        use plotters::prelude::*;
+        let render = |style: MeshStyle| {
+            let mut config = ChartConfig::new();
+            config.mesh = style;
+            let (w, h) = (300, 200);
+            let mut buffer = vec![0; 3 * w * h];
+            {
+                let root = BitMapBackend::with_buffer(&mut buffer, (w as u32, h as u32))
+                    .into_drawing_area();
+                draw_chart(
+                    &root,
+                    &config,
+                    &[],
+                    &[],
+                    (0.0, 1.0),
+                    (0.0, 1.0),
+                    &|_, _, _| {},
+                );
+            }
+            buffer
+        };
+        let white = |buffer: &[u8]| buffer.chunks(3).filter(|px| *px == [255, 255, 255]).count();
+        let default = render(MeshStyle::new());
+        let plain = render(MeshStyle::new().without_lines());
+        assert!(white(&plain) > white(&default));
+
+        let red = render(
+            MeshStyle::new()
+                .with_axis_color(&RED)
+                .with_bold_lines(&BLUE, 1.0),
+        );
+        assert!(red.chunks(3).any(|px| px == [255, 0, 0]));
+        assert!(red.chunks(3).any(|px| px == [0, 0, 255]));
+    }
+
+    #[test]
+    fn palette_colors() {
+        let series = Series::line_auto("A");
+        assert!(series.auto_color);
+        assert!(!Series::line("B", &RED).auto_color);
+
+        assert_eq!(palette_color(&[], 12).rgb(), ColorPalette::pick(12).rgb());
+        let palette = [RGBColor(1, 2, 3), RGBColor(4, 5, 6)];
+        assert_eq!(palette_color(&palette, 0).rgb(), (1, 2, 3));
+        assert_eq!(palette_color(&palette, 3).rgb(), (4, 5, 6));
+    }
+
+    #[test]
+    fn deadband() {
+        let mut series = Series::line("A", &RED);
+        for (i, y) in [0.0, 0.05, 0.08, 1.0, 1.0, 1.0, f64::NAN]
+            .iter()
+            .enumerate()
+        {
+            series.append_if_changed((i as f64, *y), 0.1, None);
+        }
+        series.append_if_changed((7.0, 2.0), 0.1, None);
+        series.append_if_changed((8.0, 2.0), 0.1, None);
+        let x: Vec<_> = series.data.iter().map(|xy| xy.0).collect();
+        assert_eq!(x, vec![0.0, 2.0, 3.0, 5.0, 6.0, 7.0]);
+        series.flush(None);
+        assert_eq!(series.data.back(), Some(&(8.0, 2.0)));
+        series.flush(None);
+        assert_eq!(series.data.len(), 7);
+    }
+
+    #[test]
+    fn marker_radius() {
+        use plotters::prelude::*;
+        let render = |series: Series, pixel_scale: f64| {
+            let mut series = series;
+            series.push((0.2, 0.2));
+            let mut config = ChartConfig::new();
+            config.pixel_scale = pixel_scale;
+            config.mesh = crate::ui::chart::MeshStyle::new().without_lines();
+            let (w, h) = (200, 200);
+            let mut buffer = vec![0; 3 * w * h];
+            {
+                let root = BitMapBackend::with_buffer(&mut buffer, (w as u32, h as u32))
+                    .into_drawing_area();
+                draw_chart(
+                    &root,
+                    &config,
+                    &[series],
+                    &[],
+                    (0.0, 1.0),
+                    (0.0, 1.0),
+                    &|_, _, _| {},
+                );
+            }
+            buffer
+                .chunks(3)
+                .filter(|px| px[0] > 200 && px[1] < 150)
+                .count()
+        };
+        let small = render(Series::point("A", &RED), 1.0);
+        let large = render(Series::point("A", &RED).with_radius(6), 1.0);
+        let scaled = render(Series::point("A", &RED).with_radius(6), 3.0);
+        assert!(large > small + 100);
+        assert_eq!(scaled, small);
+    }
+
+    #[test]
+    fn x_window() {
+        let data: VecDeque<_> = (0..10).map(|i| (i as f64, 0.0)).collect();
+        assert_eq!(visible_range(&data, (2.5, 5.0)), 2..7);
+        assert_eq!(visible_range(&data, (-5.0, 20.0)), 0..10);
+        assert_eq!(visible_range(&data, (20.0, 30.0)), 9..10);
+        assert_eq!(visible_range(&VecDeque::new(), (0.0, 1.0)), 0..0);
+    }
+
+    #[test]
+    fn batch_push() {
+        let mut series = Series::line("A", &RED).with_limit(3).with_decimation(2);
+        series.extend((0..10).map(|i| (i as f64, 0.0)), Some(100));
+        let x: Vec<_> = series.data.iter().map(|xy| xy.0).collect();
+        assert_eq!(x, vec![4.0, 6.0, 8.0]);
+        series.append((10.0, 0.0), None);
+        assert_eq!(series.data.back(), Some(&(10.0, 0.0)));
+
+        let mut series = Series::line("B", &RED);
+        series.extend(vec![(0.0, 1.0), (1.0, 2.0)], None);
+        assert_eq!(series.data.len(), 2);
+    }
+
+    #[test]
+    fn y_transform() {
+        use plotters::prelude::*;
+        let mut series = Series::line("A", &RED).with_transform(|y| -2.0 * y);
+        series.append((1.0, 3.0), None);
+        let copy = series.clone();
+        assert_eq!(copy.data.back(), Some(&(1.0, 3.0)));
+        assert_eq!(copy.transformed(&(1.0, 3.0)), (1.0, -6.0));
+
+        let (w, h) = (200, 200);
+        let mut buffer = vec![0; 3 * w * h];
+        let data = vec![series];
+        let coords = {
+            let root =
+                BitMapBackend::with_buffer(&mut buffer, (w as u32, h as u32)).into_drawing_area();
+            draw_chart(
+                &root,
+                &ChartConfig::new(),
+                &data,
+                &[],
+                (0.0, 2.0),
+                (-10.0, 0.0),
+                &|_, _, _| {},
+            )
+        };
+        let (x, y) = coords.to_pixel((1.0, -6.0));
+        let (ox, oy) = coords.origin();
+        assert_eq!(
+            pick_nearest(&data, &coords, (ox + x, oy + y)),
+            Some((0, 0, (1.0, -6.0)))
+        );
+    }
+
+    #[test]
+    fn derived() {
+        use crate::ui::chart::{Derivation, DerivedPlot};
+        let mut source: VecDeque<_> = vec![(0.0, 1.0), (1.0, 3.0), (3.0, 4.0)].into();
+        let mut rate = DerivedPlot::new(1, 0, Derivation::Derivative);
+        let mut total = DerivedPlot::new(2, 0, Derivation::Cumulative);
+        assert_eq!(rate.derive(&source), vec![(1.0, 2.0), (3.0, 0.5)]);
+        assert_eq!(
+            total.derive(&source),
+            vec![(0.0, 1.0), (1.0, 4.0), (3.0, 8.0)]
+        );
+        assert!(rate.derive(&source).is_empty());
+
+        source.pop_front();
+        source.push_back((4.0, f64::NAN));
+        source.push_back((5.0, 2.0));
+        assert!(!total.is_reset(&source));
+        assert_eq!(total.derive(&source), vec![(4.0, 8.0), (5.0, 10.0)]);
+        let rates = rate.derive(&source);
+        assert!(rates[0].1.is_nan());
+
+        source.clear();
+        assert!(rate.is_reset(&source));
+        source.push_back((0.0, 1.0));
+        assert!(total.is_reset(&source));
+    }
+
+    #[test]
+    fn push_with_derived() {
+        let mut chart = ChartBuilder::new()
+            .with_dimensions(100, 80)
+            .add_series(Series::line("A", &RED))
+            .add_series(Series::line("Total A", &BLUE))
+            .add_series(Series::line("B", &RED))
+            .build_headless();
+        chart.derive_series(1, 0, Derivation::Cumulative);
+        chart.push_time_series(0.0, &[1.0, 10.0]);
+        chart.push_time_series_batch(&[1.0, 2.0], &[&[2.0, 3.0], &[20.0, 30.0]]);
+        chart.push_time_series_if_changed(3.0, &[4.0, 40.0], 0.0);
+        chart.update();
+        let data = |index: usize| chart.data[index].data.iter().cloned().collect::<Vec<_>>();
+        assert_eq!(
+            data(0),
+            vec![(0.0, 1.0), (1.0, 2.0), (2.0, 3.0), (3.0, 4.0)]
+        );
+        assert_eq!(
+            data(2),
+            vec![(0.0, 10.0), (1.0, 20.0), (2.0, 30.0), (3.0, 40.0)]
+        );
+        assert_eq!(
+            data(1),
+            vec![(0.0, 1.0), (1.0, 3.0), (2.0, 6.0), (3.0, 10.0)]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn push_derived_value() {
+        let mut chart = ChartBuilder::new()
+            .add_series(Series::line("A", &RED))
+            .add_series(Series::line("Total A", &BLUE))
+            .build_headless();
+        chart.derive_series(1, 0, Derivation::Cumulative);
+        chart.push_time_series(0.0, &[1.0, 1.0]);
+    }
+
+    #[test]
+    fn headless() {
+        let mut chart = ChartBuilder::new()
+            .with_dimensions(100, 80)
+            .add_series(Series::line("A", &RED))
+            .build_headless();
+        assert!(chart.window().is_headless());
+        assert!(chart.is_open());
+        chart.push_time_series(0.0, &[1.0]);
+        chart.push_time_series(1.0, &[2.0]);
+        assert!(chart.update_stats().rendered);
+        assert!(chart.window().buffer_u8.iter().any(|v| *v != 0));
+        assert!(!chart.update_stats().rendered);
+    }
+
+    #[test]
+    fn non_finite() {
+        assert_eq!(valid_range((f64::MAX, f64::MIN)), (0.0, 1.0));
+        assert_eq!(valid_range((2.0, 2.0)), (1.8, 2.2));
+        assert_eq!(valid_range((0.0, 0.0)), (-1.0, 1.0));
+        assert_eq!(
+            clamp_infinite((f64::NEG_INFINITY, f64::INFINITY), (0.0, 1.0), (2.0, 3.0)),
+            (0.0, 3.0)
+        );
+        assert!(clamp_infinite((0.5, f64::NAN), (0.0, 1.0), (2.0, 3.0))
+            .1
+            .is_nan());
+
+        for mode in &[NonFinite::Skip, NonFinite::Clamp] {
+            let mut chart = ChartBuilder::new()
+                .with_dimensions(100, 80)
+                .with_non_finite(*mode)
+                .add_series(Series::line("A", &RED))
+                .add_series(Series::point("B", &BLUE))
+                .build_headless();
+            let values = [1.0, f64::INFINITY, 2.0, f64::NAN, f64::NEG_INFINITY, 3.0];
+            for (i, v) in values.iter().enumerate() {
+                chart.push_time_series(i as f64, &[*v, *v]);
+            }
+            assert_eq!(chart.calc_axis_ranges(), ((0.0, 5.0), (1.0, 3.0)));
+            assert!(chart.update_stats().rendered);
+        }
+
+        let mut chart = ChartBuilder::new()
+            .add_series(Series::line("A", &RED))
+            .build_headless();
+        chart.push_time_series(0.0, &[f64::NAN]);
+        assert_eq!(chart.calc_axis_ranges(), ((-1.0, 1.0), (0.0, 1.0)));
+        assert!(chart.update_stats().rendered);
+    }
+
+    #[test]
+    fn density() {
+        let map = || LinearColorMap::new(&[&BLUE, &RED]);
+        let square = Density::histogram(map());
+        let cells = square.bin(
+            vec![(1, 1), (9, 9), (10, 0), (-1, 0), (5, 20)].into_iter(),
+            (20, 20),
+            10.0,
+        );
+        assert_eq!(cells.len(), 2);
+        assert_eq!(cells[&(0, 0)], 2);
+        assert_eq!(
+            square.outline((1, 0), 10.0),
+            vec![(10, 0), (20, 0), (20, 10), (10, 10)]
+        );
+
+        let hex = Density::hexbin(map());
+        for cell in &[(0, 0), (2, -1), (-3, 4)] {
+            let outline = hex.outline(*cell, 10.0);
+            let cx = outline.iter().map(|p| p.0).sum::<i32>() / 6;
+            let cy = outline.iter().map(|p| p.1).sum::<i32>() / 6;
+            assert_eq!(hex.cell((cx, cy), 10.0), *cell);
+            assert_eq!(hex.cell((cx + 3, cy - 3), 10.0), *cell);
+        }
+
+        let mut chart = ChartBuilder::new()
+            .with_dimensions(200, 150)
+            .add_series(
+                Series::point("A", &RED).with_density(Density::hexbin(map()).with_threshold(100)),
+            )
+            .build_headless();
         let mut rng = rand::thread_rng();
+        let data: Vec<_> = (0..1000)
+            .map(|_| (rng.gen::<f64>(), rng.gen::<f64>()))
+            .collect();
+        chart.extend_series(0, &data);
+        assert!(chart.update_stats().rendered);
+    }
+
+    #[test]
+    fn inverted_axes() {
         let mut chart = ChartBuilder::new()
-            .with_title("Scatter")
-            .with_labels("some x", "some y")
-            .with_dimensions(400, 400)
+            .with_dimensions(200, 150)
+            .with_y_inverted()
+            .add_series(Series::line("A", &RED))
+            .build_headless();
+        chart.push_time_series(0.0, &[0.0]);
+        chart.push_time_series(1.0, &[1.0]);
+        chart.update();
+        let coords = chart.coords.clone().unwrap();
+        let (low, high) = (coords.to_pixel((0.0, 0.0)), coords.to_pixel((1.0, 1.0)));
+        assert!(low.0 < high.0 && low.1 < high.1);
+
+        chart.set_inverted(true, false);
+        chart.update();
+        let coords = chart.coords.clone().unwrap();
+        let (low, high) = (coords.to_pixel((0.0, 0.0)), coords.to_pixel((1.0, 1.0)));
+        assert!(low.0 > high.0 && low.1 > high.1);
+    }
+
+    #[test]
+    fn range_queries() {
+        let mut series = Series::line("A", &RED);
+        for x in 0..10 {
+            series.push((x as f64, -x as f64));
+        }
+        let range: Vec<_> = series.range(2.5, 5.0).collect();
+        assert_eq!(range, vec![(3.0, -3.0), (4.0, -4.0), (5.0, -5.0)]);
+        assert_eq!(series.range(-5.0, 0.0).count(), 1);
+        assert_eq!(series.range(20.0, 30.0).count(), 0);
+        assert_eq!(series.range(5.0, 2.0).count(), 0);
+
+        let mut chart = ChartBuilder::new()
+            .with_x_window(3.0)
+            .add_series(series)
+            .build_headless();
+        assert_eq!(chart.visible_ranges(), None);
+        chart.update();
+        let ((x0, x1), _) = chart.visible_ranges().unwrap();
+        assert_eq!(chart.slice_series(0, x0, x1).count(), 4);
+    }
+
+    #[test]
+    fn presets() {
+        let builder = ChartBuilder::preset(Preset::Monitoring).with_fps_skip(20.0);
+        assert_eq!(builder.fps_skip, Some(20.0));
+        assert_eq!(builder.limits.x_window, Some(60.0));
+        assert_eq!(builder.data_limit, Some(100_000));
+        assert!(builder.legend_values && builder.pause_keys);
+
+        let builder = ChartBuilder::preset(Preset::Scatter);
+        assert_eq!(builder.limits.x_window, None);
+        assert_eq!(builder.legend_position, LegendPosition::UpperRight);
+
+        let mut chart = ChartBuilder::preset(Preset::TimeSeries)
+            .add_series(Series::line("A", &RED))
+            .build_headless();
+        assert_eq!(chart.config.legend_position, LegendPosition::UpperLeft);
+        chart.push_time_series(0.0, &[1.0]);
+        chart.set_legend_position(LegendPosition::LowerRight);
+        chart.update();
+    }
+
+    #[test]
+    fn x_range_hook() {
+        let mut chart = ChartBuilder::new()
+            .add_series(Series::line("A", &RED))
+            .with_xlim(Some(0.0), Some(10.0))
+            .build_headless();
+        let ranges = Rc::new(RefCell::new(vec![]));
+        let hooked = ranges.clone();
+        chart.on_x_range_change(move |chart, (x0, x1)| {
+            hooked.borrow_mut().push((x0, x1));
+            chart.replace_series(0, &[(x0, 1.0), (x1, 2.0)]);
+        });
+        chart.update();
+        chart.update();
+        assert_eq!(*ranges.borrow(), vec![(0.0, 10.0)]);
+        assert_eq!(chart.data[0].data.len(), 2);
+
+        chart.set_xlim(Some(5.0), Some(20.0));
+        chart.update();
+        assert_eq!(*ranges.borrow(), vec![(0.0, 10.0), (5.0, 20.0)]);
+        assert_eq!(chart.data[0].data.back(), Some(&(20.0, 2.0)));
+
+        chart.clear_hooks();
+        chart.set_xlim(Some(0.0), Some(1.0));
+        chart.update();
+        assert_eq!(ranges.borrow().len(), 2);
+    }
+
+    #[test]
+    fn minimap() {
+        let mut chart = ChartBuilder::new()
+            .with_dimensions(400, 300)
+            .with_minimap()
+            .with_x_window(10.0)
+            .add_series(Series::line("A", &RED))
+            .build_headless();
+        for t in 0..20 {
+            chart.push_time_series(t as f64, &[t as f64]);
+        }
+        chart.update();
+        let coords = chart.coords.clone().unwrap();
+        let minimap = coords.minimap.clone().unwrap();
+        assert_eq!(minimap.x_range, (0.0, 19.0));
+        let px = minimap.to_pixel((5.0, 10.0));
+        let xy = minimap.to_data(px).unwrap();
+        assert!(
+            (xy.0 - 5.0).abs() < 0.5 && (xy.1 - 10.0).abs() < 0.5,
+            "{:?}",
+            xy
+        );
+        assert!(minimap.to_data((0, 0)).is_none());
+
+        let origin = coords.origin();
+        chart.minimap_jump((px.0 + origin.0, px.1 + origin.1));
+        let (xlim, _) = chart.calc_axis_ranges();
+        assert!(
+            (xlim.0 - 0.0).abs() < 0.5 && (xlim.1 - 10.0).abs() < 0.5,
+            "{:?}",
+            xlim
+        );
+
+        chart.set_xlim(None, None);
+        chart.update();
+        assert!(chart.coords.as_ref().unwrap().minimap.is_none());
+    }
+
+    #[test]
+    fn y_break() {
+        let mut chart = ChartBuilder::new()
+            .with_dimensions(400, 300)
+            .with_y_break((0.0, 10.0), (900.0, 1000.0))
+            .add_series(Series::line("A", &RED))
+            .build_headless();
+        chart.push_time_series(0.0, &[1.0]);
+        chart.push_time_series(1.0, &[950.0]);
+        let (_, ylim) = chart.calc_axis_ranges();
+        assert_eq!(ylim, (0.0, 1000.0));
+        chart.update();
+
+        let coords = chart.coords.clone().unwrap();
+        let (_, height) = coords.size();
+        let (_, y) = coords.to_pixel((0.0, 5.0));
+        assert!((y as f64 - 0.76 * height as f64).abs() <= 2.0);
+        let (_, y) = coords.to_pixel((0.0, 950.0));
+        assert!((y as f64 - 0.24 * height as f64).abs() <= 2.0);
+
+        chart.set_y_break(None);
+        let (_, ylim) = chart.calc_axis_ranges();
+        assert!(ylim.1 < 1000.0);
+    }
+
+    #[test]
+    fn x_tick_labels() {
+        let axis = ChartAxis::new(0.0..1_000_000.0, AxisKind::Linear);
+        let font = ("sans-serif", 12.0).into_font();
+        let mut config = ChartConfig::new();
+        let (count, (width, height)) = x_label_layout(&config, &axis, &font, 200.0);
+        assert!(count < 15);
+        assert!(width > height);
+
+        config.x_label_rotation = 90.0;
+        let (rotated, _) = x_label_layout(&config, &axis, &font, 200.0);
+        assert!(rotated > count);
+
+        assert_eq!(config.x_tick_label(&font, 123456.0, 1.0), "123456");
+        config.max_label_width = Some(25);
+        let label = config.x_tick_label(&font, 123456.0, 1.0);
+        assert!(label.ends_with('…') && label.len() < 8);
+
+        let mut chart = ChartBuilder::new()
+            .with_dimensions(300, 200)
+            .with_x_label_rotation(45.0)
+            .add_series(Series::line("A", &RED))
+            .build_headless();
+        chart.push_time_series(0.0, &[0.0]);
+        chart.push_time_series(1e6, &[1.0]);
+        chart.update();
+        let (_, plot_height) = chart.coords.clone().unwrap().size();
+        chart.set_x_label_rotation(0.0);
+        chart.update();
+        assert!(chart.coords.clone().unwrap().size().1 > plot_height);
+    }
+
+    #[test]
+    fn legend_values() {
+        assert_eq!(format_legend_value(1204.0), "1,204");
+        assert_eq!(format_legend_value(-1234567.6), "-1,234,568");
+        assert_eq!(format_legend_value(0.12345), "0.123");
+        assert_eq!(format_legend_value(-0.0001), "0");
+        assert_eq!(format_legend_value(12.5), "12.5");
+
+        let mut chart = ChartBuilder::new()
+            .with_legend_values()
+            .add_series(Series::line("A", &RED).with_decimation(10))
+            .add_series(Series::line("B", &BLUE).with_legend_format(|n, v| format!("{}={}", n, v)))
+            .build_headless();
+        assert_eq!(chart.data[0].legend_label(true), "A");
+        for t in 0..5 {
+            chart.push_time_series(t as f64, &[1000.0 + t as f64, 0.5]);
+        }
+        assert_eq!(chart.data[0].data.len(), 1);
+        assert_eq!(chart.data[0].latest_value(), Some(1004.0));
+        assert_eq!(chart.data[0].legend_label(true), "A: 1,004");
+        assert_eq!(chart.data[1].legend_label(false), "B=0.5");
+        assert_eq!(chart.data[0].legend_label(false), "A");
+        chart.update();
+    }
+
+    #[test]
+    fn series_metadata() {
+        let mut chart = ChartBuilder::new()
+            .add_series(
+                Series::line("Biomass", &RED)
+                    .with_unit("kg")
+                    .with_meta("source", "survey")
+                    .with_meta("source", "model, v2"),
+            )
+            .add_series(Series::line("B \"1\"", &BLUE))
+            .with_xlim(Some(-1.0), Some(2.0))
+            .with_ylim(Some(0.0), Some(4.0))
+            .build_headless();
+        assert_eq!(chart.data[0].unit(), Some("kg"));
+        assert_eq!(chart.data[0].metadata().len(), 1);
+        assert_eq!(chart.data[0].meta("source"), Some("model, v2"));
+        assert_eq!(chart.data[0].legend_label(false), "Biomass [kg]");
+        chart.push_xy(0, (0.0, 1.5));
+        chart.push_xy(0, (1.0, 2.5));
+        chart.push_xy(1, (0.0, 3.0));
+        assert_eq!(chart.data[0].legend_label(true), "Biomass: 2.5 kg");
+
+        let mut csv = Vec::new();
+        chart.write_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "# Biomass: source = model, v2\n\
+             Biomass x,Biomass [kg],\"B \"\"1\"\" x\",\"B \"\"1\"\"\"\n\
+             0,1.5,0,3\n\
+             1,2.5,,\n"
+        );
+
+        let summary = chart.summary();
+        assert_eq!(summary.series[0].unit.as_deref(), Some("kg"));
+        assert!(summary.to_markdown().contains("| Biomass [kg] | 2 |"));
+
+        chart.update();
+        let coords = chart.coords.clone().unwrap();
+        let (x, y) = coords.to_pixel((1.0, 2.5));
+        let origin = coords.origin();
+        assert_eq!(
+            chart.tooltip((x + origin.0, y + origin.1)).as_deref(),
+            Some("Biomass: (1, 2.5 kg)")
+        );
+    }
+
+    #[test]
+    fn line_simplification() {
+        let identity = |xy: &(f64, f64)| *xy;
+        let noisy: Vec<_> = (0..100)
+            .map(|i| (i as f64, if i % 2 == 0 { 0.2 } else { -0.2 }))
+            .collect();
+        let line = simplify(&noisy, identity, 0.5);
+        assert_eq!(line, vec![noisy[0], noisy[99]]);
+
+        let peak = vec![(0.0, 0.0), (1.0, 2.4), (2.0, 5.0), (3.0, 2.6), (4.0, 0.0)];
+        let line = simplify(&peak, identity, 0.5);
+        assert_eq!(line, vec![(0.0, 0.0), (2.0, 5.0), (4.0, 0.0)]);
+        assert_eq!(simplify(&peak, identity, 0.0), peak);
+        // Tolerance applies in pixels
+        let line = simplify(&peak, |xy| (xy.0 * 0.01, xy.1 * 0.01), 0.5);
+        assert_eq!(line.len(), 2);
+
+        let mut chart = ChartBuilder::new()
+            .with_line_simplification(0.5)
+            .add_series(Series::line("A", &RED))
+            .build_headless();
+        for (x, y) in noisy {
+            chart.push_xy(0, (x, y));
+        }
+        chart.update();
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn ndarray() {
+        use ndarray::{arr1, arr2};
+
+        let mut chart = ChartBuilder::new()
+            .add_series(Series::line("A", &RED))
+            .add_series(Series::line("B", &BLUE))
+            .build_headless();
+        chart.push_time_series_array(0.0, arr1(&[1.0, 2.0]).view());
+        chart.push_time_series_arrays(
+            arr1(&[1.0, 2.0]).view(),
+            arr2(&[[3.0, 4.0], [5.0, 6.0]]).view(),
+        );
+        chart.extend_series_arrays(0, arr1(&[3.0]).view(), arr1(&[7.0]).view());
+        let a: Vec<_> = chart.data[0].data.iter().cloned().collect();
+        assert_eq!(a, vec![(0.0, 1.0), (1.0, 3.0), (2.0, 4.0), (3.0, 7.0)]);
+        let b: Vec<_> = chart.data[1].data.iter().map(|xy| xy.1).collect();
+        assert_eq!(b, vec![2.0, 5.0, 6.0]);
+    }
+
+    #[cfg(feature = "profiling")]
+    #[test]
+    fn profiling() {
+        use crate::ui::profile::Stage;
+        use std::time::Duration;
+
+        let mut chart = ChartBuilder::new()
+            .with_dimensions(400, 300)
+            .with_data_limit(10)
+            .with_profile_overlay()
+            .add_series(Series::line("A", &RED))
+            .build_headless();
+        for t in 0..100 {
+            chart.push_time_series(t as f64, &[t as f64]);
+        }
+        chart.update();
+        chart.push_time_series(100.0, &[0.0]);
+        chart.update();
+
+        let profile = chart.profile();
+        assert_eq!(profile.count(Stage::SeriesDraw), 2);
+        assert_eq!(profile.count(Stage::DataTrim), 2);
+        assert!(profile.last(Stage::MeshDraw) > Duration::default());
+        assert!(chart.config.profile.is_some());
+
+        chart.reset_profile();
+        assert_eq!(chart.profile().count(Stage::Present), 0);
+    }
+
+    #[test]
+    fn segment_colors() {
+        let map = || LinearColorMap::new(&[&BLUE, &RED]);
+        let mut chart = ChartBuilder::new()
+            .with_dimensions(200, 100)
+            .add_series(Series::line("A", &GREEN).with_color_map(map()))
+            .with_data_limit(3)
+            .build_headless();
+        chart.push_xyv(0, (0.0, 0.0), 0.0);
+        chart.push_xy(0, (1.0, 1.0));
+        chart.push_xyv(0, (2.0, 0.0), 1.0);
+        chart.push_xyv(0, (3.0, 1.0), 2.0);
+        let colors = chart.data[0].colors.as_ref().unwrap();
+        let values: Vec<_> = colors.values.iter().map(|v| v.to_string()).collect();
+        assert_eq!(values, vec!["NaN", "1", "2"]);
+        assert_eq!(colors.value_range(), (1.0, 2.0));
+
+        chart.update();
+        let pixels: Vec<_> = chart
+            .window()
+            .buffer_u8
+            .chunks(3)
+            .map(|p| p.to_vec())
+            .collect();
+        assert!(pixels.contains(&vec![0, 255, 0]));
+        assert!(pixels.contains(&vec![0, 0, 255]));
+        assert!(pixels.contains(&vec![255, 0, 0]));
+
+        let series = Series::line("B", &GREEN)
+            .with_color_map(map())
+            .with_color_range(0.0, 10.0);
+        assert_eq!(series.colors.unwrap().value_range(), (0.0, 10.0));
+    }
+
+    #[test]
+    fn range_animation() {
+        let mut chart = ChartBuilder::new()
+            .with_dimensions(200, 100)
+            .add_series(Series::line("A", &RED))
             .with_xlim(Some(0.0), Some(1.0))
             .with_ylim(Some(0.0), Some(1.0))
-            .with_fps_limit(30.0)
-            .add_series(Series::point("A", &RED))
-            .build();
+            .with_range_animation(4)
+            .build_headless();
+        chart.update();
+        assert_eq!(chart.drawn_ranges, Some(((0.0, 1.0), (0.0, 1.0))));
 
-        for _i in 1..5 {
-            let points: Vec<_> = (0..100)
-                .map(|_| (rng.gen_range(0.0, 1.0), rng.gen_range(0.0, 1.0)))
-                .collect();
-            chart.replace_series(0, &points);
+        chart.set_ylim(Some(0.0), Some(9.0));
+        chart.update();
+        let (xlim, ylim) = chart.drawn_ranges.unwrap();
+        assert_eq!(xlim, (0.0, 1.0));
+        assert!(ylim.1 > 1.0 && ylim.1 < 9.0);
+        for _ in 0..3 {
+            chart.update();
+        }
+        assert_eq!(chart.drawn_ranges, Some(((0.0, 1.0), (0.0, 9.0))));
+        assert!(chart.range_animation.is_none());
+
+        chart.set_range_animation(0);
+        chart.set_xlim(Some(0.0), Some(5.0));
+        chart.update();
+        assert_eq!(chart.drawn_ranges, Some(((0.0, 5.0), (0.0, 9.0))));
+    }
+
+    #[test]
+    fn context_menu_actions() {
+        let mut chart = ChartBuilder::new()
+            .add_series(Series::line("A", &RED))
+            .with_xlim(Some(0.0), Some(10.0))
+            .with_context_menu()
+            .build_headless();
+        chart.add_menu_action("Custom", |chart| chart.set_y_log(true));
+        assert_eq!(chart.menu_actions.len(), 1);
+        assert!(!chart.context_menu.as_ref().unwrap().is_open());
+        for t in 0..5 {
+            chart.push_time_series(t as f64, &[t as f64]);
+        }
+        chart.clear_data();
+        assert!(chart.data[0].data.is_empty());
+
+        chart.set_xlim(Some(5.0), Some(6.0));
+        chart.reset_zoom();
+        assert_eq!(chart.limits.x_min, Some(0.0));
+        assert_eq!(chart.limits.x_max, Some(10.0));
+    }
+
+    #[test]
+    fn retention() {
+        let dir = std::env::temp_dir().join(format!("easy_graph_retention_{}", std::process::id()));
+        let mut chart = ChartBuilder::new()
+            .add_series(Series::line("A b", &RED))
+            .with_data_limit(3)
+            .with_retention(dir.to_str().unwrap())
+            .build_headless();
+        for t in 0..10 {
+            chart.push_time_series(t as f64, &[(t * 10) as f64]);
+        }
+        assert_eq!(chart.data[0].data.len(), 3);
+        assert_eq!(chart.retained_len(0), 7);
+        assert_eq!(chart.retention_path(0), Some(dir.join("0_A_b.bin")));
+        assert_eq!(
+            chart.retained(0, 1..3).unwrap(),
+            vec![(1.0, 10.0), (2.0, 20.0)]
+        );
+        assert_eq!(
+            chart.retained_between(0, 5.0, 8.0).unwrap(),
+            vec![(5.0, 50.0), (6.0, 60.0)]
+        );
+        let full = chart.full_data(0).unwrap();
+        assert_eq!(full.len(), 10);
+        assert_eq!(full[9], (9.0, 90.0));
+
+        let copy = chart.data[0].clone();
+        assert!(copy.retention.is_none());
+
+        // Series created by records
+        chart.push_records((0..5).map(|t| ("rec", t as f64, t as f64)));
+        assert_eq!(chart.retention_path(1), Some(dir.join("1_rec.bin")));
+        assert_eq!(
+            chart.retained(1, 0..2).unwrap(),
+            vec![(0.0, 0.0), (1.0, 1.0)]
+        );
+        assert_eq!(chart.data[1].data.len(), 3);
+        chart.remove_series(1);
+
+        chart.add_series(Series::line("C", &BLUE));
+        assert_eq!(chart.retention_path(1), Some(dir.join("2_C.bin")));
+        assert!(chart.take_error().is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+        chart.add_series(Series::line("D", &BLUE));
+        assert!(chart.retention_path(2).is_none());
+        assert!(chart.take_error().is_some());
+        assert!(chart.take_error().is_none());
+
+        chart.set_retention(None).unwrap();
+        assert_eq!(chart.retained_len(0), 0);
+    }
+
+    #[test]
+    fn contact_sheet() {
+        let mut chart = ChartBuilder::new()
+            .with_dimensions(200, 100)
+            .add_series(Series::line("A", &RED))
+            .build_headless();
+        chart.snapshot_every(2);
+        chart.set_snapshot_width(50);
+        for t in 0..7 {
+            chart.push_time_series(t as f64, &[t as f64]);
             chart.update();
         }
+        assert_eq!(chart.num_snapshots(), 3);
+        let images = &chart.snapshots.as_ref().unwrap().images;
+        assert_eq!(images[0].dimensions(), (50, 25));
+
+        let sheet = super::contact_sheet(images, 2).unwrap();
+        assert_eq!(sheet.dimensions(), (2 * 54 + 4, 2 * 29 + 4));
+        assert!(super::contact_sheet(&[], 2).is_none());
+
+        chart.clear_snapshots();
+        let path = std::env::temp_dir().join("easy_graph_contact_sheet.png");
+        assert!(chart.save_contact_sheet(&path, 2).is_err());
+    }
+
+    #[test]
+    fn image_metadata() {
+        let mut chart = ChartBuilder::new()
+            .with_title("Run 1")
+            .with_dimensions(120, 80)
+            .add_series(Series::line("A", &RED).with_unit("m"))
+            .add_ensemble(EnsembleSeries::new("B", &BLUE))
+            .with_xlim(Some(0.0), Some(10.0))
+            .with_screenshot_metadata(MetadataFormat::Sidecar)
+            .build_headless();
+        chart.push_xy(0, (1.0, 2.0));
+        chart.push_ensemble(0, 1.0, &[1.0, 2.0]);
+        chart.push_ensemble(0, 2.0, &[1.0, 2.0]);
+        let metadata = chart.image_metadata();
+        assert_eq!(metadata.title, "Run 1");
+        assert_eq!(metadata.x_range, None);
+        assert_eq!(metadata.series[0].unit.as_deref(), Some("m"));
+        assert_eq!(
+            metadata.series.iter().map(|s| s.count).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+
+        chart.update();
+        assert_eq!(chart.image_metadata().x_range, Some((0.0, 10.0)));
+
+        let dir = std::env::temp_dir().join("easy_graph_chart_metadata_test");
+        chart.window().set_screenshot_dir(&dir.to_string_lossy());
+        let path = chart.window().save_screenshot().unwrap();
+        let json = std::fs::read_to_string(sidecar_path(&path)).unwrap();
+        assert!(json.contains(r#""x_range":[0,10]"#), "{}", json);
+        assert!(read_png_text(&path).unwrap().is_empty());
+
+        let path = dir.join("chart.png");
+        chart.save_image(&path, MetadataFormat::PngText).unwrap();
+        let text = read_png_text(&path).unwrap();
+        assert!(text[2].1.contains(r#""name":"B","unit":null,"count":2"#));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn mesh_cache() {
+        use plotters::prelude::*;
+        let (w, h) = (240, 160);
+        let draw_uncached = |config: &ChartConfig, data: &[Series]| {
+            let mut buffer = vec![0; 3 * w * h];
+            {
+                let root = BitMapBackend::with_buffer(&mut buffer, (w as u32, h as u32))
+                    .into_drawing_area();
+                draw_chart(
+                    &root,
+                    config,
+                    data,
+                    &[],
+                    (0.0, 10.0),
+                    (0.0, 1.0),
+                    &|_, _, _| {},
+                );
+            }
+            buffer
+        };
+        let mut cache = MeshCache::new();
+        let mut draw_cached = |config: &ChartConfig, data: &[Series]| {
+            let mut buffer = vec![0; 3 * w * h];
+            cache.draw_chart(
+                &mut buffer,
+                (w as u32, h as u32),
+                config,
+                data,
+                &[],
+                (0.0, 10.0),
+                (0.0, 1.0),
+                &|_, _, _| {},
+            );
+            buffer
+        };
+
+        let mut config = ChartConfig::new();
+        let mut series = Series::line("A", &RED);
+        series.data.push_back((0.0, 0.0));
+        series.data.push_back((5.0, 1.0));
+        let first = draw_cached(&config, &[series.clone()]);
+        assert!(first == draw_uncached(&config, &[series.clone()]));
+
+        // Background is reused, series are redrawn
+        series.data.push_back((10.0, 0.0));
+        let second = draw_cached(&config, &[series.clone()]);
+        assert!(second != first);
+        assert!(second == draw_uncached(&config, &[series.clone()]));
+
+        config.x_label = "Time".to_string();
+        let relabeled = draw_cached(&config, &[series.clone()]);
+        assert!(relabeled != second);
+        assert!(relabeled == draw_uncached(&config, &[series]));
+    }
+
+    #[test]
+    fn alerts() {
+        assert!(Threshold::Above(1.0).is_crossed(1.5));
+        assert!(!Threshold::Below(1.0).is_crossed(f64::NAN));
+        assert!(Threshold::Outside(0.0, 1.0).is_crossed(-0.5));
+        assert!(!Threshold::Outside(0.0, 1.0).is_crossed(0.5));
+
+        let mut chart = ChartBuilder::new()
+            .with_dimensions(200, 150)
+            .add_series(Series::line("A", &RED))
+            .add_series(Series::line("B", &BLUE))
+            .with_xlim(Some(0.0), Some(10.0))
+            .with_ylim(Some(0.0), Some(2.0))
+            .build_headless();
+        let values = Rc::new(RefCell::new(vec![]));
+        let received = values.clone();
+        chart.add_alert(1, Threshold::Above(1.0), move |chart, value| {
+            received.borrow_mut().push(value);
+            chart.set_title("Alert");
+        });
+        chart.push_time_series(0.0, &[0.5, 0.5]);
+        chart.update();
+        let background = chart.window().buffer_u8.clone();
+        assert!(!chart.is_alert_active(0));
+
+        chart.push_time_series(1.0, &[0.5, 1.5]);
+        chart.push_time_series(2.0, &[0.5, 1.6]);
+        chart.update();
+        assert!(chart.is_alert_active(0));
+        assert_eq!(*values.borrow(), vec![1.6]);
+        assert_eq!(chart.window().title(), "Alert");
+        // Tinted plotting area
+        let (x, y) = chart.coords.clone().unwrap().origin();
+        let px = 3 * ((y + 5) as usize * 200 + (x + 5) as usize);
+        assert_eq!(background[px], background[px + 2]);
+        assert!(chart.window().buffer_u8[px + 2] > chart.window().buffer_u8[px]);
+
+        // Re-armed when no longer crossed
+        chart.push_time_series(3.0, &[0.5, 1.7]);
+        chart.update();
+        chart.push_time_series(4.0, &[0.5, 0.5]);
+        chart.update();
+        assert!(!chart.is_alert_active(0));
+        chart.push_time_series(5.0, &[0.5, 1.2]);
+        chart.update();
+        assert_eq!(*values.borrow(), vec![1.6, 1.2]);
+
+        chart.remove_series(0);
+        assert!(chart.is_alert_active(0));
+        chart.clear_alerts();
+        assert_eq!(chart.num_alerts(), 0);
+    }
+
+    #[test]
+    fn series_bindings() {
+        struct State {
+            t: f64,
+            a: f64,
+            b: usize,
+        }
+        let mut chart = ChartBuilder::new()
+            .add_series(Series::line("A", &RED))
+            .add_series(Series::line("B", &BLUE))
+            .add_series(Series::line("C", &GREEN))
+            .build_headless();
+        chart.bind_series(0, |s: &State| (s.t, s.a));
+        chart.bind_series(1, |s: &State| (s.t, s.b as f64));
+        chart.bind_series(2, |x: &f64| (*x, x * x));
+
+        let mut state = State {
+            t: 0.0,
+            a: 1.5,
+            b: 3,
+        };
+        chart.update_from(&state);
+        state.t = 1.0;
+        assert_eq!(chart.push_from(&state), 2);
+        assert_eq!(chart.push_from(&2.0_f64), 1);
+        assert_eq!(chart.data[0].data, vec![(0.0, 1.5), (1.0, 1.5)]);
+        assert_eq!(chart.data[1].data, vec![(0.0, 3.0), (1.0, 3.0)]);
+        assert_eq!(chart.data[2].data, vec![(2.0, 4.0)]);
+
+        chart.remove_series(0);
+        assert_eq!(chart.num_bindings(), 2);
+        assert_eq!(chart.push_from(&state), 1);
+        assert_eq!(chart.data[0].data.len(), 3);
+        chart.clear_bindings();
+        assert_eq!(chart.push_from(&state), 0);
+    }
+
+    #[test]
+    fn x_axis_types() {
+        let font = ("sans-serif", 12.0).into_font();
+        let mut config = ChartConfig::new();
+        config.x_axis = XAxisType::Integer;
+        assert_eq!(config.x_tick_label(&font, 3.0000001, 10.0), "3");
+        assert_eq!(config.x_value_label(-2.0), "-2");
+        config.x_axis = XAxisType::Categories(vec!["A".to_string(), "B".to_string()]);
+        assert_eq!(config.x_tick_label(&font, 1.0, 10.0), "B");
+        assert_eq!(config.x_tick_label(&font, 2.0, 10.0), "");
+        assert_eq!(config.x_tick_label(&font, 0.5, 10.0), "");
+        assert_eq!(config.x_value_label(0.0), "A");
+
+        let mut chart = ChartBuilder::new()
+            .with_dimensions(300, 200)
+            .with_x_categories(&["Wolves", "Sheep", "Grass"])
+            .add_series(Series::point("Count", &RED))
+            .build_headless();
+        chart.push_xy(0, (0.0, 12.0));
+        chart.push_xy(0, (2.0, 1800.0));
+        let (xlim, _) = chart.calc_axis_ranges();
+        assert_eq!(xlim, (-0.5, 2.5));
+        chart.update();
+        let coords = chart.coords.clone().unwrap();
+        let (x, y) = coords.to_pixel((2.0, 1800.0));
+        let px = (x + coords.origin().0, y + coords.origin().1);
+        assert_eq!(chart.tooltip(px).unwrap(), "Count: (Grass, 1,800)");
+
+        chart.set_x_axis_type(XAxisType::Integer);
+        chart.update();
+        assert_eq!(chart.tooltip(px).unwrap(), "Count: (2, 1,800)");
     }
 }
@@ -0,0 +1,260 @@
+//!
+//! Paces simulations in real time, independent of rendering.
+//!
+//! A [`SimClock`](struct.SimClock.html) runs a simulation at a target rate of ticks per second, or as fast as possible,
+//! and tells when a frame is due. Windows should not use an FPS limit when driven by a clock,
+//! as their blocking updates would throttle the simulation as well. See [`SimClock::with_frame_rate()`](struct.SimClock.html#method.with_frame_rate).
+//!
+//! # Example
+//! ```no_run
+//! use easy_graph::ui::clock::SimClock;
+//! use easy_graph::ui::window::WindowBuilder;
+//! use easy_graph::color::style::WHITE;
+//! use easy_graph::ui::drawing::IntoDrawingArea;
+//!
+//! let mut win = WindowBuilder::new().build();
+//! let mut clock = SimClock::new(Some(100.0)).with_frame_rate(30.0);
+//!
+//! while win.is_open() {
+//!     let tick = clock.tick();
+//!     // ... advance the simulation
+//!     clock.handle_keys(&mut win); // Page up/down to speed up or slow down
+//!     if clock.frame_due() {
+//!         win.draw(|b| {
+//!             b.into_drawing_area().fill(&WHITE).unwrap();
+//!         });
+//!     }
+//! }
+//! ```
+//!
+
+use crate::ui::window::BufferWindow;
+use minifb::{Key, KeyRepeat};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Lag behind schedule after which the clock gives up catching up, in seconds.
+const MAX_LAG: f64 = 0.25;
+/// Interval for measuring the actual tick rate, in seconds.
+const MEASURE_INTERVAL: f64 = 1.0;
+
+///
+/// Maps simulation ticks to wall-clock time, for a target rate of ticks per second.
+///
+pub struct SimClock {
+    rate: Option<f64>,
+    ticks: u64,
+    base: (Instant, u64),
+    frame_interval: Option<Duration>,
+    last_frame: Option<Instant>,
+    measure: (Instant, u64),
+    actual_rate: f64,
+    keys: Option<(Key, Key)>,
+}
+
+impl SimClock {
+    /// Creates a clock with the given rate in ticks per second. `None`, or a rate that is not positive and finite,
+    /// runs as fast as possible.
+    pub fn new(rate: Option<f64>) -> Self {
+        let now = Instant::now();
+        SimClock {
+            rate: rate.filter(|r| is_valid_rate(*r)),
+            ticks: 0,
+            base: (now, 0),
+            frame_interval: None,
+            last_frame: None,
+            measure: (now, 0),
+            actual_rate: 0.0,
+            keys: Some((Key::PageUp, Key::PageDown)),
+        }
+    }
+    /// Sets the frame rate for [`frame_due()`](#method.frame_due). Default: a frame after every tick.
+    ///
+    /// The clock's frame rate replaces the FPS limit of the window drawn on due frames. Build the window without
+    /// [`with_fps_limit()`](../window/struct.WindowBuilder.html#method.with_fps_limit), as it blocks in each update
+    /// and throttles the simulation. An FPS skip ([`with_fps_skip()`](../window/struct.WindowBuilder.html#method.with_fps_skip))
+    /// does not block, but drops due frames if it is lower than the clock's frame rate.
+    ///
+    /// # Panics
+    /// Panics if `fps` is not positive and finite.
+    pub fn with_frame_rate(mut self, fps: f64) -> Self {
+        assert!(
+            is_valid_rate(fps),
+            "Frame rate must be positive and finite, got {}",
+            fps
+        );
+        self.frame_interval = Some(Duration::from_secs_f64(1.0 / fps));
+        self
+    }
+    /// Sets the hotkeys for speeding up and slowing down, or `None` to disable. Default: `PageUp` and `PageDown`.
+    pub fn with_speed_keys(mut self, keys: Option<(Key, Key)>) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    /// Advances the clock by one tick, and waits until the tick's target time if ahead of schedule.
+    /// Returns the number of the tick, starting at 0.
+    ///
+    /// If the simulation can't keep up with the target rate, the schedule is shifted instead of catching up later.
+    pub fn tick(&mut self) -> u64 {
+        let (tick, wait) = self.advance(Instant::now());
+        if let Some(wait) = wait {
+            thread::sleep(wait);
+        }
+        self.measure_rate(Instant::now());
+        tick
+    }
+
+    /// Advances the clock by one tick at the given time. Returns the number of the tick,
+    /// and the time to wait until the tick's target time if ahead of schedule.
+    fn advance(&mut self, now: Instant) -> (u64, Option<Duration>) {
+        let tick = self.ticks;
+        let mut wait = None;
+        if let Some(rate) = self.rate {
+            let target = (tick - self.base.1) as f64 / rate;
+            let elapsed = now.saturating_duration_since(self.base.0).as_secs_f64();
+            if target > elapsed {
+                wait = Some(Duration::from_secs_f64(target - elapsed));
+            } else if elapsed - target > MAX_LAG {
+                self.base = (now, tick);
+            }
+        }
+        self.ticks += 1;
+        (tick, wait)
+    }
+
+    /// Updates the measured tick rate, if the measurement interval has passed at the given time.
+    fn measure_rate(&mut self, now: Instant) {
+        let measured = now.saturating_duration_since(self.measure.0).as_secs_f64();
+        if measured >= MEASURE_INTERVAL {
+            self.actual_rate = (self.ticks - self.measure.1) as f64 / measured;
+            self.measure = (now, self.ticks);
+        }
+    }
+
+    /// Returns if a frame should be rendered, according to the frame rate. Counts as a rendered frame if it returns `true`.
+    pub fn frame_due(&mut self) -> bool {
+        self.frame_due_at(Instant::now())
+    }
+
+    /// Returns if a frame should be rendered at the given time, see [`frame_due()`](#method.frame_due).
+    fn frame_due_at(&mut self, now: Instant) -> bool {
+        let due = match (self.frame_interval, self.last_frame) {
+            (Some(interval), Some(last)) => now.saturating_duration_since(last) >= interval,
+            _ => true,
+        };
+        if due {
+            self.last_frame = Some(now);
+        }
+        due
+    }
+
+    /// The number of ticks so far.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+    /// The target rate in ticks per second, or `None` if running as fast as possible.
+    pub fn rate(&self) -> Option<f64> {
+        self.rate
+    }
+    /// The measured rate in ticks per second, updated every second.
+    pub fn actual_rate(&self) -> f64 {
+        self.actual_rate
+    }
+    /// Sets the target rate in ticks per second. `None`, or a rate that is not positive and finite, runs as fast as possible.
+    pub fn set_rate(&mut self, rate: Option<f64>) {
+        self.rate = rate.filter(|r| is_valid_rate(*r));
+        self.base = (Instant::now(), self.ticks);
+    }
+    /// Doubles the target rate. Has no effect when running as fast as possible.
+    pub fn speed_up(&mut self) {
+        if let Some(rate) = self.rate {
+            self.set_rate(Some(rate * 2.0));
+        }
+    }
+    /// Halves the target rate. When running as fast as possible, halves the measured rate.
+    pub fn slow_down(&mut self) {
+        match self.rate {
+            Some(rate) => self.set_rate(Some(rate / 2.0)),
+            None if self.actual_rate > 0.0 => self.set_rate(Some(self.actual_rate / 2.0)),
+            None => {}
+        }
+    }
+
+    /// Speeds up or slows down when the hotkeys are pressed in the window.
+    pub fn handle_keys(&mut self, window: &mut BufferWindow) {
+        if let Some((faster, slower)) = self.keys {
+            if window.is_key_pressed(faster, KeyRepeat::No) {
+                self.speed_up();
+            }
+            if window.is_key_pressed(slower, KeyRepeat::No) {
+                self.slow_down();
+            }
+        }
+    }
+}
+
+/// Returns if a rate is positive and finite.
+fn is_valid_rate(rate: f64) -> bool {
+    rate.is_finite() && rate > 0.0
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::clock::SimClock;
+    use std::time::Duration;
+
+    #[test]
+    fn pacing() {
+        let mut clock = SimClock::new(Some(200.0));
+        let start = clock.base.0;
+        let ms = |ms: u64| start + Duration::from_millis(ms);
+        let wait_ms = |wait: Option<Duration>| wait.map(|w| (w.as_secs_f64() * 1000.0).round());
+
+        let (tick, wait) = clock.advance(ms(0));
+        assert_eq!((tick, wait), (0, None));
+        let (tick, wait) = clock.advance(ms(2));
+        assert_eq!((tick, wait_ms(wait)), (1, Some(3.0)));
+        let (tick, wait) = clock.advance(ms(10));
+        assert_eq!((tick, wait), (2, None));
+        // Lagging behind by more than MAX_LAG shifts the schedule
+        clock.advance(ms(1000));
+        let (tick, wait) = clock.advance(ms(1000));
+        assert_eq!((tick, wait_ms(wait)), (4, Some(5.0)));
+
+        clock.measure_rate(ms(1000));
+        assert_eq!(clock.actual_rate(), 5.0);
+
+        clock.speed_up();
+        assert_eq!(clock.rate(), Some(400.0));
+        clock.slow_down();
+        clock.slow_down();
+        assert_eq!(clock.rate(), Some(100.0));
+        clock.set_rate(Some(f64::INFINITY));
+        assert_eq!(clock.rate(), None);
+        clock.slow_down();
+        assert_eq!(clock.rate(), Some(2.5));
+    }
+
+    #[test]
+    fn frame_rate() {
+        let mut clock = SimClock::new(None).with_frame_rate(10.0);
+        let start = clock.base.0;
+        let ms = |ms: u64| start + Duration::from_millis(ms);
+        let due: Vec<_> = [0, 50, 99, 100, 150, 210]
+            .iter()
+            .map(|t| clock.frame_due_at(ms(*t)))
+            .collect();
+        assert_eq!(due, vec![true, false, false, true, false, true]);
+        assert_eq!(clock.advance(ms(0)), (0, None));
+        clock.speed_up();
+        assert_eq!(clock.rate(), None);
+        assert_eq!(SimClock::new(Some(-1.0)).rate(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_frame_rate() {
+        SimClock::new(None).with_frame_rate(0.0);
+    }
+}
@@ -0,0 +1,128 @@
+//!
+//! Embeds charts into [egui](https://github.com/emilk/egui) applications, enabled by feature `egui`.
+//!
+//! An [`EguiChart`](struct.EguiChart.html) renders a headless [`Chart`](../chart/struct.Chart.html) into an egui texture,
+//! and shows it as a widget, e.g. in a panel of an existing application.
+//!
+//! # Example
+//! ```no_run
+//! use easy_graph::ui::chart::{ChartBuilder, Series};
+//! use easy_graph::ui::embed::EguiChart;
+//! use easy_graph::color::style::RED;
+//!
+//! let chart = ChartBuilder::new()
+//!     .with_dimensions(400, 300)
+//!     .add_series(Series::line("Population", &RED))
+//!     .build_headless();
+//! let mut chart = EguiChart::new(chart);
+//!
+//! // In the egui application's update function
+//! # let ctx = egui::Context::default();
+//! egui::CentralPanel::default().show(&ctx, |ui| {
+//!     chart.chart_mut().push_time_series(0.0, &[100.0]);
+//!     chart.show(ui);
+//! });
+//! ```
+//!
+
+use crate::ui::chart::Chart;
+use egui::load::SizedTexture;
+use egui::{ColorImage, Image, Response, Sense, TextureHandle, TextureOptions, Ui, Vec2};
+
+///
+/// Shows a [`Chart`](../chart/struct.Chart.html) as an egui widget.
+///
+/// The chart should be built with [`ChartBuilder::build_headless()`](../chart/struct.ChartBuilder.html#method.build_headless),
+/// to prevent opening a separate window. Input like hotkeys is not forwarded to the chart,
+/// but hovering a data point shows its series and coordinates, see [`Chart::tooltip()`](../chart/struct.Chart.html#method.tooltip).
+///
+pub struct EguiChart {
+    chart: Chart,
+    texture: Option<TextureHandle>,
+}
+
+impl EguiChart {
+    /// Wraps a chart.
+    pub fn new(chart: Chart) -> Self {
+        EguiChart {
+            chart,
+            texture: None,
+        }
+    }
+    /// The wrapped chart.
+    pub fn chart(&self) -> &Chart {
+        &self.chart
+    }
+    /// The wrapped chart, e.g. for pushing data.
+    pub fn chart_mut(&mut self) -> &mut Chart {
+        &mut self.chart
+    }
+    /// Unwraps the chart.
+    pub fn into_chart(self) -> Chart {
+        self.chart
+    }
+
+    /// Updates the chart and shows it, scaled down to the available width if necessary.
+    ///
+    /// The texture is only uploaded when the chart rendered a new frame.
+    pub fn show(&mut self, ui: &mut Ui) -> Response {
+        let stats = self.chart.update_stats();
+        let window = self.chart.window();
+        let (width, height) = window.dimensions();
+        if stats.rendered || self.texture.is_none() {
+            let image = ColorImage::from_rgb([width, height], &window.buffer_u8);
+            match &mut self.texture {
+                Some(texture) => texture.set(image, TextureOptions::LINEAR),
+                None => {
+                    self.texture = Some(ui.ctx().load_texture(
+                        "easy_graph_chart",
+                        image,
+                        TextureOptions::LINEAR,
+                    ))
+                }
+            }
+        }
+        let texture = self.texture.as_ref().expect("Texture was just loaded.");
+
+        let scale = (ui.available_width() / width as f32).clamp(0.0, 1.0);
+        let size = Vec2::new(width as f32, height as f32) * scale;
+        let image = Image::from_texture(SizedTexture::new(texture.id(), size));
+        let response = ui.add(image.sense(Sense::hover()));
+
+        let hovered = response.hover_pos().and_then(|pos| {
+            let px = (pos - response.rect.min) / scale;
+            self.chart.tooltip((px.x as i32, px.y as i32))
+        });
+        match hovered {
+            Some(text) => response.on_hover_text(text),
+            None => response,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::chart::{ChartBuilder, Series};
+    use crate::ui::embed::EguiChart;
+    use plotters::style::RED;
+
+    #[test]
+    fn egui_chart() {
+        let chart = ChartBuilder::new()
+            .with_dimensions(200, 100)
+            .add_series(Series::line("A", &RED))
+            .build_headless();
+        let mut chart = EguiChart::new(chart);
+        chart.chart_mut().push_time_series(0.0, &[1.0]);
+        chart.chart_mut().push_time_series(1.0, &[2.0]);
+
+        let ctx = egui::Context::default();
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                chart.show(ui);
+            });
+        });
+        let texture = chart.texture.as_ref().unwrap();
+        assert_eq!(texture.size(), [200, 100]);
+    }
+}
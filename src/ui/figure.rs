@@ -0,0 +1,332 @@
+//!
+//! Composite figures of multiple charts and grids, exported to a single image without a window.
+//!
+//! Panels are arranged row by row on a regular grid, and labelled (a), (b), (c), ... in their upper left corner.
+//! Charts are drawn at the figure's resolution, with fonts and line widths scaled accordingly.
+//! Grids are rendered at their native size, and scaled to fit their panel.
+//!
+//! # Example
+//! ```no_run
+//! use easy_graph::geom::grid::Grid;
+//! use easy_graph::ui::chart::{ChartBuilder, Series};
+//! use easy_graph::ui::figure::Figure;
+//! use easy_graph::ui::grid::GridRenderer;
+//! use easy_graph::color::style::{RGBColor, RED};
+//!
+//! let mut chart = ChartBuilder::new()
+//!     .add_series(Series::line("Population", &RED))
+//!     .build_headless();
+//! for t in 0..100 {
+//!     chart.push_time_series(t as f64, &[(t * t) as f64]);
+//! }
+//! let mut grid = Grid::new(20, 10, 0.0);
+//! grid.fill_xy(|x, y| (x + y) as f64 / 30.0);
+//! let renderer = GridRenderer::new(|v: &f64| RGBColor((*v * 255.0) as u8, 0, 0));
+//!
+//! Figure::new(2)
+//!     .with_title("Run 1")
+//!     .with_panel_size(400, 300)
+//!     .with_scale(3.0)
+//!     .add_chart(&mut chart)
+//!     .add_grid(&grid, &renderer)
+//!     .save("figure.png")
+//!     .unwrap();
+//! ```
+//!
+
+use crate::geom::grid::Grid;
+use crate::ui::chart::Chart;
+use crate::ui::grid::{CategoryRenderer, GridRenderer};
+use plotters::coord::Shift;
+use plotters::drawing::bitmap_pixel::RGBPixel;
+use plotters::prelude::*;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::path::Path;
+
+/// Font size of the figure title, in unscaled pixels.
+const TITLE_FONT_SIZE: f64 = 24.0;
+/// Font size of panel labels, in unscaled pixels.
+const LABEL_FONT_SIZE: f64 = 16.0;
+/// Gap between panels and around the figure, in unscaled pixels.
+const GAP: f64 = 10.0;
+
+/// A single panel of a figure.
+enum Panel<'a> {
+    /// A chart, drawn when the figure is rendered.
+    Chart(&'a mut Chart),
+    /// A grid, rendered at its native size when added.
+    Image(image::RgbImage),
+}
+
+///
+/// Arranges charts and grids into a single image, with a shared title and panel labels.
+///
+/// See the [module docs](index.html) for an example.
+///
+pub struct Figure<'a> {
+    columns: usize,
+    panel_size: (u32, u32),
+    scale: f64,
+    title: Option<String>,
+    labels: bool,
+    panels: Vec<Panel<'a>>,
+}
+
+impl<'a> Figure<'a> {
+    /// Creates an empty figure with the given number of panels per row.
+    pub fn new(columns: usize) -> Self {
+        Figure {
+            columns: columns.max(1),
+            panel_size: (600, 400),
+            scale: 1.0,
+            title: None,
+            labels: true,
+            panels: Vec::new(),
+        }
+    }
+    /// Sets the title shown above all panels.
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+    /// Sets the size of each panel in unscaled pixels. Default: 600 x 400.
+    pub fn with_panel_size(mut self, width: u32, height: u32) -> Self {
+        self.panel_size = (width.max(1), height.max(1));
+        self
+    }
+    /// Sets the scale of the exported image, e.g. 3.0 for print resolution. Default: 1.0.
+    ///
+    /// Scales panel sizes, gaps, fonts, markers and line widths.
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.scale = scale.max(0.01);
+        self
+    }
+    /// Hides the panel labels (a), (b), (c), ...
+    pub fn without_labels(mut self) -> Self {
+        self.labels = false;
+        self
+    }
+    /// Adds a chart as the next panel. The chart is drawn when the figure is rendered.
+    pub fn add_chart(mut self, chart: &'a mut Chart) -> Self {
+        self.panels.push(Panel::Chart(chart));
+        self
+    }
+    /// Adds a grid as the next panel, in its current state.
+    pub fn add_grid<T: Clone>(mut self, grid: &Grid<T>, renderer: &GridRenderer<T>) -> Self {
+        let img = render_image(renderer.size(grid), |root| renderer.draw(grid, root));
+        self.panels.push(Panel::Image(img));
+        self
+    }
+    /// Adds a grid of categories with its legend as the next panel, in its current state.
+    pub fn add_category_grid<T>(mut self, grid: &Grid<T>, renderer: &CategoryRenderer<T>) -> Self
+    where
+        T: Clone + Eq + Hash + Debug + 'static,
+    {
+        let img = render_image(renderer.size(grid), |root| renderer.draw(grid, root));
+        self.panels.push(Panel::Image(img));
+        self
+    }
+
+    /// The number of panels.
+    pub fn num_panels(&self) -> usize {
+        self.panels.len()
+    }
+    /// The size of the rendered image, in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        let (width, height) = self.scaled_panel_size();
+        let gap = self.scaled(GAP);
+        let columns = self.columns.min(self.panels.len().max(1)) as u32;
+        let rows = self.panels.len().div_ceil(self.columns).max(1) as u32;
+        (
+            columns * (width + gap) + gap,
+            self.title_height() + rows * (height + gap) + gap,
+        )
+    }
+
+    /// Renders all panels into an image. Charts poll their data sources like in
+    /// [`Chart::draw_onto()`](../chart/struct.Chart.html#method.draw_onto).
+    pub fn render(&mut self) -> image::RgbImage {
+        let (width, height) = self.size();
+        let (panel_width, panel_height) = self.scaled_panel_size();
+        let gap = self.scaled(GAP);
+        let top = self.title_height();
+        let (columns, scale) = (self.columns, self.scale);
+        let mut figure = image::RgbImage::from_pixel(width, height, image::Rgb([255, 255, 255]));
+        for (i, panel) in self.panels.iter_mut().enumerate() {
+            let rendered = match panel {
+                Panel::Chart(chart) => {
+                    render_image((panel_width as usize, panel_height as usize), |root| {
+                        chart.draw_onto_scaled(root, scale);
+                    })
+                }
+                Panel::Image(img) => fit_image(img, (panel_width, panel_height)),
+            };
+            let x = gap + (i % columns) as u32 * (panel_width + gap);
+            let y = top + gap + (i / columns) as u32 * (panel_height + gap);
+            image::imageops::replace(&mut figure, &rendered, x, y);
+        }
+        self.draw_text(&mut figure);
+        figure
+    }
+    /// Renders the figure and saves it to an image file. The format is derived from the file extension.
+    pub fn save<P: AsRef<Path>>(&mut self, path: P) -> Result<(), image::ImageError> {
+        self.render().save(path)
+    }
+
+    /// Draws the title and panel labels onto the rendered figure.
+    fn draw_text(&self, figure: &mut image::RgbImage) {
+        let (width, height) = figure.dimensions();
+        let (panel_width, panel_height) = self.scaled_panel_size();
+        let gap = self.scaled(GAP) as i32;
+        let root = BitMapBackend::with_buffer(figure, (width, height)).into_drawing_area();
+        if let Some(title) = &self.title {
+            let font = ("sans-serif", TITLE_FONT_SIZE * self.scale).into_font();
+            let text_width = font.box_size(title).map(|s| s.0).unwrap_or(0) as i32;
+            root.draw_text(
+                title,
+                &font.color(&BLACK),
+                ((width as i32 - text_width) / 2, gap),
+            )
+            .unwrap();
+        }
+        if !self.labels {
+            return;
+        }
+        let font = ("sans-serif", LABEL_FONT_SIZE * self.scale).into_font();
+        let top = self.title_height() as i32;
+        for i in 0..self.panels.len() {
+            let x = gap + (i % self.columns) as i32 * (panel_width as i32 + gap);
+            let y = top + gap + (i / self.columns) as i32 * (panel_height as i32 + gap);
+            root.draw_text(
+                &panel_label(i),
+                &font.color(&BLACK),
+                (x + gap / 2, y + gap / 2),
+            )
+            .unwrap();
+        }
+    }
+
+    fn scaled(&self, size: f64) -> u32 {
+        (size * self.scale).round() as u32
+    }
+    fn scaled_panel_size(&self) -> (u32, u32) {
+        (
+            (self.panel_size.0 as f64 * self.scale).round().max(1.0) as u32,
+            (self.panel_size.1 as f64 * self.scale).round().max(1.0) as u32,
+        )
+    }
+    /// The height of the title area, 0 without a title.
+    fn title_height(&self) -> u32 {
+        match self.title {
+            Some(_) => self.scaled(TITLE_FONT_SIZE * 1.5),
+            None => 0,
+        }
+    }
+}
+
+/// The label of the panel with the given index: (a) to (z), then (aa), (ab), ...
+fn panel_label(index: usize) -> String {
+    let mut letters = Vec::new();
+    let mut i = index + 1;
+    while i > 0 {
+        i -= 1;
+        letters.push((b'a' + (i % 26) as u8) as char);
+        i /= 26;
+    }
+    format!("({})", letters.iter().rev().collect::<String>())
+}
+
+/// Renders into a new image of the given size with white background.
+fn render_image<F>(size: (usize, usize), draw: F) -> image::RgbImage
+where
+    F: FnOnce(&DrawingArea<BitMapBackend<RGBPixel>, Shift>),
+{
+    let (width, height) = (size.0.max(1) as u32, size.1.max(1) as u32);
+    let mut img = image::RgbImage::from_pixel(width, height, image::Rgb([255, 255, 255]));
+    {
+        let root = BitMapBackend::with_buffer(&mut img, (width, height)).into_drawing_area();
+        draw(&root);
+    }
+    img
+}
+
+/// Scales an image to fit into the given size, preserving its aspect ratio, and centers it on a white background.
+/// Uses nearest neighbor sampling to keep cells crisp.
+fn fit_image(img: &image::RgbImage, size: (u32, u32)) -> image::RgbImage {
+    let (width, height) = img.dimensions();
+    let factor = (size.0 as f64 / width as f64).min(size.1 as f64 / height as f64);
+    let scaled = image::imageops::resize(
+        img,
+        ((width as f64 * factor).round() as u32).clamp(1, size.0),
+        ((height as f64 * factor).round() as u32).clamp(1, size.1),
+        image::imageops::FilterType::Nearest,
+    );
+    let mut panel = image::RgbImage::from_pixel(size.0, size.1, image::Rgb([255, 255, 255]));
+    image::imageops::replace(
+        &mut panel,
+        &scaled,
+        (size.0 - scaled.width()) / 2,
+        (size.1 - scaled.height()) / 2,
+    );
+    panel
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::style::{RED, WHITE};
+    use crate::geom::grid::Grid;
+    use crate::ui::chart::{ChartBuilder, Series};
+    use crate::ui::figure::{fit_image, panel_label, Figure};
+    use crate::ui::grid::GridRenderer;
+
+    #[test]
+    fn labels() {
+        assert_eq!(panel_label(0), "(a)");
+        assert_eq!(panel_label(2), "(c)");
+        assert_eq!(panel_label(25), "(z)");
+        assert_eq!(panel_label(26), "(aa)");
+        assert_eq!(panel_label(27), "(ab)");
+    }
+
+    #[test]
+    fn fit_grid_image() {
+        let img = image::RgbImage::from_pixel(2, 1, image::Rgb([255, 0, 0]));
+        let panel = fit_image(&img, (10, 10));
+        assert_eq!(panel.dimensions(), (10, 10));
+        assert_eq!(panel.get_pixel(5, 5), &image::Rgb([255, 0, 0]));
+        assert_eq!(panel.get_pixel(5, 1), &image::Rgb([255, 255, 255]));
+    }
+
+    #[test]
+    fn render_panels() {
+        let mut chart = ChartBuilder::new()
+            .with_dimensions(100, 80)
+            .add_series(Series::line("A", &RED))
+            .build_headless();
+        chart.push_time_series(0.0, &[1.0]);
+        chart.push_time_series(1.0, &[2.0]);
+        let mut grid = Grid::new(4, 4, false);
+        grid.set(3, 3, true);
+        let renderer = GridRenderer::new(|v: &bool| if *v { RED } else { WHITE });
+
+        let mut figure = Figure::new(2)
+            .with_title("Figure")
+            .with_panel_size(100, 80)
+            .with_scale(2.0)
+            .add_chart(&mut chart)
+            .add_grid(&grid, &renderer)
+            .add_grid(&grid, &renderer);
+        assert_eq!(figure.num_panels(), 3);
+        let (width, height) = figure.size();
+        assert_eq!(width, 2 * (200 + 20) + 20);
+        assert_eq!(height, 72 + 2 * (160 + 20) + 20);
+
+        let rendered = figure.render();
+        assert_eq!(rendered.dimensions(), (width, height));
+        // lower right cell of the first grid, centered in the second panel
+        let (x, y) = (20 + 220 + 20 + 3 * 40 + 20, 72 + 20 + 3 * 40 + 20);
+        assert_eq!(rendered.get_pixel(x, y), &image::Rgb([255, 0, 0]));
+        assert_eq!(rendered.get_pixel(x - 40, y), &image::Rgb([255, 255, 255]));
+    }
+}
@@ -0,0 +1,384 @@
+//!
+//! GPU-accelerated window backend, enabled by feature `gpu`.
+//!
+//! With the feature enabled, [`BufferWindow`](../window/struct.BufferWindow.html) and [`Chart`](../chart/struct.Chart.html)
+//! present their frames through [wgpu](https://wgpu.rs) (via crate `pixels`) instead of minifb.
+//! Scaling and resizing are done on the GPU, which takes load off the CPU for large scaled windows at high frame rates.
+//!
+//! The API stays the same, except that [`BufferWindow::window()`](../window/struct.BufferWindow.html#method.window) is replaced
+//! by [`BufferWindow::gpu_window()`](../window/struct.BufferWindow.html#method.gpu_window).
+//! [`GpuWindow`](struct.GpuWindow.html) provides the methods of `minifb::Window` used by this crate, with minifb's key and mouse types.
+//! Not supported are menus, cursor styles, scroll wheel, and [`Scale::FitScreen`](../enum.Scale.html#variant.FitScreen),
+//! which falls back to 1x scale.
+//!
+//! Windows must be created and updated on the same thread, which should be the main thread on most platforms.
+//!
+//! ```toml
+//! [dependencies]
+//! easy_graph = { version = "0.1", features = ["gpu"] }
+//! ```
+//!
+
+use crate::ui::window::PixelFormat;
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Scale, WindowOptions};
+use pixels::{Pixels, SurfaceTexture};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::rc::Rc;
+use std::thread;
+use std::time::{Duration, Instant};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{ElementState, Event, VirtualKeyCode, WindowEvent};
+use winit::event_loop::{ControlFlow, EventLoop};
+use winit::platform::run_return::EventLoopExtRunReturn;
+use winit::window::WindowId;
+
+/// Input state of a window, filled by the shared event loop.
+#[derive(Default)]
+struct Input {
+    closed: bool,
+    resized: Option<(u32, u32)>,
+    keys_down: HashSet<Key>,
+    /// Keys pressed since the window's last update, and whether the press is a repeat.
+    pending: Vec<(Key, bool)>,
+    mouse_pos: Option<(f32, f32)>,
+    mouse_down: [bool; 3],
+}
+
+/// The event loop shared by all windows of a thread, as only one can exist.
+struct Shared {
+    event_loop: EventLoop<()>,
+    inputs: HashMap<WindowId, Rc<RefCell<Input>>>,
+}
+
+thread_local! {
+    static SHARED: RefCell<Option<Shared>> = const { RefCell::new(None) };
+}
+
+///
+/// A window presenting frames through the GPU, with the interface of `minifb::Window`. See [`gpu`](index.html) module docs.
+///
+pub struct GpuWindow {
+    // Declared before the window, as it must be dropped first
+    pixels: Pixels,
+    window: winit::window::Window,
+    input: Rc<RefCell<Input>>,
+    pressed: Vec<(Key, bool)>,
+    dim: (usize, usize),
+    format: PixelFormat,
+    rate: Option<Duration>,
+    last_update: Instant,
+}
+
+impl GpuWindow {
+    /// Opens a window with a buffer of the given size.
+    pub fn new(
+        name: &str,
+        width: usize,
+        height: usize,
+        opts: WindowOptions,
+    ) -> Result<Self, Box<dyn Error>> {
+        let scale = match opts.scale {
+            Scale::FitScreen | Scale::X1 => 1,
+            Scale::X2 => 2,
+            Scale::X4 => 4,
+            Scale::X8 => 8,
+            Scale::X16 => 16,
+            Scale::X32 => 32,
+        };
+        let size = PhysicalSize::new((width * scale) as u32, (height * scale) as u32);
+        let builder = winit::window::WindowBuilder::new()
+            .with_title(name)
+            .with_inner_size(size)
+            .with_resizable(opts.resize)
+            .with_decorations(opts.title && !opts.borderless);
+
+        let (window, input) = SHARED.with(|shared| {
+            let mut shared = shared.borrow_mut();
+            let shared = shared.get_or_insert_with(|| Shared {
+                event_loop: EventLoop::new(),
+                inputs: HashMap::new(),
+            });
+            let window = builder.build(&shared.event_loop)?;
+            let input = Rc::new(RefCell::new(Input::default()));
+            shared.inputs.insert(window.id(), input.clone());
+            Ok::<_, Box<dyn Error>>((window, input))
+        })?;
+        let inner = window.inner_size();
+        let surface = SurfaceTexture::new(inner.width, inner.height, &window);
+        let pixels = Pixels::new(width as u32, height as u32, surface)?;
+        Ok(GpuWindow {
+            pixels,
+            window,
+            input,
+            pressed: Vec::new(),
+            dim: (width, height),
+            format: PixelFormat::Xrgb,
+            rate: None,
+            last_update: Instant::now(),
+        })
+    }
+
+    /// Sets the window's title.
+    pub fn set_title(&mut self, title: &str) {
+        self.window.set_title(title);
+    }
+
+    /// Sets the pixel format of buffers passed to [`update_with_buffer()`](#method.update_with_buffer).
+    /// Default: `Xrgb`, which is also what `Auto` resolves to.
+    ///
+    /// Called by [`BufferWindow::set_pixel_format()`](../window/struct.BufferWindow.html#method.set_pixel_format).
+    pub fn set_pixel_format(&mut self, format: PixelFormat) {
+        self.format = format.resolve();
+    }
+
+    /// Presents a buffer of pixels in the format set by [`set_pixel_format()`](#method.set_pixel_format), and processes events.
+    pub fn update_with_buffer(
+        &mut self,
+        buffer: &[u32],
+        width: usize,
+        height: usize,
+    ) -> Result<(), pixels::Error> {
+        if (width, height) != self.dim {
+            self.pixels.resize_buffer(width as u32, height as u32)?;
+            self.dim = (width, height);
+        }
+        let format = self.format;
+        for (pixel, value) in self.pixels.frame_mut().chunks_exact_mut(4).zip(buffer) {
+            let (r, g, b) = format.decode(*value);
+            pixel.copy_from_slice(&[r, g, b, 255]);
+        }
+        self.pixels.render()?;
+        self.update();
+        Ok(())
+    }
+
+    /// Processes events, waiting for the update rate limit if necessary.
+    pub fn update(&mut self) {
+        if let Some(rate) = self.rate {
+            let elapsed = self.last_update.elapsed();
+            if elapsed < rate {
+                thread::sleep(rate - elapsed);
+            }
+        }
+        self.last_update = Instant::now();
+        pump_events();
+
+        let mut input = self.input.borrow_mut();
+        self.pressed = std::mem::take(&mut input.pending);
+        if let Some((width, height)) = input.resized.take() {
+            if width > 0 && height > 0 {
+                // Only fails for zero or oversized surfaces
+                let _ = self.pixels.resize_surface(width, height);
+            }
+        }
+    }
+
+    /// Returns if the window is open.
+    pub fn is_open(&self) -> bool {
+        !self.input.borrow().closed
+    }
+
+    /// Sets the position of the window's upper left corner in screen pixels.
+    pub fn set_position(&mut self, x: isize, y: isize) {
+        self.window
+            .set_outer_position(PhysicalPosition::new(x as i32, y as i32));
+    }
+
+    /// Limits the rate of updates, by waiting in [`update()`](#method.update).
+    pub fn limit_update_rate(&mut self, time: Option<Duration>) {
+        self.rate = time;
+    }
+
+    /// Returns the size of the window on screen, in pixels.
+    pub fn get_size(&self) -> (usize, usize) {
+        let size = self.window.inner_size();
+        (size.width as usize, size.height as usize)
+    }
+
+    /// The mouse position in buffer pixels, if the mouse is over the window.
+    pub fn get_mouse_pos(&self, mode: MouseMode) -> Option<(f32, f32)> {
+        let pos = self.input.borrow().mouse_pos?;
+        match self.pixels.window_pos_to_pixel(pos) {
+            Ok((x, y)) => Some((x as f32, y as f32)),
+            Err(pos) => match mode {
+                MouseMode::Discard => None,
+                MouseMode::Clamp => {
+                    let (x, y) = self.pixels.clamp_pixel_pos(pos);
+                    Some((x as f32, y as f32))
+                }
+                MouseMode::Pass => Some((pos.0 as f32, pos.1 as f32)),
+            },
+        }
+    }
+
+    /// Returns if the mouse button is down.
+    pub fn get_mouse_down(&self, button: MouseButton) -> bool {
+        let index = match button {
+            MouseButton::Left => 0,
+            MouseButton::Middle => 1,
+            MouseButton::Right => 2,
+        };
+        self.input.borrow().mouse_down[index]
+    }
+
+    /// The keys currently down.
+    pub fn get_keys(&self) -> Option<Vec<Key>> {
+        Some(self.input.borrow().keys_down.iter().cloned().collect())
+    }
+
+    /// The keys pressed since the last update, optionally including repeats of held keys.
+    pub fn get_keys_pressed(&self, repeat: KeyRepeat) -> Option<Vec<Key>> {
+        let keys = self
+            .pressed
+            .iter()
+            .filter(|(_, repeated)| repeat == KeyRepeat::Yes || !repeated)
+            .map(|(key, _)| *key)
+            .collect();
+        Some(keys)
+    }
+
+    /// Returns if the key is currently down.
+    pub fn is_key_down(&self, key: Key) -> bool {
+        self.input.borrow().keys_down.contains(&key)
+    }
+
+    /// Returns if the key was pressed since the last update, optionally including repeats.
+    pub fn is_key_pressed(&self, key: Key, repeat: KeyRepeat) -> bool {
+        self.pressed
+            .iter()
+            .any(|(k, repeated)| *k == key && (repeat == KeyRepeat::Yes || !repeated))
+    }
+}
+
+impl Drop for GpuWindow {
+    fn drop(&mut self) {
+        let id = self.window.id();
+        SHARED.with(|shared| {
+            if let Some(shared) = shared.borrow_mut().as_mut() {
+                shared.inputs.remove(&id);
+            }
+        });
+    }
+}
+
+/// Processes pending events of all windows of the thread.
+fn pump_events() {
+    SHARED.with(|shared| {
+        let mut shared = shared.borrow_mut();
+        let Shared { event_loop, inputs } = match shared.as_mut() {
+            Some(shared) => shared,
+            None => return,
+        };
+        event_loop.run_return(|event, _, flow| {
+            *flow = ControlFlow::Poll;
+            match event {
+                Event::WindowEvent { window_id, event } => {
+                    if let Some(input) = inputs.get(&window_id) {
+                        handle_event(&mut input.borrow_mut(), event);
+                    }
+                }
+                Event::MainEventsCleared => *flow = ControlFlow::Exit,
+                _ => {}
+            }
+        });
+    });
+}
+
+/// Updates a window's input state from an event.
+fn handle_event(input: &mut Input, event: WindowEvent) {
+    match event {
+        WindowEvent::CloseRequested | WindowEvent::Destroyed => input.closed = true,
+        WindowEvent::Resized(size) => input.resized = Some((size.width, size.height)),
+        WindowEvent::KeyboardInput { input: key, .. } => {
+            let key = match key.virtual_keycode.map(map_key) {
+                Some(k) => (k, key.state),
+                None => return,
+            };
+            match key {
+                (key, ElementState::Pressed) => {
+                    let repeated = !input.keys_down.insert(key);
+                    input.pending.push((key, repeated));
+                }
+                (key, ElementState::Released) => {
+                    input.keys_down.remove(&key);
+                }
+            }
+        }
+        WindowEvent::CursorMoved { position, .. } => {
+            input.mouse_pos = Some((position.x as f32, position.y as f32))
+        }
+        WindowEvent::CursorLeft { .. } => input.mouse_pos = None,
+        WindowEvent::MouseInput { state, button, .. } => {
+            let index = match button {
+                winit::event::MouseButton::Left => 0,
+                winit::event::MouseButton::Middle => 1,
+                winit::event::MouseButton::Right => 2,
+                winit::event::MouseButton::Other(_) => return,
+            };
+            input.mouse_down[index] = state == ElementState::Pressed;
+        }
+        WindowEvent::Focused(false) => {
+            input.keys_down.clear();
+            input.mouse_down = [false; 3];
+        }
+        _ => {}
+    }
+}
+
+/// Maps winit key codes to minifb keys.
+fn map_key(code: VirtualKeyCode) -> Key {
+    macro_rules! keys {
+        ($($from:ident => $to:ident),* $(,)?) => {
+            match code {
+                $(VirtualKeyCode::$from => Key::$to,)*
+                _ => Key::Unknown,
+            }
+        };
+    }
+    keys!(
+        Key0 => Key0, Key1 => Key1, Key2 => Key2, Key3 => Key3, Key4 => Key4,
+        Key5 => Key5, Key6 => Key6, Key7 => Key7, Key8 => Key8, Key9 => Key9,
+        A => A, B => B, C => C, D => D, E => E, F => F, G => G, H => H, I => I,
+        J => J, K => K, L => L, M => M, N => N, O => O, P => P, Q => Q, R => R,
+        S => S, T => T, U => U, V => V, W => W, X => X, Y => Y, Z => Z,
+        F1 => F1, F2 => F2, F3 => F3, F4 => F4, F5 => F5, F6 => F6, F7 => F7, F8 => F8,
+        F9 => F9, F10 => F10, F11 => F11, F12 => F12, F13 => F13, F14 => F14, F15 => F15,
+        Down => Down, Left => Left, Right => Right, Up => Up,
+        Apostrophe => Apostrophe, Grave => Backquote, Backslash => Backslash, Comma => Comma,
+        Equals => Equal, LBracket => LeftBracket, Minus => Minus, Period => Period,
+        RBracket => RightBracket, Semicolon => Semicolon, Slash => Slash,
+        Back => Backspace, Delete => Delete, End => End, Return => Enter, Escape => Escape,
+        Home => Home, Insert => Insert, Apps => Menu, PageDown => PageDown, PageUp => PageUp,
+        Pause => Pause, Space => Space, Tab => Tab,
+        Numlock => NumLock, Capital => CapsLock, Scroll => ScrollLock,
+        LShift => LeftShift, RShift => RightShift, LControl => LeftCtrl, RControl => RightCtrl,
+        Numpad0 => NumPad0, Numpad1 => NumPad1, Numpad2 => NumPad2, Numpad3 => NumPad3,
+        Numpad4 => NumPad4, Numpad5 => NumPad5, Numpad6 => NumPad6, Numpad7 => NumPad7,
+        Numpad8 => NumPad8, Numpad9 => NumPad9, NumpadDecimal => NumPadDot,
+        NumpadDivide => NumPadSlash, NumpadMultiply => NumPadAsterisk,
+        NumpadSubtract => NumPadMinus, NumpadAdd => NumPadPlus, NumpadEnter => NumPadEnter,
+        LAlt => LeftAlt, RAlt => RightAlt, LWin => LeftSuper, RWin => RightSuper,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::gpu::{handle_event, map_key, Input};
+    use minifb::Key;
+    use winit::event::VirtualKeyCode;
+
+    #[test]
+    fn key_mapping() {
+        assert_eq!(map_key(VirtualKeyCode::A), Key::A);
+        assert_eq!(map_key(VirtualKeyCode::Key0), Key::Key0);
+        assert_eq!(map_key(VirtualKeyCode::Equals), Key::Equal);
+        assert_eq!(map_key(VirtualKeyCode::NumpadAdd), Key::NumPadPlus);
+        assert_eq!(map_key(VirtualKeyCode::Mute), Key::Unknown);
+
+        let mut input = Input::default();
+        handle_event(&mut input, winit::event::WindowEvent::CloseRequested);
+        assert!(input.closed);
+    }
+}
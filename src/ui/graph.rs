@@ -0,0 +1,174 @@
+//!
+//! Renders [`Graph`](../../geom/graph/struct.Graph.html)s to drawing areas or windows, as nodes and edges.
+//!
+//! # Example
+//! ```no_run
+//! use easy_graph::geom::graph::{Graph, GraphLayout};
+//! use easy_graph::ui::graph::GraphRenderer;
+//! use easy_graph::ui::window::WindowBuilder;
+//! use easy_graph::color::style::{BLUE, RED};
+//!
+//! let mut graph = Graph::new();
+//! for i in 0..20 {
+//!     graph.add_node(i % 3 == 0); // infected?
+//!     if i > 0 {
+//!         graph.add_edge(i, i / 2);
+//!     }
+//! }
+//! let layout = GraphLayout::force_directed(&graph, 200);
+//!
+//! let renderer = GraphRenderer::new(|infected: &bool| if *infected { RED } else { BLUE })
+//!     .with_node_size(|_| 6.0);
+//! let mut win = WindowBuilder::new().with_dimensions(400, 400).build();
+//!
+//! for _ in 0..10 { // change upper limit for longer run!
+//!     renderer.draw_window(&graph, &layout, &mut win);
+//! }
+//! ```
+//!
+
+use crate::geom::graph::{Graph, GraphLayout};
+use crate::ui::window::BufferWindow;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use plotters::style::RGBAColor;
+
+///
+/// Draws a graph with node colors and sizes given by closures, at the positions of a [`GraphLayout`](../../geom/graph/struct.GraphLayout.html).
+///
+/// The layout's unit square is scaled to the drawing area, minus a margin.
+///
+pub struct GraphRenderer<N> {
+    color: Box<dyn Fn(&N) -> RGBColor>,
+    size: Box<dyn Fn(&N) -> f64>,
+    edge_color: RGBAColor,
+    background: Option<RGBColor>,
+    margin: u32,
+}
+
+impl<N> GraphRenderer<N> {
+    /// Creates a renderer using the given closure to derive a node's color from its data.
+    pub fn new<F>(color: F) -> Self
+    where
+        F: Fn(&N) -> RGBColor + 'static,
+    {
+        GraphRenderer {
+            color: Box::new(color),
+            size: Box::new(|_| 4.0),
+            edge_color: BLACK.mix(0.3),
+            background: Some(WHITE),
+            margin: 10,
+        }
+    }
+    /// Sets a closure to derive a node's radius in pixels from its data. Default: 4 px.
+    pub fn with_node_size<F>(mut self, size: F) -> Self
+    where
+        F: Fn(&N) -> f64 + 'static,
+    {
+        self.size = Box::new(size);
+        self
+    }
+    /// Sets the color and opacity (0 - 1) of edges. Default: black with opacity 0.3.
+    pub fn with_edge_color(mut self, color: &RGBColor, alpha: f64) -> Self {
+        self.edge_color = color.mix(alpha);
+        self
+    }
+    /// Sets the background color. Use `None` to draw on top of existing content. Default: white.
+    pub fn with_background(mut self, color: Option<&RGBColor>) -> Self {
+        self.background = color.map(|c| RGBColor(c.0, c.1, c.2));
+        self
+    }
+    /// Sets the margin around the layout in pixels. Default: 10.
+    pub fn with_margin(mut self, margin: u32) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Converts a layout position to pixel coordinates in an area of the given size.
+    pub fn to_pixel(&self, pos: (f64, f64), size: (u32, u32)) -> (i32, i32) {
+        let margin = self.margin as f64;
+        let (w, h) = (size.0 as f64 - 2.0 * margin, size.1 as f64 - 2.0 * margin);
+        (
+            (margin + pos.0 * w).round() as i32,
+            (margin + pos.1 * h).round() as i32,
+        )
+    }
+
+    /// Draws the graph onto a drawing area, edges first.
+    pub fn draw<DB: DrawingBackend>(
+        &self,
+        graph: &Graph<N>,
+        layout: &GraphLayout,
+        area: &DrawingArea<DB, Shift>,
+    ) {
+        if let Some(background) = &self.background {
+            area.fill(background).unwrap();
+        }
+        let size = area.dim_in_pixel();
+        let pixels: Vec<_> = layout
+            .positions()
+            .iter()
+            .map(|pos| self.to_pixel(*pos, size))
+            .collect();
+        for &(a, b) in graph.edges() {
+            area.draw(&PathElement::new(
+                vec![pixels[a], pixels[b]],
+                &self.edge_color,
+            ))
+            .unwrap();
+        }
+        for (node, pos) in graph.nodes().iter().zip(&pixels) {
+            let radius = (self.size)(node).round().max(1.0) as i32;
+            area.draw(&Circle::new(*pos, radius, (self.color)(node).filled()))
+                .unwrap();
+        }
+    }
+
+    /// Draws the graph into a window, filling the window.
+    pub fn draw_window(&self, graph: &Graph<N>, layout: &GraphLayout, window: &mut BufferWindow) {
+        window.draw(|b| {
+            let root = b.into_drawing_area();
+            self.draw(graph, layout, &root);
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::geom::graph::{Graph, GraphLayout};
+    use crate::ui::graph::GraphRenderer;
+    use plotters::prelude::*;
+
+    #[test]
+    fn draw_graph() {
+        let mut graph = Graph::new();
+        let a = graph.add_node(true);
+        let b = graph.add_node(false);
+        graph.add_edge(a, b);
+        let layout = GraphLayout::circular(&graph);
+
+        let renderer =
+            GraphRenderer::new(|v: &bool| if *v { RED } else { BLUE }).with_node_size(|_| 5.0);
+        let (w, h) = (100, 100);
+        let mut buffer = vec![0; 3 * w * h];
+        {
+            let root =
+                BitMapBackend::with_buffer(&mut buffer, (w as u32, h as u32)).into_drawing_area();
+            renderer.draw(&graph, &layout, &root);
+        }
+        let pixel = |(x, y): (i32, i32)| {
+            let i = 3 * (y as usize * w + x as usize);
+            [buffer[i], buffer[i + 1], buffer[i + 2]]
+        };
+        let size = (w as u32, h as u32);
+        assert_eq!(
+            pixel(renderer.to_pixel(layout.position(a), size)),
+            [255, 0, 0]
+        );
+        assert_eq!(
+            pixel(renderer.to_pixel(layout.position(b), size)),
+            [0, 0, 255]
+        );
+        assert_eq!(pixel((50, 20)), [255, 255, 255]);
+    }
+}
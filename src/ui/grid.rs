@@ -0,0 +1,644 @@
+//!
+//! Renders [`Grid`](../../geom/grid/struct.Grid.html)s to drawing areas or windows, one rectangle per cell.
+//!
+//! # Example
+//! ```
+//! use easy_graph::geom::grid::Grid;
+//! use easy_graph::ui::grid::GridRenderer;
+//! use easy_graph::ui::window::WindowBuilder;
+//! use easy_graph::color::style::{RGBColor, BLACK};
+//!
+//! let mut grid = Grid::new(20, 10, 0.0);
+//! grid.fill_xy(|x, y| (x + y) as f64 / 30.0);
+//!
+//! let renderer = GridRenderer::new(|v: &f64| RGBColor((*v * 255.0) as u8, 0, 0))
+//!     .with_cell_size(10)
+//!     .with_border(&BLACK);
+//!
+//! let (width, height) = renderer.size(&grid);
+//! let mut win = WindowBuilder::new()
+//!     .with_dimensions(width, height)
+//!     .build();
+//!
+//! for _ in 0..10 { // change upper limit for longer run!
+//!     renderer.draw_window(&grid, &mut win);
+//! }
+//! ```
+//!
+//! Large grids, e.g. 2048² cells, are drawn by a [`TileRenderer`](struct.TileRenderer.html),
+//! which caches rendered tiles and only re-renders tiles with changed cells:
+//! ```
+//! use easy_graph::geom::grid::Grid;
+//! use easy_graph::ui::grid::TileRenderer;
+//! use easy_graph::color::style::RGBColor;
+//!
+//! let mut grid = Grid::new(2048, 2048, 0.0);
+//! let mut renderer = TileRenderer::new(|v: &f64| RGBColor((*v * 255.0) as u8, 0, 0));
+//! assert_eq!(renderer.update(&mut grid), 64);
+//!
+//! grid.set(100, 100, 1.0);
+//! assert_eq!(renderer.update(&mut grid), 1);
+//! ```
+//!
+//! Grids of categories, like enums, are drawn with a legend by a [`CategoryRenderer`](struct.CategoryRenderer.html):
+//! ```
+//! use easy_graph::geom::grid::Grid;
+//! use easy_graph::ui::grid::CategoryRenderer;
+//! use easy_graph::color::style::{BLUE, GREEN, RED};
+//! use std::collections::HashMap;
+//!
+//! #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+//! enum Health {
+//!     Susceptible,
+//!     Infected,
+//!     Recovered,
+//! }
+//!
+//! let mut grid = Grid::new(20, 10, Health::Susceptible);
+//! grid.set(5, 5, Health::Infected);
+//!
+//! let mut colors = HashMap::new();
+//! colors.insert(Health::Susceptible, BLUE);
+//! colors.insert(Health::Infected, RED);
+//! colors.insert(Health::Recovered, GREEN);
+//! let renderer = CategoryRenderer::from_map(colors).with_cell_size(10);
+//! let (width, height) = renderer.size(&grid);
+//! ```
+//!
+
+use crate::geom::grid::{Grid, GridView};
+use crate::ui::window::BufferWindow;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::rc::Rc;
+
+/// Default tile size of [`TileRenderer`](struct.TileRenderer.html), in pixels.
+pub const TILE_SIZE: usize = 256;
+/// Font size of legend labels, in pixels.
+const FONT_SIZE: f64 = 14.0;
+/// Padding around the legend and between its entries, in pixels.
+const PADDING: i32 = 5;
+
+///
+/// Draws a grid with a color per cell, given by a closure.
+///
+/// Cells are drawn as squares of a configurable size in pixels, independent of the window's scale.
+/// Optionally, cells are separated by 1px border lines.
+///
+pub struct GridRenderer<T: Clone> {
+    cell_size: usize,
+    border: Option<RGBColor>,
+    color: Box<dyn Fn(&T) -> RGBColor>,
+}
+
+impl<T: Clone> GridRenderer<T> {
+    /// Creates a renderer using the given closure to derive a cell's color from its value.
+    pub fn new<F>(color: F) -> Self
+    where
+        F: Fn(&T) -> RGBColor + 'static,
+    {
+        GridRenderer {
+            cell_size: 1,
+            border: None,
+            color: Box::new(color),
+        }
+    }
+    /// Sets the size of each cell in pixels. Default: 1.
+    ///
+    /// If borders are enabled, the size includes one border line.
+    pub fn with_cell_size(mut self, size: usize) -> Self {
+        self.cell_size = size.max(1);
+        self
+    }
+    /// Draws 1px border lines of the given color between cells, and around the grid.
+    pub fn with_border(mut self, color: &RGBColor) -> Self {
+        self.border = Some(RGBColor(color.0, color.1, color.2));
+        self
+    }
+    /// Returns the size in pixels required to draw the given grid.
+    pub fn size(&self, grid: &Grid<T>) -> (usize, usize) {
+        let extra = if self.border.is_some() { 1 } else { 0 };
+        (
+            grid.width() as usize * self.cell_size + extra,
+            grid.height() as usize * self.cell_size + extra,
+        )
+    }
+    /// Converts grid coordinates to the pixel coordinates of the cell's upper left corner.
+    pub fn to_pixel(&self, x: usize, y: usize) -> (i32, i32) {
+        ((x * self.cell_size) as i32, (y * self.cell_size) as i32)
+    }
+    /// Converts pixel coordinates to grid coordinates. Returns `None` for pixels outside the grid.
+    pub fn to_cell(&self, grid: &Grid<T>, px: i32, py: i32) -> Option<(usize, usize)> {
+        if px < 0 || py < 0 {
+            return None;
+        }
+        let (x, y) = (px as usize / self.cell_size, py as usize / self.cell_size);
+        if grid.contains(x as i32, y as i32) {
+            Some((x, y))
+        } else {
+            None
+        }
+    }
+
+    /// Draws the grid onto a drawing area, with the upper left corner at the area's origin.
+    pub fn draw<DB: DrawingBackend>(&self, grid: &Grid<T>, area: &DrawingArea<DB, Shift>) {
+        self.draw_cells((grid.width(), grid.height()), |x, y| grid.get(x, y), area);
+    }
+
+    /// Draws a view of a grid region onto a drawing area, with the region's upper left corner at the area's origin.
+    /// Allows zooming into large grids without copying.
+    pub fn draw_view<DB: DrawingBackend>(
+        &self,
+        view: &GridView<'_, T>,
+        area: &DrawingArea<DB, Shift>,
+    ) {
+        self.draw_cells((view.width(), view.height()), |x, y| view.get(x, y), area);
+    }
+
+    fn draw_cells<'a, DB, F>(&self, dim: (i32, i32), cell: F, area: &DrawingArea<DB, Shift>)
+    where
+        DB: DrawingBackend,
+        F: Fn(usize, usize) -> &'a T,
+        T: 'a,
+    {
+        let size = self.cell_size as i32;
+        let inset = if self.border.is_some() { 1 } else { 0 };
+        for (x, y) in (0..dim.0).flat_map(|x| (0..dim.1).map(move |y| (x, y))) {
+            let color = (self.color)(cell(x as usize, y as usize));
+            if size == 1 {
+                area.draw_pixel((x, y), &color).unwrap();
+            } else {
+                let (x0, y0) = (x * size + inset, y * size + inset);
+                area.draw(&Rectangle::new(
+                    [(x0, y0), (x0 + size - 1 - inset, y0 + size - 1 - inset)],
+                    color.filled(),
+                ))
+                .unwrap();
+            }
+        }
+        if let Some(border) = &self.border {
+            let (width, height) = (dim.0 * size, dim.1 * size);
+            for x in 0..=dim.0 {
+                let px = x * size;
+                area.draw(&PathElement::new(vec![(px, 0), (px, height)], border))
+                    .unwrap();
+            }
+            for y in 0..=dim.1 {
+                let py = y * size;
+                area.draw(&PathElement::new(vec![(0, py), (width, py)], border))
+                    .unwrap();
+            }
+        }
+    }
+
+    /// Draws the grid into a window, with the upper left corner at the window's origin.
+    pub fn draw_window(&self, grid: &Grid<T>, window: &mut BufferWindow) {
+        window.draw(|b| {
+            let root = b.into_drawing_area();
+            self.draw(grid, &root);
+        });
+    }
+}
+
+///
+/// Draws a grid of categories, e.g. enum values, with a color per category and a legend right of the grid.
+///
+/// Legend labels are the categories' `Debug` representation, unless set via [`with_labels()`](#method.with_labels).
+/// The legend lists the categories of the color map, or those present in the grid in order of appearance
+/// for renderers using a closure. Use [`with_categories()`](#method.with_categories) to set the listed categories and their order.
+///
+pub struct CategoryRenderer<T: Clone> {
+    renderer: GridRenderer<T>,
+    color: Rc<dyn Fn(&T) -> RGBColor>,
+    label: Box<dyn Fn(&T) -> String>,
+    categories: Option<Vec<T>>,
+    legend: bool,
+}
+
+impl<T: Clone + Eq + Hash + Debug + 'static> CategoryRenderer<T> {
+    /// Creates a renderer using the given closure to derive a category's color.
+    pub fn new<F>(color: F) -> Self
+    where
+        F: Fn(&T) -> RGBColor + 'static,
+    {
+        let color: Rc<dyn Fn(&T) -> RGBColor> = Rc::new(color);
+        let cell_color = color.clone();
+        CategoryRenderer {
+            renderer: GridRenderer::new(move |v| cell_color(v)),
+            color,
+            label: Box::new(|v| format!("{:?}", v)),
+            categories: None,
+            legend: true,
+        }
+    }
+    /// Creates a renderer with colors from a map. Categories not in the map are drawn black.
+    ///
+    /// The legend lists all categories of the map, ordered by label.
+    pub fn from_map(colors: HashMap<T, RGBColor>) -> Self {
+        let mut categories: Vec<_> = colors.keys().cloned().collect();
+        categories.sort_by_key(|c| format!("{:?}", c));
+        let renderer = Self::new(move |v| match colors.get(v) {
+            Some(c) => RGBColor(c.0, c.1, c.2),
+            None => BLACK,
+        });
+        renderer.with_categories(&categories)
+    }
+    /// Sets the categories listed in the legend, in the given order.
+    pub fn with_categories(mut self, categories: &[T]) -> Self {
+        self.categories = Some(categories.to_vec());
+        self
+    }
+    /// Sets a closure to derive legend labels from categories. Default: the categories' `Debug` representation.
+    pub fn with_labels<F>(mut self, label: F) -> Self
+    where
+        F: Fn(&T) -> String + 'static,
+    {
+        self.label = Box::new(label);
+        self
+    }
+    /// Sets the size of each cell in pixels. Default: 1.
+    ///
+    /// See [`GridRenderer::with_cell_size()`](struct.GridRenderer.html#method.with_cell_size).
+    pub fn with_cell_size(mut self, size: usize) -> Self {
+        self.renderer = self.renderer.with_cell_size(size);
+        self
+    }
+    /// Draws 1px border lines of the given color between cells, and around the grid.
+    pub fn with_border(mut self, color: &RGBColor) -> Self {
+        self.renderer = self.renderer.with_border(color);
+        self
+    }
+    /// Hides the legend.
+    pub fn without_legend(mut self) -> Self {
+        self.legend = false;
+        self
+    }
+
+    /// The underlying renderer of grid cells, e.g. for converting between pixel and grid coordinates.
+    pub fn renderer(&self) -> &GridRenderer<T> {
+        &self.renderer
+    }
+    /// The legend entries for the given grid, as pairs of label and color.
+    pub fn legend(&self, grid: &Grid<T>) -> Vec<(String, RGBColor)> {
+        let present;
+        let categories = match &self.categories {
+            Some(categories) => categories,
+            None => {
+                let mut seen = Vec::new();
+                for y in 0..grid.height() as usize {
+                    for x in 0..grid.width() as usize {
+                        let value = grid.get(x, y);
+                        if !seen.contains(value) {
+                            seen.push(value.clone());
+                        }
+                    }
+                }
+                present = seen;
+                &present
+            }
+        };
+        categories
+            .iter()
+            .map(|c| ((self.label)(c), (self.color)(c)))
+            .collect()
+    }
+    /// Returns the size in pixels required to draw the given grid, including the legend.
+    pub fn size(&self, grid: &Grid<T>) -> (usize, usize) {
+        let (width, height) = self.renderer.size(grid);
+        if !self.legend {
+            return (width, height);
+        }
+        let font = ("sans-serif", FONT_SIZE).into_font();
+        let entries = self.legend(grid);
+        let text = entries
+            .iter()
+            .map(|(label, _)| font.box_size(label).map(|s| s.0).unwrap_or(0) as usize)
+            .max()
+            .unwrap_or(0);
+        let legend_height =
+            entries.len() * (FONT_SIZE as usize + PADDING as usize) + PADDING as usize;
+        (
+            width + 3 * PADDING as usize + FONT_SIZE as usize + text + PADDING as usize,
+            height.max(legend_height),
+        )
+    }
+
+    /// Draws the grid onto a drawing area, with the upper left corner at the area's origin, and the legend right of the grid.
+    pub fn draw<DB: DrawingBackend>(&self, grid: &Grid<T>, area: &DrawingArea<DB, Shift>) {
+        self.renderer.draw(grid, area);
+        if !self.legend {
+            return;
+        }
+        let font = ("sans-serif", FONT_SIZE).into_font();
+        let x = self.renderer.size(grid).0 as i32 + 2 * PADDING;
+        let swatch = FONT_SIZE as i32;
+        for (i, (label, color)) in self.legend(grid).iter().enumerate() {
+            let y = PADDING + i as i32 * (swatch + PADDING);
+            area.draw(&Rectangle::new(
+                [(x, y), (x + swatch, y + swatch)],
+                color.filled(),
+            ))
+            .unwrap();
+            area.draw(&Rectangle::new([(x, y), (x + swatch, y + swatch)], &BLACK))
+                .unwrap();
+            area.draw_text(label, &font.color(&BLACK), (x + swatch + PADDING, y))
+                .unwrap();
+        }
+    }
+
+    /// Draws the grid and legend into a window, after clearing it.
+    pub fn draw_window(&self, grid: &Grid<T>, window: &mut BufferWindow) {
+        window.draw(|b| {
+            let root = b.into_drawing_area();
+            root.fill(&WHITE).unwrap();
+            self.draw(grid, &root);
+        });
+    }
+}
+
+///
+/// Draws large grids tile by tile, with a color per cell given by a closure.
+///
+/// Rendered tiles are cached, and [`update()`](#method.update) only re-renders tiles containing cells that changed since the previous update.
+/// Changes are detected via the grid's change bitmap, which is enabled by the first update.
+/// It is independent of the grid's opt-in change tracking via [`Grid::enable_tracking()`](../../geom/grid/struct.Grid.html#method.enable_tracking).
+/// Thus, cells must only be modified through the grid's accessors like `set()` or `get_mut()`, which flag changed cells.
+///
+/// If colors depend on anything else than cell values, call [`invalidate()`](#method.invalidate) to re-render all tiles.
+///
+pub struct TileRenderer<T: Clone> {
+    cell_size: usize,
+    tile_size: usize,
+    color: Box<dyn Fn(&T) -> RGBColor>,
+    grid_dim: (usize, usize),
+    tile_counts: (usize, usize),
+    tiles: Vec<Tile>,
+}
+
+/// A rendered tile, as RGB pixels.
+struct Tile {
+    cells: (usize, usize, usize, usize),
+    dirty: bool,
+    pixels: Vec<u8>,
+}
+
+impl<T: Clone> TileRenderer<T> {
+    /// Creates a renderer using the given closure to derive a cell's color from its value.
+    pub fn new<F>(color: F) -> Self
+    where
+        F: Fn(&T) -> RGBColor + 'static,
+    {
+        TileRenderer {
+            cell_size: 1,
+            tile_size: TILE_SIZE,
+            color: Box::new(color),
+            grid_dim: (0, 0),
+            tile_counts: (0, 0),
+            tiles: vec![],
+        }
+    }
+    /// Sets the size of each cell in pixels. Default: 1.
+    pub fn with_cell_size(mut self, size: usize) -> Self {
+        self.cell_size = size.max(1);
+        self.invalidate();
+        self
+    }
+    /// Sets the approximate size of tiles in pixels, rounded down to full cells. Default: [`TILE_SIZE`](constant.TILE_SIZE.html).
+    pub fn with_tile_size(mut self, size: usize) -> Self {
+        self.tile_size = size.max(1);
+        self.invalidate();
+        self
+    }
+    /// Returns the size in pixels required to draw the given grid.
+    pub fn size(&self, grid: &Grid<T>) -> (usize, usize) {
+        (
+            grid.width() as usize * self.cell_size,
+            grid.height() as usize * self.cell_size,
+        )
+    }
+    /// Returns the number of tiles in x and y direction, as of the last update.
+    pub fn tile_counts(&self) -> (usize, usize) {
+        self.tile_counts
+    }
+    /// Marks all tiles for re-rendering on the next update.
+    pub fn invalidate(&mut self) {
+        self.tiles.clear();
+    }
+
+    /// Re-renders all tiles with changed cells, and resets the grid's change flags.
+    /// Renders all tiles on the first call, after invalidation, or if the grid's size changed.
+    ///
+    /// Returns the number of re-rendered tiles.
+    pub fn update(&mut self, grid: &mut Grid<T>) -> usize {
+        let dim = (grid.width() as usize, grid.height() as usize);
+        if dim != self.grid_dim || self.tiles.is_empty() || !grid.tracks_changes() {
+            self.create_tiles(dim);
+            grid.track_changes();
+        } else {
+            let tile_cells = self.tile_cells();
+            for index in (0..dim.0 * dim.1).filter(|i| grid.is_changed(*i)) {
+                let (x, y) = (index / dim.1, index % dim.1);
+                let tile = (x / tile_cells) * self.tile_counts.1 + y / tile_cells;
+                self.tiles[tile].dirty = true;
+            }
+        }
+        grid.clear_changes();
+
+        let mut count = 0;
+        for tile in self.tiles.iter_mut().filter(|t| t.dirty) {
+            render_tile(tile, grid, self.cell_size, &self.color);
+            count += 1;
+        }
+        count
+    }
+
+    /// Copies the rendered tiles into an RGB buffer of the given size, with the grid's upper left corner at the buffer's origin.
+    /// Pixels outside the grid are not modified.
+    ///
+    /// # Panics
+    /// Panics if the length of `buffer` does not equal 3 * width * height.
+    pub fn draw_buffer(&self, buffer: &mut [u8], dim: (u32, u32)) {
+        let (width, height) = (dim.0 as usize, dim.1 as usize);
+        if buffer.len() != 3 * width * height {
+            panic!("Length of RGB buffer must equal 3 * width * height!");
+        }
+        let size = self.cell_size;
+        for tile in &self.tiles {
+            let (x0, y0, w, h) = tile.cells;
+            let (px, py) = (x0 * size, y0 * size);
+            let (tile_width, tile_height) = (w * size, h * size);
+            if px >= width || py >= height {
+                continue;
+            }
+            let row_len = 3 * tile_width.min(width - px);
+            for row in 0..tile_height.min(height - py) {
+                let src = 3 * row * tile_width;
+                let dst = 3 * ((py + row) * width + px);
+                buffer[dst..dst + row_len].copy_from_slice(&tile.pixels[src..src + row_len]);
+            }
+        }
+    }
+
+    /// Updates the tiles and draws them into a window, with the upper left corner at the window's origin.
+    pub fn draw_window(&mut self, grid: &mut Grid<T>, window: &mut BufferWindow) {
+        self.update(grid);
+        window.draw_rgb(|buffer, dim| self.draw_buffer(buffer, dim));
+    }
+
+    /// The number of cells per tile in each direction.
+    fn tile_cells(&self) -> usize {
+        (self.tile_size / self.cell_size).max(1)
+    }
+
+    fn create_tiles(&mut self, dim: (usize, usize)) {
+        let tile_cells = self.tile_cells();
+        self.grid_dim = dim;
+        self.tile_counts = (dim.0.div_ceil(tile_cells), dim.1.div_ceil(tile_cells));
+        self.tiles.clear();
+        for tx in 0..self.tile_counts.0 {
+            for ty in 0..self.tile_counts.1 {
+                let (x0, y0) = (tx * tile_cells, ty * tile_cells);
+                let (w, h) = (tile_cells.min(dim.0 - x0), tile_cells.min(dim.1 - y0));
+                self.tiles.push(Tile {
+                    cells: (x0, y0, w, h),
+                    dirty: true,
+                    pixels: vec![0; 3 * w * h * self.cell_size * self.cell_size],
+                });
+            }
+        }
+    }
+}
+
+fn render_tile<T: Clone>(
+    tile: &mut Tile,
+    grid: &Grid<T>,
+    cell_size: usize,
+    color: &dyn Fn(&T) -> RGBColor,
+) {
+    let (x0, y0, w, h) = tile.cells;
+    let row_len = 3 * w * cell_size;
+    for x in 0..w {
+        for y in 0..h {
+            let c = color(grid.get(x0 + x, y0 + y));
+            for py in y * cell_size..(y + 1) * cell_size {
+                let start = py * row_len + 3 * x * cell_size;
+                for pixel in tile.pixels[start..start + 3 * cell_size].chunks_exact_mut(3) {
+                    pixel.copy_from_slice(&[c.0, c.1, c.2]);
+                }
+            }
+        }
+    }
+    tile.dirty = false;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::geom::grid::Grid;
+    use crate::ui::grid::{CategoryRenderer, GridRenderer, TileRenderer};
+    use plotters::prelude::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn draw_cells_and_borders() {
+        let mut grid = Grid::new(3, 2, false);
+        grid.set(1, 0, true);
+        let renderer = GridRenderer::new(|v: &bool| if *v { RED } else { WHITE })
+            .with_cell_size(4)
+            .with_border(&BLACK);
+        let (w, h) = renderer.size(&grid);
+        assert_eq!((w, h), (13, 9));
+
+        let mut buffer = vec![0; 3 * w * h];
+        {
+            let root =
+                BitMapBackend::with_buffer(&mut buffer, (w as u32, h as u32)).into_drawing_area();
+            renderer.draw(&grid, &root);
+        }
+        let pixel = |x: usize, y: usize| {
+            let i = 3 * (y * w + x);
+            (buffer[i], buffer[i + 1], buffer[i + 2])
+        };
+        assert_eq!(pixel(0, 0), (0, 0, 0));
+        assert_eq!(pixel(2, 2), (255, 255, 255));
+        assert_eq!(pixel(6, 2), (255, 0, 0));
+        assert_eq!(pixel(8, 2), (0, 0, 0));
+        assert_eq!(renderer.to_cell(&grid, 6, 2), Some((1, 0)));
+        assert_eq!(renderer.to_cell(&grid, 13, 2), None);
+    }
+
+    #[test]
+    fn categories() {
+        let mut grid = Grid::new(4, 2, 0_u8);
+        grid.set(1, 1, 2);
+        let renderer = CategoryRenderer::new(|v: &u8| if *v == 0 { WHITE } else { RED })
+            .with_cell_size(5)
+            .with_labels(|v| format!("Type {}", v));
+        let legend = renderer.legend(&grid);
+        let labels: Vec<_> = legend.iter().map(|(l, _)| &l[..]).collect();
+        assert_eq!(labels, vec!["Type 0", "Type 2"]);
+        let color = &legend[1].1;
+        assert_eq!((color.0, color.1, color.2), (255, 0, 0));
+
+        let mut colors = HashMap::new();
+        colors.insert(1_u8, BLUE);
+        colors.insert(0_u8, GREEN);
+        let renderer = CategoryRenderer::from_map(colors).with_cell_size(5);
+        let labels: Vec<_> = renderer.legend(&grid).into_iter().map(|e| e.0).collect();
+        assert_eq!(labels, vec!["0", "1"]);
+        let (w, h) = renderer.size(&grid);
+        assert!(w > 20 && h >= 10);
+
+        let mut buffer = vec![0; 3 * w * h];
+        {
+            let root =
+                BitMapBackend::with_buffer(&mut buffer, (w as u32, h as u32)).into_drawing_area();
+            renderer.draw(&grid, &root);
+        }
+        let pixel = |x: usize, y: usize| {
+            let i = 3 * (y * w + x);
+            (buffer[i], buffer[i + 1], buffer[i + 2])
+        };
+        assert_eq!(pixel(2, 2), (0, 255, 0));
+        assert_eq!(pixel(7, 7), (0, 0, 0));
+        assert!(buffer.chunks(3).any(|p| p == [0, 0, 255]));
+    }
+
+    #[test]
+    fn tile_cache() {
+        let mut grid = Grid::new(10, 7, false);
+        let mut renderer = TileRenderer::new(|v: &bool| if *v { RED } else { WHITE })
+            .with_cell_size(2)
+            .with_tile_size(8);
+        assert_eq!(renderer.update(&mut grid), 6);
+        assert_eq!(renderer.tile_counts(), (3, 2));
+        assert_eq!(renderer.update(&mut grid), 0);
+
+        grid.set(9, 6, true);
+        *grid.get_mut(0, 0) = true;
+        assert_eq!(renderer.update(&mut grid), 2);
+
+        let (w, h) = renderer.size(&grid);
+        let mut buffer = vec![0; 3 * w * h];
+        renderer.draw_buffer(&mut buffer, (w as u32, h as u32));
+        let pixel = |x: usize, y: usize| {
+            let i = 3 * (y * w + x);
+            (buffer[i], buffer[i + 1], buffer[i + 2])
+        };
+        assert_eq!(pixel(1, 1), (255, 0, 0));
+        assert_eq!(pixel(2, 1), (255, 255, 255));
+        assert_eq!(pixel(19, 13), (255, 0, 0));
+        assert_eq!(pixel(17, 13), (255, 255, 255));
+
+        let mut small = vec![0; 3 * 5 * 5];
+        renderer.draw_buffer(&mut small, (5, 5));
+        assert_eq!(&small[..3], &[255, 0, 0]);
+
+        renderer.invalidate();
+        assert_eq!(renderer.update(&mut grid), 6);
+        let mut grid = Grid::new(3, 3, false);
+        assert_eq!(renderer.update(&mut grid), 1);
+    }
+}
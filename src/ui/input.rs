@@ -0,0 +1,164 @@
+//!
+//! Broadcasts key presses of multiple windows to a shared channel, for a single pause/quit control across a dashboard.
+//!
+//! An [`InputGroup`](struct.InputGroup.html) receives the key presses of all windows and charts it is attached to,
+//! via [`WindowBuilder::with_key_broadcast()`](../window/struct.WindowBuilder.html#method.with_key_broadcast),
+//! [`ChartBuilder::with_key_broadcast()`](../chart/struct.ChartBuilder.html#method.with_key_broadcast)
+//! or [`WindowServer::broadcast_keys()`](../server/struct.WindowServer.html#method.broadcast_keys).
+//! It toggles pausing with `Space` and requests quitting with `Escape`, no matter which window has the focus.
+//!
+//! # Example
+//! ```no_run
+//! use easy_graph::ui::chart::{ChartBuilder, Series};
+//! use easy_graph::ui::input::InputGroup;
+//! use easy_graph::color::style::{BLUE, RED};
+//!
+//! let mut input = InputGroup::new();
+//! let mut prey = ChartBuilder::new()
+//!     .with_title("Prey")
+//!     .add_series(Series::line("Prey", &BLUE))
+//!     .with_key_broadcast(input.sender())
+//!     .build();
+//! let mut predators = ChartBuilder::new()
+//!     .with_title("Predators")
+//!     .add_series(Series::line("Predators", &RED))
+//!     .with_key_broadcast(input.sender())
+//!     .build();
+//!
+//! let mut t = 0.0;
+//! while prey.is_open() && predators.is_open() {
+//!     input.poll();
+//!     if input.quit_requested() {
+//!         break;
+//!     }
+//!     if !input.is_paused() {
+//!         t += 0.1;
+//!         prey.push_time_series(t, &[t.sin()]);
+//!         predators.push_time_series(t, &[t.cos()]);
+//!     }
+//!     prey.update();
+//!     predators.update();
+//! }
+//! ```
+//!
+
+use minifb::Key;
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+///
+/// A key press in a window, broadcast to an [`InputGroup`](struct.InputGroup.html) or any other receiver.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyEvent {
+    /// The title of the window the key was pressed in.
+    pub window: String,
+    /// The pressed key.
+    pub key: Key,
+}
+
+///
+/// Receives key presses of multiple windows, and tracks a shared pause and quit state.
+///
+/// See the [module docs](index.html) for an example.
+///
+pub struct InputGroup {
+    sender: Sender<KeyEvent>,
+    receiver: Receiver<KeyEvent>,
+    pause_key: Option<Key>,
+    quit_key: Option<Key>,
+    paused: bool,
+    quit: bool,
+}
+
+impl Default for InputGroup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InputGroup {
+    /// Creates an input group, with `Space` as pause key and `Escape` as quit key.
+    pub fn new() -> Self {
+        let (sender, receiver) = channel();
+        InputGroup {
+            sender,
+            receiver,
+            pause_key: Some(Key::Space),
+            quit_key: Some(Key::Escape),
+            paused: false,
+            quit: false,
+        }
+    }
+    /// Sets the key toggling the pause state. Use `None` to disable. Default: `Space`.
+    pub fn with_pause_key(mut self, key: Option<Key>) -> Self {
+        self.pause_key = key;
+        self
+    }
+    /// Sets the key requesting to quit. Use `None` to disable. Default: `Escape`.
+    pub fn with_quit_key(mut self, key: Option<Key>) -> Self {
+        self.quit_key = key;
+        self
+    }
+
+    /// A sender to attach windows to the group, see the [module docs](index.html).
+    pub fn sender(&self) -> Sender<KeyEvent> {
+        self.sender.clone()
+    }
+
+    /// Receives all pending key presses, and updates the pause and quit state. Returns the received key presses.
+    ///
+    /// Call this once per iteration of the application's main loop.
+    pub fn poll(&mut self) -> Vec<KeyEvent> {
+        let events: Vec<_> = self.receiver.try_iter().collect();
+        for event in &events {
+            if Some(event.key) == self.pause_key {
+                self.paused = !self.paused;
+            }
+            if Some(event.key) == self.quit_key {
+                self.quit = true;
+            }
+        }
+        events
+    }
+
+    /// Returns if the group is paused. Toggled by the pause key in any window.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+    /// Pauses or resumes the group.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+    /// Returns if the quit key was pressed in any window.
+    pub fn quit_requested(&self) -> bool {
+        self.quit
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::input::{InputGroup, KeyEvent};
+    use minifb::Key;
+
+    #[test]
+    fn input_group() {
+        let mut input = InputGroup::new().with_quit_key(Some(Key::Q));
+        let sender = input.sender();
+        let event = |key| KeyEvent {
+            window: "A".to_string(),
+            key,
+        };
+        sender.send(event(Key::Space)).unwrap();
+        sender.send(event(Key::Escape)).unwrap();
+        assert_eq!(input.poll().len(), 2);
+        assert!(input.is_paused());
+        assert!(!input.quit_requested());
+
+        sender.send(event(Key::Space)).unwrap();
+        sender.send(event(Key::Q)).unwrap();
+        input.poll();
+        assert!(!input.is_paused());
+        assert!(input.quit_requested());
+        assert!(input.poll().is_empty());
+    }
+}
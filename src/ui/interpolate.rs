@@ -0,0 +1,229 @@
+//!
+//! Interpolates between simulation states, for smooth animation at frame rates above the simulation's tick rate.
+//!
+//! An [`Interpolator`](struct.Interpolator.html) keeps snapshots of the last two states, and tells how far the current frame is
+//! between them. Drawing is one tick behind the simulation, blending from the previous to the latest state.
+//! See [`BufferWindow::draw_interpolated()`](../window/struct.BufferWindow.html#method.draw_interpolated).
+//!
+//! # Example
+//! ```no_run
+//! use easy_graph::ui::interpolate::{Interpolate, Interpolator};
+//! use easy_graph::ui::window::WindowBuilder;
+//! use easy_graph::ui::drawing::IntoDrawingArea;
+//! use easy_graph::ui::element::Circle;
+//! use easy_graph::color::style::{WHITE, RED, Color};
+//! use std::time::{Duration, Instant};
+//!
+//! let mut win = WindowBuilder::new().build();
+//! let mut states = Interpolator::new().with_tick_rate(10.0);
+//! let mut position = (0.0, 100.0);
+//! let mut last_tick = Instant::now();
+//!
+//! while win.is_open() {
+//!     // 10 ticks per second
+//!     if last_tick.elapsed() >= Duration::from_millis(100) {
+//!         last_tick = Instant::now();
+//!         position.0 += 20.0;
+//!         states.push(position);
+//!     }
+//!     // Up to 60 frames per second
+//!     win.draw_interpolated(&states, |b, prev, curr, alpha| {
+//!         let (x, y) = prev.interpolate(curr, alpha);
+//!         let root = b.into_drawing_area();
+//!         root.fill(&WHITE).unwrap();
+//!         root.draw(&Circle::new((x as i32, y as i32), 10, RED.filled())).unwrap();
+//!     });
+//!     std::thread::sleep(Duration::from_millis(16));
+//! }
+//! ```
+//!
+
+use std::time::{Duration, Instant};
+
+///
+/// Linear interpolation between two values.
+///
+/// Implemented for `f64`, `f32`, tuples of two and three `f64` and vectors of interpolatable values.
+///
+pub trait Interpolate {
+    /// Interpolates from `self` (alpha 0) to `other` (alpha 1).
+    fn interpolate(&self, other: &Self, alpha: f64) -> Self;
+}
+
+impl Interpolate for f64 {
+    fn interpolate(&self, other: &Self, alpha: f64) -> Self {
+        self + (other - self) * alpha
+    }
+}
+impl Interpolate for f32 {
+    fn interpolate(&self, other: &Self, alpha: f64) -> Self {
+        self + (other - self) * alpha as f32
+    }
+}
+impl Interpolate for (f64, f64) {
+    fn interpolate(&self, other: &Self, alpha: f64) -> Self {
+        (
+            self.0.interpolate(&other.0, alpha),
+            self.1.interpolate(&other.1, alpha),
+        )
+    }
+}
+impl Interpolate for (f64, f64, f64) {
+    fn interpolate(&self, other: &Self, alpha: f64) -> Self {
+        (
+            self.0.interpolate(&other.0, alpha),
+            self.1.interpolate(&other.1, alpha),
+            self.2.interpolate(&other.2, alpha),
+        )
+    }
+}
+/// Vectors of different length are not interpolated, and result in a clone of `other`, e.g. when agents were added.
+impl<T: Interpolate + Clone> Interpolate for Vec<T> {
+    fn interpolate(&self, other: &Self, alpha: f64) -> Self {
+        if self.len() != other.len() {
+            return other.clone();
+        }
+        self.iter()
+            .zip(other)
+            .map(|(a, b)| a.interpolate(b, alpha))
+            .collect()
+    }
+}
+
+///
+/// Snapshots of the last two simulation states, and the time since the latest one.
+///
+/// The interpolation factor `alpha` is the time since the latest snapshot, relative to the tick interval.
+/// The tick interval is measured between the last two snapshots, unless set via [`with_tick_rate()`](#method.with_tick_rate).
+///
+pub struct Interpolator<S> {
+    previous: Option<S>,
+    current: Option<S>,
+    last_push: Option<Instant>,
+    interval: Option<Duration>,
+    fixed_interval: bool,
+}
+
+impl<S> Default for Interpolator<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Interpolator<S> {
+    /// Creates an interpolator without snapshots.
+    pub fn new() -> Self {
+        Interpolator {
+            previous: None,
+            current: None,
+            last_push: None,
+            interval: None,
+            fixed_interval: false,
+        }
+    }
+    /// Sets the simulation's tick rate in ticks per second, instead of measuring it.
+    pub fn with_tick_rate(mut self, rate: f64) -> Self {
+        self.interval = Some(Duration::from_secs_f64(1.0 / rate));
+        self.fixed_interval = true;
+        self
+    }
+
+    /// Pushes a snapshot of the latest state, usually once per simulation tick.
+    pub fn push(&mut self, state: S) {
+        let now = Instant::now();
+        if let (Some(last), false) = (self.last_push, self.fixed_interval) {
+            self.interval = Some(now - last);
+        }
+        self.last_push = Some(now);
+        self.previous = self.current.replace(state);
+    }
+    /// Removes all snapshots, e.g. when the simulation is reset.
+    pub fn clear(&mut self) {
+        self.previous = None;
+        self.current = None;
+        self.last_push = None;
+        if !self.fixed_interval {
+            self.interval = None;
+        }
+    }
+
+    /// The latest state, if any.
+    pub fn current(&self) -> Option<&S> {
+        self.current.as_ref()
+    }
+    /// The previous state, or the latest if there is only one.
+    pub fn previous(&self) -> Option<&S> {
+        self.previous.as_ref().or(self.current.as_ref())
+    }
+
+    /// The interpolation factor from the previous to the latest state, in the range 0 - 1.
+    ///
+    /// 1 if the tick interval is not known yet, or if the next snapshot is overdue.
+    pub fn alpha(&self) -> f64 {
+        match (self.last_push, self.interval) {
+            (Some(last), Some(interval)) if interval > Duration::from_secs(0) => {
+                (last.elapsed().as_secs_f64() / interval.as_secs_f64()).min(1.0)
+            }
+            _ => 1.0,
+        }
+    }
+
+    /// The previous and latest states, with the interpolation factor. `None` if there are no snapshots.
+    pub fn states(&self) -> Option<(&S, &S, f64)> {
+        match (self.previous(), self.current()) {
+            (Some(previous), Some(current)) => Some((previous, current, self.alpha())),
+            _ => None,
+        }
+    }
+}
+
+impl<S: Interpolate> Interpolator<S> {
+    /// The interpolated state for the current time. `None` if there are no snapshots.
+    pub fn interpolated(&self) -> Option<S> {
+        self.states()
+            .map(|(previous, current, alpha)| previous.interpolate(current, alpha))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::interpolate::{Interpolate, Interpolator};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn interpolate_values() {
+        assert_eq!(1.0_f64.interpolate(&3.0, 0.25), 1.5);
+        assert_eq!((0.0, 2.0).interpolate(&(4.0, 0.0), 0.5), (2.0, 1.0));
+        assert_eq!(
+            vec![0.0, 1.0].interpolate(&vec![1.0, 1.0], 0.5),
+            vec![0.5, 1.0]
+        );
+        assert_eq!(vec![0.0].interpolate(&vec![1.0, 2.0], 0.5), vec![1.0, 2.0]);
+    }
+
+    #[test]
+    fn interpolator() {
+        let mut states = Interpolator::new().with_tick_rate(10.0);
+        assert!(states.states().is_none());
+        states.push(0.0);
+        assert_eq!(states.states().map(|s| (*s.0, *s.1)), Some((0.0, 0.0)));
+        states.push(10.0);
+        let alpha = states.alpha();
+        assert!(alpha < 0.5);
+        let value = states.interpolated().unwrap();
+        assert!((0.0..5.0).contains(&value));
+        thread::sleep(Duration::from_millis(120));
+        assert_eq!(states.alpha(), 1.0);
+        assert_eq!(states.interpolated(), Some(10.0));
+
+        let mut states = Interpolator::new();
+        states.push(0.0);
+        assert_eq!(states.alpha(), 1.0);
+        thread::sleep(Duration::from_millis(20));
+        states.push(1.0);
+        assert!(states.alpha() < 1.0);
+        states.clear();
+        assert!(states.current().is_none());
+    }
+}
@@ -0,0 +1,364 @@
+//!
+//! Layers that can be drawn into windows and charts, like raster images or agent trajectories.
+//!
+//! # Example
+//! ```no_run
+//! use easy_graph::ui::layer::ImageLayer;
+//! use easy_graph::ui::window::WindowBuilder;
+//! use easy_graph::ui::drawing::IntoDrawingArea;
+//!
+//! let map = ImageLayer::open("map.png").unwrap().resized(600, 400);
+//! let mut win = WindowBuilder::new().with_dimensions(600, 400).build();
+//!
+//! win.draw(|mut b| {
+//!     map.blit(&mut b, (0, 0));
+//!     let root = b.into_drawing_area();
+//!     // draw agents on top...
+//! });
+//! ```
+//!
+//! Moving agents with fading trails of their recent positions:
+//! ```no_run
+//! use easy_graph::ui::layer::TrajectoryLayer;
+//! use easy_graph::ui::window::WindowBuilder;
+//! use easy_graph::ui::drawing::IntoDrawingArea;
+//! use easy_graph::color::style::{BLUE, RED, WHITE};
+//!
+//! let mut trails = TrajectoryLayer::new(50).with_palette(&[RED, BLUE]);
+//! let mut win = WindowBuilder::new().with_dimensions(600, 400).build();
+//!
+//! for t in 0..100 {
+//!     for id in 0..10 {
+//!         let angle = t as f64 * 0.05 + id as f64;
+//!         trails.record(id, (300.0 + 150.0 * angle.cos(), 200.0 + 150.0 * angle.sin()));
+//!     }
+//!     win.draw(|b| {
+//!         let root = b.into_drawing_area();
+//!         root.fill(&WHITE).unwrap();
+//!         trails.draw(&root);
+//!     });
+//! }
+//! ```
+//!
+
+use image::imageops::FilterType;
+use image::{ImageError, RgbImage};
+use plotters::coord::Shift;
+use plotters::drawing::backend::DrawingBackend;
+use plotters::prelude::*;
+use std::collections::{BTreeMap, VecDeque};
+use std::path::Path;
+
+///
+/// An RGB raster image that can be blitted or scaled into windows and charts.
+///
+#[derive(Clone)]
+pub struct ImageLayer {
+    image: RgbImage,
+}
+
+impl ImageLayer {
+    /// Loads an image from a file. The format is derived from the file extension.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ImageError> {
+        Ok(ImageLayer {
+            image: image::open(path)?.to_rgb(),
+        })
+    }
+
+    /// Creates an image from a buffer of RGB pixels in row-major order.
+    ///
+    /// # Panics
+    /// Panics if the length of `rgb` does not equal 3 * width * height.
+    pub fn from_rgb(width: u32, height: u32, rgb: Vec<u8>) -> Self {
+        ImageLayer {
+            image: RgbImage::from_raw(width, height, rgb)
+                .expect("Length of RGB buffer must equal 3 * width * height!"),
+        }
+    }
+
+    /// Returns the size of the image in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        self.image.dimensions()
+    }
+
+    /// Returns the raw RGB pixels in row-major order.
+    pub fn as_rgb(&self) -> &[u8] {
+        &self.image
+    }
+
+    /// Returns a copy of the image, resized to the given size using bilinear interpolation.
+    ///
+    /// Prefer this over drawing scaled images each frame.
+    pub fn resized(&self, width: u32, height: u32) -> Self {
+        ImageLayer {
+            image: image::imageops::resize(&self.image, width, height, FilterType::Triangle),
+        }
+    }
+
+    /// Copies the image to a backend with its upper left corner at `pos`. Parts outside the backend are clipped.
+    ///
+    /// For [`BufferWindow`](../window/struct.BufferWindow.html)s, call this on the backend before converting it into a drawing area.
+    /// This is much faster than [`draw()`](#method.draw).
+    pub fn blit<DB: DrawingBackend>(&self, backend: &mut DB, pos: (i32, i32)) {
+        let (width, height) = self.size();
+        backend
+            .blit_bitmap(pos, (width, height), &self.image)
+            .unwrap();
+    }
+
+    /// Draws the image onto a drawing area with its upper left corner at `pos`, pixel by pixel.
+    pub fn draw<DB: DrawingBackend>(&self, area: &DrawingArea<DB, Shift>, pos: (i32, i32)) {
+        let (width, height) = self.size();
+        self.draw_scaled(area, pos, (width, height));
+    }
+
+    /// Draws the image onto a drawing area with its upper left corner at `pos`,
+    /// scaled to `size` using nearest neighbor sampling.
+    pub fn draw_scaled<DB: DrawingBackend>(
+        &self,
+        area: &DrawingArea<DB, Shift>,
+        pos: (i32, i32),
+        size: (u32, u32),
+    ) {
+        let (width, height) = self.size();
+        if size.0 == 0 || size.1 == 0 {
+            return;
+        }
+        for y in 0..size.1 {
+            let sy = (y as u64 * height as u64 / size.1 as u64) as u32;
+            for x in 0..size.0 {
+                let sx = (x as u64 * width as u64 / size.0 as u64) as u32;
+                let px = self.image.get_pixel(sx, sy);
+                area.draw_pixel(
+                    (pos.0 + x as i32, pos.1 + y as i32),
+                    &RGBColor(px[0], px[1], px[2]),
+                )
+                .unwrap();
+            }
+        }
+    }
+}
+
+///
+/// Records the recent positions of agents by id, and draws them as trails fading out towards older positions.
+///
+/// Positions are given in pixel coordinates of the drawing area, or in arbitrary coordinates transformed
+/// via [`draw_with()`](#method.draw_with). Each agent keeps up to the configured trail length of positions.
+///
+pub struct TrajectoryLayer {
+    length: usize,
+    line_width: u32,
+    fade: bool,
+    palette: Vec<RGBColor>,
+    trails: BTreeMap<usize, Trail>,
+}
+
+/// The recent positions of an agent.
+struct Trail {
+    positions: VecDeque<(f64, f64)>,
+    color: Option<RGBColor>,
+}
+
+impl TrajectoryLayer {
+    /// Creates a layer keeping up to `length` positions per agent.
+    pub fn new(length: usize) -> Self {
+        TrajectoryLayer {
+            length: length.max(1),
+            line_width: 1,
+            fade: true,
+            palette: vec![BLACK],
+            trails: BTreeMap::new(),
+        }
+    }
+    /// Sets the color of all agents. Default: black.
+    pub fn with_color(mut self, color: &RGBColor) -> Self {
+        self.palette = vec![RGBColor(color.0, color.1, color.2)];
+        self
+    }
+    /// Sets colors for agents by id, cycling through the palette. Ignored if empty.
+    pub fn with_palette(mut self, colors: &[RGBColor]) -> Self {
+        if !colors.is_empty() {
+            self.palette = colors.iter().map(|c| RGBColor(c.0, c.1, c.2)).collect();
+        }
+        self
+    }
+    /// Sets the width of trail lines in pixels. Default: 1.
+    pub fn with_line_width(mut self, width: u32) -> Self {
+        self.line_width = width.max(1);
+        self
+    }
+    /// Draws trails in solid colors instead of fading out towards older positions.
+    pub fn without_fade(mut self) -> Self {
+        self.fade = false;
+        self
+    }
+
+    /// Sets the maximum number of positions per agent. Drops the oldest positions of longer trails.
+    pub fn set_trail_length(&mut self, length: usize) {
+        self.length = length.max(1);
+        for trail in self.trails.values_mut() {
+            while trail.positions.len() > self.length {
+                trail.positions.pop_front();
+            }
+        }
+    }
+    /// Returns the maximum number of positions per agent.
+    pub fn trail_length(&self) -> usize {
+        self.length
+    }
+    /// Sets the color of an agent, overriding the palette. The color is kept until the agent is removed.
+    pub fn set_color(&mut self, id: usize, color: &RGBColor) {
+        self.trails.entry(id).or_insert_with(Trail::new).color =
+            Some(RGBColor(color.0, color.1, color.2));
+    }
+    /// Returns the color of an agent.
+    pub fn color(&self, id: usize) -> RGBColor {
+        match self.trails.get(&id).and_then(|t| t.color.as_ref()) {
+            Some(color) => RGBColor(color.0, color.1, color.2),
+            None => {
+                let color = &self.palette[id % self.palette.len()];
+                RGBColor(color.0, color.1, color.2)
+            }
+        }
+    }
+
+    /// Records the current position of an agent, dropping its oldest position if the trail is full.
+    pub fn record(&mut self, id: usize, pos: (f64, f64)) {
+        let trail = self.trails.entry(id).or_insert_with(Trail::new);
+        if trail.positions.len() >= self.length {
+            trail.positions.pop_front();
+        }
+        trail.positions.push_back(pos);
+    }
+    /// Removes an agent and its trail, e.g. when it dies.
+    pub fn remove(&mut self, id: usize) {
+        self.trails.remove(&id);
+    }
+    /// Keeps only agents for which the predicate returns `true`.
+    pub fn retain<F: FnMut(usize) -> bool>(&mut self, mut keep: F) {
+        self.trails.retain(|id, _| keep(*id));
+    }
+    /// Removes all agents.
+    pub fn clear(&mut self) {
+        self.trails.clear();
+    }
+    /// Returns the number of agents.
+    pub fn len(&self) -> usize {
+        self.trails.len()
+    }
+    /// Returns if there are no agents.
+    pub fn is_empty(&self) -> bool {
+        self.trails.is_empty()
+    }
+    /// Returns the recorded positions of an agent, from oldest to latest.
+    pub fn trail(&self, id: usize) -> Option<impl Iterator<Item = &(f64, f64)>> {
+        self.trails.get(&id).map(|t| t.positions.iter())
+    }
+
+    /// Draws all trails onto a drawing area, with positions in pixel coordinates.
+    pub fn draw<DB: DrawingBackend>(&self, area: &DrawingArea<DB, Shift>) {
+        self.draw_with(area, |(x, y)| (x.round() as i32, y.round() as i32));
+    }
+
+    /// Draws all trails onto a drawing area, converting positions to pixel coordinates using the given closure.
+    /// E.g. for positions in world coordinates, scale them to the window size.
+    pub fn draw_with<DB, F>(&self, area: &DrawingArea<DB, Shift>, to_pixel: F)
+    where
+        DB: DrawingBackend,
+        F: Fn((f64, f64)) -> (i32, i32),
+    {
+        for (id, trail) in &self.trails {
+            let color = self.color(*id);
+            let points: Vec<_> = trail.positions.iter().map(|p| to_pixel(*p)).collect();
+            if !self.fade {
+                area.draw(&PathElement::new(
+                    points,
+                    color.stroke_width(self.line_width),
+                ))
+                .unwrap();
+                continue;
+            }
+            let segments = points.len().saturating_sub(1);
+            for (i, pair) in points.windows(2).enumerate() {
+                let alpha = (i + 1) as f64 / segments as f64;
+                area.draw(&PathElement::new(
+                    vec![pair[0], pair[1]],
+                    color.mix(alpha).stroke_width(self.line_width),
+                ))
+                .unwrap();
+            }
+        }
+    }
+}
+
+impl Trail {
+    fn new() -> Self {
+        Trail {
+            positions: VecDeque::new(),
+            color: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::layer::{ImageLayer, TrajectoryLayer};
+    use plotters::prelude::*;
+
+    #[test]
+    fn blit_and_scale() {
+        let layer = ImageLayer::from_rgb(2, 1, vec![255, 0, 0, 0, 0, 255]);
+        let mut buffer = vec![0; 3 * 4 * 2];
+        {
+            let mut backend = BitMapBackend::with_buffer(&mut buffer, (4, 2));
+            layer.blit(&mut backend, (1, 1));
+        }
+        assert_eq!(&buffer[3 * 5..3 * 7], &[255, 0, 0, 0, 0, 255]);
+
+        let mut buffer = vec![0; 3 * 4 * 2];
+        {
+            let root = BitMapBackend::with_buffer(&mut buffer, (4, 2)).into_drawing_area();
+            layer.draw_scaled(&root, (0, 0), (4, 2));
+        }
+        assert_eq!(
+            &buffer[0..12],
+            &[255, 0, 0, 255, 0, 0, 0, 0, 255, 0, 0, 255]
+        );
+    }
+
+    #[test]
+    fn trajectories() {
+        let mut layer = TrajectoryLayer::new(3)
+            .with_palette(&[RED, BLUE])
+            .with_line_width(1);
+        for x in 0..5 {
+            layer.record(0, (x as f64, 1.0));
+            layer.record(1, (x as f64, 3.0));
+        }
+        assert_eq!(layer.len(), 2);
+        let trail: Vec<_> = layer.trail(0).unwrap().cloned().collect();
+        assert_eq!(trail, vec![(2.0, 1.0), (3.0, 1.0), (4.0, 1.0)]);
+        assert_eq!(layer.color(1).to_rgba().rgb(), (0, 0, 255));
+        layer.set_color(1, &GREEN);
+        assert_eq!(layer.color(1).to_rgba().rgb(), (0, 255, 0));
+        assert_eq!(layer.color(2).to_rgba().rgb(), (255, 0, 0));
+
+        let mut buffer = vec![255; 3 * 6 * 5];
+        {
+            let root = BitMapBackend::with_buffer(&mut buffer, (6, 5)).into_drawing_area();
+            layer.draw(&root);
+        }
+        let pixel = |x: usize, y: usize| &buffer[3 * (y * 6 + x)..3 * (y * 6 + x) + 3];
+        assert_eq!(pixel(4, 1), &[255, 0, 0]);
+        assert!(pixel(2, 1)[1] > 0 && pixel(2, 1)[1] < 255);
+        assert_eq!(pixel(1, 1), &[255, 255, 255]);
+        assert_eq!(pixel(4, 3), &[0, 255, 0]);
+
+        layer.set_trail_length(1);
+        assert_eq!(layer.trail(1).unwrap().count(), 1);
+        layer.retain(|id| id != 0);
+        assert!(layer.trail(0).is_none());
+        layer.remove(1);
+        assert!(layer.is_empty());
+    }
+}
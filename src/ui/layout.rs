@@ -0,0 +1,534 @@
+//!
+//! Computes and applies screen positions for multiple windows and charts.
+//!
+//! # Example
+//! ```no_run
+//! use easy_graph::ui::chart::ChartBuilder;
+//! use easy_graph::ui::layout::{Layout, Tiling};
+//! use easy_graph::ui::window::WindowBuilder;
+//!
+//! let mut chart = ChartBuilder::new().build();
+//! let mut win = WindowBuilder::new().build();
+//!
+//! Layout::new(1920, 1080)
+//!     .with_tiling(Tiling::SideBySide)
+//!     .apply(&mut [&mut chart, &mut win]);
+//! ```
+//!
+//! Layouts can be saved to a file and restored on the next run, see [`SavedLayout`](struct.SavedLayout.html):
+//! ```no_run
+//! use easy_graph::ui::chart::ChartBuilder;
+//! use easy_graph::ui::layout::{restore_layout, save_layout};
+//! use easy_graph::ui::window::WindowBuilder;
+//!
+//! let mut chart = ChartBuilder::new().with_title("Population").build();
+//! let mut win = WindowBuilder::new().with_title("Map").build();
+//!
+//! restore_layout("layout.toml", &mut [&mut chart, &mut win]).ok();
+//! // ...
+//! save_layout("layout.toml", &[&chart, &win]).unwrap();
+//! ```
+//!
+
+use crate::ui::window::BufferWindow;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+///
+/// Something with a size that can be positioned on the screen, like [`BufferWindow`](../window/struct.BufferWindow.html) and [`Chart`](../chart/struct.Chart.html).
+///
+pub trait Positioned {
+    /// The size on screen, in pixels.
+    fn screen_size(&self) -> (usize, usize);
+    /// Sets the position of the upper left corner, in screen pixels.
+    fn set_position(&mut self, pos: (isize, isize));
+    /// The position last set, if any.
+    fn position(&self) -> Option<(isize, isize)>;
+    /// The title.
+    fn title(&self) -> &str;
+    /// Sets the title.
+    fn set_title(&mut self, title: &str);
+}
+
+impl Positioned for BufferWindow {
+    fn screen_size(&self) -> (usize, usize) {
+        BufferWindow::screen_size(self)
+    }
+    fn set_position(&mut self, pos: (isize, isize)) {
+        BufferWindow::set_position(self, pos);
+    }
+    fn position(&self) -> Option<(isize, isize)> {
+        BufferWindow::position(self)
+    }
+    fn title(&self) -> &str {
+        BufferWindow::title(self)
+    }
+    fn set_title(&mut self, title: &str) {
+        BufferWindow::set_title(self, title);
+    }
+}
+
+/// Arrangement of windows, see [`Layout`](struct.Layout.html).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tiling {
+    /// Rows with a fixed number of columns.
+    Grid(usize),
+    /// Rows from left to right, wrapping when the screen width is exceeded.
+    Flow,
+    /// All windows in a single row.
+    SideBySide,
+    /// All windows in a single column.
+    Stacked,
+}
+
+///
+/// Computes window positions for a tiling on a screen of a given size.
+///
+pub struct Layout {
+    screen: (usize, usize),
+    origin: (isize, isize),
+    gap: usize,
+    tiling: Tiling,
+}
+
+impl Layout {
+    /// Creates a layout for a screen of the given size, with [`Tiling::Flow`](enum.Tiling.html#variant.Flow).
+    pub fn new(screen_width: usize, screen_height: usize) -> Self {
+        Layout {
+            screen: (screen_width, screen_height),
+            origin: (0, 0),
+            gap: 0,
+            tiling: Tiling::Flow,
+        }
+    }
+
+    /// Sets the position of the upper left corner of the first window. Default (0, 0).
+    pub fn with_origin(mut self, x: isize, y: isize) -> Self {
+        self.origin = (x, y);
+        self
+    }
+
+    /// Sets the gap between windows, in pixels. Default 0.
+    ///
+    /// Window decorations are not included in window sizes, so a gap may be required to avoid overlaps.
+    pub fn with_gap(mut self, gap: usize) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Sets the tiling.
+    pub fn with_tiling(mut self, tiling: Tiling) -> Self {
+        self.tiling = tiling;
+        self
+    }
+
+    /// Computes the positions of windows with the given sizes.
+    ///
+    /// Columns are as wide as their widest window, rows as high as their highest window.
+    pub fn positions(&self, sizes: &[(usize, usize)]) -> Vec<(isize, isize)> {
+        let cells = self.cells(sizes);
+        let columns = cells.iter().map(|c| c.0 + 1).max().unwrap_or(0);
+        let rows = cells.iter().map(|c| c.1 + 1).max().unwrap_or(0);
+        let mut widths = vec![0; columns];
+        let mut heights = vec![0; rows];
+        for (&(col, row), size) in cells.iter().zip(sizes) {
+            widths[col] = widths[col].max(size.0);
+            heights[row] = heights[row].max(size.1);
+        }
+        let offsets = |sizes: &[usize], start: isize| {
+            sizes
+                .iter()
+                .scan(start, |pos, size| {
+                    let current = *pos;
+                    *pos += (size + self.gap) as isize;
+                    Some(current)
+                })
+                .collect::<Vec<_>>()
+        };
+        let x = offsets(&widths, self.origin.0);
+        let y = offsets(&heights, self.origin.1);
+        cells.iter().map(|&(col, row)| (x[col], y[row])).collect()
+    }
+
+    /// Positions the given windows and charts.
+    pub fn apply(&self, windows: &mut [&mut dyn Positioned]) {
+        let sizes: Vec<_> = windows.iter().map(|win| win.screen_size()).collect();
+        for (win, pos) in windows.iter_mut().zip(self.positions(&sizes)) {
+            win.set_position(pos);
+        }
+    }
+
+    /// Assigns (column, row) cells to windows.
+    fn cells(&self, sizes: &[(usize, usize)]) -> Vec<(usize, usize)> {
+        match self.tiling {
+            Tiling::Grid(columns) => {
+                let columns = columns.max(1);
+                (0..sizes.len())
+                    .map(|i| (i % columns, i / columns))
+                    .collect()
+            }
+            Tiling::SideBySide => (0..sizes.len()).map(|i| (i, 0)).collect(),
+            Tiling::Stacked => (0..sizes.len()).map(|i| (0, i)).collect(),
+            Tiling::Flow => {
+                let available = self.screen.0 as isize - self.origin.0;
+                let mut cells = Vec::with_capacity(sizes.len());
+                let (mut col, mut row, mut x) = (0, 0, 0);
+                for size in sizes {
+                    if col > 0 && x + size.0 as isize > available {
+                        col = 0;
+                        row += 1;
+                        x = 0;
+                    }
+                    cells.push((col, row));
+                    col += 1;
+                    x += (size.0 + self.gap) as isize;
+                }
+                cells
+            }
+        }
+    }
+}
+
+/// Saves the layout of the given windows and charts to a file. See [`SavedLayout`](struct.SavedLayout.html).
+pub fn save_layout<P: AsRef<Path>>(path: P, windows: &[&dyn Positioned]) -> io::Result<()> {
+    SavedLayout::capture(windows).save(path)
+}
+
+/// Restores the layout of the given windows and charts from a file. See [`SavedLayout`](struct.SavedLayout.html).
+pub fn restore_layout<P: AsRef<Path>>(
+    path: P,
+    windows: &mut [&mut dyn Positioned],
+) -> io::Result<()> {
+    SavedLayout::load(path)?.apply(windows);
+    Ok(())
+}
+
+/// Title, position and size of a single window in a [`SavedLayout`](struct.SavedLayout.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowPlacement {
+    /// The window's title.
+    pub title: String,
+    /// The position of the upper left corner in screen pixels, if it was set.
+    pub position: Option<(isize, isize)>,
+    /// The size on screen, in pixels.
+    pub size: (usize, usize),
+}
+
+///
+/// Positions, sizes and titles of a group of windows, for saving a dashboard arrangement between program runs.
+///
+/// Layouts are stored in a small text format, with one `[[window]]` table per window:
+///
+/// ```toml
+/// # Saved by easy_graph
+/// [[window]]
+/// title = "Population"
+/// position = [0, 0]
+/// size = [600, 400]
+/// ```
+///
+/// The format is a subset of TOML, and written files are valid TOML. Parsing is restricted to the subset:
+/// `[[window]]` table headers, and `key = value` lines with strings (with the escapes of TOML basic strings,
+/// or single-quoted literal strings) and arrays of two integers. Comments are allowed on own lines and after values.
+/// Other TOML features, like inline tables or multi-line strings, are rejected. Unknown keys are ignored.
+///
+/// Only positions set by the program are known, as the windowing backend does not report windows moved by the user.
+/// Sizes can't be changed after a window is created, so they are not restored.
+/// Use [`placement()`](#method.placement) to create windows with their saved size instead.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SavedLayout {
+    windows: Vec<WindowPlacement>,
+}
+
+impl SavedLayout {
+    /// Records the layout of the given windows and charts.
+    pub fn capture(windows: &[&dyn Positioned]) -> Self {
+        SavedLayout {
+            windows: windows
+                .iter()
+                .map(|win| WindowPlacement {
+                    title: win.title().to_string(),
+                    position: win.position(),
+                    size: win.screen_size(),
+                })
+                .collect(),
+        }
+    }
+
+    /// The saved windows, in order.
+    pub fn windows(&self) -> &[WindowPlacement] {
+        &self.windows
+    }
+
+    /// The first saved window with the given title.
+    pub fn placement(&self, title: &str) -> Option<&WindowPlacement> {
+        self.windows.iter().find(|win| win.title == title)
+    }
+
+    /// Applies positions and titles to the given windows and charts.
+    ///
+    /// Windows are matched to saved entries by title. Windows without a matching title
+    /// take the remaining entries in order, and get their titles.
+    pub fn apply(&self, windows: &mut [&mut dyn Positioned]) {
+        let mut used = vec![false; self.windows.len()];
+        let mut entries: Vec<_> = windows
+            .iter()
+            .map(|win| {
+                let found = (0..self.windows.len())
+                    .find(|&i| !used[i] && self.windows[i].title == win.title());
+                if let Some(i) = found {
+                    used[i] = true;
+                }
+                found
+            })
+            .collect();
+        let mut remaining = (0..self.windows.len()).filter(|&i| !used[i]);
+        for entry in entries.iter_mut().filter(|e| e.is_none()) {
+            *entry = remaining.next();
+        }
+        for (win, entry) in windows.iter_mut().zip(entries) {
+            if let Some(entry) = entry.map(|i| &self.windows[i]) {
+                if win.title() != entry.title {
+                    win.set_title(&entry.title);
+                }
+                if let Some(pos) = entry.position {
+                    win.set_position(pos);
+                }
+            }
+        }
+    }
+
+    /// Saves the layout to a file, in the format written by [`to_text()`](#method.to_text).
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+
+    /// Loads a layout from a file, see [`from_text()`](#method.from_text).
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::from_text(&fs::read_to_string(path)?)
+    }
+
+    /// Serializes the layout to its text format, which is valid TOML. See [`SavedLayout`](struct.SavedLayout.html) for the format.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for win in &self.windows {
+            text.push_str(&format!(
+                "[[window]]\ntitle = {}\n",
+                quote_string(&win.title)
+            ));
+            if let Some((x, y)) = win.position {
+                text.push_str(&format!("position = [{}, {}]\n", x, y));
+            }
+            text.push_str(&format!("size = [{}, {}]\n\n", win.size.0, win.size.1));
+        }
+        text
+    }
+
+    /// Parses a layout from its text format, as written by [`to_text()`](#method.to_text).
+    /// See [`SavedLayout`](struct.SavedLayout.html) for the supported subset of TOML.
+    pub fn from_text(text: &str) -> io::Result<Self> {
+        let invalid = |line: usize, msg: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid layout in line {}: {}", line + 1, msg),
+            )
+        };
+        let mut windows: Vec<WindowPlacement> = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with('[') {
+                if strip_comment(line) != Some("[[window]]") {
+                    return Err(invalid(index, "expected [[window]]"));
+                }
+                windows.push(WindowPlacement {
+                    title: String::new(),
+                    position: None,
+                    size: (0, 0),
+                });
+                continue;
+            }
+            let (key, value) = match line.find('=') {
+                Some(pos) => (line[..pos].trim(), line[pos + 1..].trim()),
+                None => return Err(invalid(index, "expected key = value")),
+            };
+            let win = windows
+                .last_mut()
+                .ok_or_else(|| invalid(index, "expected [[window]]"))?;
+            match key {
+                "title" => {
+                    win.title = parse_string(value).ok_or_else(|| invalid(index, "bad title"))?
+                }
+                "position" => {
+                    win.position =
+                        Some(parse_pair(value).ok_or_else(|| invalid(index, "bad position"))?)
+                }
+                "size" => win.size = parse_pair(value).ok_or_else(|| invalid(index, "bad size"))?,
+                _ if value.starts_with('{') => {
+                    return Err(invalid(index, "inline tables are not supported"))
+                }
+                _ => {}
+            }
+        }
+        Ok(SavedLayout { windows })
+    }
+}
+
+/// Quotes and escapes a string as a TOML basic string.
+fn quote_string(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+    result.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            '\r' => result.push_str("\\r"),
+            '\u{8}' => result.push_str("\\b"),
+            '\u{c}' => result.push_str("\\f"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04X}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// Parses a TOML basic string (double quotes, with escapes) or literal string (single quotes), followed by an optional comment.
+fn parse_string(value: &str) -> Option<String> {
+    let mut chars = value.chars();
+    let quote = chars.next()?;
+    let mut result = String::new();
+    match quote {
+        '\'' => loop {
+            match chars.next()? {
+                '\'' => break,
+                c => result.push(c),
+            }
+        },
+        '"' => loop {
+            match chars.next()? {
+                '"' => break,
+                '\\' => result.push(match chars.next()? {
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
+                    'b' => '\u{8}',
+                    'f' => '\u{c}',
+                    '"' => '"',
+                    '\\' => '\\',
+                    'u' => parse_unicode(&mut chars, 4)?,
+                    'U' => parse_unicode(&mut chars, 8)?,
+                    _ => return None,
+                }),
+                c if c.is_control() && c != '\t' => return None,
+                c => result.push(c),
+            }
+        },
+        _ => return None,
+    }
+    match strip_comment(chars.as_str()) {
+        Some("") => Some(result),
+        _ => None,
+    }
+}
+
+/// Parses the hex digits of a unicode escape sequence.
+fn parse_unicode(chars: &mut std::str::Chars, digits: usize) -> Option<char> {
+    let hex: String = chars.take(digits).collect();
+    if hex.len() != digits {
+        return None;
+    }
+    std::char::from_u32(u32::from_str_radix(&hex, 16).ok()?)
+}
+
+/// Removes a trailing comment and surrounding whitespace from a value without strings.
+fn strip_comment(value: &str) -> Option<&str> {
+    Some(value.split('#').next()?.trim())
+}
+
+/// Parses a TOML array of two integers, followed by an optional comment.
+fn parse_pair<T: std::str::FromStr>(value: &str) -> Option<(T, T)> {
+    let inner = strip_comment(value)?.strip_prefix('[')?.strip_suffix(']')?;
+    let mut parts = inner.split(',').map(|v| v.trim().parse::<T>());
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(Ok(a)), Some(Ok(b)), None) => Some((a, b)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::layout::{Layout, SavedLayout, Tiling, WindowPlacement};
+
+    #[test]
+    fn tiling() {
+        let sizes = [(600, 400), (300, 200), (600, 400)];
+        let layout = Layout::new(1000, 800).with_gap(10);
+        assert_eq!(layout.positions(&sizes), vec![(0, 0), (610, 0), (0, 410)]);
+        let layout = layout.with_tiling(Tiling::Grid(2)).with_origin(5, 5);
+        assert_eq!(layout.positions(&sizes), vec![(5, 5), (615, 5), (5, 415)]);
+        let layout = layout.with_tiling(Tiling::Stacked);
+        assert_eq!(layout.positions(&sizes), vec![(5, 5), (5, 415), (5, 625)]);
+        let layout = layout.with_tiling(Tiling::SideBySide);
+        assert_eq!(layout.positions(&sizes), vec![(5, 5), (615, 5), (925, 5)]);
+    }
+
+    #[test]
+    fn saved_layout() {
+        let layout = SavedLayout {
+            windows: vec![
+                WindowPlacement {
+                    title: "Chart \\ \"A\"".to_string(),
+                    position: Some((-10, 20)),
+                    size: (600, 400),
+                },
+                WindowPlacement {
+                    title: "Map".to_string(),
+                    position: None,
+                    size: (300, 200),
+                },
+            ],
+        };
+        let text = layout.to_text();
+        assert!(text
+            .starts_with("[[window]]\ntitle = \"Chart \\\\ \\\"A\\\"\"\nposition = [-10, 20]\n"));
+        assert_eq!(SavedLayout::from_text(&text).unwrap(), layout);
+        assert_eq!(layout.placement("Map").unwrap().size, (300, 200));
+
+        assert!(SavedLayout::from_text("title = \"X\"").is_err());
+        assert!(SavedLayout::from_text("[[window]]\nsize = [1, 2, 3]").is_err());
+        assert!(SavedLayout::from_text("[[window]]\ntitle = \"X\\q\"").is_err());
+        assert!(SavedLayout::from_text("[[window]]\nsize = { x = 1, y = 2 }").is_err());
+        assert!(SavedLayout::from_text("[window]\nsize = [1, 2]").is_err());
+    }
+
+    #[test]
+    fn saved_layout_roundtrip() {
+        let layout = SavedLayout {
+            windows: vec![WindowPlacement {
+                title: "Tab\there\nnew # line \u{7}\u{1b} \"é\" 'x'\r".to_string(),
+                position: Some((0, -1)),
+                size: (1, 2),
+            }],
+        };
+        let text = layout.to_text();
+        assert_eq!(text.lines().count(), 5);
+        assert!(text.contains("\\t") && text.contains("\\n") && text.contains("\\u0007"));
+        assert_eq!(SavedLayout::from_text(&text).unwrap(), layout);
+
+        let text =
+            "# Layout\n[[window]] # first\ntitle = 'C:\\x' # path\nposition = [1, 2] # pos\n\
+                    size = [3, 4]\n[[window]]\ntitle = \"\\u00e9 # \\U0001F600\"\nsize = [5, 6]\n";
+        let layout = SavedLayout::from_text(text).unwrap();
+        assert_eq!(layout.windows()[0].title, "C:\\x");
+        assert_eq!(layout.windows()[0].position, Some((1, 2)));
+        assert_eq!(layout.windows()[1].title, "é # 😀");
+        assert_eq!(layout.windows()[1].size, (5, 6));
+    }
+}
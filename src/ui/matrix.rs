@@ -0,0 +1,290 @@
+//!
+//! Renders matrices as colored cells with row and column labels and a color bar, e.g. for confusion or interaction matrices.
+//!
+//! # Example
+//! ```no_run
+//! use easy_graph::color::LinearColorMap;
+//! use easy_graph::color::style::{BLUE, WHITE};
+//! use easy_graph::ui::matrix::MatrixChart;
+//! use easy_graph::ui::window::WindowBuilder;
+//!
+//! let mut matrix = MatrixChart::new(LinearColorMap::new(&[&WHITE, &BLUE]))
+//!     .with_row_labels(&["Wolf", "Sheep", "Grass"])
+//!     .with_column_labels(&["Wolf", "Sheep", "Grass"])
+//!     .with_values(2);
+//! let mut win = WindowBuilder::new().with_dimensions(500, 400).build();
+//!
+//! for i in 0..10 { // change upper limit for longer run!
+//!     let v = i as f64 / 10.0;
+//!     matrix.set_values(vec![
+//!         vec![0.0, v, 0.0],
+//!         vec![-v, 0.0, 1.0 - v],
+//!         vec![0.0, v - 1.0, 0.0],
+//!     ]);
+//!     matrix.draw_window(&mut win);
+//! }
+//! ```
+//!
+
+use crate::color::ColorMap;
+use crate::geom::grid::Grid;
+use crate::ui::window::BufferWindow;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+
+/// Font size of labels, in pixels.
+const FONT_SIZE: f64 = 14.0;
+/// Padding around labels, in pixels.
+const PADDING: i32 = 5;
+/// Width of the color bar, in pixels.
+const BAR_WIDTH: i32 = 20;
+/// Number of tick labels on the color bar.
+const BAR_TICKS: usize = 5;
+
+///
+/// A matrix of values drawn as colored cells, with labeled rows and columns and a color bar.
+///
+/// Cells fill the drawing area, excluding space for labels and the color bar.
+///
+pub struct MatrixChart<M: ColorMap> {
+    values: Vec<Vec<f64>>,
+    row_labels: Vec<String>,
+    column_labels: Vec<String>,
+    color_map: M,
+    range: Option<(f64, f64)>,
+    precision: Option<usize>,
+    color_bar: bool,
+}
+
+impl<M: ColorMap> MatrixChart<M> {
+    /// Creates an empty matrix chart using the given color map.
+    pub fn new(color_map: M) -> Self {
+        MatrixChart {
+            values: Vec::new(),
+            row_labels: Vec::new(),
+            column_labels: Vec::new(),
+            color_map,
+            range: None,
+            precision: None,
+            color_bar: true,
+        }
+    }
+    /// Sets the labels of rows, from top to bottom.
+    pub fn with_row_labels(mut self, labels: &[&str]) -> Self {
+        self.row_labels = labels.iter().map(|l| l.to_string()).collect();
+        self
+    }
+    /// Sets the labels of columns, from left to right.
+    pub fn with_column_labels(mut self, labels: &[&str]) -> Self {
+        self.column_labels = labels.iter().map(|l| l.to_string()).collect();
+        self
+    }
+    /// Sets a fixed value range for the color map. Default: the range of the current values.
+    pub fn with_range(mut self, min: f64, max: f64) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+    /// Shows values in cells, with the given number of decimal places.
+    pub fn with_values(mut self, precision: usize) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+    /// Hides the color bar.
+    pub fn without_color_bar(mut self) -> Self {
+        self.color_bar = false;
+        self
+    }
+
+    /// Sets the values, as a vector of rows.
+    ///
+    /// # Panics
+    /// Panics if rows have different lengths.
+    pub fn set_values(&mut self, rows: Vec<Vec<f64>>) {
+        if rows.windows(2).any(|w| w[0].len() != w[1].len()) {
+            panic!("All rows of a matrix must have the same length!");
+        }
+        self.values = rows;
+    }
+    /// Sets the values from a grid, with grid rows (y) as matrix rows.
+    pub fn set_grid(&mut self, grid: &Grid<f64>) {
+        self.values = (0..grid.height() as usize)
+            .map(|y| {
+                (0..grid.width() as usize)
+                    .map(|x| *grid.get(x, y))
+                    .collect()
+            })
+            .collect();
+    }
+    /// Number of rows and columns.
+    pub fn shape(&self) -> (usize, usize) {
+        (
+            self.values.len(),
+            self.values.first().map(|r| r.len()).unwrap_or(0),
+        )
+    }
+    /// The value range used for the color map: the fixed range if set, otherwise the range of finite values.
+    pub fn value_range(&self) -> (f64, f64) {
+        if let Some(range) = self.range {
+            return range;
+        }
+        let (min, max) = self
+            .values
+            .iter()
+            .flatten()
+            .filter(|v| v.is_finite())
+            .fold((f64::MAX, f64::MIN), |(min, max), v| {
+                (min.min(*v), max.max(*v))
+            });
+        if min > max {
+            (0.0, 1.0)
+        } else if min == max {
+            (min - 0.5, max + 0.5)
+        } else {
+            (min, max)
+        }
+    }
+
+    /// Draws the matrix onto a drawing area, filling the area.
+    pub fn draw<DB: DrawingBackend>(&self, area: &DrawingArea<DB, Shift>) {
+        area.fill(&WHITE).unwrap();
+        let font = ("sans-serif", FONT_SIZE).into_font();
+        let text_width = |labels: &mut dyn Iterator<Item = &String>| {
+            labels
+                .map(|l| area.estimate_text_size(l, &font).unwrap().0 as i32)
+                .max()
+                .unwrap_or(0)
+        };
+        let (width, height) = area.dim_in_pixel();
+        let (rows, cols) = self.shape();
+        let (min, max) = self.value_range();
+        let bar_labels: Vec<_> = (0..BAR_TICKS)
+            .map(|i| min + (max - min) * i as f64 / (BAR_TICKS - 1) as f64)
+            .map(|v| format!("{:.*}", self.precision.unwrap_or(2), v))
+            .collect();
+
+        let left = PADDING + text_width(&mut self.row_labels.iter()) + PADDING;
+        let top = if self.column_labels.is_empty() {
+            PADDING
+        } else {
+            FONT_SIZE as i32 + 2 * PADDING
+        };
+        let right = if self.color_bar {
+            width as i32 - 3 * PADDING - BAR_WIDTH - text_width(&mut bar_labels.iter())
+        } else {
+            width as i32 - PADDING
+        };
+        let bottom = height as i32 - PADDING;
+        if rows == 0 || cols == 0 || right <= left || bottom <= top {
+            return;
+        }
+        let cell = |row: usize, col: usize| {
+            (
+                left + (col as i32 * (right - left)) / cols as i32,
+                top + (row as i32 * (bottom - top)) / rows as i32,
+            )
+        };
+
+        for (r, row) in self.values.iter().enumerate() {
+            for (c, value) in row.iter().enumerate() {
+                let (x0, y0) = cell(r, c);
+                let (x1, y1) = cell(r + 1, c + 1);
+                if value.is_finite() {
+                    let color = self.color(*value, min, max);
+                    area.draw(&Rectangle::new([(x0, y0), (x1, y1)], color.filled()))
+                        .unwrap();
+                }
+                if let Some(precision) = self.precision {
+                    let text = format!("{:.*}", precision, value);
+                    let (w, _) = area.estimate_text_size(&text, &font).unwrap();
+                    let pos = ((x0 + x1 - w as i32) / 2, (y0 + y1 - FONT_SIZE as i32) / 2);
+                    area.draw_text(&text, &font.color(&BLACK), pos).unwrap();
+                }
+            }
+        }
+        area.draw(&Rectangle::new([(left, top), (right, bottom)], &BLACK))
+            .unwrap();
+
+        for (r, label) in self.row_labels.iter().enumerate().take(rows) {
+            let (_, y0) = cell(r, 0);
+            let (_, y1) = cell(r + 1, 0);
+            let pos = (PADDING, (y0 + y1 - FONT_SIZE as i32) / 2);
+            area.draw_text(label, &font.color(&BLACK), pos).unwrap();
+        }
+        for (c, label) in self.column_labels.iter().enumerate().take(cols) {
+            let (x0, _) = cell(0, c);
+            let (x1, _) = cell(0, c + 1);
+            let (w, _) = area.estimate_text_size(label, &font).unwrap();
+            let pos = ((x0 + x1 - w as i32) / 2, PADDING);
+            area.draw_text(label, &font.color(&BLACK), pos).unwrap();
+        }
+
+        if self.color_bar {
+            let x0 = right + PADDING;
+            let x1 = x0 + BAR_WIDTH;
+            for y in top..bottom {
+                let frac = (bottom - 1 - y) as f64 / (bottom - top - 1).max(1) as f64;
+                let color = self.color(min + frac * (max - min), min, max);
+                area.draw(&PathElement::new(vec![(x0, y), (x1, y)], &color))
+                    .unwrap();
+            }
+            area.draw(&Rectangle::new([(x0, top), (x1, bottom)], &BLACK))
+                .unwrap();
+            for (i, label) in bar_labels.iter().enumerate() {
+                let frac = i as f64 / (BAR_TICKS - 1) as f64;
+                let y = bottom - (frac * (bottom - top) as f64) as i32;
+                let pos = (x1 + PADDING, y - FONT_SIZE as i32 / 2);
+                area.draw_text(label, &font.color(&BLACK), pos).unwrap();
+            }
+        }
+    }
+
+    /// Draws the matrix into a window, filling the window.
+    pub fn draw_window(&self, window: &mut BufferWindow) {
+        window.draw(|b| {
+            let root = b.into_drawing_area();
+            self.draw(&root);
+        });
+    }
+
+    /// Maps a value to a color, clamped to the range.
+    fn color(&self, value: f64, min: f64, max: f64) -> RGBColor {
+        let norm = ((value - min) / (max - min)).clamp(0.0, 1.0);
+        self.color_map.get_color_norm(norm)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::color::LinearColorMap;
+    use crate::geom::grid::Grid;
+    use crate::ui::matrix::MatrixChart;
+    use plotters::prelude::*;
+
+    #[test]
+    fn draw_matrix() {
+        let mut matrix = MatrixChart::new(LinearColorMap::new(&[&BLUE, &RED]))
+            .with_row_labels(&["a", "b"])
+            .with_column_labels(&["x", "y"]);
+        let mut grid = Grid::new(2, 2, 0.0);
+        grid.set(1, 0, 1.0);
+        matrix.set_grid(&grid);
+        assert_eq!(matrix.shape(), (2, 2));
+        assert_eq!(matrix.values[0], vec![0.0, 1.0]);
+        assert_eq!(matrix.value_range(), (0.0, 1.0));
+
+        let (w, h) = (200, 100);
+        let mut buffer = vec![0; 3 * w * h];
+        {
+            let root =
+                BitMapBackend::with_buffer(&mut buffer, (w as u32, h as u32)).into_drawing_area();
+            matrix.draw(&root);
+        }
+        let pixels: Vec<_> = buffer.chunks(3).collect();
+        assert!(pixels.contains(&&[255, 0, 0][..]));
+        assert!(pixels.contains(&&[0, 0, 255][..]));
+        // Top-right cell is red, bottom-right is blue
+        let row = |y: usize| &pixels[y * w..(y + 1) * w];
+        let red_x = row(40).iter().rposition(|p| *p == [255, 0, 0]).unwrap();
+        assert_eq!(row(80)[red_x], [0, 0, 255]);
+    }
+}
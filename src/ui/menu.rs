@@ -0,0 +1,218 @@
+//!
+//! Right-click context menu of [`Chart`](../chart/struct.Chart.html) windows, drawn into the window's buffer.
+//!
+
+use plotters::coord::Shift;
+use plotters::prelude::*;
+
+/// Font size of menu items, in pixels.
+const FONT_SIZE: f64 = 13.0;
+/// Horizontal padding of menu items, in pixels.
+const PADDING: f64 = 8.0;
+/// Height of menu items, in pixels.
+const ITEM_HEIGHT: f64 = 22.0;
+
+/// The action of a context menu item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum MenuAction {
+    /// Saves a screenshot of the chart.
+    SavePng,
+    /// Saves the data of all series to a CSV file.
+    ExportCsv,
+    /// Removes the data of all series.
+    ClearData,
+    /// Restores the initial axis limits.
+    ResetZoom,
+    /// Runs the user callback with the given index.
+    Custom(usize),
+}
+
+/// A context menu, opened by a right click and closed by a left click.
+pub(crate) struct ContextMenu {
+    items: Vec<(String, MenuAction)>,
+    position: Option<(i32, i32)>,
+    hovered: Option<usize>,
+    buttons: (bool, bool),
+    /// If the left button is held down since the click that closed the menu.
+    consumed: bool,
+}
+
+impl ContextMenu {
+    /// Creates a closed menu with the built-in actions.
+    pub(crate) fn new() -> Self {
+        ContextMenu {
+            items: vec![
+                ("Save PNG".to_string(), MenuAction::SavePng),
+                ("Export CSV".to_string(), MenuAction::ExportCsv),
+                ("Clear data".to_string(), MenuAction::ClearData),
+                ("Reset zoom".to_string(), MenuAction::ResetZoom),
+            ],
+            position: None,
+            hovered: None,
+            buttons: (false, false),
+            consumed: false,
+        }
+    }
+    /// Appends an item.
+    pub(crate) fn add_item(&mut self, label: &str, action: MenuAction) {
+        self.items.push((label.to_string(), action));
+    }
+    /// Returns if the menu is open.
+    pub(crate) fn is_open(&self) -> bool {
+        self.position.is_some()
+    }
+    /// Returns if the menu handles the left mouse button: while it is open,
+    /// and while the click that closed it is held down. Other left click handlers should ignore the mouse then.
+    pub(crate) fn captures_mouse(&self) -> bool {
+        self.is_open() || self.consumed
+    }
+
+    /// Processes the mouse state of a window update: opens the menu at a right click,
+    /// and closes it at a left click, selecting the clicked item if any.
+    ///
+    /// Returns if the menu needs to be redrawn, and the selected action.
+    pub(crate) fn update(
+        &mut self,
+        mouse: Option<(i32, i32)>,
+        buttons: (bool, bool),
+        dim: (usize, usize),
+        ui_scale: f64,
+    ) -> (bool, Option<MenuAction>) {
+        let clicked = (buttons.0 && !self.buttons.0, buttons.1 && !self.buttons.1);
+        self.buttons = buttons;
+        self.consumed &= buttons.0;
+        let mut changed = false;
+        let mut selected = None;
+        if let (true, Some(pos)) = (clicked.1, mouse) {
+            let (width, height) = self.size(ui_scale);
+            self.position = Some((
+                pos.0.min(dim.0 as i32 - width).max(0),
+                pos.1.min(dim.1 as i32 - height).max(0),
+            ));
+            changed = true;
+        } else if clicked.0 && self.is_open() {
+            selected = mouse
+                .and_then(|pos| self.item_at(pos, ui_scale))
+                .map(|index| self.items[index].1);
+            self.position = None;
+            self.consumed = true;
+            changed = true;
+        }
+        let hovered = mouse.and_then(|pos| self.item_at(pos, ui_scale));
+        if hovered != self.hovered {
+            self.hovered = hovered;
+            changed |= self.is_open();
+        }
+        (changed, selected)
+    }
+
+    /// The index of the item under a pixel, if the menu is open.
+    fn item_at(&self, pos: (i32, i32), ui_scale: f64) -> Option<usize> {
+        let corner = self.position?;
+        let (width, height) = self.size(ui_scale);
+        let (x, y) = (pos.0 - corner.0, pos.1 - corner.1);
+        if x < 0 || y < 0 || x >= width || y >= height {
+            return None;
+        }
+        Some((y / (ITEM_HEIGHT * ui_scale) as i32).min(self.items.len() as i32 - 1) as usize)
+    }
+    /// The size of the menu in pixels.
+    fn size(&self, ui_scale: f64) -> (i32, i32) {
+        let font = ("sans-serif", FONT_SIZE * ui_scale).into_font();
+        let text = self
+            .items
+            .iter()
+            .map(|(label, _)| font.box_size(label).map(|s| s.0).unwrap_or(0) as i32)
+            .max()
+            .unwrap_or(0);
+        (
+            text + (2.0 * PADDING * ui_scale) as i32,
+            self.items.len() as i32 * (ITEM_HEIGHT * ui_scale) as i32,
+        )
+    }
+
+    /// Draws the menu onto a drawing area covering the window, if it is open.
+    pub(crate) fn draw<DB: DrawingBackend>(&self, area: &DrawingArea<DB, Shift>, ui_scale: f64) {
+        let (x0, y0) = match self.position {
+            Some(pos) => pos,
+            None => return,
+        };
+        let (width, height) = self.size(ui_scale);
+        let font = ("sans-serif", FONT_SIZE * ui_scale).into_font();
+        let item_height = (ITEM_HEIGHT * ui_scale) as i32;
+        let corner = (x0 + width, y0 + height);
+        area.draw(&Rectangle::new([(x0, y0), corner], WHITE.filled()))
+            .unwrap();
+        for (i, (label, _)) in self.items.iter().enumerate() {
+            let y = y0 + i as i32 * item_height;
+            if self.hovered == Some(i) {
+                area.draw(&Rectangle::new(
+                    [(x0, y), (corner.0, y + item_height)],
+                    BLUE.mix(0.2).filled(),
+                ))
+                .unwrap();
+            }
+            let text_height = font.box_size(label).map(|s| s.1).unwrap_or(0) as i32;
+            let pos = (
+                x0 + (PADDING * ui_scale) as i32,
+                y + (item_height - text_height) / 2,
+            );
+            area.draw_text(label, &font.color(&BLACK), pos).unwrap();
+        }
+        area.draw(&Rectangle::new([(x0, y0), corner], &BLACK))
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::menu::{ContextMenu, MenuAction};
+
+    #[test]
+    fn open_and_select() {
+        let mut menu = ContextMenu::new();
+        menu.add_item("Custom", MenuAction::Custom(0));
+        let dim = (400, 300);
+        assert_eq!(
+            menu.update(Some((10, 10)), (true, false), dim, 1.0),
+            (false, None)
+        );
+        assert!(!menu.is_open());
+
+        assert_eq!(
+            menu.update(Some((10, 10)), (false, true), dim, 1.0),
+            (true, None)
+        );
+        assert!(menu.is_open());
+        // holding the button does not reopen the menu
+        assert_eq!(
+            menu.update(Some((10, 10)), (false, true), dim, 1.0),
+            (false, None)
+        );
+
+        let item = (20, 10 + 2 * 22 + 5);
+        assert_eq!(
+            menu.update(Some(item), (false, false), dim, 1.0),
+            (true, None)
+        );
+        assert_eq!(
+            menu.update(Some(item), (true, false), dim, 1.0),
+            (true, Some(MenuAction::ClearData))
+        );
+        assert!(!menu.is_open());
+        // the selecting click is consumed until released
+        assert!(menu.captures_mouse());
+        menu.update(Some(item), (true, false), dim, 1.0);
+        assert!(menu.captures_mouse());
+        menu.update(Some(item), (false, false), dim, 1.0);
+        assert!(!menu.captures_mouse());
+
+        menu.update(Some((395, 295)), (false, true), dim, 1.0);
+        let (x, y) = menu.position.unwrap();
+        let (width, height) = menu.size(1.0);
+        assert_eq!((x + width, y + height), (400, 300));
+        menu.update(None, (false, false), dim, 1.0);
+        assert_eq!(menu.update(None, (true, false), dim, 1.0), (true, None));
+        assert!(!menu.is_open());
+    }
+}
@@ -0,0 +1,377 @@
+//!
+//! Metadata of saved chart images, to keep screenshots traceable, e.g. from long experiment campaigns.
+//!
+//! [`ImageMetadata`](struct.ImageMetadata.html) holds the chart title, axis ranges, series names and data counts,
+//! the time of saving and the crate version. It can be written as a JSON sidecar file next to the image
+//! (`chart.png` and `chart.json`), or embedded as text chunks into PNG images, see [`MetadataFormat`](enum.MetadataFormat.html).
+//!
+//! Metadata is written by [`Chart::save_image()`](../chart/struct.Chart.html#method.save_image),
+//! and by the screenshot hotkey if enabled via [`ChartBuilder::with_screenshot_metadata()`](../chart/struct.ChartBuilder.html#method.with_screenshot_metadata).
+//!
+//! # Example
+//! ```no_run
+//! use easy_graph::ui::chart::{ChartBuilder, Series};
+//! use easy_graph::ui::metadata::{read_png_text, MetadataFormat};
+//! use easy_graph::color::style::RED;
+//!
+//! let mut chart = ChartBuilder::new()
+//!     .with_title("Run 42")
+//!     .add_series(Series::line("Population", &RED))
+//!     .with_screenshot_metadata(MetadataFormat::Both)
+//!     .build();
+//! for t in 0..100 {
+//!     chart.push_time_series(t as f64, &[(t * t) as f64]);
+//! }
+//! chart.update();
+//! chart.save_image("run_42.png", MetadataFormat::Both).unwrap();
+//!
+//! for (key, text) in read_png_text("run_42.png").unwrap() {
+//!     println!("{}: {}", key, text);
+//! }
+//! ```
+//!
+
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Keyword of the PNG text chunk holding the JSON metadata.
+pub const PNG_METADATA_KEY: &str = "Comment";
+
+///
+/// Where to write the metadata of saved images.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MetadataFormat {
+    /// No metadata.
+    #[default]
+    None,
+    /// A JSON file next to the image, with the same name and extension `.json`.
+    Sidecar,
+    /// Text chunks embedded into the image. Ignored for formats other than PNG.
+    PngText,
+    /// Both a sidecar file and PNG text chunks.
+    Both,
+}
+
+impl MetadataFormat {
+    /// If a JSON sidecar is written.
+    pub fn sidecar(self) -> bool {
+        self == MetadataFormat::Sidecar || self == MetadataFormat::Both
+    }
+    /// If PNG text chunks are written.
+    pub fn png_text(self) -> bool {
+        self == MetadataFormat::PngText || self == MetadataFormat::Both
+    }
+}
+
+///
+/// Data count of a single series in [`ImageMetadata`](struct.ImageMetadata.html).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct SeriesMetadata {
+    /// The series' name.
+    pub name: String,
+    /// The series' unit, if any.
+    pub unit: Option<String>,
+    /// Number of data points.
+    pub count: usize,
+}
+
+///
+/// Metadata of a saved chart image. See the [module docs](index.html).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageMetadata {
+    /// The chart's title.
+    pub title: String,
+    /// Version of this crate.
+    pub version: String,
+    /// Time of saving, in milliseconds since the Unix epoch.
+    pub timestamp: u128,
+    /// The visible x range, if the chart was drawn.
+    pub x_range: Option<(f64, f64)>,
+    /// The visible y range, if the chart was drawn.
+    pub y_range: Option<(f64, f64)>,
+    /// The chart's series.
+    pub series: Vec<SeriesMetadata>,
+}
+
+impl ImageMetadata {
+    /// Creates metadata without ranges and series, timestamped now.
+    pub fn new(title: &str) -> Self {
+        ImageMetadata {
+            title: title.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            timestamp: unix_millis(),
+            x_range: None,
+            y_range: None,
+            series: vec![],
+        }
+    }
+
+    /// Sets the timestamp to now.
+    pub fn touch(&mut self) {
+        self.timestamp = unix_millis();
+    }
+
+    /// Formats the metadata as a JSON object.
+    pub fn to_json(&self) -> String {
+        let range = |range: Option<(f64, f64)>| match range {
+            Some((min, max)) => format!("[{},{}]", json_number(min), json_number(max)),
+            None => "null".to_string(),
+        };
+        let series: Vec<_> = self
+            .series
+            .iter()
+            .map(|s| {
+                format!(
+                    r#"{{"name":{},"unit":{},"count":{}}}"#,
+                    json_string(&s.name),
+                    s.unit.as_deref().map_or("null".to_string(), json_string),
+                    s.count
+                )
+            })
+            .collect();
+        format!(
+            r#"{{"title":{},"version":{},"timestamp":{},"x_range":{},"y_range":{},"series":[{}]}}"#,
+            json_string(&self.title),
+            json_string(&self.version),
+            self.timestamp,
+            range(self.x_range),
+            range(self.y_range),
+            series.join(",")
+        )
+    }
+
+    /// The entries written as PNG text chunks: title, software and the JSON metadata (keyword [`PNG_METADATA_KEY`](constant.PNG_METADATA_KEY.html)).
+    pub fn png_text(&self) -> Vec<(String, String)> {
+        vec![
+            ("Title".to_string(), self.title.clone()),
+            (
+                "Software".to_string(),
+                format!("easy_graph {}", self.version),
+            ),
+            (PNG_METADATA_KEY.to_string(), self.to_json()),
+        ]
+    }
+
+    /// Saves an RGB buffer as an image, with metadata in the given format.
+    /// The image format is derived from the file extension.
+    ///
+    /// # Panics
+    /// Panics if the length of `rgb` does not equal 3 * width * height.
+    pub fn save_image<P: AsRef<Path>>(
+        &self,
+        path: P,
+        rgb: &[u8],
+        dim: (u32, u32),
+        format: MetadataFormat,
+    ) -> Result<(), image::ImageError> {
+        let path = path.as_ref();
+        if rgb.len() != 3 * dim.0 as usize * dim.1 as usize {
+            panic!("Length of RGB buffer must equal 3 * width * height of the image!");
+        }
+        let is_png = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("png"));
+        if format.png_text() && is_png {
+            let mut png = vec![];
+            image::png::PNGEncoder::new(&mut png).encode(
+                rgb,
+                dim.0,
+                dim.1,
+                image::ColorType::Rgb8,
+            )?;
+            fs::write(path, insert_png_text(&png, &self.png_text()))?;
+        } else {
+            image::save_buffer(path, rgb, dim.0, dim.1, image::ColorType::Rgb8)?;
+        }
+        if format.sidecar() {
+            fs::write(sidecar_path(path), self.to_json())?;
+        }
+        Ok(())
+    }
+}
+
+/// The path of the JSON sidecar file of an image: the same path with extension `.json`.
+pub fn sidecar_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    path.as_ref().with_extension("json")
+}
+
+/// Reads the text chunks of a PNG file, as (keyword, text) pairs. Compressed chunks are skipped.
+pub fn read_png_text<P: AsRef<Path>>(path: P) -> io::Result<Vec<(String, String)>> {
+    let mut bytes = vec![];
+    File::open(path)?.read_to_end(&mut bytes)?;
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Not a valid PNG file");
+    if bytes.len() < 8 || bytes[..8] != PNG_SIGNATURE {
+        return Err(invalid());
+    }
+    let mut entries = vec![];
+    let mut pos = 8;
+    while pos + 12 <= bytes.len() {
+        let len = u32::from_be_bytes([bytes[pos], bytes[pos + 1], bytes[pos + 2], bytes[pos + 3]])
+            as usize;
+        let end = pos + 12 + len;
+        if end > bytes.len() {
+            return Err(invalid());
+        }
+        let data = &bytes[pos + 8..pos + 8 + len];
+        match &bytes[pos + 4..pos + 8] {
+            b"tEXt" => {
+                let mut parts = data.splitn(2, |b| *b == 0);
+                if let (Some(key), Some(text)) = (parts.next(), parts.next()) {
+                    entries.push((latin1(key), latin1(text)));
+                }
+            }
+            b"iTXt" => {
+                // keyword \0 compression flag, method, language \0 translated keyword \0 text
+                let mut parts = data.splitn(2, |b| *b == 0);
+                if let (Some(key), Some(rest)) = (parts.next(), parts.next()) {
+                    if rest.len() >= 2 && rest[0] == 0 {
+                        let mut parts = rest[2..].splitn(3, |b| *b == 0);
+                        if let (Some(_), Some(_), Some(text)) =
+                            (parts.next(), parts.next(), parts.next())
+                        {
+                            entries.push((latin1(key), String::from_utf8_lossy(text).to_string()));
+                        }
+                    }
+                }
+            }
+            b"IEND" => break,
+            _ => {}
+        }
+        pos = end;
+    }
+    Ok(entries)
+}
+
+const PNG_SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+
+/// Inserts uncompressed international text chunks after the header chunk of an encoded PNG image.
+fn insert_png_text(png: &[u8], entries: &[(String, String)]) -> Vec<u8> {
+    // Signature, and the header chunk with length, type, 13 bytes of data and CRC
+    let header_end = 8 + 4 + 4 + 13 + 4;
+    let mut out = Vec::with_capacity(png.len() + 256);
+    out.extend_from_slice(&png[..header_end]);
+    for (key, text) in entries {
+        let mut chunk = b"iTXt".to_vec();
+        // Keywords are restricted to 1 - 79 Latin-1 characters
+        let key: Vec<u8> = key
+            .chars()
+            .filter(|c| (*c as u32) > 0 && (*c as u32) < 256)
+            .take(79)
+            .map(|c| c as u8)
+            .collect();
+        chunk.extend_from_slice(&key);
+        chunk.extend_from_slice(&[0, 0, 0, 0, 0]);
+        chunk.extend_from_slice(text.as_bytes());
+        out.extend_from_slice(&(chunk.len() as u32 - 4).to_be_bytes());
+        out.extend_from_slice(&chunk);
+        out.extend_from_slice(&crc32(&chunk).to_be_bytes());
+    }
+    out.extend_from_slice(&png[header_end..]);
+    out
+}
+
+/// CRC-32 as used by PNG chunks.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffff_u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| *b as char).collect()
+}
+
+fn unix_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("System clock was before 1970.")
+        .as_millis()
+}
+
+/// Formats a number for JSON, with non-finite values as `null`.
+pub(crate) fn json_number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+/// Quotes and escapes a string for JSON.
+pub(crate) fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::metadata::{
+        crc32, read_png_text, sidecar_path, ImageMetadata, MetadataFormat, SeriesMetadata,
+        PNG_METADATA_KEY,
+    };
+    use std::path::Path;
+
+    #[test]
+    fn metadata_json() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        let mut meta = ImageMetadata::new("Run \"1\"");
+        meta.timestamp = 1000;
+        meta.version = "0.1.0".to_string();
+        meta.x_range = Some((0.0, 1.5));
+        meta.series.push(SeriesMetadata {
+            name: "A".to_string(),
+            unit: Some("m".to_string()),
+            count: 10,
+        });
+        assert_eq!(
+            meta.to_json(),
+            r#"{"title":"Run \"1\"","version":"0.1.0","timestamp":1000,"x_range":[0,1.5],"y_range":null,"series":[{"name":"A","unit":"m","count":10}]}"#
+        );
+        assert_eq!(sidecar_path("a/b.png"), Path::new("a/b.json"));
+    }
+
+    #[test]
+    fn save_with_metadata() {
+        let dir = std::env::temp_dir().join("easy_graph_metadata_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("image.png");
+        let meta = ImageMetadata::new("Titlé");
+        meta.save_image(&path, &[255; 3 * 4 * 2], (4, 2), MetadataFormat::Both)
+            .unwrap();
+
+        let image = image::open(&path).unwrap().to_rgb();
+        assert_eq!(image.dimensions(), (4, 2));
+        let text = read_png_text(&path).unwrap();
+        assert_eq!(text[0], ("Title".to_string(), "Titlé".to_string()));
+        assert_eq!(text[2], (PNG_METADATA_KEY.to_string(), meta.to_json()));
+        let json = std::fs::read_to_string(sidecar_path(&path)).unwrap();
+        assert_eq!(json, meta.to_json());
+
+        meta.save_image(&path, &[0; 3 * 4 * 2], (4, 2), MetadataFormat::None)
+            .unwrap();
+        assert!(read_png_text(&path).unwrap().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
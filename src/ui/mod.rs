@@ -1,4 +1,38 @@
+mod axis;
+pub mod camera;
 pub mod chart;
+pub mod clock;
+#[cfg(feature = "egui")]
+pub mod embed;
+pub mod figure;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub mod graph;
+pub mod grid;
+pub mod input;
+pub mod interpolate;
+pub mod layer;
+pub mod layout;
+pub mod matrix;
+mod menu;
+pub mod metadata;
+pub mod pattern;
+#[cfg(feature = "profiling")]
+pub mod profile;
+pub mod record;
+pub mod retention;
+pub mod scatter3d;
+pub mod server;
+pub mod source;
+pub mod store;
+#[cfg(feature = "http")]
+pub mod stream;
+pub mod summary;
+pub mod tail;
+pub mod timeline;
+#[cfg(feature = "trace")]
+pub mod trace;
+pub mod widgets;
 pub mod window;
 
 #[doc(no_inline)]
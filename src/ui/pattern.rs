@@ -0,0 +1,264 @@
+//!
+//! Pattern fills like hatching, dots and stripes, to keep charts distinguishable in grayscale prints or for color-blind users.
+//!
+//! Patterns are selected per series, see [`Series::with_pattern()`](../chart/struct.Series.html#method.with_pattern)
+//! and [`EnsembleSeries::with_pattern()`](../chart/struct.EnsembleSeries.html#method.with_pattern).
+//! They are applied to envelopes, point markers and legend entries.
+//! Polygons in custom drawing code can be filled via [`Pattern::fill_polygon()`](enum.Pattern.html#method.fill_polygon).
+//!
+//! # Example
+//! ```no_run
+//! use easy_graph::ui::chart::{ChartBuilder, EnsembleSeries};
+//! use easy_graph::ui::pattern::Pattern;
+//! use easy_graph::color::style::{BLUE, RED};
+//!
+//! let mut chart = ChartBuilder::new()
+//!     .add_ensemble(EnsembleSeries::new("A", &RED).with_pattern(Pattern::Hatch))
+//!     .add_ensemble(EnsembleSeries::new("B", &BLUE).with_pattern(Pattern::Dots))
+//!     .build();
+//! for t in 0..100 {
+//!     let t = t as f64;
+//!     chart.push_ensemble(0, t, &[t.sin(), t.sin() + 0.5]);
+//!     chart.push_ensemble(1, t, &[t.cos(), t.cos() + 0.5]);
+//! }
+//! chart.update();
+//! ```
+//!
+
+use plotters::coord::Shift;
+use plotters::drawing::backend::{BackendCoord, DrawingErrorKind};
+use plotters::element::{Drawable, PointCollection};
+use plotters::prelude::*;
+
+/// Distance between pattern lines or dots at UI scale 1, in pixels.
+pub const PATTERN_SPACING: f64 = 6.0;
+
+///
+/// A fill pattern, drawn in the series' color.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Pattern {
+    /// Filled completely.
+    #[default]
+    Solid,
+    /// Diagonal lines, rising to the right.
+    Hatch,
+    /// Diagonal lines, falling to the right.
+    BackHatch,
+    /// Diagonal lines in both directions.
+    CrossHatch,
+    /// A regular grid of dots.
+    Dots,
+    /// Horizontal stripes.
+    HorizontalStripes,
+    /// Vertical stripes.
+    VerticalStripes,
+}
+
+impl Pattern {
+    /// All patterns, e.g. for assigning distinct patterns to series.
+    pub const ALL: [Pattern; 7] = [
+        Pattern::Solid,
+        Pattern::Hatch,
+        Pattern::BackHatch,
+        Pattern::CrossHatch,
+        Pattern::Dots,
+        Pattern::HorizontalStripes,
+        Pattern::VerticalStripes,
+    ];
+
+    /// Returns if the pattern covers a pixel, for a pattern repeating every `spacing` pixels.
+    pub fn covers(self, px: (i32, i32), spacing: i32) -> bool {
+        let spacing = spacing.max(2);
+        let width = (spacing / 4).max(1);
+        let (x, y) = px;
+        let hatch = (x + y).rem_euclid(spacing) < width;
+        let back_hatch = (x - y).rem_euclid(spacing) < width;
+        match self {
+            Pattern::Solid => true,
+            Pattern::Hatch => hatch,
+            Pattern::BackHatch => back_hatch,
+            Pattern::CrossHatch => hatch || back_hatch,
+            Pattern::Dots => x.rem_euclid(spacing) <= width && y.rem_euclid(spacing) <= width,
+            Pattern::HorizontalStripes => y.rem_euclid(spacing) < spacing / 2,
+            Pattern::VerticalStripes => x.rem_euclid(spacing) < spacing / 2,
+        }
+    }
+
+    /// Fills a polygon given in pixel coordinates of the drawing area with the pattern. Pixels outside the area are skipped.
+    pub fn fill_polygon<DB: DrawingBackend>(
+        self,
+        area: &DrawingArea<DB, Shift>,
+        points: &[(i32, i32)],
+        color: &RGBColor,
+        spacing: i32,
+    ) {
+        let (width, height) = area.dim_in_pixel();
+        let (width, height) = (width as i32, height as i32);
+        scan_polygon(points, (0, height), |y, x0, x1| {
+            for x in x0.max(0)..x1.min(width) {
+                if self.covers((x, y), spacing) {
+                    area.draw_pixel((x, y), color).unwrap();
+                }
+            }
+        });
+    }
+}
+
+/// Calls `span(y, x0, x1)` for all horizontal pixel spans inside a polygon, with `x1` exclusive,
+/// for rows in the range `rows` (exclusive end). Uses the even-odd rule at pixel centers.
+fn scan_polygon<F: FnMut(i32, i32, i32)>(points: &[(i32, i32)], rows: (i32, i32), mut span: F) {
+    if points.len() < 3 {
+        return;
+    }
+    let y_min = points.iter().map(|p| p.1).min().unwrap().max(rows.0);
+    let y_max = points.iter().map(|p| p.1).max().unwrap().min(rows.1 - 1);
+    let mut crossings = Vec::new();
+    for y in y_min..=y_max {
+        let center = y as f64 + 0.5;
+        crossings.clear();
+        for (i, &(x0, y0)) in points.iter().enumerate() {
+            let (x1, y1) = points[(i + 1) % points.len()];
+            let (y0, y1) = (y0 as f64, y1 as f64);
+            if (y0 <= center) != (y1 <= center) {
+                let t = (center - y0) / (y1 - y0);
+                crossings.push(x0 as f64 + t * (x1 - x0) as f64);
+            }
+        }
+        crossings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        for pair in crossings.chunks_exact(2) {
+            let x0 = (pair[0] - 0.5).ceil() as i32;
+            let x1 = (pair[1] - 0.5).ceil() as i32;
+            if x1 > x0 {
+                span(y, x0, x1);
+            }
+        }
+    }
+}
+
+/// A polygon approximating a circle, in pixel coordinates.
+pub(crate) fn circle_polygon(center: (i32, i32), radius: f64) -> Vec<(i32, i32)> {
+    let n = ((radius * 2.0) as usize).clamp(8, 32);
+    (0..n)
+        .map(|i| {
+            let angle = i as f64 / n as f64 * std::f64::consts::TAU;
+            (
+                center.0 + (radius * angle.cos()).round() as i32,
+                center.1 + (radius * angle.sin()).round() as i32,
+            )
+        })
+        .collect()
+}
+
+///
+/// A rectangle element filled with a pattern, with an outline for patterns other than `Solid`. Used for legend entries.
+///
+pub struct PatternRect {
+    corners: [BackendCoord; 2],
+    pattern: Pattern,
+    color: RGBColor,
+    spacing: i32,
+}
+
+impl PatternRect {
+    /// Creates a rectangle from two opposite corners.
+    pub fn new(
+        corners: [BackendCoord; 2],
+        pattern: Pattern,
+        color: &RGBColor,
+        spacing: i32,
+    ) -> Self {
+        PatternRect {
+            corners,
+            pattern,
+            color: RGBColor(color.0, color.1, color.2),
+            spacing,
+        }
+    }
+}
+
+impl<'a> PointCollection<'a, BackendCoord> for &'a PatternRect {
+    type Borrow = &'a BackendCoord;
+    type IntoIter = &'a [BackendCoord];
+    fn point_iter(self) -> Self::IntoIter {
+        &self.corners[..]
+    }
+}
+
+impl<DB: DrawingBackend> Drawable<DB> for PatternRect {
+    fn draw<I: Iterator<Item = BackendCoord>>(
+        &self,
+        mut pos: I,
+        backend: &mut DB,
+        _: (u32, u32),
+    ) -> Result<(), DrawingErrorKind<DB::ErrorType>> {
+        let (a, b) = match (pos.next(), pos.next()) {
+            (Some(a), Some(b)) => (a, b),
+            _ => return Ok(()),
+        };
+        let (x0, y0) = (a.0.min(b.0), a.1.min(b.1));
+        let (x1, y1) = (a.0.max(b.0), a.1.max(b.1));
+        let color = self.color.to_rgba();
+        if self.pattern == Pattern::Solid {
+            return backend.draw_rect((x0, y0), (x1, y1), &color, true);
+        }
+        let (width, height) = backend.get_size();
+        for y in y0.max(0)..=y1.min(height as i32 - 1) {
+            for x in x0.max(0)..=x1.min(width as i32 - 1) {
+                if self.pattern.covers((x, y), self.spacing) {
+                    backend.draw_pixel((x, y), &color)?;
+                }
+            }
+        }
+        backend.draw_rect((x0, y0), (x1, y1), &color, false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::pattern::{circle_polygon, scan_polygon, Pattern};
+
+    #[test]
+    fn pattern_coverage() {
+        let covered = |pattern: Pattern| {
+            let mut count = 0;
+            for x in 0..12 {
+                for y in 0..12 {
+                    if pattern.covers((x, y), 6) {
+                        count += 1;
+                    }
+                }
+            }
+            count
+        };
+        assert_eq!(covered(Pattern::Solid), 144);
+        assert_eq!(covered(Pattern::Hatch), 24);
+        assert_eq!(covered(Pattern::HorizontalStripes), 72);
+        assert!(covered(Pattern::CrossHatch) > covered(Pattern::BackHatch));
+        assert!(Pattern::Hatch.covers((-3, -3), 6));
+        assert!(Pattern::Dots.covers((6, 12), 6) && !Pattern::Dots.covers((3, 3), 6));
+    }
+
+    #[test]
+    fn polygon_scan() {
+        let mut pixels = 0;
+        scan_polygon(
+            &[(0, 0), (10, 0), (10, 5), (0, 5)],
+            (0, 100),
+            |_, x0, x1| pixels += x1 - x0,
+        );
+        assert_eq!(pixels, 50);
+
+        let mut rows = vec![];
+        scan_polygon(&[(0, 0), (10, 0), (10, 5), (0, 5)], (2, 4), |y, _, _| {
+            rows.push(y)
+        });
+        assert_eq!(rows, vec![2, 3]);
+
+        let mut pixels = 0;
+        scan_polygon(&circle_polygon((20, 20), 10.0), (0, 100), |_, x0, x1| {
+            pixels += x1 - x0
+        });
+        assert!((pixels as f64 - 314.0).abs() < 20.0, "{}", pixels);
+    }
+}
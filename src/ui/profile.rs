@@ -0,0 +1,217 @@
+//!
+//! Per-stage timings of chart updates, enabled by feature `profiling`.
+//!
+//! A [`Chart`](../chart/struct.Chart.html) records how long each [`Stage`](enum.Stage.html) of its updates takes,
+//! accessible via [`Chart::profile()`](../chart/struct.Chart.html#method.profile).
+//! Timings can also be shown as an overlay, see [`ChartBuilder::with_profile_overlay()`](../chart/struct.ChartBuilder.html#method.with_profile_overlay).
+//!
+//! # Example
+//! ```no_run
+//! use easy_graph::ui::chart::{ChartBuilder, Series};
+//! use easy_graph::color::style::RED;
+//!
+//! let mut chart = ChartBuilder::new()
+//!     .add_series(Series::line("A", &RED))
+//!     .with_data_limit(1000)
+//!     .build();
+//! for t in 0..10000 {
+//!     chart.push_time_series(t as f64, &[(t as f64).sin()]);
+//!     chart.update();
+//! }
+//! println!("{}", chart.profile());
+//! ```
+//!
+
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::fmt;
+use std::time::Duration;
+
+/// Font size of the overlay, in pixels.
+const FONT_SIZE: f64 = 12.0;
+/// Padding of the overlay, in pixels.
+const PADDING: i32 = 5;
+
+///
+/// A stage of a chart update.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    /// Dropping old data to respect data limits, accumulated over all pushes since the last rendered frame.
+    DataTrim,
+    /// Calculating the axis ranges. Measured on every update.
+    RangeCalc,
+    /// Drawing the background, axes, mesh and labels.
+    MeshDraw,
+    /// Drawing ensembles, series and the legend.
+    SeriesDraw,
+    /// Converting the rendered RGB buffer to the window's pixel format.
+    BufferTransfer,
+    /// Presenting the buffer in the window, including event processing.
+    Present,
+}
+
+impl Stage {
+    /// All stages, in the order they are executed.
+    pub const ALL: [Stage; 6] = [
+        Stage::DataTrim,
+        Stage::RangeCalc,
+        Stage::MeshDraw,
+        Stage::SeriesDraw,
+        Stage::BufferTransfer,
+        Stage::Present,
+    ];
+
+    /// A short, human-readable name of the stage.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::DataTrim => "data trim",
+            Stage::RangeCalc => "range calc",
+            Stage::MeshDraw => "mesh draw",
+            Stage::SeriesDraw => "series draw",
+            Stage::BufferTransfer => "buffer transfer",
+            Stage::Present => "present",
+        }
+    }
+}
+
+/// Timing statistics of a single stage.
+#[derive(Clone, Copy, Debug, Default)]
+struct Timing {
+    last: Duration,
+    total: Duration,
+    max: Duration,
+    count: u32,
+}
+
+///
+/// Timings of all stages of a chart's updates: last, mean and maximum per stage.
+///
+/// Means and maxima are taken over all measurements since the last [`reset()`](#method.reset).
+/// Drawing stages are only measured for rendered frames.
+///
+#[derive(Clone, Debug, Default)]
+pub struct Profile {
+    timings: [Timing; 6],
+}
+
+impl Profile {
+    /// Creates a profile without measurements.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a measurement of a stage.
+    pub(crate) fn record(&mut self, stage: Stage, duration: Duration) {
+        let timing = &mut self.timings[stage as usize];
+        timing.last = duration;
+        timing.total += duration;
+        timing.max = timing.max.max(duration);
+        timing.count += 1;
+    }
+    /// Removes all measurements.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// The latest measurement of a stage.
+    pub fn last(&self, stage: Stage) -> Duration {
+        self.timings[stage as usize].last
+    }
+    /// The mean of all measurements of a stage.
+    pub fn mean(&self, stage: Stage) -> Duration {
+        let timing = &self.timings[stage as usize];
+        if timing.count == 0 {
+            Duration::default()
+        } else {
+            timing.total / timing.count
+        }
+    }
+    /// The maximum of all measurements of a stage.
+    pub fn max(&self, stage: Stage) -> Duration {
+        self.timings[stage as usize].max
+    }
+    /// The number of measurements of a stage.
+    pub fn count(&self, stage: Stage) -> u32 {
+        self.timings[stage as usize].count
+    }
+    /// The sum of the latest measurements of all stages, i.e. the time spent on the latest frame.
+    pub fn last_total(&self) -> Duration {
+        self.timings.iter().map(|timing| timing.last).sum()
+    }
+
+    /// Draws the latest and mean timings as a table in the upper left corner of a drawing area.
+    pub fn draw<DB: DrawingBackend>(&self, area: &DrawingArea<DB, Shift>, ui_scale: f64) {
+        let font = ("monospace", FONT_SIZE * ui_scale).into_font();
+        let lines: Vec<_> = self.to_string().lines().map(str::to_string).collect();
+        let line_height = (FONT_SIZE * ui_scale * 1.2) as i32;
+        let padding = (PADDING as f64 * ui_scale) as i32;
+        let width = lines
+            .iter()
+            .map(|line| area.estimate_text_size(line, &font).unwrap().0 as i32)
+            .max()
+            .unwrap_or(0);
+        let height = lines.len() as i32 * line_height;
+        let corner = (width + 2 * padding, height + 2 * padding);
+        area.draw(&Rectangle::new([(0, 0), corner], WHITE.mix(0.8).filled()))
+            .unwrap();
+        for (i, line) in lines.iter().enumerate() {
+            let pos = (padding, padding + i as i32 * line_height);
+            area.draw_text(line, &font.color(&BLACK), pos).unwrap();
+        }
+    }
+}
+
+/// Converts a duration to milliseconds.
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Formats the profile as a table of the latest, mean and maximum timings per stage, in milliseconds.
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<16}{:>8}{:>8}{:>8}",
+            "stage [ms]", "last", "mean", "max"
+        )?;
+        for stage in Stage::ALL.iter() {
+            writeln!(
+                f,
+                "{:<16}{:>8.2}{:>8.2}{:>8.2}",
+                stage.name(),
+                millis(self.last(*stage)),
+                millis(self.mean(*stage)),
+                millis(self.max(*stage)),
+            )?;
+        }
+        write!(f, "{:<16}{:>8.2}", "total", millis(self.last_total()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::profile::{Profile, Stage};
+    use std::time::Duration;
+
+    #[test]
+    fn profile() {
+        let mut profile = Profile::new();
+        assert_eq!(profile.mean(Stage::Present), Duration::default());
+        profile.record(Stage::Present, Duration::from_millis(2));
+        profile.record(Stage::Present, Duration::from_millis(4));
+        profile.record(Stage::MeshDraw, Duration::from_millis(1));
+        assert_eq!(profile.last(Stage::Present), Duration::from_millis(4));
+        assert_eq!(profile.mean(Stage::Present), Duration::from_millis(3));
+        assert_eq!(profile.max(Stage::Present), Duration::from_millis(4));
+        assert_eq!(profile.count(Stage::Present), 2);
+        assert_eq!(profile.last_total(), Duration::from_millis(5));
+
+        let table = profile.to_string();
+        assert_eq!(table.lines().count(), Stage::ALL.len() + 2);
+        assert!(table.contains("present             4.00    3.00    4.00"));
+
+        profile.reset();
+        assert_eq!(profile.count(Stage::Present), 0);
+    }
+}
@@ -0,0 +1,582 @@
+//!
+//! Records data pushed into a [`Chart`](../chart/struct.Chart.html), and replays it into a fresh chart.
+//!
+//! A [`ChartRecorder`](struct.ChartRecorder.html) attached to a chart logs every push call, with its time, to a compact binary file.
+//! A [`ChartReplayer`](struct.ChartReplayer.html) feeds the recording back, at the original or an adjusted speed.
+//! This allows for reproducing a visualization of a stochastic run, e.g. for investigating rendering issues.
+//!
+//! Besides push calls, replacing, clearing and removing series data is recorded. Series added while running,
+//! with [`Chart::add_series()`](../chart/struct.Chart.html#method.add_series), are not recorded, as their styles can't be serialized.
+//! They must be added to the replaying chart up front. Settings like limits, styles, derived series and function plots
+//! are not recorded either, and should be set up for the replaying chart like for the recorded one.
+//!
+//! # Example
+//! ```no_run
+//! use easy_graph::ui::chart::{ChartBuilder, Series};
+//! use easy_graph::ui::record::{ChartRecorder, ChartReplayer};
+//! use easy_graph::color::style::RED;
+//!
+//! let mut chart = ChartBuilder::new()
+//!     .add_series(Series::line("Population", &RED))
+//!     .with_recorder(ChartRecorder::create("run.egrec").unwrap())
+//!     .build();
+//! for t in 0..100 {
+//!     chart.push_time_series(t as f64, &[rand::random()]);
+//!     chart.update();
+//! }
+//! chart.take_recorder().unwrap().finish().unwrap();
+//!
+//! // Later, replay at double speed
+//! let mut chart = ChartBuilder::new()
+//!     .add_series(Series::line("Population", &RED))
+//!     .build();
+//! ChartReplayer::open("run.egrec").unwrap().with_speed(2.0).run(&mut chart);
+//! ```
+//!
+
+use crate::ui::chart::Chart;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::time::Instant;
+
+/// File signature of recordings, followed by the format version.
+const MAGIC: &[u8; 4] = b"EGRC";
+/// Version of the recording format.
+const VERSION: u8 = 1;
+/// Size of lengths and indices in recordings, in bytes.
+const LEN_SIZE: usize = 4;
+
+///
+/// A recorded push call to a chart. See the corresponding methods of [`Chart`](../chart/struct.Chart.html).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum PushEvent {
+    /// [`Chart::push_time_series()`](../chart/struct.Chart.html#method.push_time_series)
+    TimeSeries { t: f64, y: Vec<f64> },
+    /// [`Chart::push_time_series_batch()`](../chart/struct.Chart.html#method.push_time_series_batch)
+    TimeSeriesBatch { t: Vec<f64>, y: Vec<Vec<f64>> },
+    /// [`Chart::push_time_series_if_changed()`](../chart/struct.Chart.html#method.push_time_series_if_changed)
+    TimeSeriesIfChanged { t: f64, y: Vec<f64>, epsilon: f64 },
+    /// [`Chart::flush()`](../chart/struct.Chart.html#method.flush)
+    Flush,
+    /// [`Chart::extend_series()`](../chart/struct.Chart.html#method.extend_series)
+    ExtendSeries { index: usize, data: Vec<(f64, f64)> },
+    /// [`Chart::push_xy()`](../chart/struct.Chart.html#method.push_xy)
+    Xy { index: usize, xy: (f64, f64) },
+    /// [`Chart::push_xyv()`](../chart/struct.Chart.html#method.push_xyv)
+    Xyv {
+        index: usize,
+        xy: (f64, f64),
+        value: f64,
+    },
+    /// [`Chart::push_segment()`](../chart/struct.Chart.html#method.push_segment)
+    Segment {
+        index: usize,
+        from: (f64, f64),
+        to: (f64, f64),
+    },
+    /// [`Chart::push_ensemble()`](../chart/struct.Chart.html#method.push_ensemble)
+    Ensemble {
+        index: usize,
+        x: f64,
+        values: Vec<f64>,
+    },
+    /// [`Chart::push_records()`](../chart/struct.Chart.html#method.push_records)
+    Records(Vec<(String, f64, f64)>),
+    /// [`Chart::replace_series()`](../chart/struct.Chart.html#method.replace_series)
+    ReplaceSeries { index: usize, data: Vec<(f64, f64)> },
+    /// [`Chart::clear_data()`](../chart/struct.Chart.html#method.clear_data)
+    ClearData,
+    /// [`Chart::remove_series()`](../chart/struct.Chart.html#method.remove_series)
+    RemoveSeries { index: usize },
+}
+
+impl PushEvent {
+    /// Applies the push call to a chart.
+    ///
+    /// # Panics
+    /// Panics under the same conditions as the corresponding method of [`Chart`](../chart/struct.Chart.html),
+    /// e.g. if the chart has fewer series than the recorded one.
+    pub fn apply(&self, chart: &mut Chart) {
+        match self {
+            PushEvent::TimeSeries { t, y } => chart.push_time_series(*t, y),
+            PushEvent::TimeSeriesBatch { t, y } => {
+                let y: Vec<_> = y.iter().map(|values| &values[..]).collect();
+                chart.push_time_series_batch(t, &y);
+            }
+            PushEvent::TimeSeriesIfChanged { t, y, epsilon } => {
+                chart.push_time_series_if_changed(*t, y, *epsilon)
+            }
+            PushEvent::Flush => chart.flush(),
+            PushEvent::ExtendSeries { index, data } => chart.extend_series(*index, data),
+            PushEvent::Xy { index, xy } => chart.push_xy(*index, *xy),
+            PushEvent::Xyv { index, xy, value } => chart.push_xyv(*index, *xy, *value),
+            PushEvent::Segment { index, from, to } => chart.push_segment(*index, *from, *to),
+            PushEvent::Ensemble { index, x, values } => chart.push_ensemble(*index, *x, values),
+            PushEvent::Records(records) => {
+                chart.push_records(records.iter().map(|(name, x, y)| (name.as_str(), *x, *y)))
+            }
+            PushEvent::ReplaceSeries { index, data } => chart.replace_series(*index, data),
+            PushEvent::ClearData => chart.clear_data(),
+            PushEvent::RemoveSeries { index } => {
+                chart.remove_series(*index);
+            }
+        }
+    }
+
+    fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        match self {
+            PushEvent::TimeSeries { t, y } => {
+                w.write_all(&[0])?;
+                write_f64(w, *t)?;
+                write_values(w, y)
+            }
+            PushEvent::TimeSeriesBatch { t, y } => {
+                w.write_all(&[1])?;
+                write_values(w, t)?;
+                write_len(w, y.len())?;
+                y.iter().try_for_each(|values| write_values(w, values))
+            }
+            PushEvent::TimeSeriesIfChanged { t, y, epsilon } => {
+                w.write_all(&[2])?;
+                write_f64(w, *t)?;
+                write_values(w, y)?;
+                write_f64(w, *epsilon)
+            }
+            PushEvent::Flush => w.write_all(&[3]),
+            PushEvent::ExtendSeries { index, data } => {
+                w.write_all(&[4])?;
+                write_len(w, *index)?;
+                write_len(w, data.len())?;
+                data.iter().try_for_each(|xy| write_pair(w, *xy))
+            }
+            PushEvent::Xy { index, xy } => {
+                w.write_all(&[5])?;
+                write_len(w, *index)?;
+                write_pair(w, *xy)
+            }
+            PushEvent::Segment { index, from, to } => {
+                w.write_all(&[6])?;
+                write_len(w, *index)?;
+                write_pair(w, *from)?;
+                write_pair(w, *to)
+            }
+            PushEvent::Ensemble { index, x, values } => {
+                w.write_all(&[7])?;
+                write_len(w, *index)?;
+                write_f64(w, *x)?;
+                write_values(w, values)
+            }
+            PushEvent::Records(records) => {
+                w.write_all(&[8])?;
+                write_len(w, records.len())?;
+                records.iter().try_for_each(|(name, x, y)| {
+                    write_len(w, name.len())?;
+                    w.write_all(name.as_bytes())?;
+                    write_pair(w, (*x, *y))
+                })
+            }
+            PushEvent::Xyv { index, xy, value } => {
+                w.write_all(&[9])?;
+                write_len(w, *index)?;
+                write_pair(w, *xy)?;
+                write_f64(w, *value)
+            }
+            PushEvent::ReplaceSeries { index, data } => {
+                w.write_all(&[10])?;
+                write_len(w, *index)?;
+                write_len(w, data.len())?;
+                data.iter().try_for_each(|xy| write_pair(w, *xy))
+            }
+            PushEvent::ClearData => w.write_all(&[11]),
+            PushEvent::RemoveSeries { index } => {
+                w.write_all(&[12])?;
+                write_len(w, *index)
+            }
+        }
+    }
+
+    /// Reads an event from the remaining bytes of a recording.
+    fn read(tag: u8, r: &mut &[u8]) -> io::Result<Self> {
+        Ok(match tag {
+            0 => PushEvent::TimeSeries {
+                t: read_f64(r)?,
+                y: read_values(r)?,
+            },
+            1 => {
+                let t = read_values(r)?;
+                let len = read_len(r, LEN_SIZE)?;
+                let y = (0..len)
+                    .map(|_| read_values(r))
+                    .collect::<io::Result<_>>()?;
+                PushEvent::TimeSeriesBatch { t, y }
+            }
+            2 => PushEvent::TimeSeriesIfChanged {
+                t: read_f64(r)?,
+                y: read_values(r)?,
+                epsilon: read_f64(r)?,
+            },
+            3 => PushEvent::Flush,
+            4 => {
+                let index = read_index(r)?;
+                let data = read_pairs(r)?;
+                PushEvent::ExtendSeries { index, data }
+            }
+            5 => PushEvent::Xy {
+                index: read_index(r)?,
+                xy: read_pair(r)?,
+            },
+            6 => PushEvent::Segment {
+                index: read_index(r)?,
+                from: read_pair(r)?,
+                to: read_pair(r)?,
+            },
+            7 => PushEvent::Ensemble {
+                index: read_index(r)?,
+                x: read_f64(r)?,
+                values: read_values(r)?,
+            },
+            8 => {
+                let len = read_len(r, LEN_SIZE + 16)?;
+                let records = (0..len)
+                    .map(|_| {
+                        let mut name = vec![0; read_len(r, 1)?];
+                        r.read_exact(&mut name)?;
+                        let name = String::from_utf8(name)
+                            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                        let (x, y) = read_pair(r)?;
+                        Ok((name, x, y))
+                    })
+                    .collect::<io::Result<_>>()?;
+                PushEvent::Records(records)
+            }
+            9 => PushEvent::Xyv {
+                index: read_index(r)?,
+                xy: read_pair(r)?,
+                value: read_f64(r)?,
+            },
+            10 => {
+                let index = read_index(r)?;
+                let data = read_pairs(r)?;
+                PushEvent::ReplaceSeries { index, data }
+            }
+            11 => PushEvent::ClearData,
+            12 => PushEvent::RemoveSeries {
+                index: read_index(r)?,
+            },
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown event type {} in recording", tag),
+                ))
+            }
+        })
+    }
+}
+
+///
+/// Logs push calls to a chart, with timestamps, to a file or other writer.
+///
+/// Attach it with [`ChartBuilder::with_recorder()`](../chart/struct.ChartBuilder.html#method.with_recorder)
+/// or [`Chart::set_recorder()`](../chart/struct.Chart.html#method.set_recorder).
+/// Timestamps are in seconds since the recorder was created.
+///
+/// Write errors stop recording, and are reported by [`finish()`](#method.finish).
+///
+pub struct ChartRecorder {
+    writer: Box<dyn Write>,
+    start: Instant,
+    events: usize,
+    error: Option<io::Error>,
+}
+
+impl ChartRecorder {
+    /// Creates a recorder writing to a file. An existing file is overwritten.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+    /// Creates a recorder writing to any writer, e.g. a `Vec<u8>`.
+    pub fn new<W: Write + 'static>(mut writer: W) -> io::Result<Self> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        Ok(ChartRecorder {
+            writer: Box::new(writer),
+            start: Instant::now(),
+            events: 0,
+            error: None,
+        })
+    }
+
+    /// Writes an event, with the current time.
+    pub(crate) fn record(&mut self, event: &PushEvent) {
+        if self.error.is_some() {
+            return;
+        }
+        let time = self.start.elapsed().as_secs_f64();
+        let result = event
+            .write(&mut self.writer)
+            .and_then(|_| write_f64(&mut self.writer, time));
+        match result {
+            Ok(()) => self.events += 1,
+            Err(err) => self.error = Some(err),
+        }
+    }
+
+    /// The number of recorded events.
+    pub fn events(&self) -> usize {
+        self.events
+    }
+    /// The error that stopped recording, if any.
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+    /// Flushes the recording. Returns the error that stopped recording, if any.
+    pub fn finish(mut self) -> io::Result<()> {
+        match self.error.take() {
+            Some(err) => Err(err),
+            None => self.writer.flush(),
+        }
+    }
+}
+
+///
+/// Feeds a recording from a [`ChartRecorder`](struct.ChartRecorder.html) back into a chart.
+///
+/// The chart should have the same series as the recorded one.
+/// Recordings that end with an incomplete event, e.g. from a crashed run, are read up to the last complete event.
+///
+pub struct ChartReplayer {
+    events: Vec<(f64, PushEvent)>,
+    next: usize,
+    speed: f64,
+    start: Option<Instant>,
+}
+
+impl ChartReplayer {
+    /// Reads a recording from a file.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+    /// Reads a recording from any reader.
+    ///
+    /// The recording is read into memory as a whole, so that lengths in corrupt recordings can be validated
+    /// against the remaining data before allocating.
+    pub fn from_reader<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let mut reader = &bytes[..];
+        let mut header = [0; 5];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != MAGIC || header[4] != VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Not a chart recording, or unsupported version",
+            ));
+        }
+        let mut events = Vec::new();
+        while let Some((&tag, rest)) = reader.split_first() {
+            reader = rest;
+            let event = PushEvent::read(tag, &mut reader).and_then(|event| {
+                let time = read_f64(&mut reader)?;
+                Ok((time, event))
+            });
+            match event {
+                Ok(event) => events.push(event),
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(ChartReplayer {
+            events,
+            next: 0,
+            speed: 1.0,
+            start: None,
+        })
+    }
+    /// Sets the replay speed, relative to the original speed. Default: 1.0.
+    ///
+    /// Use `f64::INFINITY` to replay as fast as possible.
+    pub fn with_speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// The recorded events, with their time in seconds since the start of the recording.
+    pub fn events(&self) -> &[(f64, PushEvent)] {
+        &self.events
+    }
+    /// The duration of the recording in seconds, at original speed.
+    pub fn duration(&self) -> f64 {
+        self.events.last().map(|(time, _)| *time).unwrap_or(0.0)
+    }
+    /// Returns if all events were replayed.
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.events.len()
+    }
+    /// Restarts the replay from the first event. Does not clear the chart.
+    pub fn rewind(&mut self) {
+        self.next = 0;
+        self.start = None;
+    }
+
+    /// Applies the next event to the chart, regardless of its time. Returns `false` if the replay is finished.
+    pub fn step(&mut self, chart: &mut Chart) -> bool {
+        match self.events.get(self.next) {
+            Some((_, event)) => {
+                event.apply(chart);
+                self.next += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Applies all events that are due according to the replay speed, and returns their number.
+    /// The replay clock starts with the first call.
+    pub fn poll(&mut self, chart: &mut Chart) -> usize {
+        let start = *self.start.get_or_insert_with(Instant::now);
+        let time = if self.speed.is_infinite() {
+            f64::INFINITY
+        } else {
+            start.elapsed().as_secs_f64() * self.speed
+        };
+        let mut count = 0;
+        while self.next < self.events.len() && self.events[self.next].0 <= time {
+            self.step(chart);
+            count += 1;
+        }
+        count
+    }
+
+    /// Replays all remaining events into the chart, updating it, until the replay is finished or the chart's window is closed.
+    pub fn run(mut self, chart: &mut Chart) {
+        while chart.is_open() && !self.is_finished() {
+            self.poll(chart);
+            chart.update();
+        }
+    }
+}
+
+fn write_f64<W: Write>(w: &mut W, value: f64) -> io::Result<()> {
+    w.write_all(&value.to_le_bytes())
+}
+fn write_len<W: Write>(w: &mut W, len: usize) -> io::Result<()> {
+    w.write_all(&(len as u32).to_le_bytes())
+}
+fn write_pair<W: Write>(w: &mut W, xy: (f64, f64)) -> io::Result<()> {
+    write_f64(w, xy.0)?;
+    write_f64(w, xy.1)
+}
+fn write_values<W: Write>(w: &mut W, values: &[f64]) -> io::Result<()> {
+    write_len(w, values.len())?;
+    values.iter().try_for_each(|v| write_f64(w, *v))
+}
+
+fn read_f64(r: &mut &[u8]) -> io::Result<f64> {
+    let mut bytes = [0; 8];
+    r.read_exact(&mut bytes)?;
+    Ok(f64::from_le_bytes(bytes))
+}
+fn read_index(r: &mut &[u8]) -> io::Result<usize> {
+    let mut bytes = [0; LEN_SIZE];
+    r.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes) as usize)
+}
+/// Reads the length of a sequence of items with the given minimum size in bytes.
+/// Fails like a truncated recording if the items can't fit into the remaining bytes.
+fn read_len(r: &mut &[u8], item_size: usize) -> io::Result<usize> {
+    let len = read_index(r)?;
+    if len.saturating_mul(item_size) > r.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "Length exceeds the remaining recording",
+        ));
+    }
+    Ok(len)
+}
+fn read_pair(r: &mut &[u8]) -> io::Result<(f64, f64)> {
+    Ok((read_f64(r)?, read_f64(r)?))
+}
+fn read_pairs(r: &mut &[u8]) -> io::Result<Vec<(f64, f64)>> {
+    let len = read_len(r, 16)?;
+    (0..len).map(|_| read_pair(r)).collect()
+}
+fn read_values(r: &mut &[u8]) -> io::Result<Vec<f64>> {
+    let len = read_len(r, 8)?;
+    (0..len).map(|_| read_f64(r)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::chart::{ChartBuilder, Series};
+    use crate::ui::record::{ChartRecorder, ChartReplayer, PushEvent};
+    use plotters::style::{BLUE, RED};
+
+    #[test]
+    fn record_replay() {
+        let path = std::env::temp_dir().join(format!("easy_graph_{}.egrec", std::process::id()));
+        let build = || {
+            ChartBuilder::new()
+                .add_series(Series::line("A", &RED))
+                .add_series(Series::line("B", &BLUE))
+                .build_headless()
+        };
+        let mut chart = build();
+        chart.set_recorder(Some(ChartRecorder::create(&path).unwrap()));
+        chart.push_time_series(0.0, &[1.0, 2.0]);
+        chart.push_time_series_batch(&[1.0, 2.0], &[&[3.0, 4.0], &[5.0, f64::NAN]]);
+        chart.extend_series(0, &[(3.0, 6.0)]);
+        chart.push_xy(1, (3.0, 7.0));
+        chart.push_xyv(1, (4.0, 1.0), 0.5);
+        chart.push_records(vec![("C", 0.0, 8.0), ("A", 4.0, 9.0)]);
+        chart.replace_series(1, &[(0.0, 1.0), (1.0, 2.0)]);
+        chart.remove_series(2);
+        let recorder = chart.take_recorder().unwrap();
+        assert_eq!(recorder.events(), 8);
+        recorder.finish().unwrap();
+
+        let mut replayer = ChartReplayer::open(&path).unwrap();
+        assert_eq!(replayer.events().len(), 8);
+        assert_eq!(
+            replayer.events()[4].1,
+            PushEvent::Xyv {
+                index: 1,
+                xy: (4.0, 1.0),
+                value: 0.5
+            }
+        );
+        assert_eq!(
+            replayer.events()[5].1,
+            PushEvent::Records(vec![
+                ("C".to_string(), 0.0, 8.0),
+                ("A".to_string(), 4.0, 9.0)
+            ])
+        );
+        let mut replayed = build();
+        assert!(replayer.step(&mut replayed));
+        assert_eq!(replayed.summary().get("A").unwrap().n, 1);
+        replayer = replayer.with_speed(f64::INFINITY);
+        assert_eq!(replayer.poll(&mut replayed), 7);
+        assert!(replayer.is_finished());
+        assert_eq!(replayed.num_series(), 2);
+        assert_eq!(replayed.summary().to_string(), chart.summary().to_string());
+
+        // Truncated recordings are read up to the last complete event
+        let bytes = std::fs::read(&path).unwrap();
+        let replayer = ChartReplayer::from_reader(&bytes[..bytes.len() - 3]).unwrap();
+        assert_eq!(replayer.events().len(), 7);
+        assert!(ChartReplayer::from_reader(&b"CSV,1"[..]).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn corrupt_length() {
+        let mut bytes = b"EGRC\x01".to_vec();
+        bytes.extend_from_slice(&[3, 0, 0, 0, 0, 0, 0, 0, 0]);
+        // A time series push claiming u32::MAX values
+        bytes.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 255, 255, 255, 255, 1, 2, 3]);
+        let replayer = ChartReplayer::from_reader(&bytes[..]).unwrap();
+        assert_eq!(replayer.events(), &[(0.0, PushEvent::Flush)]);
+    }
+}
@@ -0,0 +1,193 @@
+//!
+//! Disk-backed retention of chart data trimmed by data limits.
+//!
+//! With retention enabled, see [`ChartBuilder::with_retention()`](../chart/struct.ChartBuilder.html#method.with_retention),
+//! entries dropped from the front of a series are appended to a file per series instead of being discarded.
+//! The chart keeps only the latest entries in memory, while the full run can be read back for scrubbing or export.
+//!
+//! Files are plain sequences of `(x, y)` entries as little-endian `f64` pairs, named `<n>_<series name>.bin`.
+//! They are not memory-mapped: appends are buffered, and each read flushes pending entries and reads from the file.
+//!
+//! Errors writing retention files do not interrupt the chart. They are kept until queried with
+//! [`Chart::take_error()`](../chart/struct.Chart.html#method.take_error).
+//!
+//! # Example
+//! ```no_run
+//! use easy_graph::ui::chart::{ChartBuilder, Series};
+//! use easy_graph::color::style::RED;
+//!
+//! let mut chart = ChartBuilder::new()
+//!     .add_series(Series::line("Population", &RED))
+//!     .with_data_limit(1000)
+//!     .with_retention("run_data")
+//!     .build();
+//! for t in 0..100_000 {
+//!     chart.push_time_series(t as f64, &[(t % 100) as f64]);
+//! }
+//! assert_eq!(chart.retained_len(0), 99_000);
+//!
+//! let first = chart.retained(0, 0..100).unwrap();
+//! let window = chart.retained_between(0, 5000.0, 6000.0).unwrap();
+//! let full_run = chart.full_data(0).unwrap();
+//! ```
+//!
+
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Size of a retained entry in bytes.
+const ENTRY_SIZE: usize = 16;
+
+///
+/// An append-only file of `(x, y)` entries, with random access for reading.
+///
+/// The file is accessed through regular reads and writes, it is not memory-mapped.
+///
+pub struct RetentionFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    len: usize,
+    error: Option<io::Error>,
+}
+
+impl RetentionFile {
+    /// Creates an empty retention file at the given path, replacing an existing file.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let writer = BufWriter::new(File::create(&path)?);
+        Ok(RetentionFile {
+            path,
+            writer,
+            len: 0,
+            error: None,
+        })
+    }
+    /// The path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    /// The number of retained entries.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Returns if no entries were retained.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Returns and clears the error of the last failed append that was recorded, if any.
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    /// Appends entries to the back (end) of the file.
+    pub fn append<'a, I: IntoIterator<Item = &'a (f64, f64)>>(
+        &mut self,
+        entries: I,
+    ) -> io::Result<()> {
+        for (x, y) in entries {
+            self.writer.write_all(&x.to_le_bytes())?;
+            self.writer.write_all(&y.to_le_bytes())?;
+            self.len += 1;
+        }
+        Ok(())
+    }
+
+    /// Appends entries like [`append()`](#method.append), but records an error instead of returning it.
+    /// See [`take_error()`](#method.take_error).
+    pub(crate) fn append_or_record<'a, I: IntoIterator<Item = &'a (f64, f64)>>(
+        &mut self,
+        entries: I,
+    ) {
+        if let Err(err) = self.append(entries) {
+            self.error = Some(err);
+        }
+    }
+
+    /// Reads the entries in a range of indices. The range is clamped to the retained entries.
+    pub fn read(&mut self, range: Range<usize>) -> io::Result<Vec<(f64, f64)>> {
+        let end = range.end.min(self.len);
+        let start = range.start.min(end);
+        let mut file = self.reader()?;
+        file.seek(SeekFrom::Start((start * ENTRY_SIZE) as u64))?;
+        let mut bytes = vec![0; (end - start) * ENTRY_SIZE];
+        file.read_exact(&mut bytes)?;
+        Ok(bytes.chunks_exact(ENTRY_SIZE).map(decode).collect())
+    }
+
+    /// Reads the entries with x values from `x0` to `x1` (inclusive).
+    ///
+    /// Uses binary search, and thus assumes increasing x values, as for time series.
+    pub fn read_between(&mut self, x0: f64, x1: f64) -> io::Result<Vec<(f64, f64)>> {
+        let mut file = self.reader()?;
+        let start = partition_point(&mut file, self.len, |xy| xy.0 < x0)?;
+        let end = partition_point(&mut file, self.len, |xy| xy.0 <= x1)?.max(start);
+        self.read(start..end)
+    }
+
+    /// Flushes pending entries, and opens the file for reading.
+    fn reader(&mut self) -> io::Result<File> {
+        self.writer.flush()?;
+        File::open(&self.path)
+    }
+}
+
+/// Decodes an entry from its bytes.
+fn decode(bytes: &[u8]) -> (f64, f64) {
+    let (mut x, mut y) = ([0; 8], [0; 8]);
+    x.copy_from_slice(&bytes[..8]);
+    y.copy_from_slice(&bytes[8..ENTRY_SIZE]);
+    (f64::from_le_bytes(x), f64::from_le_bytes(y))
+}
+
+/// Index of the first of `len` entries in the file for which the predicate is `false`, by binary search.
+fn partition_point<F>(file: &mut File, len: usize, pred: F) -> io::Result<usize>
+where
+    F: Fn(&(f64, f64)) -> bool,
+{
+    let (mut low, mut high) = (0, len);
+    let mut bytes = [0; ENTRY_SIZE];
+    while low < high {
+        let mid = low + (high - low) / 2;
+        file.seek(SeekFrom::Start((mid * ENTRY_SIZE) as u64))?;
+        file.read_exact(&mut bytes)?;
+        if pred(&decode(&bytes)) {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(low)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::retention::RetentionFile;
+
+    #[test]
+    fn append_and_read() {
+        let path = std::env::temp_dir().join(format!(
+            "easy_graph_retention_file_{}.bin",
+            std::process::id()
+        ));
+        let mut file = RetentionFile::create(&path).unwrap();
+        assert!(file.is_empty());
+        let entries: Vec<_> = (0..10).map(|i| (i as f64, (i * i) as f64)).collect();
+        file.append(&entries[..6]).unwrap();
+        file.append(&entries[6..]).unwrap();
+        assert_eq!(file.len(), 10);
+
+        assert_eq!(file.read(2..4).unwrap(), vec![(2.0, 4.0), (3.0, 9.0)]);
+        assert_eq!(file.read(8..20).unwrap(), vec![(8.0, 64.0), (9.0, 81.0)]);
+        assert!(file.read(12..20).unwrap().is_empty());
+        assert_eq!(file.read(0..10).unwrap(), entries);
+
+        assert_eq!(
+            file.read_between(2.5, 4.0).unwrap(),
+            vec![(3.0, 9.0), (4.0, 16.0)]
+        );
+        assert!(file.read_between(20.0, 30.0).unwrap().is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,370 @@
+//!
+//! Renders 3D scatter plots of `(x, y, z)` points, rotatable with the mouse.
+//!
+//! # Example
+//! ```no_run
+//! use easy_graph::ui::scatter3d::Scatter3D;
+//! use easy_graph::ui::window::WindowBuilder;
+//! use easy_graph::color::style::{BLUE, RED};
+//!
+//! let mut plot = Scatter3D::new()
+//!     .with_labels("energy", "age", "speed")
+//!     .with_perspective(3.0)
+//!     .add_series("Wolves", &RED)
+//!     .add_series("Sheep", &BLUE);
+//! let mut win = WindowBuilder::new().with_dimensions(500, 500).build();
+//!
+//! for i in 0..10 { // change upper limit for longer run!
+//!     let t = i as f64 * 0.1;
+//!     plot.push(0, (t.sin(), t.cos(), t));
+//!     plot.push(1, (t.cos(), t, t.sin()));
+//!     // Drag with the left mouse button to rotate
+//!     plot.draw_window(&mut win);
+//! }
+//! ```
+//!
+
+use crate::ui::window::BufferWindow;
+use minifb::{MouseButton, MouseMode};
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::f64::consts::FRAC_PI_2;
+
+/// Rotation in radians per pixel of mouse movement.
+const ROTATION_SPEED: f64 = 0.01;
+/// Size of the unit cube relative to the smaller side of the drawing area.
+const CUBE_SCALE: f64 = 0.3;
+/// Font size of labels, in pixels.
+const FONT_SIZE: f64 = 14.0;
+
+/// A named series of 3D points.
+struct Series3D {
+    name: String,
+    color: RGBColor,
+    points: Vec<(f64, f64, f64)>,
+}
+
+///
+/// A 3D scatter plot with one or more series, drawn in a bounding box that is fitted to the data.
+///
+/// The z axis points up. Rotation is given by yaw (around the z axis) and pitch (tilt towards the viewer).
+///
+pub struct Scatter3D {
+    series: Vec<Series3D>,
+    labels: [String; 3],
+    yaw: f64,
+    pitch: f64,
+    perspective: Option<f64>,
+    radius: u32,
+    limit: Option<usize>,
+    drag: Option<(i32, i32)>,
+}
+
+impl Default for Scatter3D {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scatter3D {
+    /// Creates an empty plot with orthographic projection.
+    pub fn new() -> Self {
+        Scatter3D {
+            series: Vec::new(),
+            labels: ["X".to_string(), "Y".to_string(), "Z".to_string()],
+            yaw: 0.6,
+            pitch: 0.4,
+            perspective: None,
+            radius: 3,
+            limit: None,
+            drag: None,
+        }
+    }
+    /// Adds an empty series.
+    pub fn add_series(mut self, name: &str, color: &RGBColor) -> Self {
+        self.series.push(Series3D {
+            name: name.to_string(),
+            color: RGBColor(color.0, color.1, color.2),
+            points: Vec::new(),
+        });
+        self
+    }
+    /// Sets the axis labels.
+    pub fn with_labels(mut self, x: &str, y: &str, z: &str) -> Self {
+        self.labels = [x.to_string(), y.to_string(), z.to_string()];
+        self
+    }
+    /// Uses a perspective projection with the camera at the given distance from the center, in units of the bounding box half-size.
+    /// Values around 3 give a moderate perspective. Default: orthographic projection.
+    pub fn with_perspective(mut self, distance: f64) -> Self {
+        self.perspective = Some(distance.max(1.5));
+        self
+    }
+    /// Sets the initial rotation, in radians. See [`set_rotation()`](#method.set_rotation).
+    pub fn with_rotation(mut self, yaw: f64, pitch: f64) -> Self {
+        self.set_rotation(yaw, pitch);
+        self
+    }
+    /// Sets the radius of point markers, in pixels. Default: 3.
+    pub fn with_radius(mut self, radius: u32) -> Self {
+        self.radius = radius;
+        self
+    }
+    /// Limits the number of points per series. Older points are dropped.
+    pub fn with_limit(mut self, max_points: usize) -> Self {
+        self.limit = Some(max_points);
+        self
+    }
+
+    /// Number of series.
+    pub fn num_series(&self) -> usize {
+        self.series.len()
+    }
+    /// Adds a point to a series.
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of series indices.
+    pub fn push(&mut self, index: usize, xyz: (f64, f64, f64)) {
+        let points = &mut self.series[index].points;
+        points.push(xyz);
+        if let Some(limit) = self.limit {
+            if points.len() > limit {
+                points.drain(..points.len() - limit);
+            }
+        }
+    }
+    /// Replaces all points of a series, e.g. with the current state of all agents.
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of series indices.
+    pub fn set_points(&mut self, index: usize, points: Vec<(f64, f64, f64)>) {
+        self.series[index].points = points;
+    }
+    /// Removes all points of all series.
+    pub fn clear(&mut self) {
+        for series in &mut self.series {
+            series.points.clear();
+        }
+    }
+    /// The current rotation as (yaw, pitch), in radians.
+    pub fn rotation(&self) -> (f64, f64) {
+        (self.yaw, self.pitch)
+    }
+    /// Sets the rotation, in radians. Yaw rotates around the z axis, pitch tilts the z axis towards the viewer.
+    /// Pitch is clamped to the range -π/2 to π/2.
+    pub fn set_rotation(&mut self, yaw: f64, pitch: f64) {
+        self.yaw = yaw;
+        self.pitch = pitch.clamp(-FRAC_PI_2, FRAC_PI_2);
+    }
+    /// Rotates by the given angles, in radians.
+    pub fn rotate(&mut self, yaw: f64, pitch: f64) {
+        self.set_rotation(self.yaw + yaw, self.pitch + pitch);
+    }
+
+    /// The bounding box of all points, as (min, max) per axis.
+    pub fn bounds(&self) -> [(f64, f64); 3] {
+        let mut bounds = [(f64::MAX, f64::MIN); 3];
+        let points = self.series.iter().flat_map(|s| s.points.iter());
+        for (x, y, z) in points.filter(|p| p.0.is_finite() && p.1.is_finite() && p.2.is_finite()) {
+            for (b, v) in bounds.iter_mut().zip(&[*x, *y, *z]) {
+                *b = (b.0.min(*v), b.1.max(*v));
+            }
+        }
+        for b in &mut bounds {
+            if b.0 > b.1 {
+                *b = (0.0, 1.0);
+            } else if b.0 == b.1 {
+                *b = (b.0 - 0.5, b.1 + 0.5);
+            }
+        }
+        bounds
+    }
+
+    /// Draws the plot onto a drawing area.
+    pub fn draw<DB: DrawingBackend>(&self, area: &DrawingArea<DB, Shift>) {
+        area.fill(&WHITE).unwrap();
+        let (width, height) = area.dim_in_pixel();
+        let scale = width.min(height) as f64 * CUBE_SCALE;
+        let center = (width as f64 / 2.0, height as f64 / 2.0);
+        let to_pixel = |p: [f64; 3]| {
+            let (x, y, depth) = project(p, self.yaw, self.pitch, self.perspective);
+            (
+                (center.0 + x * scale).round() as i32,
+                (center.1 - y * scale).round() as i32,
+                depth,
+            )
+        };
+        let corner = |i: usize| {
+            [
+                if i & 1 == 0 { -1.0 } else { 1.0 },
+                if i & 2 == 0 { -1.0 } else { 1.0 },
+                if i & 4 == 0 { -1.0 } else { 1.0 },
+            ]
+        };
+
+        // Bounding box
+        let box_style = BLACK.mix(0.3);
+        for i in 0..8 {
+            for bit in &[1, 2, 4] {
+                if i & bit == 0 {
+                    let (x0, y0, _) = to_pixel(corner(i));
+                    let (x1, y1, _) = to_pixel(corner(i | bit));
+                    area.draw(&PathElement::new(vec![(x0, y0), (x1, y1)], &box_style))
+                        .unwrap();
+                }
+            }
+        }
+
+        // Axis labels at the center of the edges starting at the lower front left corner
+        let bounds = self.bounds();
+        let font = ("sans-serif", FONT_SIZE).into_font();
+        for (axis, label) in self.labels.iter().enumerate() {
+            let mut pos = [-1.1; 3];
+            pos[axis] = 0.0;
+            let (x, y, _) = to_pixel(pos);
+            let text = format!("{} [{:.2} - {:.2}]", label, bounds[axis].0, bounds[axis].1);
+            let (w, _) = area.estimate_text_size(&text, &font).unwrap();
+            area.draw_text(&text, &font.color(&BLACK), (x - w as i32 / 2, y))
+                .unwrap();
+        }
+
+        // Points, back to front
+        let normalize = |v: f64, (min, max): (f64, f64)| 2.0 * (v - min) / (max - min) - 1.0;
+        let mut points: Vec<_> = self
+            .series
+            .iter()
+            .enumerate()
+            .flat_map(|(s, series)| series.points.iter().map(move |p| (s, p)))
+            .filter(|(_, p)| p.0.is_finite() && p.1.is_finite() && p.2.is_finite())
+            .map(|(s, p)| {
+                let (x, y, depth) = to_pixel([
+                    normalize(p.0, bounds[0]),
+                    normalize(p.1, bounds[1]),
+                    normalize(p.2, bounds[2]),
+                ]);
+                (depth, s, (x, y))
+            })
+            .collect();
+        points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        for (_, s, xy) in points {
+            let color = &self.series[s].color;
+            area.draw(&Circle::new(xy, self.radius, color.filled()))
+                .unwrap();
+            area.draw(&Circle::new(xy, self.radius, &BLACK.mix(0.5)))
+                .unwrap();
+        }
+
+        // Legend
+        for (i, series) in self.series.iter().enumerate() {
+            let y = 10 + i as i32 * (FONT_SIZE as i32 + 4);
+            area.draw(&Rectangle::new(
+                [(10, y), (10 + FONT_SIZE as i32, y + FONT_SIZE as i32)],
+                series.color.filled(),
+            ))
+            .unwrap();
+            area.draw_text(
+                &series.name,
+                &font.color(&BLACK),
+                (16 + FONT_SIZE as i32, y),
+            )
+            .unwrap();
+        }
+    }
+
+    /// Rotates the plot by dragging with the left mouse button, and draws it into the window.
+    pub fn draw_window(&mut self, window: &mut BufferWindow) {
+        let pressed = window.is_mouse_down(MouseButton::Left);
+        let pos = if pressed {
+            window.mouse_pos_with(MouseMode::Pass)
+        } else {
+            None
+        };
+        match (pressed, pos, self.drag) {
+            (true, Some(pos), Some(last)) => {
+                self.rotate(
+                    (pos.0 - last.0) as f64 * ROTATION_SPEED,
+                    (pos.1 - last.1) as f64 * ROTATION_SPEED,
+                );
+                self.drag = Some(pos);
+            }
+            (true, Some(pos), None) => self.drag = Some(pos),
+            _ => self.drag = None,
+        }
+        window.draw(|b| {
+            let root = b.into_drawing_area();
+            self.draw(&root);
+        });
+    }
+}
+
+/// Projects a point in normalized coordinates (-1 to 1) to the view plane.
+/// Returns (x, y, depth), with y pointing up and larger depth closer to the viewer.
+fn project(p: [f64; 3], yaw: f64, pitch: f64, perspective: Option<f64>) -> (f64, f64, f64) {
+    // Yaw around the z axis
+    let (sin, cos) = yaw.sin_cos();
+    let (x, y) = (p[0] * cos - p[1] * sin, p[0] * sin + p[1] * cos);
+    // Pitch around the screen x axis. y points into the screen before.
+    let (sin, cos) = pitch.sin_cos();
+    let (up, depth) = (p[2] * cos + y * sin, y * cos - p[2] * sin);
+    let depth = -depth;
+    match perspective {
+        Some(distance) => {
+            let factor = distance / (distance - depth);
+            (x * factor, up * factor, depth)
+        }
+        None => (x, up, depth),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::scatter3d::{project, Scatter3D};
+    use plotters::prelude::*;
+
+    fn assert_close(a: (f64, f64, f64), b: (f64, f64, f64)) {
+        assert!(
+            (a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9 && (a.2 - b.2).abs() < 1e-9,
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn projection() {
+        // Front view: x right, z up, y into the screen
+        assert_close(project([1.0, 0.0, 0.0], 0.0, 0.0, None), (1.0, 0.0, 0.0));
+        assert_close(project([0.0, 0.0, 1.0], 0.0, 0.0, None), (0.0, 1.0, 0.0));
+        assert_close(project([0.0, 1.0, 0.0], 0.0, 0.0, None), (0.0, 0.0, -1.0));
+        // Top view
+        let half_pi = std::f64::consts::FRAC_PI_2;
+        assert_close(
+            project([0.0, 1.0, 0.0], 0.0, half_pi, None),
+            (0.0, 1.0, 0.0),
+        );
+        // Perspective: nearer points appear larger
+        let near = project([1.0, -1.0, 0.0], 0.0, 0.0, Some(3.0));
+        let far = project([1.0, 1.0, 0.0], 0.0, 0.0, Some(3.0));
+        assert!(near.0 > far.0 && near.2 > far.2);
+    }
+
+    #[test]
+    fn draw_scatter() {
+        let mut plot = Scatter3D::new().add_series("A", &RED).with_limit(2);
+        plot.push(0, (0.0, 0.0, 0.0));
+        plot.push(0, (1.0, 2.0, 3.0));
+        plot.push(0, (2.0, 4.0, 6.0));
+        assert_eq!(plot.bounds(), [(1.0, 2.0), (2.0, 4.0), (3.0, 6.0)]);
+        plot.rotate(0.0, 10.0);
+        assert_eq!(plot.rotation().1, std::f64::consts::FRAC_PI_2);
+
+        let (w, h) = (200, 200);
+        let mut buffer = vec![0; 3 * w * h];
+        {
+            let root =
+                BitMapBackend::with_buffer(&mut buffer, (w as u32, h as u32)).into_drawing_area();
+            plot.draw(&root);
+        }
+        assert!(buffer.chunks(3).any(|px| px == [255, 0, 0]));
+    }
+}
@@ -0,0 +1,511 @@
+//!
+//! Owns windows and charts on a dedicated UI thread, and updates them through lightweight handles.
+//!
+//! A [`WindowServer`](struct.WindowServer.html) creates windows on its own thread, and returns
+//! [`WindowHandle`](struct.WindowHandle.html)s and [`ChartHandle`](struct.ChartHandle.html)s.
+//! Handles are `Send`, `Sync` and cheap to clone. They send drawing closures and chart data to the UI thread,
+//! so visuals can be updated from regular (not thread-local) systems of ECS schedulers, or from multiple worker threads.
+//!
+//! The UI thread keeps all windows responsive, independent of the simulation.
+//! As all windows share the UI thread, FPS limits of windows and charts skip updates instead of blocking,
+//! like [`with_fps_skip()`](../window/struct.WindowBuilder.html#method.with_fps_skip).
+//!
+//! Commands from handles are queued in a bounded channel, see [`WindowServer::with_capacity()`](struct.WindowServer.html#method.with_capacity).
+//! When the queue is full, handles block until the UI thread catches up, which throttles producers that outpace the UI.
+//!
+//! # Platform support
+//!
+//! Windows are created and updated on the server's thread, which is not the main thread.
+//! This is not supported on macOS, where windows can only be used from the main thread, and with feature `gpu`
+//! on most platforms. There, create windows on the main thread and send data to them through a channel instead.
+//!
+//! # Example
+//! ```no_run
+//! use easy_graph::ui::chart::{ChartBuilder, Series};
+//! use easy_graph::ui::server::WindowServer;
+//! use easy_graph::ui::window::WindowBuilder;
+//! use easy_graph::ui::drawing::IntoDrawingArea;
+//! use easy_graph::ui::element::Circle;
+//! use easy_graph::color::style::{Color, BLACK, RED, WHITE};
+//! use std::thread;
+//!
+//! let server = WindowServer::new();
+//! let window = server.add_window(|| WindowBuilder::new().with_dimensions(200, 200).build());
+//! let chart = server.add_chart(|| {
+//!     ChartBuilder::new()
+//!         .add_series(Series::line("Position", &RED))
+//!         .build()
+//! });
+//!
+//! let worker = thread::spawn(move || {
+//!     for step in 0..200 {
+//!         let x = step % 200;
+//!         window.draw(move |b| {
+//!             let root = b.into_drawing_area();
+//!             root.fill(&WHITE).unwrap();
+//!             root.draw(&Circle::new((x, 100), 10, BLACK.filled())).unwrap();
+//!         });
+//!         chart.push_time_series(step as f64, &[x as f64]);
+//!         thread::sleep(std::time::Duration::from_millis(20));
+//!     }
+//! });
+//! worker.join().unwrap();
+//! // Keep the windows open until the user closes them
+//! server.wait();
+//! ```
+//!
+
+use crate::ui::chart::Chart;
+use crate::ui::input::KeyEvent;
+use crate::ui::window::{BufferWindow, EVENT_PUMP_INTERVAL};
+use plotters::prelude::*;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, RecvTimeoutError, Sender, SyncSender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
+
+/// A drawing closure for a window.
+type DrawFn = Box<dyn FnOnce(BitMapBackend) + Send>;
+/// A closure with access to a window.
+type WindowFn = Box<dyn FnOnce(&mut BufferWindow) + Send>;
+/// A closure with access to a chart.
+type ChartFn = Box<dyn FnOnce(&mut Chart) + Send>;
+
+/// Default capacity of the command queue.
+const COMMAND_CAPACITY: usize = 1024;
+
+/// Messages from handles to the UI thread.
+enum Command {
+    AddWindow(
+        usize,
+        Box<dyn FnOnce() -> BufferWindow + Send>,
+        Arc<AtomicBool>,
+    ),
+    AddChart(usize, Box<dyn FnOnce() -> Chart + Send>, Arc<AtomicBool>),
+    Draw(usize, DrawFn),
+    Window(usize, WindowFn),
+    Chart(usize, ChartFn),
+    Close(usize),
+    BroadcastKeys(Sender<KeyEvent>),
+    /// Stop when all windows are closed.
+    Finish,
+    /// Stop immediately, closing all windows.
+    Shutdown,
+}
+
+/// A window or chart owned by the UI thread.
+enum Entry {
+    Window(Box<BufferWindow>),
+    Chart(Box<Chart>),
+}
+impl Entry {
+    fn is_open(&self) -> bool {
+        match self {
+            Entry::Window(win) => win.is_open(),
+            Entry::Chart(chart) => chart.is_open(),
+        }
+    }
+    fn window(&mut self) -> &mut BufferWindow {
+        match self {
+            Entry::Window(win) => win,
+            Entry::Chart(chart) => chart.window(),
+        }
+    }
+}
+
+///
+/// Owns windows and charts on a dedicated UI thread. See the [module docs](index.html) for an example.
+///
+/// Dropping the server closes all windows. Use [`wait()`](#method.wait) to keep them open until closed by the user.
+///
+pub struct WindowServer {
+    commands: SyncSender<Command>,
+    next_id: AtomicUsize,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Default for WindowServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WindowServer {
+    /// Starts the UI thread, without any windows.
+    ///
+    /// See the [module docs](index.html#platform-support) for platform limitations.
+    pub fn new() -> Self {
+        Self::with_capacity(COMMAND_CAPACITY)
+    }
+
+    /// Starts the UI thread, with a command queue for the given number of pending commands. Default: 1024.
+    ///
+    /// Handles block when the queue is full. Note that calls to handles from closures executed on the UI thread
+    /// block forever when the queue is full.
+    ///
+    /// # Panics
+    /// Panics if `capacity` is zero.
+    pub fn with_capacity(capacity: usize) -> Self {
+        assert!(capacity > 0, "Command queue capacity must be positive");
+        let (sender, receiver) = sync_channel(capacity);
+        let handle = thread::spawn(move || {
+            let mut entries: HashMap<usize, (Entry, Arc<AtomicBool>)> = HashMap::new();
+            let mut finish = false;
+            let mut broadcast: Option<Sender<KeyEvent>> = None;
+            loop {
+                // Only the latest pending drawing closure per window is executed
+                let mut draws: HashMap<usize, DrawFn> = HashMap::new();
+                let mut command = receiver.recv_timeout(EVENT_PUMP_INTERVAL);
+                let start = Instant::now();
+                loop {
+                    match command {
+                        Ok(Command::AddWindow(id, build, open)) => {
+                            let mut entry = Entry::Window(Box::new(build()));
+                            entry.window().skip_instead_of_limit();
+                            if let Some(sender) = &broadcast {
+                                entry.window().set_key_broadcast(Some(sender.clone()));
+                            }
+                            entries.insert(id, (entry, open));
+                        }
+                        Ok(Command::AddChart(id, build, open)) => {
+                            let mut entry = Entry::Chart(Box::new(build()));
+                            entry.window().skip_instead_of_limit();
+                            if let Some(sender) = &broadcast {
+                                entry.window().set_key_broadcast(Some(sender.clone()));
+                            }
+                            entries.insert(id, (entry, open));
+                        }
+                        Ok(Command::Draw(id, draw)) => {
+                            draws.insert(id, draw);
+                        }
+                        Ok(Command::Window(id, func)) => {
+                            if let Some((Entry::Window(win), _)) = entries.get_mut(&id) {
+                                if let Some(draw) = draws.remove(&id) {
+                                    win.draw(draw);
+                                }
+                                func(win);
+                            }
+                        }
+                        Ok(Command::Chart(id, func)) => {
+                            if let Some((Entry::Chart(chart), _)) = entries.get_mut(&id) {
+                                func(chart);
+                            }
+                        }
+                        Ok(Command::Close(id)) => {
+                            if let Some((_, open)) = entries.remove(&id) {
+                                open.store(false, Ordering::Relaxed);
+                            }
+                        }
+                        Ok(Command::BroadcastKeys(sender)) => {
+                            for (entry, _) in entries.values_mut() {
+                                entry.window().set_key_broadcast(Some(sender.clone()));
+                            }
+                            broadcast = Some(sender);
+                        }
+                        Ok(Command::Finish) => finish = true,
+                        Ok(Command::Shutdown) | Err(RecvTimeoutError::Disconnected) => {
+                            entries.clear();
+                            return;
+                        }
+                        Err(RecvTimeoutError::Timeout) => break,
+                    }
+                    if start.elapsed() > EVENT_PUMP_INTERVAL {
+                        // Keep windows responsive under a constant stream of commands
+                        break;
+                    }
+                    command = match receiver.try_recv() {
+                        Ok(command) => Ok(command),
+                        Err(_) => Err(RecvTimeoutError::Timeout),
+                    };
+                }
+                for (id, (entry, _)) in entries.iter_mut() {
+                    match entry {
+                        Entry::Window(win) => match draws.remove(id) {
+                            Some(draw) => {
+                                win.draw(draw);
+                            }
+                            None => win.update_events_only(),
+                        },
+                        Entry::Chart(chart) => chart.update(),
+                    }
+                }
+                entries.retain(|_, (entry, open)| {
+                    // Only ever cleared here, to not overwrite a concurrent close by a handle
+                    let is_open = entry.is_open();
+                    if !is_open {
+                        open.store(false, Ordering::Relaxed);
+                    }
+                    is_open
+                });
+                if finish && entries.is_empty() {
+                    return;
+                }
+            }
+        });
+        WindowServer {
+            commands: sender,
+            next_id: AtomicUsize::new(0),
+            handle: Some(handle),
+        }
+    }
+
+    /// Adds a window, built on the UI thread by the given closure.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use easy_graph::ui::server::WindowServer;
+    /// use easy_graph::ui::window::WindowBuilder;
+    ///
+    /// let server = WindowServer::new();
+    /// let window = server.add_window(|| WindowBuilder::new().with_title("Agents").build());
+    /// ```
+    pub fn add_window<F>(&self, build: F) -> WindowHandle
+    where
+        F: FnOnce() -> BufferWindow + Send + 'static,
+    {
+        let (id, open) = self.next_entry();
+        self.send(Command::AddWindow(id, Box::new(build), open.clone()));
+        WindowHandle {
+            id,
+            commands: self.commands.clone(),
+            open,
+        }
+    }
+
+    /// Adds a chart, built on the UI thread by the given closure.
+    ///
+    /// The chart is updated by the UI thread, so there is no need to call [`Chart::update()`](../chart/struct.Chart.html#method.update).
+    pub fn add_chart<F>(&self, build: F) -> ChartHandle
+    where
+        F: FnOnce() -> Chart + Send + 'static,
+    {
+        let (id, open) = self.next_entry();
+        self.send(Command::AddChart(id, Box::new(build), open.clone()));
+        ChartHandle {
+            id,
+            commands: self.commands.clone(),
+            open,
+        }
+    }
+
+    /// Sends all key presses in the server's windows and charts to a shared channel,
+    /// e.g. of an [`InputGroup`](../input/struct.InputGroup.html). Also applies to windows added later.
+    ///
+    /// Overrides key broadcasts set up in the windows' builders.
+    pub fn broadcast_keys(&self, sender: Sender<KeyEvent>) {
+        self.send(Command::BroadcastKeys(sender));
+    }
+
+    /// Blocks until all windows are closed by the user or through their handles, and stops the UI thread.
+    ///
+    /// # Panics
+    /// Panics if the UI thread panicked, e.g. in a drawing closure.
+    pub fn wait(mut self) {
+        self.send(Command::Finish);
+        self.join();
+    }
+
+    fn next_entry(&self) -> (usize, Arc<AtomicBool>) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        (id, Arc::new(AtomicBool::new(true)))
+    }
+
+    fn send(&self, command: Command) {
+        // Sending only fails if the UI thread panicked, which is reported on join
+        let _ = self.commands.send(command);
+    }
+
+    fn join(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle
+                .join()
+                .unwrap_or_else(|err| std::panic::resume_unwind(err));
+        }
+    }
+}
+
+impl Drop for WindowServer {
+    fn drop(&mut self) {
+        if self.handle.is_some() {
+            self.send(Command::Shutdown);
+            if let Some(handle) = self.handle.take() {
+                let _ = handle.join();
+            }
+        }
+    }
+}
+
+///
+/// A handle to a window owned by a [`WindowServer`](struct.WindowServer.html).
+///
+/// All calls only send a message to the UI thread and return immediately, unless the server's command queue is full.
+/// Calls after the window was closed, or after the server was dropped, have no effect.
+///
+#[derive(Clone)]
+pub struct WindowHandle {
+    id: usize,
+    commands: SyncSender<Command>,
+    open: Arc<AtomicBool>,
+}
+
+impl WindowHandle {
+    /// Draws the window's content given a drawing closure, like [`BufferWindow::draw()`](../window/struct.BufferWindow.html#method.draw).
+    ///
+    /// If multiple drawing closures are pending when the UI thread gets to them, only the latest one is executed.
+    pub fn draw<F>(&self, draw: F)
+    where
+        F: FnOnce(BitMapBackend) + Send + 'static,
+    {
+        let _ = self.commands.send(Command::Draw(self.id, Box::new(draw)));
+    }
+    /// Runs a closure with access to the window on the UI thread, e.g. to change its title.
+    ///
+    /// A pending drawing closure is executed first.
+    pub fn with<F>(&self, func: F)
+    where
+        F: FnOnce(&mut BufferWindow) + Send + 'static,
+    {
+        let _ = self.commands.send(Command::Window(self.id, Box::new(func)));
+    }
+    /// Returns if the window is open. May lag behind by one update of the UI thread.
+    pub fn is_open(&self) -> bool {
+        self.open.load(Ordering::Relaxed)
+    }
+    /// Closes the window.
+    pub fn close(&self) {
+        self.open.store(false, Ordering::Relaxed);
+        let _ = self.commands.send(Command::Close(self.id));
+    }
+}
+
+///
+/// A handle to a chart owned by a [`WindowServer`](struct.WindowServer.html).
+///
+/// All calls only send a message to the UI thread and return immediately, unless the server's command queue is full.
+/// Calls after the chart was closed, or after the server was dropped, have no effect.
+///
+#[derive(Clone)]
+pub struct ChartHandle {
+    id: usize,
+    commands: SyncSender<Command>,
+    open: Arc<AtomicBool>,
+}
+
+impl ChartHandle {
+    /// Pushes a time step with a value for each series.
+    ///
+    /// See [`Chart::push_time_series()`](../chart/struct.Chart.html#method.push_time_series).
+    pub fn push_time_series(&self, t: f64, y: &[f64]) {
+        let y = y.to_vec();
+        self.with(move |chart| chart.push_time_series(t, &y));
+    }
+    /// Pushes an xy data point to a series.
+    ///
+    /// See [`Chart::push_xy()`](../chart/struct.Chart.html#method.push_xy).
+    pub fn push_xy(&self, index: usize, xy: (f64, f64)) {
+        self.with(move |chart| chart.push_xy(index, xy));
+    }
+    /// Runs a closure with access to the chart on the UI thread, e.g. to push data or change settings.
+    pub fn with<F>(&self, func: F)
+    where
+        F: FnOnce(&mut Chart) + Send + 'static,
+    {
+        let _ = self.commands.send(Command::Chart(self.id, Box::new(func)));
+    }
+    /// Returns if the chart's window is open. May lag behind by one update of the UI thread.
+    pub fn is_open(&self) -> bool {
+        self.open.load(Ordering::Relaxed)
+    }
+    /// Closes the chart's window.
+    pub fn close(&self) {
+        self.open.store(false, Ordering::Relaxed);
+        let _ = self.commands.send(Command::Close(self.id));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::chart::{ChartBuilder, Series};
+    use crate::ui::server::WindowServer;
+    use crate::ui::window::WindowBuilder;
+    use plotters::prelude::*;
+    use std::sync::mpsc::channel;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn window_server() {
+        let server = WindowServer::new();
+        let window = server.add_window(|| {
+            WindowBuilder::new()
+                .with_dimensions(20, 10)
+                .build_headless()
+        });
+        let chart = server.add_chart(|| {
+            ChartBuilder::new()
+                .with_dimensions(200, 100)
+                .add_series(Series::line("A", &RED))
+                .build_headless()
+        });
+        assert!(window.is_open());
+
+        let workers: Vec<_> = (0..4)
+            .map(|i| {
+                let chart = chart.clone();
+                thread::spawn(move || chart.push_time_series(i as f64, &[1.0]))
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        window.draw(|b| {
+            let root = b.into_drawing_area();
+            root.fill(&RED).unwrap();
+        });
+
+        let (sender, receiver) = channel();
+        chart.with(move |chart| {
+            sender
+                .send(vec![chart.slice_series(0, 0.0, 10.0).count() as u8])
+                .unwrap()
+        });
+        assert_eq!(receiver.recv_timeout(Duration::from_secs(5)), Ok(vec![4]));
+
+        // Pending drawing is executed before accessing the window
+        let (sender, receiver) = channel();
+        window.with(move |win| sender.send(win.buffer_u8[0..3].to_vec()).unwrap());
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_secs(5)),
+            Ok(vec![255, 0, 0])
+        );
+
+        window.close();
+        assert!(!window.is_open());
+        chart.close();
+        server.wait();
+    }
+
+    #[test]
+    fn bounded_queue() {
+        let server = WindowServer::with_capacity(2);
+        let chart = server.add_chart(|| {
+            ChartBuilder::new()
+                .with_dimensions(200, 100)
+                .add_series(Series::line("A", &RED))
+                .build_headless()
+        });
+        // Blocks when full instead of dropping commands
+        for i in 0..100 {
+            chart.push_time_series(i as f64, &[1.0]);
+        }
+        let (sender, receiver) = channel();
+        chart.with(move |chart| {
+            sender
+                .send(chart.slice_series(0, 0.0, 100.0).count())
+                .unwrap()
+        });
+        assert_eq!(receiver.recv_timeout(Duration::from_secs(5)), Ok(100));
+    }
+}
@@ -0,0 +1,96 @@
+//!
+//! Data sources that are polled by [`Chart`](../chart/struct.Chart.html)s on each update, instead of pushing data explicitly.
+//!
+//! # Example
+//! ```
+//! use easy_graph::ui::chart::{ChartBuilder, Series};
+//! use easy_graph::color::style::{RED, BLUE};
+//!
+//! let mut chart = ChartBuilder::new()
+//!     .add_series(Series::line("sin", &RED))
+//!     .add_series(Series::line("cos", &BLUE))
+//!     .with_data_source(|t: f64| vec![t.sin(), t.cos()])
+//!     .build();
+//!
+//! for _ in 0..10 { // Increase upper limit for longer run!
+//!     chart.update();
+//! }
+//! ```
+//!
+
+use std::cell::RefCell;
+use std::sync::mpsc::Receiver;
+
+///
+/// A source of data rows for a chart, with one value per series.
+///
+/// Implemented for closures `Fn(f64) -> Vec<f64>`.
+///
+pub trait DataSource {
+    /// Samples the values of all series at time `t`, in seconds since the source was attached to the chart.
+    ///
+    /// Returns an empty vector if no data is available.
+    fn sample(&self, t: f64) -> Vec<f64>;
+}
+
+impl<F> DataSource for F
+where
+    F: Fn(f64) -> Vec<f64>,
+{
+    fn sample(&self, t: f64) -> Vec<f64> {
+        self(t)
+    }
+}
+
+///
+/// A data source receiving data rows through a channel, e.g. from a simulation thread.
+///
+/// Sampling returns the most recently received row. Rows received in between samples are dropped.
+/// Before the first row is received, samples are empty.
+///
+pub struct ChannelSource {
+    receiver: Receiver<Vec<f64>>,
+    last: RefCell<Vec<f64>>,
+}
+
+impl ChannelSource {
+    /// Creates a data source from the receiving end of a channel.
+    pub fn new(receiver: Receiver<Vec<f64>>) -> Self {
+        ChannelSource {
+            receiver,
+            last: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+impl DataSource for ChannelSource {
+    fn sample(&self, _t: f64) -> Vec<f64> {
+        if let Some(row) = self.receiver.try_iter().last() {
+            *self.last.borrow_mut() = row;
+        }
+        self.last.borrow().clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::source::{ChannelSource, DataSource};
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn closure_source() {
+        let source = |t: f64| vec![t, 2.0 * t];
+        assert_eq!(source.sample(1.0), vec![1.0, 2.0]);
+    }
+
+    #[test]
+    fn channel_source() {
+        let (sender, receiver) = channel();
+        let source = ChannelSource::new(receiver);
+        assert!(source.sample(0.0).is_empty());
+        sender.send(vec![1.0]).unwrap();
+        sender.send(vec![2.0]).unwrap();
+        assert_eq!(source.sample(0.0), vec![2.0]);
+        assert_eq!(source.sample(0.0), vec![2.0]);
+    }
+}
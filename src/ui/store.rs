@@ -0,0 +1,503 @@
+//!
+//! Multi-resolution storage of [`Chart`](../chart/struct.Chart.html) series data.
+//!
+//! Besides the raw entries, a [`SeriesStore`](struct.SeriesStore.html) maintains levels of pre-aggregated buckets,
+//! each summarizing the minimum, maximum and mean y value of `16`, `256`, `4096`, ... consecutive entries.
+//! Levels are updated incrementally when entries are pushed or dropped.
+//!
+//! Zoomed-out views of long line series are drawn from the coarsest level that still has at least one bucket per pixel,
+//! so that drawing millions of entries takes time proportional to the chart's width rather than to the number of entries.
+//!
+//! # Example
+//! ```no_run
+//! use easy_graph::ui::chart::{ChartBuilder, Series};
+//! use easy_graph::color::style::RED;
+//!
+//! let mut chart = ChartBuilder::new()
+//!     .add_series(Series::line("Signal", &RED))
+//!     .build();
+//! for t in 0..1_000_000 {
+//!     chart.push_time_series(t as f64, &[(t as f64 * 0.01).sin()]);
+//! }
+//! for bucket in chart.series_buckets(0, 0.0, 1_000_000.0, 10) {
+//!     println!("{:?} {:?} {:?}", bucket.x_range(), bucket.min(), bucket.mean());
+//! }
+//! ```
+//!
+
+use std::collections::vec_deque::{self, VecDeque};
+use std::fmt;
+use std::ops::{Deref, Range};
+
+/// Number of buckets (or entries) of a level merged into a bucket of the next coarser level.
+const BRANCHING: usize = 16;
+
+/// Number of entries summarized by a bucket of the given level.
+fn factor(level: usize) -> usize {
+    BRANCHING.pow(level as u32 + 1)
+}
+
+///
+/// Summary of consecutive entries of a series. Non-finite y values are ignored.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bucket {
+    x: (f64, f64),
+    min: (f64, f64),
+    max: (f64, f64),
+    sum: f64,
+    count: usize,
+}
+
+impl Bucket {
+    fn new(xy: &(f64, f64)) -> Self {
+        let finite = xy.1.is_finite();
+        Bucket {
+            x: (xy.0, xy.0),
+            min: *xy,
+            max: *xy,
+            sum: if finite { xy.1 } else { 0.0 },
+            count: finite as usize,
+        }
+    }
+    /// Merges with the summary of the entries directly following this bucket's entries.
+    fn merge(self, other: Bucket) -> Bucket {
+        let pick = |own: (f64, f64), other_xy: (f64, f64), replace: bool| {
+            if self.count == 0 || (other.count > 0 && replace) {
+                other_xy
+            } else {
+                own
+            }
+        };
+        Bucket {
+            x: (self.x.0, other.x.1),
+            min: pick(self.min, other.min, other.min.1 < self.min.1),
+            max: pick(self.max, other.max, other.max.1 > self.max.1),
+            sum: self.sum + other.sum,
+            count: self.count + other.count,
+        }
+    }
+
+    /// The x values of the first and the last entry.
+    pub fn x_range(&self) -> (f64, f64) {
+        self.x
+    }
+    /// The number of entries with a finite y value.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+    /// The entry with the minimum y value, if any.
+    pub fn min(&self) -> Option<(f64, f64)> {
+        Some(self.min).filter(|_| self.count > 0)
+    }
+    /// The entry with the maximum y value, if any.
+    pub fn max(&self) -> Option<(f64, f64)> {
+        Some(self.max).filter(|_| self.count > 0)
+    }
+    /// The mean y value, if any.
+    pub fn mean(&self) -> Option<f64> {
+        Some(self.sum / self.count as f64).filter(|_| self.count > 0)
+    }
+
+    /// The minimum and maximum entry in x order, or a gap (`NaN`) if the bucket has no finite values.
+    fn extremes(&self) -> impl Iterator<Item = (f64, f64)> {
+        let points = match (self.min(), self.max()) {
+            (Some(min), Some(max)) if min == max => [Some(min), None],
+            (Some(min), Some(max)) if min.0 <= max.0 => [Some(min), Some(max)],
+            (Some(min), Some(max)) => [Some(max), Some(min)],
+            _ => [Some((self.x.0, f64::NAN)), None],
+        };
+        IntoIterator::into_iter(points).flatten()
+    }
+}
+
+/// Buckets of a level, aligned to absolute entry indices.
+#[derive(Clone)]
+struct Level {
+    /// Absolute index of the first bucket.
+    first: usize,
+    buckets: VecDeque<Bucket>,
+}
+
+///
+/// Storage of a series' `(x, y)` entries with pre-aggregated levels of buckets.
+///
+/// Dereferences to the raw entries for reading. Entries are changed through the store's methods only,
+/// to keep levels consistent.
+///
+#[derive(Clone)]
+pub struct SeriesStore {
+    data: VecDeque<(f64, f64)>,
+    /// Absolute index of the first entry, i.e. the number of entries dropped from the front.
+    start: usize,
+    levels: Vec<Level>,
+    sorted: bool,
+}
+
+impl SeriesStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        SeriesStore {
+            data: VecDeque::new(),
+            start: 0,
+            levels: Vec::new(),
+            sorted: true,
+        }
+    }
+
+    /// Returns if x values were pushed in non-decreasing order, since the store was created or cleared.
+    pub fn is_sorted(&self) -> bool {
+        self.sorted
+    }
+
+    /// Pushes an entry to the back (end).
+    pub fn push_back(&mut self, xy: (f64, f64)) {
+        if let Some(last) = self.data.back() {
+            self.sorted &= xy.0 >= last.0;
+        }
+        let index = self.start + self.data.len();
+        self.data.push_back(xy);
+        for (level, lev) in self.levels.iter_mut().enumerate() {
+            let bucket = index / factor(level);
+            if !lev.buckets.is_empty() && bucket < lev.first + lev.buckets.len() {
+                let last = lev.buckets.back_mut().unwrap();
+                *last = last.merge(Bucket::new(&xy));
+            } else {
+                if lev.buckets.is_empty() {
+                    lev.first = bucket;
+                }
+                lev.buckets.push_back(Bucket::new(&xy));
+            }
+        }
+        if self.data.len() >= BRANCHING * factor(self.levels.len()) {
+            self.add_level();
+        }
+    }
+    /// Pushes entries to the back (end).
+    pub fn extend<I: IntoIterator<Item = (f64, f64)>>(&mut self, entries: I) {
+        for xy in entries {
+            self.push_back(xy);
+        }
+    }
+
+    /// Removes the first entry and returns it, if any.
+    pub fn pop_front(&mut self) -> Option<(f64, f64)> {
+        let first = self.data.front().cloned();
+        self.drop_front(1);
+        first
+    }
+    /// Removes the last entry and returns it, if any.
+    pub fn pop_back(&mut self) -> Option<(f64, f64)> {
+        let last = self.data.back().cloned();
+        self.truncate(self.data.len().saturating_sub(1));
+        last
+    }
+
+    /// Removes `count` entries from the front.
+    pub fn drop_front(&mut self, count: usize) {
+        let count = count.min(self.data.len());
+        if count == 0 {
+            return;
+        }
+        self.data.drain(..count);
+        self.start += count;
+        let (start, end) = (self.start, self.end());
+        for level in 0..self.levels.len() {
+            let f = factor(level);
+            let lev = &mut self.levels[level];
+            while lev.first < start / f && lev.buckets.pop_front().is_some() {
+                lev.first += 1;
+            }
+            if start % f != 0 && !lev.buckets.is_empty() {
+                let end = end.min((start / f + 1) * f);
+                match self.summarize(level, start..end) {
+                    Some(bucket) => self.levels[level].buckets[0] = bucket,
+                    None => {
+                        self.levels[level].buckets.pop_front();
+                    }
+                }
+            }
+        }
+    }
+    /// Shortens the store to `len` entries, removing entries from the back (end).
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.data.len() {
+            return;
+        }
+        self.data.truncate(len);
+        let (start, end) = (self.start, self.end());
+        for level in 0..self.levels.len() {
+            let f = factor(level);
+            let lev = &mut self.levels[level];
+            while !lev.buckets.is_empty() && (lev.first + lev.buckets.len() - 1) * f >= end {
+                lev.buckets.pop_back();
+            }
+            if end % f != 0 && !lev.buckets.is_empty() {
+                let last = lev.buckets.len() - 1;
+                let begin = start.max((end - 1) / f * f);
+                if let Some(bucket) = self.summarize(level, begin..end) {
+                    self.levels[level].buckets[last] = bucket;
+                }
+            }
+        }
+    }
+    /// Removes all entries.
+    pub fn clear(&mut self) {
+        *self = SeriesStore::new();
+    }
+
+    /// Summarizes the entries in a range of indices by at least `count` buckets, if the range is long enough.
+    /// Otherwise, returns one bucket per entry.
+    pub fn buckets(&self, range: Range<usize>, count: usize) -> Vec<Bucket> {
+        let end = range.end.min(self.data.len());
+        let start = range.start.min(end);
+        match self.level_for(end - start, count) {
+            Some(level) => self.level_buckets(level, start..end, count),
+            None => self.data.range(start..end).map(Bucket::new).collect(),
+        }
+    }
+
+    /// The entries in a range of indices, reduced to the minimum and maximum entry per bucket
+    /// if the range has more than `count` buckets worth of entries, and x values are sorted.
+    pub fn reduced(&self, range: Range<usize>, count: usize) -> Vec<(f64, f64)> {
+        let end = range.end.min(self.data.len());
+        let start = range.start.min(end);
+        match self.level_for(end - start, count).filter(|_| self.sorted) {
+            Some(level) => self
+                .level_buckets(level, start..end, count)
+                .iter()
+                .flat_map(Bucket::extremes)
+                .collect(),
+            None => self.data.range(start..end).cloned().collect(),
+        }
+    }
+
+    /// Absolute index after the last entry.
+    fn end(&self) -> usize {
+        self.start + self.data.len()
+    }
+
+    /// The coarsest level with at least `count` buckets in `len` entries.
+    fn level_for(&self, len: usize, count: usize) -> Option<usize> {
+        (0..self.levels.len())
+            .rev()
+            .find(|level| factor(*level) * count.max(1) <= len)
+    }
+
+    /// Buckets of a level covering a range of (relative) indices, merged to at most about `2 * count` buckets.
+    fn level_buckets(&self, level: usize, range: Range<usize>, count: usize) -> Vec<Bucket> {
+        let f = factor(level);
+        let range = (range.start + self.start)..(range.end + self.start);
+        let (first, last) = (range.start / f, (range.end - 1) / f);
+        let group = ((last - first + 1) / count.max(1)).max(1);
+        let mut buckets: Vec<Bucket> = Vec::with_capacity((last - first) / group + 2);
+        let mut current = None;
+        for index in first..=last {
+            let span = (index * f).max(range.start)..((index + 1) * f).min(range.end);
+            let bucket = if self.is_whole(level, &span) {
+                Some(self.levels[level].buckets[index - self.levels[level].first])
+            } else {
+                self.summarize(level, span)
+            };
+            if let Some(bucket) = bucket {
+                current = match current {
+                    Some((g, merged)) if g == index / group => {
+                        Some((g, Bucket::merge(merged, bucket)))
+                    }
+                    Some((_, merged)) => {
+                        buckets.push(merged);
+                        Some((index / group, bucket))
+                    }
+                    None => Some((index / group, bucket)),
+                };
+            }
+        }
+        buckets.extend(current.map(|(_, merged)| merged));
+        buckets
+    }
+
+    /// Returns if a span of absolute indices, within a single bucket of the level, covers all stored entries of the bucket.
+    fn is_whole(&self, level: usize, span: &Range<usize>) -> bool {
+        let f = factor(level);
+        let index = span.start / f;
+        span.start == (index * f).max(self.start) && span.end == ((index + 1) * f).min(self.end())
+    }
+
+    /// Summarizes the entries in a range of absolute indices within a single bucket of a level,
+    /// using the buckets of the next finer level.
+    fn summarize(&self, level: usize, range: Range<usize>) -> Option<Bucket> {
+        if range.start >= range.end {
+            return None;
+        }
+        if level == 0 {
+            let range = (range.start - self.start)..(range.end - self.start);
+            return self
+                .data
+                .range(range)
+                .map(Bucket::new)
+                .reduce(Bucket::merge);
+        }
+        let f = factor(level - 1);
+        let finer = &self.levels[level - 1];
+        (range.start / f..=(range.end - 1) / f)
+            .filter_map(|index| {
+                let span = (index * f).max(range.start)..((index + 1) * f).min(range.end);
+                if self.is_whole(level - 1, &span) {
+                    finer.buckets.get(index - finer.first).cloned()
+                } else {
+                    self.summarize(level - 1, span)
+                }
+            })
+            .reduce(Bucket::merge)
+    }
+
+    /// Adds the next coarser level, built from the current coarsest level.
+    fn add_level(&mut self) {
+        let level = self.levels.len();
+        let f = factor(level);
+        let (start, end) = (self.start, self.end());
+        let buckets = (start / f..=(end - 1) / f)
+            .filter_map(|index| {
+                self.summarize(level, (index * f).max(start)..((index + 1) * f).min(end))
+            })
+            .collect();
+        self.levels.push(Level {
+            first: start / f,
+            buckets,
+        });
+    }
+}
+
+impl Default for SeriesStore {
+    fn default() -> Self {
+        SeriesStore::new()
+    }
+}
+
+impl Deref for SeriesStore {
+    type Target = VecDeque<(f64, f64)>;
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+impl<'a> IntoIterator for &'a SeriesStore {
+    type Item = &'a (f64, f64);
+    type IntoIter = vec_deque::Iter<'a, (f64, f64)>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.iter()
+    }
+}
+
+impl PartialEq<Vec<(f64, f64)>> for SeriesStore {
+    fn eq(&self, other: &Vec<(f64, f64)>) -> bool {
+        self.data == *other
+    }
+}
+
+impl fmt::Debug for SeriesStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.data.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::store::{factor, Bucket, SeriesStore, BRANCHING};
+
+    /// Checks all levels against buckets computed directly from the raw entries.
+    fn check_levels(store: &SeriesStore) {
+        for (level, lev) in store.levels.iter().enumerate() {
+            let f = factor(level);
+            let expected: Vec<_> = (store.start / f..=(store.end().max(1) - 1) / f)
+                .filter_map(|index| {
+                    let start = (index * f).max(store.start) - store.start;
+                    let end = ((index + 1) * f).min(store.end()).max(store.start) - store.start;
+                    store
+                        .data
+                        .range(start..end)
+                        .map(Bucket::new)
+                        .reduce(Bucket::merge)
+                })
+                .collect();
+            let actual: Vec<_> = lev.buckets.iter().cloned().collect();
+            assert_eq!(actual, expected, "level {}", level);
+        }
+    }
+
+    #[test]
+    fn levels() {
+        let mut store = SeriesStore::new();
+        let n = 3 * BRANCHING * BRANCHING + 5;
+        for i in 0..n {
+            let y = if i % 37 == 0 {
+                f64::NAN
+            } else {
+                (i % 23) as f64
+            };
+            store.push_back((i as f64, y));
+        }
+        assert_eq!(store.levels.len(), 1);
+        check_levels(&store);
+        store.extend((n..n + 20 * BRANCHING * BRANCHING).map(|i| (i as f64, -(i as f64))));
+        assert_eq!(store.levels.len(), 2);
+        check_levels(&store);
+
+        store.drop_front(BRANCHING * BRANCHING + 4);
+        check_levels(&store);
+        assert_eq!(store.pop_front(), Some((260.0, 7.0)));
+        check_levels(&store);
+        store.truncate(store.len() - 2 * BRANCHING - 1);
+        check_levels(&store);
+        assert!(store.pop_back().is_some());
+        check_levels(&store);
+
+        store.drop_front(store.len() - 1);
+        check_levels(&store);
+        assert!(store.is_sorted());
+        store.push_back((0.0, 1.0));
+        assert!(!store.is_sorted());
+        check_levels(&store);
+        store.drop_front(2);
+        assert!(store.is_empty());
+        check_levels(&store);
+        store.clear();
+        assert!(store.is_sorted() && store.levels.is_empty());
+    }
+
+    #[test]
+    fn reduced() {
+        let mut store = SeriesStore::new();
+        let n = 100_000;
+        store.extend((0..n).map(|i| (i as f64, ((i * 7919) % 1000) as f64)));
+        assert_eq!(
+            store.reduced(10..20, 100),
+            store.range(10..20).cloned().collect::<Vec<_>>()
+        );
+
+        let points = store.reduced(0..n, 100);
+        assert!(points.len() <= 400 && points.len() >= 100);
+        assert!(points.windows(2).all(|w| w[0].0 <= w[1].0));
+        let ys = |points: &[(f64, f64)]| {
+            points
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(a, b), xy| {
+                    (a.min(xy.1), b.max(xy.1))
+                })
+        };
+        assert_eq!(ys(&points), (0.0, 999.0));
+
+        let buckets = store.buckets(123..n - 45, 100);
+        let count: usize = buckets.iter().map(|b| b.count()).sum();
+        assert_eq!(count, n - 45 - 123);
+        assert_eq!(buckets[0].x_range().0, 123.0);
+        assert_eq!(buckets[buckets.len() - 1].x_range().1, (n - 46) as f64);
+        let mean = buckets
+            .iter()
+            .map(|b| b.mean().unwrap() * b.count() as f64)
+            .sum::<f64>()
+            / count as f64;
+        let expected = store.range(123..n - 45).map(|xy| xy.1).sum::<f64>() / count as f64;
+        assert!((mean - expected).abs() < 1e-9);
+    }
+}
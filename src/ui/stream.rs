@@ -0,0 +1,313 @@
+//!
+//! Serves rendered frames and series data over HTTP, to watch simulations from a remote browser.
+//!
+//! Requires feature `http`. The server is minimal and has no authentication, so it should only be exposed in trusted networks.
+//!
+//! Endpoints:
+//! * `/` - HTML page showing the live frame
+//! * `/frame.png` - The most recent frame as PNG
+//! * `/data.json` - The most recent series data as JSON, if published
+//!
+//! # Example
+//! ```no_run
+//! use easy_graph::ui::chart::{ChartBuilder, Series};
+//! use easy_graph::color::style::RED;
+//!
+//! let mut chart = ChartBuilder::new()
+//!     .add_series(Series::line("A", &RED))
+//!     .with_http_server("127.0.0.1:8080")
+//!     .build();
+//! // Open http://localhost:8080 in a browser. Listen on "0.0.0.0:8080" to allow access from other hosts.
+//! ```
+//!
+
+use crate::ui::metadata::{json_number, json_string};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Interval for polling the listener for new connections and for shutdown.
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(20);
+/// Timeout for reading requests and writing responses, per client.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+const PAGE: &str = r#"<!DOCTYPE html>
+<html><head><title>easy_graph</title></head>
+<body style="margin:0;background:#333">
+<img id="frame" src="frame.png" style="display:block;margin:auto">
+<script>
+const img = document.getElementById("frame");
+img.onload = img.onerror = () => setTimeout(() => img.src = "frame.png?" + Date.now(), 200);
+</script>
+</body></html>
+"#;
+
+/// The latest published content, shared with the server thread.
+#[derive(Default)]
+struct Content {
+    frame: Option<(Vec<u8>, u32, u32)>,
+    json: Option<String>,
+}
+
+///
+/// A minimal HTTP server for frames and data, running on a background thread.
+///
+/// Each client is served on its own thread, so that slow clients do not block others.
+/// The server stops when dropped, or on [`shutdown()`](#method.shutdown).
+///
+/// Use [`ChartBuilder::with_http_server()`](../chart/struct.ChartBuilder.html#method.with_http_server) for charts,
+/// or publish frames of a [`BufferWindow`](../window/struct.BufferWindow.html) manually.
+///
+pub struct FrameServer {
+    address: SocketAddr,
+    content: Arc<Mutex<Content>>,
+    interval: Duration,
+    last_publish: Option<Instant>,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FrameServer {
+    /// Starts a server listening on the given address, e.g. `"127.0.0.1:8080"`.
+    pub fn start<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        // Non-blocking, to check for shutdown between connections
+        listener.set_nonblocking(true)?;
+        let content = Arc::new(Mutex::new(Content::default()));
+        let running = Arc::new(AtomicBool::new(true));
+        let shared = content.clone();
+        let run = running.clone();
+        let thread = thread::spawn(move || {
+            while run.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        let shared = shared.clone();
+                        // Errors only affect a single client
+                        thread::spawn(move || {
+                            let _ = handle_request(stream, &shared);
+                        });
+                    }
+                    Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                        thread::sleep(ACCEPT_INTERVAL)
+                    }
+                    Err(_) => {}
+                }
+            }
+        });
+        Ok(FrameServer {
+            address,
+            content,
+            interval: Duration::from_millis(200),
+            last_publish: None,
+            running,
+            thread: Some(thread),
+        })
+    }
+
+    /// Stops accepting connections, and waits for the server thread to finish.
+    /// Requests in progress are completed in the background. Called on drop.
+    pub fn shutdown(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+
+    /// Returns if the server accepts connections, i.e. it was not shut down.
+    pub fn is_running(&self) -> bool {
+        self.thread.is_some()
+    }
+
+    /// The address the server listens on.
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Sets the minimum interval between published updates, to limit the overhead of copying frames. Default: 200 ms.
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    /// Returns if enough time passed since the last publish. Counts as a publish if it returns `true`.
+    pub fn is_due(&mut self) -> bool {
+        let due = self
+            .last_publish
+            .map(|last| last.elapsed() >= self.interval)
+            .unwrap_or(true);
+        if due {
+            self.last_publish = Some(Instant::now());
+        }
+        due
+    }
+
+    /// Publishes an RGB frame. It is encoded to PNG only when requested by a client.
+    ///
+    /// # Panics
+    /// Panics if the length of `rgb` does not equal 3 * width * height.
+    pub fn publish_frame(&self, rgb: &[u8], width: u32, height: u32) {
+        if rgb.len() != 3 * width as usize * height as usize {
+            panic!("Length of RGB buffer must equal 3 * width * height!");
+        }
+        self.content.lock().unwrap().frame = Some((rgb.to_vec(), width, height));
+    }
+
+    /// Publishes data as JSON, served at `/data.json`.
+    pub fn publish_json(&self, json: String) {
+        self.content.lock().unwrap().json = Some(json);
+    }
+}
+
+impl Drop for FrameServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Serializes named (x, y) series to JSON: `{"series":[{"name":"A","data":[[x,y],...]},...]}`.
+///
+/// Non-finite values are written as `null`.
+pub fn series_to_json<'a, I, D>(series: I) -> String
+where
+    I: IntoIterator<Item = (&'a str, D)>,
+    D: IntoIterator<Item = &'a (f64, f64)>,
+{
+    let entries: Vec<_> = series
+        .into_iter()
+        .map(|(name, data)| {
+            let points: Vec<_> = data
+                .into_iter()
+                .map(|(x, y)| format!("[{},{}]", json_number(*x), json_number(*y)))
+                .collect();
+            format!(
+                "{{\"name\":{},\"data\":[{}]}}",
+                json_string(name),
+                points.join(",")
+            )
+        })
+        .collect();
+    format!("{{\"series\":[{}]}}", entries.join(","))
+}
+
+fn handle_request(stream: TcpStream, content: &Mutex<Content>) -> io::Result<()> {
+    // Accepted streams may inherit non-blocking mode from the listener
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Skip headers
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 {
+        line.clear();
+    }
+    let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+    let path = path.split('?').next().unwrap_or("/");
+
+    let (status, content_type, body) = match path {
+        "/" | "/index.html" => ("200 OK", "text/html", PAGE.as_bytes().to_vec()),
+        "/frame.png" => {
+            let frame = content.lock().unwrap().frame.clone();
+            match frame {
+                Some((rgb, width, height)) => {
+                    ("200 OK", "image/png", encode_png(&rgb, width, height)?)
+                }
+                None => (
+                    "404 Not Found",
+                    "text/plain",
+                    b"No frame published".to_vec(),
+                ),
+            }
+        }
+        "/data.json" => match content.lock().unwrap().json.clone() {
+            Some(json) => ("200 OK", "application/json", json.into_bytes()),
+            None => ("404 Not Found", "text/plain", b"No data published".to_vec()),
+        },
+        _ => ("404 Not Found", "text/plain", b"Not found".to_vec()),
+    };
+
+    let mut stream = reader.into_inner();
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(&body)?;
+    stream.flush()
+}
+
+fn encode_png(rgb: &[u8], width: u32, height: u32) -> io::Result<Vec<u8>> {
+    let mut png = Vec::new();
+    image::png::PNGEncoder::new(&mut png)
+        .encode(rgb, width, height, image::ColorType::Rgb8)
+        .map_err(|err| io::Error::other(err.to_string()))?;
+    Ok(png)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::stream::{series_to_json, FrameServer};
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::time::{Duration, Instant};
+
+    fn get(server: &FrameServer, path: &str) -> Vec<u8> {
+        let mut stream = TcpStream::connect(server.address()).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn json() {
+        let a = vec![(0.0, 1.5), (1.0, f64::NAN)];
+        let json = series_to_json(vec![("A \"1\"", &a)]);
+        assert_eq!(
+            json,
+            r#"{"series":[{"name":"A \"1\"","data":[[0,1.5],[1,null]]}]}"#
+        );
+    }
+
+    #[test]
+    fn serve() {
+        let server = FrameServer::start("127.0.0.1:0").unwrap();
+        let response = String::from_utf8(get(&server, "/frame.png")).unwrap();
+        assert!(response.starts_with("HTTP/1.1 404"));
+
+        server.publish_frame(&[255; 3 * 4 * 2], 4, 2);
+        server.publish_json("{}".to_string());
+        let response = get(&server, "/frame.png");
+        assert!(response.starts_with(b"HTTP/1.1 200 OK"));
+        assert!(response.windows(4).any(|w| w == b"\x89PNG"));
+        let response = String::from_utf8(get(&server, "/data.json?t=1")).unwrap();
+        assert!(response.ends_with("\r\n\r\n{}"));
+    }
+
+    #[test]
+    fn concurrent_clients() {
+        let server = FrameServer::start("127.0.0.1:0").unwrap();
+        // An idle client must not block others
+        let _idle = TcpStream::connect(server.address()).unwrap();
+        let start = Instant::now();
+        let response = String::from_utf8(get(&server, "/")).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn shutdown() {
+        let mut server = FrameServer::start("127.0.0.1:0").unwrap();
+        let address = server.address();
+        assert!(server.is_running());
+        server.shutdown();
+        assert!(!server.is_running());
+        assert!(TcpStream::connect(address).is_err());
+    }
+}
@@ -0,0 +1,299 @@
+//!
+//! Per-series statistics of a [`Chart`](../chart/struct.Chart.html), for end-of-run reports.
+//!
+//! # Example
+//! ```no_run
+//! use easy_graph::ui::chart::{ChartBuilder, Series};
+//! use easy_graph::color::style::RED;
+//!
+//! let mut chart = ChartBuilder::new()
+//!     .add_series(Series::line("Population", &RED))
+//!     .build();
+//! for t in 0..100 {
+//!     chart.push_time_series(t as f64, &[(t * t) as f64]);
+//! }
+//! let summary = chart.summary();
+//! println!("{}", summary); // plain text table
+//! println!("{}", summary.to_markdown());
+//! ```
+//!
+
+use crate::ui::axis::format_tick;
+use std::fmt;
+
+/// Significant digits of values in formatted summaries.
+const SIGNIFICANT_DIGITS: i32 = 5;
+
+///
+/// Statistics of a single series. Non-finite values are ignored, except for the last value.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct SeriesSummary {
+    /// The series' name.
+    pub name: String,
+    /// Number of finite values.
+    pub n: usize,
+    /// Minimum y value, `NaN` if there are no values.
+    pub min: f64,
+    /// Maximum y value, `NaN` if there are no values.
+    pub max: f64,
+    /// Mean y value, `NaN` if there are no values.
+    pub mean: f64,
+    /// The last entry of the series, if any.
+    pub last: Option<(f64, f64)>,
+    /// Least squares slope of y over x, for all data retained by the series, or for the entries in the slope window
+    /// (see [`Chart::summary_with_slope_window()`](../chart/struct.Chart.html#method.summary_with_slope_window)).
+    /// `NaN` for less than two distinct x values.
+    pub slope: f64,
+    /// The unit of the series' y values, if any. Shown after the name in formatted summaries.
+    pub unit: Option<String>,
+    /// The series' metadata entries, as (key, value) pairs.
+    pub meta: Vec<(String, String)>,
+}
+
+impl SeriesSummary {
+    /// Computes the statistics of a series' xy entries.
+    ///
+    /// With a `slope_window`, the slope is computed only from the entries with x values within that distance
+    /// from the last finite entry. Assumes increasing x values in that case.
+    pub(crate) fn new<'a, I>(name: &str, data: I, slope_window: Option<f64>) -> Self
+    where
+        I: IntoIterator<Item = &'a (f64, f64)>,
+        I::IntoIter: DoubleEndedIterator + Clone,
+    {
+        let data = data.into_iter();
+        let is_finite = |xy: &&(f64, f64)| xy.0.is_finite() && xy.1.is_finite();
+        let last = data.clone().next_back().cloned();
+        let (mut n, mut min, mut max, mut mean) = (0, f64::INFINITY, f64::NEG_INFINITY, 0.0);
+        for &(_, y) in data.clone().filter(is_finite) {
+            n += 1;
+            min = min.min(y);
+            max = max.max(y);
+            mean += (y - mean) / n as f64;
+        }
+        let last_x = data.clone().rev().find(is_finite).map(|xy| xy.0);
+        let slope = match (slope_window, last_x) {
+            (Some(window), Some(last_x)) => least_squares_slope(
+                data.rev()
+                    .filter(is_finite)
+                    .take_while(|xy| xy.0 >= last_x - window),
+            ),
+            _ => least_squares_slope(data.filter(is_finite)),
+        };
+        if n == 0 {
+            min = f64::NAN;
+            max = f64::NAN;
+            mean = f64::NAN;
+        }
+        SeriesSummary {
+            name: name.to_string(),
+            n,
+            min,
+            max,
+            mean,
+            last,
+            slope,
+            unit: None,
+            meta: Vec::new(),
+        }
+    }
+
+    /// Formatted cells of a table row.
+    fn cells(&self) -> Vec<String> {
+        let name = match &self.unit {
+            Some(unit) => format!("{} [{}]", self.name, unit),
+            None => self.name.clone(),
+        };
+        vec![
+            name,
+            self.n.to_string(),
+            format_value(self.min),
+            format_value(self.max),
+            format_value(self.mean),
+            self.last
+                .map(|(_, y)| format_value(y))
+                .unwrap_or_else(|| "-".to_string()),
+            format_value(self.slope),
+        ]
+    }
+}
+
+///
+/// Statistics of all series of a chart. See [`Chart::summary()`](../chart/struct.Chart.html#method.summary).
+///
+/// Formats as a plain text table via `Display`, or as a Markdown table via [`to_markdown()`](#method.to_markdown).
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChartSummary {
+    /// Statistics per series, in the order of the chart's series.
+    pub series: Vec<SeriesSummary>,
+}
+
+impl ChartSummary {
+    /// Statistics of the first series with the given name.
+    pub fn get(&self, name: &str) -> Option<&SeriesSummary> {
+        self.series.iter().find(|s| s.name == name)
+    }
+
+    /// Formats the summary as a Markdown table.
+    pub fn to_markdown(&self) -> String {
+        let mut text = format!("| {} |\n", HEADER.join(" | "));
+        text.push_str(&format!("|{}\n", "---|".repeat(HEADER.len())));
+        for series in &self.series {
+            let cells: Vec<_> = series.cells().iter().map(|c| escape_markdown(c)).collect();
+            text.push_str(&format!("| {} |\n", cells.join(" | ")));
+        }
+        text
+    }
+}
+
+/// Column headers of formatted summaries.
+const HEADER: [&str; 7] = ["Series", "n", "min", "max", "mean", "last", "slope"];
+
+impl fmt::Display for ChartSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let header: Vec<_> = HEADER.iter().map(|h| h.to_string()).collect();
+        let rows: Vec<_> = self.series.iter().map(|s| s.cells()).collect();
+        let widths: Vec<_> = (0..HEADER.len())
+            .map(|col| {
+                rows.iter()
+                    .chain(std::iter::once(&header))
+                    .map(|row| row[col].chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        for row in std::iter::once(&header).chain(rows.iter()) {
+            let line: Vec<_> = row
+                .iter()
+                .zip(&widths)
+                .enumerate()
+                .map(|(col, (cell, width))| {
+                    if col == 0 {
+                        format!("{:<width$}", cell, width = width)
+                    } else {
+                        format!("{:>width$}", cell, width = width)
+                    }
+                })
+                .collect();
+            writeln!(f, "{}", line.join("  ").trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// Least squares slope of y over x, using Welford's online algorithm for numerical stability with large x offsets.
+fn least_squares_slope<'a, I: Iterator<Item = &'a (f64, f64)>>(data: I) -> f64 {
+    let (mut n, mut mean_x, mut mean_y, mut var_x, mut cov_xy) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    for &(x, y) in data {
+        n += 1.0;
+        let dx = x - mean_x;
+        mean_x += dx / n;
+        mean_y += (y - mean_y) / n;
+        var_x += dx * (x - mean_x);
+        cov_xy += dx * (y - mean_y);
+    }
+    if n > 1.0 && var_x > 0.0 {
+        cov_xy / var_x
+    } else {
+        f64::NAN
+    }
+}
+
+/// Escapes a table cell for Markdown, so that pipes and line breaks don't break the table.
+fn escape_markdown(cell: &str) -> String {
+    cell.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+/// Formats a value with a limited number of significant digits.
+fn format_value(value: f64) -> String {
+    if !value.is_finite() {
+        return "-".to_string();
+    }
+    if value == 0.0 {
+        return "0".to_string();
+    }
+    let decimals = SIGNIFICANT_DIGITS - 1 - value.abs().log10().floor() as i32;
+    let factor = 10_f64.powi(decimals);
+    format_tick((value * factor).round() / factor)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::summary::{format_value, least_squares_slope, ChartSummary, SeriesSummary};
+
+    #[test]
+    fn series_summary() {
+        let data = vec![(0.0, 1.0), (1.0, f64::NAN), (2.0, 5.0), (3.0, 7.0)];
+        let summary = SeriesSummary::new("A", &data, None);
+        assert_eq!(summary.n, 3);
+        assert_eq!((summary.min, summary.max), (1.0, 7.0));
+        assert!((summary.mean - 13.0 / 3.0).abs() < 1e-12);
+        assert_eq!(summary.last, Some((3.0, 7.0)));
+        assert!((summary.slope - 2.0).abs() < 1e-12);
+
+        let empty = SeriesSummary::new("B", &[], None);
+        assert_eq!(empty.n, 0);
+        assert!(empty.min.is_nan() && empty.mean.is_nan() && empty.slope.is_nan());
+
+        let summary = ChartSummary {
+            series: vec![summary, empty],
+        };
+        let text = summary.to_string();
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("Series  n  min"));
+        assert_eq!(
+            lines[1].split_whitespace().collect::<Vec<_>>(),
+            vec!["A", "3", "1", "7", "4.3333", "7", "2"]
+        );
+        assert_eq!(lines[1].find('3'), lines[0].find('n'));
+        assert_eq!(
+            summary.to_markdown().lines().nth(3),
+            Some("| B | 0 | - | - | - | - | - |")
+        );
+        assert_eq!(summary.get("B").unwrap().n, 0);
+    }
+
+    #[test]
+    fn slope() {
+        // Large x offsets cancel out catastrophically with plain sums
+        let data: Vec<_> = (0..1000)
+            .map(|i| (1e9 + i as f64, 3.0 * i as f64))
+            .collect();
+        assert!((least_squares_slope(data.iter()) - 3.0).abs() < 1e-9);
+        assert!(least_squares_slope([(1.0, 1.0), (1.0, 2.0)].iter()).is_nan());
+
+        let mut data: Vec<_> = (0..10).map(|i| (i as f64, i as f64)).collect();
+        data.extend((10..20).map(|i| (i as f64, 10.0 - 2.0 * (i - 10) as f64)));
+        data.push((20.0, f64::NAN));
+        let summary = SeriesSummary::new("A", &data, Some(5.0));
+        assert!((summary.slope + 2.0).abs() < 1e-12);
+        let summary = SeriesSummary::new("A", &data, Some(0.0));
+        assert!(summary.slope.is_nan());
+        assert_eq!(summary.n, 20);
+    }
+
+    #[test]
+    fn markdown_escape() {
+        let mut series = SeriesSummary::new("A|B", &[(0.0, 1.0)], None);
+        series.unit = Some("m|s".to_string());
+        let summary = ChartSummary {
+            series: vec![series],
+        };
+        assert!(summary
+            .to_markdown()
+            .lines()
+            .nth(2)
+            .unwrap()
+            .starts_with("| A\\|B [m\\|s] | 1 |"));
+    }
+
+    #[test]
+    fn value_format() {
+        assert_eq!(format_value(1234.5678), "1234.6");
+        assert_eq!(format_value(0.000123456), "0.00012346");
+        assert_eq!(format_value(-2.0), "-2");
+        assert_eq!(format_value(f64::NAN), "-");
+    }
+}
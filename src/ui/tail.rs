@@ -0,0 +1,276 @@
+//!
+//! Follows a growing CSV or TSV file and pushes new rows into a [`Chart`](../chart/struct.Chart.html),
+//! e.g. to watch the output of a separate process live.
+//!
+//! # Example
+//! ```no_run
+//! use easy_graph::ui::chart::{ChartBuilder, Series};
+//! use easy_graph::ui::tail::ChartTail;
+//! use easy_graph::color::style::{RED, BLUE};
+//!
+//! let mut chart = ChartBuilder::new()
+//!     .add_series(Series::line("Wolves", &RED))
+//!     .add_series(Series::line("Sheep", &BLUE))
+//!     .build();
+//! // File with header line "time,wolves,sheep"
+//! let mut tail = ChartTail::new("output.csv")
+//!     .with_header()
+//!     .with_x_column_name("time")
+//!     .with_column_name("wolves", 0)
+//!     .with_column_name("sheep", 1);
+//!
+//! while chart.is_open() {
+//!     if let Err(err) = tail.poll(&mut chart) {
+//!         eprintln!("Unable to read file: {}", err);
+//!     }
+//!     chart.update();
+//! }
+//! ```
+//!
+
+use crate::ui::chart::Chart;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// A parsed row: the x value, and pairs of (series index, y value).
+type Row = (f64, Vec<(usize, f64)>);
+
+/// A column, by index or by header name.
+enum Column {
+    Index(usize),
+    Name(String),
+}
+
+impl Column {
+    fn resolve(&self, header: &[String]) -> Option<usize> {
+        match self {
+            Column::Index(index) => Some(*index),
+            Column::Name(name) => header.iter().position(|h| h == name),
+        }
+    }
+}
+
+///
+/// Follows a delimited text file and pushes rows appended to it into a chart.
+///
+/// By default, the first column is used as x value, and all further columns are pushed to series in order.
+/// Values that can't be parsed are pushed as `NaN`, and values of columns without a series in the chart are ignored. If the file is truncated or replaced by a shorter one, it is read from the start again.
+///
+pub struct ChartTail {
+    path: PathBuf,
+    delimiter: char,
+    header: bool,
+    x_column: Option<Column>,
+    columns: Vec<(Column, usize)>,
+    offset: u64,
+    partial: Vec<u8>,
+    header_names: Option<Vec<String>>,
+    rows: usize,
+}
+
+impl ChartTail {
+    /// Creates a tail for the file at the given path. The file does not need to exist yet.
+    ///
+    /// The delimiter is a tab for files with extension `.tsv` or `.tab`, and a comma otherwise.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let delimiter = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("tsv") | Some("tab") => '\t',
+            _ => ',',
+        };
+        ChartTail {
+            path,
+            delimiter,
+            header: false,
+            x_column: Some(Column::Index(0)),
+            columns: Vec::new(),
+            offset: 0,
+            partial: Vec::new(),
+            header_names: None,
+            rows: 0,
+        }
+    }
+    /// Sets the column delimiter.
+    pub fn with_delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+    /// Treats the first line as header, with column names. Required for columns by name.
+    pub fn with_header(mut self) -> Self {
+        self.header = true;
+        self
+    }
+    /// Sets the index of the x column. Default: 0.
+    pub fn with_x_column(mut self, column: usize) -> Self {
+        self.x_column = Some(Column::Index(column));
+        self
+    }
+    /// Sets the name of the x column. Requires [`with_header()`](#method.with_header).
+    pub fn with_x_column_name(mut self, name: &str) -> Self {
+        self.x_column = Some(Column::Name(name.to_string()));
+        self
+    }
+    /// Uses the row number (starting at 0, excluding the header) as x value, instead of a column.
+    pub fn with_row_index(mut self) -> Self {
+        self.x_column = None;
+        self
+    }
+    /// Pushes values of a column to the series with the given index.
+    /// If any columns are mapped, unmapped columns are ignored.
+    pub fn with_column(mut self, column: usize, series: usize) -> Self {
+        self.columns.push((Column::Index(column), series));
+        self
+    }
+    /// Pushes values of a column, by header name, to the series with the given index. Requires [`with_header()`](#method.with_header).
+    pub fn with_column_name(mut self, name: &str, series: usize) -> Self {
+        self.columns.push((Column::Name(name.to_string()), series));
+        self
+    }
+
+    /// Number of data rows read so far, excluding the header.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Reads all rows appended since the last poll and pushes them into the chart. Returns the number of rows read.
+    ///
+    /// An incomplete last line is kept until it is terminated by a line break. A missing file is not an error.
+    /// Values mapped to series indices that are not in the chart are ignored.
+    pub fn poll(&mut self, chart: &mut Chart) -> io::Result<usize> {
+        let rows = self.read_rows()?;
+        let num_series = chart.num_series();
+        for (x, values) in &rows {
+            for (series, y) in values.iter().filter(|(series, _)| *series < num_series) {
+                chart.push_xy(*series, (*x, *y));
+            }
+        }
+        Ok(rows.len())
+    }
+
+    /// Reads new rows, as x values and pairs of (series, y).
+    fn read_rows(&mut self) -> io::Result<Vec<Row>> {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+        if file.metadata()?.len() < self.offset {
+            self.offset = 0;
+            self.partial.clear();
+            self.header_names = None;
+            self.rows = 0;
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        self.offset += file.read_to_end(&mut self.partial)? as u64;
+
+        // Line breaks never occur within multi-byte UTF-8 sequences, so incomplete characters stay in the buffer
+        let end = match self.partial.iter().rposition(|b| *b == b'\n') {
+            Some(end) => end,
+            None => return Ok(Vec::new()),
+        };
+        let bytes: Vec<u8> = self.partial.drain(..=end).collect();
+        let complete = String::from_utf8_lossy(&bytes);
+        let mut rows = Vec::new();
+        for line in complete.lines() {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() {
+                continue;
+            }
+            let cells: Vec<_> = line.split(self.delimiter).map(|c| c.trim()).collect();
+            if self.header && self.header_names.is_none() {
+                self.header_names = Some(cells.iter().map(|c| c.to_string()).collect());
+                continue;
+            }
+            rows.push(self.parse_row(&cells));
+            self.rows += 1;
+        }
+        Ok(rows)
+    }
+
+    /// Extracts the x value and mapped values of a row.
+    fn parse_row(&self, cells: &[&str]) -> Row {
+        let header = self.header_names.as_deref().unwrap_or(&[]);
+        let value = |column: Option<usize>| {
+            column
+                .and_then(|c| cells.get(c))
+                .and_then(|c| c.parse::<f64>().ok())
+                .unwrap_or(f64::NAN)
+        };
+        let x_column = self.x_column.as_ref().and_then(|c| c.resolve(header));
+        let x = match self.x_column {
+            Some(_) => value(x_column),
+            None => self.rows as f64,
+        };
+        let values = if self.columns.is_empty() {
+            (0..cells.len())
+                .filter(|c| Some(*c) != x_column)
+                .enumerate()
+                .map(|(series, c)| (series, value(Some(c))))
+                .collect()
+        } else {
+            self.columns
+                .iter()
+                .map(|(column, series)| (*series, value(column.resolve(header))))
+                .collect()
+        };
+        (x, values)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::tail::ChartTail;
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+
+    #[test]
+    fn tail_file() {
+        let path = std::env::temp_dir().join(format!("easy_graph_tail_{}.csv", std::process::id()));
+        fs::write(&path, "time,a,b\n0,1,2\n1,3").unwrap();
+        let mut tail = ChartTail::new(&path)
+            .with_header()
+            .with_x_column_name("time")
+            .with_column_name("b", 0)
+            .with_column(1, 1);
+        assert_eq!(
+            tail.read_rows().unwrap(),
+            vec![(0.0, vec![(0, 2.0), (1, 1.0)])]
+        );
+
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(file, ",x\n2,5,6").unwrap();
+        let rows = tail.read_rows().unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1], (2.0, vec![(0, 6.0), (1, 5.0)]));
+        assert!(rows[0].1[0].1.is_nan());
+        assert_eq!(tail.rows(), 3);
+
+        fs::write(&path, "0\t1\t2\n").unwrap();
+        let mut tail = ChartTail::new(&path).with_delimiter('\t').with_row_index();
+        assert_eq!(
+            tail.read_rows().unwrap(),
+            vec![(0.0, vec![(0, 0.0), (1, 1.0), (2, 2.0)])]
+        );
+        fs::remove_file(&path).unwrap();
+        assert!(tail.read_rows().unwrap().is_empty());
+    }
+
+    #[test]
+    fn split_utf8() {
+        let path =
+            std::env::temp_dir().join(format!("easy_graph_tail_utf8_{}.csv", std::process::id()));
+        let text = "zeit,größe\n0,1\n".as_bytes();
+        let split = text.iter().position(|b| *b == 0xc3).unwrap() + 1;
+        fs::write(&path, &text[..split]).unwrap();
+        let mut tail = ChartTail::new(&path)
+            .with_header()
+            .with_column_name("größe", 0);
+        assert!(tail.read_rows().unwrap().is_empty());
+
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&text[split..]).unwrap();
+        assert_eq!(tail.read_rows().unwrap(), vec![(0.0, vec![(0, 1.0)])]);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,327 @@
+//!
+//! Renders timelines (Gantt charts) of intervals on named tracks, e.g. for agent states or scheduler activity.
+//!
+//! # Example
+//! ```no_run
+//! use easy_graph::color::style::{BLUE, GREEN, RED};
+//! use easy_graph::ui::timeline::TimelineChart;
+//! use easy_graph::ui::window::WindowBuilder;
+//!
+//! let mut timeline = TimelineChart::new()
+//!     .with_tracks(&["Agent 1", "Agent 2"])
+//!     .with_x_label("Time");
+//! let mut win = WindowBuilder::new().with_dimensions(600, 200).build();
+//!
+//! for t in 0..100 { // change upper limit for longer run!
+//!     let t = t as f64;
+//!     // States are extended while they don't change
+//!     if (t / 10.0) as i32 % 2 == 0 {
+//!         timeline.set_state(0, t, "Moving", &GREEN);
+//!     } else {
+//!         timeline.set_state(0, t, "Resting", &BLUE);
+//!     }
+//!     timeline.set_state(1, t, if t < 50.0 { "Searching" } else { "Eating" }, &RED);
+//!     timeline.draw_window(&mut win);
+//! }
+//! ```
+//!
+
+use crate::ui::window::BufferWindow;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::collections::VecDeque;
+
+/// Font size of labels, in pixels.
+const FONT_SIZE: f64 = 14.0;
+/// Margin around the chart, in pixels.
+const MARGIN: i32 = 10;
+/// Padding around labels, in pixels.
+const PADDING: i32 = 5;
+/// Fraction of a track's height left empty above and below intervals.
+const TRACK_GAP: f64 = 0.15;
+
+///
+/// An interval on a track of a [`TimelineChart`](struct.TimelineChart.html).
+///
+pub struct Interval {
+    /// Start of the interval.
+    pub start: f64,
+    /// End of the interval.
+    pub end: f64,
+    /// Label, drawn inside the interval if it fits.
+    pub label: String,
+    /// Fill color.
+    pub color: RGBColor,
+}
+
+/// A named row of intervals.
+struct Track {
+    name: String,
+    intervals: VecDeque<Interval>,
+}
+
+///
+/// A timeline of intervals on named tracks, drawn as horizontal bars, with tracks from top to bottom.
+///
+/// Intervals can be pushed complete via [`push()`](#method.push), or extended live via [`extend()`](#method.extend)
+/// and [`set_state()`](#method.set_state).
+///
+pub struct TimelineChart {
+    tracks: Vec<Track>,
+    x_label: String,
+    x_window: Option<f64>,
+    limit: Option<usize>,
+}
+
+impl Default for TimelineChart {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimelineChart {
+    /// Creates an empty timeline chart.
+    pub fn new() -> Self {
+        TimelineChart {
+            tracks: Vec::new(),
+            x_label: String::new(),
+            x_window: None,
+            limit: None,
+        }
+    }
+    /// Adds tracks, from top to bottom.
+    pub fn with_tracks(mut self, names: &[&str]) -> Self {
+        for name in names {
+            self.add_track(name);
+        }
+        self
+    }
+    /// Sets the label of the time axis.
+    pub fn with_x_label(mut self, label: &str) -> Self {
+        self.x_label = label.to_string();
+        self
+    }
+    /// Shows only the latest time span of the given width, following the latest interval end.
+    pub fn with_x_window(mut self, width: f64) -> Self {
+        self.x_window = Some(width);
+        self
+    }
+    /// Limits the number of intervals per track. Oldest intervals are removed first.
+    pub fn with_limit(mut self, intervals: usize) -> Self {
+        self.limit = Some(intervals);
+        self
+    }
+
+    /// Adds a track below the existing ones, and returns its index.
+    pub fn add_track(&mut self, name: &str) -> usize {
+        self.tracks.push(Track {
+            name: name.to_string(),
+            intervals: VecDeque::new(),
+        });
+        self.tracks.len() - 1
+    }
+    /// The index of the first track with the given name.
+    pub fn track_index(&self, name: &str) -> Option<usize> {
+        self.tracks.iter().position(|track| track.name == name)
+    }
+    /// The number of tracks.
+    pub fn num_tracks(&self) -> usize {
+        self.tracks.len()
+    }
+    /// The intervals of a track, from oldest to latest.
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of track indices.
+    pub fn intervals(&self, track: usize) -> impl Iterator<Item = &Interval> {
+        self.tracks[track].intervals.iter()
+    }
+
+    /// Pushes an interval to a track.
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of track indices.
+    pub fn push(&mut self, track: usize, start: f64, end: f64, label: &str, color: &RGBColor) {
+        let intervals = &mut self.tracks[track].intervals;
+        intervals.push_back(Interval {
+            start,
+            end,
+            label: label.to_string(),
+            color: RGBColor(color.0, color.1, color.2),
+        });
+        if let Some(limit) = self.limit {
+            while intervals.len() > limit {
+                intervals.pop_front();
+            }
+        }
+    }
+    /// Extends the latest interval of a track to the given end. Has no effect if the track has no intervals.
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of track indices.
+    pub fn extend(&mut self, track: usize, end: f64) {
+        if let Some(interval) = self.tracks[track].intervals.back_mut() {
+            interval.end = end;
+        }
+    }
+    /// Extends the latest interval of a track if it has the same label, or ends it and starts a new interval otherwise.
+    ///
+    /// Convenient for state durations: call it with the current state on every time step.
+    ///
+    /// # Panics
+    /// Panics if the index is not in the range of track indices.
+    pub fn set_state(&mut self, track: usize, time: f64, label: &str, color: &RGBColor) {
+        match self.tracks[track].intervals.back_mut() {
+            Some(interval) if interval.label == label => interval.end = time,
+            Some(interval) => {
+                interval.end = time;
+                self.push(track, time, time, label, color);
+            }
+            None => self.push(track, time, time, label, color),
+        }
+    }
+    /// Removes all intervals, but keeps the tracks.
+    pub fn clear(&mut self) {
+        for track in &mut self.tracks {
+            track.intervals.clear();
+        }
+    }
+
+    /// The visible time range: the range of all intervals, or the latest time window if set.
+    pub fn x_range(&self) -> (f64, f64) {
+        let (min, max) = self
+            .tracks
+            .iter()
+            .flat_map(|track| track.intervals.iter())
+            .fold((f64::MAX, f64::MIN), |(min, max), int| {
+                (min.min(int.start), max.max(int.end))
+            });
+        if min > max {
+            return (0.0, 1.0);
+        }
+        let min = match self.x_window {
+            Some(width) => max - width,
+            None => min,
+        };
+        if min == max {
+            (min - 0.5, max + 0.5)
+        } else {
+            (min, max)
+        }
+    }
+
+    /// Draws the timeline onto a drawing area, filling the area.
+    pub fn draw<DB: DrawingBackend>(&self, area: &DrawingArea<DB, Shift>) {
+        area.fill(&WHITE).unwrap();
+        let font = ("sans-serif", FONT_SIZE).into_font();
+        let names_width = self
+            .tracks
+            .iter()
+            .map(|track| area.estimate_text_size(&track.name, &font).unwrap().0 as i32)
+            .max()
+            .unwrap_or(0);
+        let (x_min, x_max) = self.x_range();
+        let rows = self.tracks.len().max(1) as f64;
+
+        let mut cc = ChartBuilder::on(area)
+            .margin(MARGIN as u32)
+            .x_label_area_size(40)
+            .y_label_area_size((names_width + 2 * PADDING) as u32)
+            .build_ranged(x_min..x_max, 0.0..rows)
+            .unwrap();
+        cc.configure_mesh()
+            .x_labels(10)
+            .y_labels(0)
+            .disable_y_mesh()
+            .x_desc(&self.x_label)
+            .draw()
+            .unwrap();
+
+        // Pixel coordinates relative to the plotting area
+        let plot = cc.plotting_area().strip_coord_spec();
+        let (plot_x, plot_y) = plot.get_base_pixel();
+        let to_plot = |xy: (f64, f64)| {
+            let (x, y) = cc.backend_coord(&xy);
+            (x - plot_x, y - plot_y)
+        };
+        let area_y = plot_y - area.get_base_pixel().1;
+
+        for (row, track) in self.tracks.iter().enumerate() {
+            let top = rows - row as f64 - TRACK_GAP;
+            let bottom = rows - row as f64 - 1.0 + TRACK_GAP;
+            let (_, y) = to_plot((x_min, rows - row as f64 - 0.5));
+            let pos = (MARGIN + PADDING, area_y + y - FONT_SIZE as i32 / 2);
+            area.draw_text(&track.name, &font.color(&BLACK), pos)
+                .unwrap();
+
+            let visible = track
+                .intervals
+                .iter()
+                .filter(|int| int.end >= x_min && int.start <= x_max);
+            for interval in visible {
+                let p0 = to_plot((interval.start.max(x_min), top));
+                let p1 = to_plot((interval.end.min(x_max), bottom));
+                plot.draw(&Rectangle::new([p0, p1], interval.color.filled()))
+                    .unwrap();
+                let (w, _) = plot.estimate_text_size(&interval.label, &font).unwrap();
+                if w as i32 + 2 * PADDING <= p1.0 - p0.0 {
+                    let color = if luminance(&interval.color) > 0.5 {
+                        &BLACK
+                    } else {
+                        &WHITE
+                    };
+                    let pos = (p0.0 + PADDING, (p0.1 + p1.1 - FONT_SIZE as i32) / 2);
+                    plot.draw_text(&interval.label, &font.color(color), pos)
+                        .unwrap();
+                }
+            }
+        }
+    }
+
+    /// Draws the timeline into a window, filling the window.
+    pub fn draw_window(&self, window: &mut BufferWindow) {
+        window.draw(|b| {
+            let root = b.into_drawing_area();
+            self.draw(&root);
+        });
+    }
+}
+
+/// Relative luminance of a color, in the range 0 - 1.
+fn luminance(color: &RGBColor) -> f64 {
+    (0.299 * color.0 as f64 + 0.587 * color.1 as f64 + 0.114 * color.2 as f64) / 255.0
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::timeline::TimelineChart;
+    use plotters::prelude::*;
+
+    #[test]
+    fn timeline() {
+        let mut timeline = TimelineChart::new().with_tracks(&["a", "b"]).with_limit(2);
+        assert_eq!(timeline.track_index("b"), Some(1));
+        assert_eq!(timeline.x_range(), (0.0, 1.0));
+
+        timeline.push(0, 0.0, 2.0, "x", &RED);
+        timeline.set_state(1, 1.0, "y", &BLUE);
+        timeline.set_state(1, 3.0, "y", &BLUE);
+        timeline.set_state(1, 4.0, "z", &GREEN);
+        timeline.set_state(1, 5.0, "w", &GREEN);
+        timeline.extend(1, 10.0);
+        let spans: Vec<_> = timeline.intervals(1).map(|i| (i.start, i.end)).collect();
+        assert_eq!(spans, vec![(4.0, 5.0), (5.0, 10.0)]);
+        assert_eq!(timeline.x_range(), (0.0, 10.0));
+
+        let (w, h) = (200, 100);
+        let mut buffer = vec![0; 3 * w * h];
+        {
+            let root =
+                BitMapBackend::with_buffer(&mut buffer, (w as u32, h as u32)).into_drawing_area();
+            timeline.draw(&root);
+        }
+        let pixels: Vec<_> = buffer.chunks(3).collect();
+        assert!(pixels.contains(&&[255, 0, 0][..]));
+        assert!(pixels.contains(&&[0, 255, 0][..]));
+        assert!(!pixels.contains(&&[0, 0, 255][..]));
+    }
+}
@@ -0,0 +1,200 @@
+//!
+//! Routes numeric fields of [`tracing`](https://docs.rs/tracing) events into chart series, enabled by feature `trace`.
+//!
+//! A [`TraceSink`](struct.TraceSink.html) provides a [`ChartLayer`](struct.ChartLayer.html) to be added to a `tracing` subscriber.
+//! The layer collects all numeric fields of events, like `infected` and `susceptible` in `info!(infected = 5, susceptible = 100)`,
+//! and sends them to the sink. On [`TraceSink::apply()`](struct.TraceSink.html#method.apply), values are pushed to the chart series
+//! with the same name as the field, adding series on demand. X values are seconds since the sink was created.
+//!
+//! As the layer only sends values through a channel, events can be emitted from any thread.
+//!
+//! # Example
+//! ```no_run
+//! use easy_graph::ui::chart::ChartBuilder;
+//! use easy_graph::ui::trace::TraceSink;
+//! use tracing::info;
+//! use tracing_subscriber::layer::SubscriberExt;
+//!
+//! let mut sink = TraceSink::new();
+//! let subscriber = tracing_subscriber::registry().with(sink.layer());
+//! tracing::subscriber::set_global_default(subscriber).unwrap();
+//!
+//! let mut chart = ChartBuilder::new().build();
+//! let (mut susceptible, mut infected) = (1000, 1);
+//! while chart.is_open() {
+//!     if susceptible > 0 {
+//!         susceptible -= 1;
+//!         infected += 1;
+//!     }
+//!     info!(infected, susceptible, "step");
+//!
+//!     sink.apply(&mut chart);
+//!     chart.update();
+//! }
+//! ```
+//!
+
+use crate::ui::chart::{Chart, Series};
+use std::fmt;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::time::Instant;
+use tracing_core::field::{Field, Visit};
+use tracing_core::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// The numeric fields of a single event.
+struct Sample {
+    time: f64,
+    values: Vec<(&'static str, f64)>,
+}
+
+/// Collects the numeric fields of an event.
+#[derive(Default)]
+struct NumericVisitor {
+    values: Vec<(&'static str, f64)>,
+}
+
+impl Visit for NumericVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.values.push((field.name(), value));
+    }
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.values.push((field.name(), value as f64));
+    }
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.values.push((field.name(), value as f64));
+    }
+    fn record_i128(&mut self, field: &Field, value: i128) {
+        self.values.push((field.name(), value as f64));
+    }
+    fn record_u128(&mut self, field: &Field, value: u128) {
+        self.values.push((field.name(), value as f64));
+    }
+    fn record_debug(&mut self, _field: &Field, _value: &dyn fmt::Debug) {}
+}
+
+///
+/// A `tracing` layer sending the numeric fields of all events to a [`TraceSink`](struct.TraceSink.html).
+///
+/// Created by [`TraceSink::layer()`](struct.TraceSink.html#method.layer). Events without numeric fields are ignored.
+///
+pub struct ChartLayer {
+    sender: Sender<Sample>,
+    start: Instant,
+}
+
+impl<S: Subscriber> Layer<S> for ChartLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = NumericVisitor::default();
+        event.record(&mut visitor);
+        if visitor.values.is_empty() {
+            return;
+        }
+        // The sink may have been dropped, e.g. when the chart was closed.
+        let _ = self.sender.send(Sample {
+            time: self.start.elapsed().as_secs_f64(),
+            values: visitor.values,
+        });
+    }
+}
+
+///
+/// Receives numeric fields of `tracing` events, and pushes them to chart series.
+///
+/// See the [module docs](index.html) for an example.
+///
+pub struct TraceSink {
+    sender: Sender<Sample>,
+    receiver: Receiver<Sample>,
+    start: Instant,
+    fields: Option<Vec<String>>,
+}
+
+impl Default for TraceSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TraceSink {
+    /// Creates a sink routing all numeric fields into chart series.
+    pub fn new() -> Self {
+        let (sender, receiver) = channel();
+        TraceSink {
+            sender,
+            receiver,
+            start: Instant::now(),
+            fields: None,
+        }
+    }
+    /// Restricts the sink to the given field names. Values of all other fields are dropped.
+    pub fn with_fields(mut self, fields: &[&str]) -> Self {
+        self.fields = Some(fields.iter().map(|field| field.to_string()).collect());
+        self
+    }
+
+    /// A layer to add to a `tracing` subscriber, see the [module docs](index.html).
+    pub fn layer(&self) -> ChartLayer {
+        ChartLayer {
+            sender: self.sender.clone(),
+            start: self.start,
+        }
+    }
+
+    /// Pushes the values of all pending events to the chart. Returns the number of pushed values.
+    ///
+    /// Each value is pushed to the first series with the field's name. Missing series are added as
+    /// [`Series::line_auto()`](../chart/struct.Series.html#method.line_auto), colored from the chart's palette.
+    ///
+    /// Call this once per iteration of the application's main loop, before [`Chart::update()`](../chart/struct.Chart.html#method.update).
+    pub fn apply(&mut self, chart: &mut Chart) -> usize {
+        let mut count = 0;
+        for sample in self.receiver.try_iter() {
+            for (name, value) in sample.values {
+                if let Some(fields) = &self.fields {
+                    if !fields.iter().any(|field| field == name) {
+                        continue;
+                    }
+                }
+                let index = match chart.series_index(name) {
+                    Some(index) => index,
+                    None => chart.add_series(Series::line_auto(name)),
+                };
+                chart.push_xy(index, (sample.time, value));
+                count += 1;
+            }
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::color::style::RED;
+    use crate::ui::chart::{ChartBuilder, Series};
+    use crate::ui::trace::TraceSink;
+    use tracing::info;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn trace_sink() {
+        let mut sink = TraceSink::new().with_fields(&["infected", "susceptible", "rate"]);
+        let subscriber = tracing_subscriber::registry().with(sink.layer());
+        tracing::subscriber::with_default(subscriber, || {
+            info!(infected = 5, susceptible = 100_u64, ignored = 1, "step");
+            info!(rate = 0.5, name = "text");
+            info!("no values");
+        });
+
+        let mut chart = ChartBuilder::new()
+            .add_series(Series::line("susceptible", &RED))
+            .build_headless();
+        assert_eq!(sink.apply(&mut chart), 3);
+        assert_eq!(chart.num_series(), 3);
+        assert_eq!(chart.series_index("susceptible"), Some(0));
+        assert_eq!(chart.series_index("infected"), Some(1));
+        assert_eq!(chart.series_index("rate"), Some(2));
+        assert_eq!(chart.series_index("ignored"), None);
+        assert_eq!(sink.apply(&mut chart), 0);
+    }
+}
@@ -0,0 +1,590 @@
+//!
+//! Simple widgets like progress bars, gauges, text boxes and size legends, positioned by pixel rectangles.
+//!
+//! # Example
+//! ```no_run
+//! use easy_graph::ui::widgets::{ProgressBar, Widget};
+//! use easy_graph::ui::window::WindowBuilder;
+//! use easy_graph::ui::drawing::IntoDrawingArea;
+//! use easy_graph::color::style::WHITE;
+//!
+//! let mut win = WindowBuilder::new().with_dimensions(400, 100).build();
+//! let mut progress = ProgressBar::new((10, 10), (380, 30)).with_label("tick").with_eta();
+//!
+//! for tick in 0..10000 {
+//!     progress.set_progress(tick, 10000);
+//!     win.draw(|b| {
+//!         let root = b.into_drawing_area();
+//!         root.fill(&WHITE).unwrap();
+//!         progress.draw(&root);
+//!     });
+//! }
+//! ```
+//!
+
+use crate::ui::axis::format_tick;
+use plotters::coord::Shift;
+use plotters::drawing::backend::DrawingBackend;
+use plotters::prelude::*;
+use std::time::Instant;
+
+/// Font size of widget texts, relative to the widget height.
+const FONT_SCALE: f64 = 0.6;
+
+///
+/// A widget that can be drawn onto a drawing area, e.g. of a [`BufferWindow`](../window/struct.BufferWindow.html).
+///
+pub trait Widget {
+    /// Draws the widget onto the area, at the widget's pixel position.
+    fn draw<DB: DrawingBackend>(&self, area: &DrawingArea<DB, Shift>);
+}
+
+///
+/// A horizontal progress bar with a label, a `value/max` readout and an optional estimate of the remaining time.
+///
+pub struct ProgressBar {
+    pos: (i32, i32),
+    size: (u32, u32),
+    label: Option<String>,
+    color: RGBColor,
+    value: usize,
+    max: usize,
+    eta: bool,
+    /// Time and value of the first progress, for the ETA.
+    start: Option<(Instant, usize)>,
+}
+
+impl ProgressBar {
+    /// Creates a progress bar with its upper left corner at `pos`.
+    pub fn new(pos: (i32, i32), size: (u32, u32)) -> Self {
+        ProgressBar {
+            pos,
+            size,
+            label: None,
+            color: RGBColor(100, 180, 100),
+            value: 0,
+            max: 1,
+            eta: false,
+            start: None,
+        }
+    }
+
+    /// Sets a label shown before the progress readout.
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    /// Sets the color of the bar.
+    pub fn with_color(mut self, color: &RGBColor) -> Self {
+        self.color = RGBColor(color.0, color.1, color.2);
+        self
+    }
+
+    /// Shows the estimated remaining time, extrapolated from the progress since the first call to [`set_progress()`](#method.set_progress).
+    pub fn with_eta(mut self) -> Self {
+        self.eta = true;
+        self
+    }
+
+    /// Sets the current progress. The ETA restarts when the progress goes backwards.
+    pub fn set_progress(&mut self, value: usize, max: usize) {
+        if self.eta && self.start.map_or(true, |(_, start)| value < start) {
+            self.start = Some((Instant::now(), value));
+        }
+        self.value = value;
+        self.max = max;
+    }
+
+    /// Returns the fraction of completed progress, in the range [0, 1].
+    pub fn fraction(&self) -> f64 {
+        if self.max == 0 {
+            1.0
+        } else {
+            (self.value as f64 / self.max as f64).clamp(0.0, 1.0)
+        }
+    }
+
+    /// Returns the estimated remaining time in seconds, if enabled and any progress was made since the first call to
+    /// [`set_progress()`](#method.set_progress).
+    pub fn eta(&self) -> Option<f64> {
+        self.eta_at(Instant::now())
+    }
+
+    fn eta_at(&self, now: Instant) -> Option<f64> {
+        let (time, start) = self.start?;
+        if self.value <= start {
+            return None;
+        }
+        let remaining = self.max.saturating_sub(self.value) as f64;
+        Some(now.duration_since(time).as_secs_f64() * remaining / (self.value - start) as f64)
+    }
+
+    fn text(&self) -> String {
+        let mut text = format!("{}/{}", self.value, self.max);
+        if let Some(label) = &self.label {
+            text = format!("{} {}", label, text);
+        }
+        if let Some(eta) = self.eta() {
+            text = format!("{}, ETA {}", text, format_duration(eta));
+        }
+        text
+    }
+}
+
+impl Widget for ProgressBar {
+    fn draw<DB: DrawingBackend>(&self, area: &DrawingArea<DB, Shift>) {
+        let filled = (self.size.0 as f64 * self.fraction()) as i32;
+        draw_frame(area, self.pos, self.size, filled, &self.color);
+        draw_text(area, self.pos, self.size, &self.text());
+    }
+}
+
+///
+/// A numeric gauge showing a labeled value, with a bar indicating its position in a range.
+///
+pub struct Gauge {
+    pos: (i32, i32),
+    size: (u32, u32),
+    label: String,
+    unit: String,
+    range: (f64, f64),
+    precision: usize,
+    color: RGBColor,
+    value: f64,
+}
+
+impl Gauge {
+    /// Creates a gauge for values in the given range, with its upper left corner at `pos`.
+    pub fn new(label: &str, pos: (i32, i32), size: (u32, u32), range: (f64, f64)) -> Self {
+        Gauge {
+            pos,
+            size,
+            label: label.to_string(),
+            unit: String::new(),
+            range,
+            precision: 2,
+            color: RGBColor(100, 140, 200),
+            value: range.0,
+        }
+    }
+
+    /// Sets a unit shown after the value.
+    pub fn with_unit(mut self, unit: &str) -> Self {
+        self.unit = unit.to_string();
+        self
+    }
+
+    /// Sets the number of decimal places shown. Default 2.
+    pub fn with_precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Sets the color of the bar.
+    pub fn with_color(mut self, color: &RGBColor) -> Self {
+        self.color = RGBColor(color.0, color.1, color.2);
+        self
+    }
+
+    /// Sets the displayed value.
+    pub fn set_value(&mut self, value: f64) {
+        self.value = value;
+    }
+
+    /// Returns the displayed value.
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+}
+
+impl Widget for Gauge {
+    fn draw<DB: DrawingBackend>(&self, area: &DrawingArea<DB, Shift>) {
+        let span = self.range.1 - self.range.0;
+        let fraction = if span > 0.0 {
+            ((self.value - self.range.0) / span).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let filled = (self.size.0 as f64 * fraction) as i32;
+        draw_frame(area, self.pos, self.size, filled, &self.color);
+        let text = format!(
+            "{}: {:.*}{}",
+            self.label, self.precision, self.value, self.unit
+        );
+        draw_text(area, self.pos, self.size, &text);
+    }
+}
+
+///
+/// A box showing one or more lines of text.
+///
+pub struct TextBox {
+    pos: (i32, i32),
+    size: (u32, u32),
+    font_size: u32,
+    border: bool,
+    lines: Vec<String>,
+}
+
+impl TextBox {
+    /// Creates an empty text box with its upper left corner at `pos`.
+    pub fn new(pos: (i32, i32), size: (u32, u32)) -> Self {
+        TextBox {
+            pos,
+            size,
+            font_size: 15,
+            border: true,
+            lines: Vec::new(),
+        }
+    }
+
+    /// Sets the font size. Default 15.
+    pub fn with_font_size(mut self, font_size: u32) -> Self {
+        self.font_size = font_size;
+        self
+    }
+
+    /// Hides the border.
+    pub fn without_border(mut self) -> Self {
+        self.border = false;
+        self
+    }
+
+    /// Sets the text. Line breaks start new lines.
+    pub fn set_text(&mut self, text: &str) {
+        self.lines = text.lines().map(|line| line.to_string()).collect();
+    }
+}
+
+impl Widget for TextBox {
+    fn draw<DB: DrawingBackend>(&self, area: &DrawingArea<DB, Shift>) {
+        let (x, y) = self.pos;
+        let (w, h) = (self.size.0 as i32, self.size.1 as i32);
+        area.draw(&Rectangle::new([(x, y), (x + w, y + h)], WHITE.filled()))
+            .unwrap();
+        if self.border {
+            area.draw(&Rectangle::new([(x, y), (x + w, y + h)], &BLACK))
+                .unwrap();
+        }
+        let font = ("sans-serif", self.font_size).into_font();
+        let padding = self.font_size as i32 / 3;
+        for (i, line) in self.lines.iter().enumerate() {
+            let line_y = y + padding + i as i32 * (self.font_size as i32 + padding);
+            if line_y + self.font_size as i32 > y + h {
+                break;
+            }
+            area.draw_text(line, &font.color(&BLACK), (x + padding, line_y))
+                .unwrap();
+        }
+    }
+}
+
+///
+/// A legend for bubble sizes: nested reference circles with value labels.
+///
+/// Marker areas are proportional to values, with the maximum value drawn at the maximum radius.
+/// Use [`radius()`](#method.radius) to size bubbles consistently with the legend.
+///
+/// # Example
+/// ```
+/// use easy_graph::ui::chart::{ChartBuilder, Series};
+/// use easy_graph::ui::widgets::{SizeLegend, Widget};
+/// use easy_graph::color::style::{Color, RED};
+/// use easy_graph::ui::element::Circle;
+///
+/// let legend = SizeLegend::new((10, 10), 1000.0, 20.0).with_label("Population");
+/// let towns = vec![((1.0, 2.0), 150.0), ((3.0, 1.0), 800.0)];
+///
+/// let mut chart = ChartBuilder::new()
+///     .add_series(Series::point("Towns", &RED))
+///     .with_xlim(Some(0.0), Some(4.0))
+///     .with_ylim(Some(0.0), Some(3.0))
+///     .build_headless();
+/// chart.post_draw(move |area, coords| {
+///     for (xy, population) in &towns {
+///         let radius = legend.radius(*population).round() as i32;
+///         area.draw(&Circle::new(coords.to_pixel(*xy), radius, RED.mix(0.5).filled()))
+///             .unwrap();
+///     }
+///     legend.draw(area);
+/// });
+/// chart.update();
+/// ```
+///
+pub struct SizeLegend {
+    pos: (i32, i32),
+    max_value: f64,
+    max_radius: f64,
+    values: Vec<f64>,
+    label: Option<String>,
+    precision: Option<usize>,
+    color: RGBColor,
+    font_size: u32,
+}
+
+impl SizeLegend {
+    /// Creates a size legend with its upper left corner at `pos`, for `max_value` drawn with a radius of `max_radius` pixels.
+    ///
+    /// Shows reference circles for a quarter, half and all of the maximum value by default.
+    pub fn new(pos: (i32, i32), max_value: f64, max_radius: f64) -> Self {
+        SizeLegend {
+            pos,
+            max_value,
+            max_radius,
+            values: vec![0.25 * max_value, 0.5 * max_value, max_value],
+            label: None,
+            precision: None,
+            color: RGBColor(0, 0, 0),
+            font_size: 12,
+        }
+    }
+
+    /// Sets the values of the reference circles.
+    pub fn with_values(mut self, values: &[f64]) -> Self {
+        self.values = values.to_vec();
+        self
+    }
+
+    /// Sets a label shown above the circles.
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    /// Sets the number of decimal places shown. Default: as many as required.
+    pub fn with_precision(mut self, precision: usize) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+
+    /// Sets the color of the circles. Default black.
+    pub fn with_color(mut self, color: &RGBColor) -> Self {
+        self.color = RGBColor(color.0, color.1, color.2);
+        self
+    }
+
+    /// Sets the font size. Default 12.
+    pub fn with_font_size(mut self, font_size: u32) -> Self {
+        self.font_size = font_size;
+        self
+    }
+
+    /// Moves the legend's upper left corner to `pos`, e.g. to align it using its [`size()`](#method.size).
+    pub fn set_position(&mut self, pos: (i32, i32)) {
+        self.pos = pos;
+    }
+
+    /// The marker radius for a value, in pixels. Marker areas are proportional to values.
+    pub fn radius(&self, value: f64) -> f64 {
+        if value > 0.0 && self.max_value > 0.0 {
+            self.max_radius * (value / self.max_value).sqrt()
+        } else {
+            0.0
+        }
+    }
+
+    /// The size of the legend in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        let font = ("sans-serif", self.font_size).into_font();
+        let text_width = |text: &str| font.box_size(text).map(|s| s.0).unwrap_or(0) as i32;
+        let padding = self.padding();
+        let labels = self
+            .labels()
+            .iter()
+            .map(|l| text_width(l))
+            .max()
+            .unwrap_or(0);
+        let circles = 2 * self.max_radius.ceil() as i32 + 2 * padding + labels;
+        let title = self.label.as_deref().map(text_width).unwrap_or(0);
+        let width = circles.max(title) + 2 * padding;
+        let height = 2 * self.max_radius.ceil() as i32 + self.title_height() + 2 * padding;
+        (width as u32, height as u32)
+    }
+
+    fn padding(&self) -> i32 {
+        (self.font_size as i32 / 3).max(1)
+    }
+
+    fn title_height(&self) -> i32 {
+        if self.label.is_some() {
+            self.font_size as i32 + self.padding()
+        } else {
+            0
+        }
+    }
+
+    fn labels(&self) -> Vec<String> {
+        self.values
+            .iter()
+            .map(|v| match self.precision {
+                Some(precision) => format!("{:.*}", precision, v),
+                None => format_tick(*v),
+            })
+            .collect()
+    }
+}
+
+impl Widget for SizeLegend {
+    fn draw<DB: DrawingBackend>(&self, area: &DrawingArea<DB, Shift>) {
+        let (x, y) = self.pos;
+        let (w, h) = self.size();
+        let corner = (x + w as i32, y + h as i32);
+        area.draw(&Rectangle::new([(x, y), corner], WHITE.mix(0.8).filled()))
+            .unwrap();
+        area.draw(&Rectangle::new([(x, y), corner], &BLACK))
+            .unwrap();
+
+        let font = ("sans-serif", self.font_size).into_font();
+        let padding = self.padding();
+        if let Some(label) = &self.label {
+            area.draw_text(label, &font.color(&BLACK), (x + padding, y + padding))
+                .unwrap();
+        }
+        let max_radius = self.max_radius.ceil() as i32;
+        let center_x = x + padding + max_radius;
+        let bottom = corner.1 - padding;
+        let label_x = center_x + max_radius + 2 * padding;
+        let mut order: Vec<_> = self.values.iter().zip(self.labels()).collect();
+        order.sort_by(|a, b| b.0.partial_cmp(a.0).unwrap_or(std::cmp::Ordering::Equal));
+        for (value, label) in order {
+            let radius = self.radius(*value).round() as i32;
+            let top = bottom - 2 * radius;
+            area.draw(&Circle::new(
+                (center_x, bottom - radius),
+                radius,
+                &self.color,
+            ))
+            .unwrap();
+            area.draw(&PathElement::new(
+                vec![(center_x, top), (label_x - padding, top)],
+                &self.color.mix(0.5),
+            ))
+            .unwrap();
+            let text_y = top - self.font_size as i32 / 2;
+            area.draw_text(&label, &font.color(&BLACK), (label_x, text_y))
+                .unwrap();
+        }
+    }
+}
+
+/// Draws a widget background with a filled part of the given width and a border.
+fn draw_frame<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    pos: (i32, i32),
+    size: (u32, u32),
+    filled: i32,
+    color: &RGBColor,
+) {
+    let (x, y) = pos;
+    let (w, h) = (size.0 as i32, size.1 as i32);
+    area.draw(&Rectangle::new([(x, y), (x + w, y + h)], WHITE.filled()))
+        .unwrap();
+    if filled > 0 {
+        area.draw(&Rectangle::new(
+            [(x, y), (x + filled, y + h)],
+            color.filled(),
+        ))
+        .unwrap();
+    }
+    area.draw(&Rectangle::new([(x, y), (x + w, y + h)], &BLACK))
+        .unwrap();
+}
+
+/// Draws a single line of text, vertically centered in a widget.
+fn draw_text<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    pos: (i32, i32),
+    size: (u32, u32),
+    text: &str,
+) {
+    let font_size = (size.1 as f64 * FONT_SCALE).max(1.0);
+    let font = ("sans-serif", font_size).into_font();
+    let text_y = pos.1 + ((size.1 as f64 - font_size) / 2.0) as i32;
+    area.draw_text(text, &font.color(&BLACK), (pos.0 + 5, text_y))
+        .unwrap();
+}
+
+/// Formats a duration in seconds as hours, minutes and seconds.
+fn format_duration(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    if seconds >= 3600 {
+        format!("{}h {}min", seconds / 3600, (seconds % 3600) / 60)
+    } else if seconds >= 60 {
+        format!("{}min {}s", seconds / 60, seconds % 60)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ui::widgets::{format_duration, Gauge, ProgressBar, SizeLegend, TextBox, Widget};
+    use plotters::prelude::*;
+    use std::time::Duration;
+
+    #[test]
+    fn progress() {
+        let mut bar = ProgressBar::new((0, 0), (100, 20)).with_label("tick");
+        bar.set_progress(25, 100);
+        assert_eq!(bar.fraction(), 0.25);
+        assert_eq!(bar.text(), "tick 25/100");
+        assert_eq!(bar.eta(), None);
+
+        let mut bar = ProgressBar::new((0, 0), (100, 20)).with_eta();
+        bar.set_progress(20, 100);
+        let (start, _) = bar.start.unwrap();
+        assert_eq!(bar.eta_at(start + Duration::from_secs(10)), None);
+        bar.set_progress(40, 100);
+        assert_eq!(bar.eta_at(start + Duration::from_secs(10)), Some(30.0));
+        bar.set_progress(0, 100);
+        assert!(bar.start.unwrap().0 >= start);
+        assert_eq!(bar.eta(), None);
+        assert_eq!(format_duration(185.0), "3min 5s");
+        assert_eq!(format_duration(7300.0), "2h 1min");
+    }
+
+    #[test]
+    fn draw_widgets() {
+        let (w, h) = (200, 100);
+        let mut buffer = vec![0; 3 * w * h];
+        {
+            let root =
+                BitMapBackend::with_buffer(&mut buffer, (w as u32, h as u32)).into_drawing_area();
+            let mut bar = ProgressBar::new((0, 0), (200, 30)).with_color(&RED);
+            bar.set_progress(1, 2);
+            bar.draw(&root);
+
+            let mut gauge = Gauge::new("R0", (0, 40), (200, 30), (0.0, 4.0)).with_color(&BLUE);
+            gauge.set_value(3.0);
+            gauge.draw(&root);
+
+            let mut text = TextBox::new((0, 70), (200, 30));
+            text.set_text("Hello");
+            text.draw(&root);
+        }
+        let pixel = |x: usize, y: usize| &buffer[3 * (y * w + x)..3 * (y * w + x + 1)];
+        assert_eq!(pixel(5, 2), [255, 0, 0]);
+        assert_eq!(pixel(195, 2), [255, 255, 255]);
+        assert_eq!(pixel(140, 42), [0, 0, 255]);
+        assert_eq!(pixel(160, 42), [255, 255, 255]);
+    }
+
+    #[test]
+    fn size_legend() {
+        let legend = SizeLegend::new((10, 10), 100.0, 20.0).with_label("Size");
+        assert_eq!(legend.radius(100.0), 20.0);
+        assert_eq!(legend.radius(25.0), 10.0);
+        assert_eq!(legend.radius(-1.0), 0.0);
+        assert_eq!(legend.labels(), vec!["25", "50", "100"]);
+        let (w, h) = legend.size();
+        assert!(w > 40 && h > 40);
+
+        let mut buffer = vec![255; 3 * 200 * 100];
+        {
+            let root = BitMapBackend::with_buffer(&mut buffer, (200, 100)).into_drawing_area();
+            legend.with_color(&RED).draw(&root);
+        }
+        assert!(buffer.chunks(3).any(|p| p == [255, 0, 0]));
+    }
+}
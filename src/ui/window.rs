@@ -27,11 +27,105 @@
 //! }
 //! ```
 //!
+//! # Externally driven windows
+//!
+//! Applications that already own a main loop can use a window without its FPS gating,
+//! see [`WindowBuilder::with_external_loop()`](struct.WindowBuilder.html#method.with_external_loop).
+//! They hand over rendered pixels whenever they like with [`BufferWindow::present_region()`](struct.BufferWindow.html#method.present_region),
+//! and keep the window responsive with [`BufferWindow::update_events_only()`](struct.BufferWindow.html#method.update_events_only)
+//! in between.
+//!
+//! ```no_run
+//! use easy_graph::ui::window::WindowBuilder;
+//!
+//! let mut win = WindowBuilder::new()
+//!     .with_dimensions(600, 400)
+//!     .with_external_loop()
+//!     .build();
+//! let mut tile = vec![0_u8; 3 * 100 * 100];
+//! let mut frame = 0_u64;
+//! while win.is_open() {
+//!     // The application's own work, timing and rendering
+//!     frame += 1;
+//!     if frame % 1000 == 0 {
+//!         tile.iter_mut().for_each(|v| *v = v.wrapping_add(1));
+//!         win.present_region((250, 150), (100, 100), &tile);
+//!     } else {
+//!         win.update_events_only();
+//!     }
+//! }
+//! ```
+//!
 
-use minifb::{Scale, ScaleMode};
+use crate::color::ColorVisionDeficiency;
+use crate::ui::camera::{Camera, CameraBackend};
+use crate::ui::input::KeyEvent;
+use crate::ui::interpolate::Interpolator;
+use crate::ui::metadata::{ImageMetadata, MetadataFormat};
+use minifb::{Key, KeyRepeat, MouseButton, Scale, ScaleMode};
 use plotters::drawing::bitmap_pixel::RGBPixel;
 use plotters::prelude::*;
-use std::time::{Duration, SystemTime};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// The platform window used by [`BufferWindow`](struct.BufferWindow.html): `minifb::Window`,
+/// or [`GpuWindow`](../gpu/struct.GpuWindow.html) with feature `gpu`.
+#[cfg(not(feature = "gpu"))]
+pub type NativeWindow = minifb::Window;
+/// The platform window used by [`BufferWindow`](struct.BufferWindow.html): `minifb::Window`,
+/// or [`GpuWindow`](../gpu/struct.GpuWindow.html) with feature `gpu`.
+#[cfg(feature = "gpu")]
+pub type NativeWindow = crate::ui::gpu::GpuWindow;
+
+///
+/// Pixel format of the `u32` buffer passed to the platform window, named by channels from the most to the least significant byte.
+///
+/// Use a fixed format when a platform or backend shows swapped colors with the default.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// The format expected by the window backend. `Xrgb` for both `minifb` and the `gpu` backend.
+    /// The `gpu` backend decodes buffers in the format set on the window, so that all formats show correct colors there.
+    #[default]
+    Auto,
+    /// `0x00RRGGBB`, unused upper byte.
+    Xrgb,
+    /// `0xBBGGRRAA`, with opaque alpha.
+    Bgra,
+}
+
+impl PixelFormat {
+    /// Resolves `Auto` to the format expected by the window backend.
+    pub fn resolve(self) -> PixelFormat {
+        match self {
+            PixelFormat::Auto => PixelFormat::Xrgb,
+            format => format,
+        }
+    }
+    /// Packs RGB channels into a pixel of this format.
+    pub fn encode(self, r: u8, g: u8, b: u8) -> u32 {
+        let (r, g, b) = (r as u32, g as u32, b as u32);
+        match self.resolve() {
+            PixelFormat::Bgra => (b << 24) | (g << 16) | (r << 8) | 0xff,
+            _ => (r << 16) | (g << 8) | b,
+        }
+    }
+    /// Unpacks the RGB channels of a pixel in this format.
+    pub fn decode(self, pixel: u32) -> (u8, u8, u8) {
+        match self.resolve() {
+            PixelFormat::Bgra => ((pixel >> 8) as u8, (pixel >> 16) as u8, (pixel >> 24) as u8),
+            _ => ((pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8),
+        }
+    }
+}
+
+///
+/// Identifier of a menu item, see [`BufferWindow::add_menu()`](struct.BufferWindow.html#method.add_menu).
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MenuId(pub usize);
 
 ///
 /// Builder for [`BufferWindow`](struct.BufferWindow.html). See [`window`](index.html) module docs for an example.
@@ -43,6 +137,24 @@ pub struct WindowBuilder {
     max_fps: Option<f64>,
     max_fps_skip: Option<f64>,
     position: Option<(isize, isize)>,
+    screenshot_key: Option<Key>,
+    screenshot_dir: String,
+    frame_alpha: f64,
+    camera_keys: bool,
+    borderless: bool,
+    title_bar: bool,
+    pixel_format: PixelFormat,
+    key_broadcast: Option<Sender<KeyEvent>>,
+    external_loop: bool,
+    cvd_preview: Option<ColorVisionDeficiency>,
+    screenshot_metadata: MetadataFormat,
+    menus: Vec<(String, Vec<(String, MenuId)>)>,
+}
+
+impl Default for WindowBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl WindowBuilder {
@@ -55,6 +167,18 @@ impl WindowBuilder {
             max_fps: None,
             max_fps_skip: None,
             position: None,
+            screenshot_key: Some(Key::F12),
+            screenshot_dir: ".".to_string(),
+            frame_alpha: 1.0,
+            camera_keys: false,
+            borderless: false,
+            title_bar: true,
+            pixel_format: PixelFormat::Auto,
+            key_broadcast: None,
+            external_loop: false,
+            cvd_preview: None,
+            screenshot_metadata: MetadataFormat::None,
+            menus: Vec::new(),
         }
     }
     /// Sets the dimensions of the window in screen pixels.
@@ -120,19 +244,135 @@ impl WindowBuilder {
         self.position = Some(pos);
         self
     }
+    /// Sets the opacity (0 - 1) of new frames when composited onto the previous frame. Default: 1.0 (replace).
+    ///
+    /// Values below 1.0 make previous frames fade out gradually, e.g. for trails or accumulation effects.
+    /// Translucent plotters styles are blended onto the previous frame's content as well.
+    pub fn with_frame_alpha(mut self, alpha: f64) -> Self {
+        self.frame_alpha = alpha;
+        self
+    }
+    /// Previews the window's content as seen with a color vision deficiency. Default: `None`.
+    ///
+    /// See [`BufferWindow::set_cvd_preview()`](struct.BufferWindow.html#method.set_cvd_preview).
+    pub fn with_cvd_preview(mut self, cvd: Option<ColorVisionDeficiency>) -> Self {
+        self.cvd_preview = cvd;
+        self
+    }
+    /// Sets the hotkey for saving screenshots. Use `None` to disable. Default: `F12`.
+    ///
+    /// See [`BufferWindow::save_screenshot()`](struct.BufferWindow.html#method.save_screenshot).
+    /// Failures to save are reported by [`BufferWindow::take_error()`](struct.BufferWindow.html#method.take_error).
+    pub fn with_screenshot_key(mut self, key: Option<Key>) -> Self {
+        self.screenshot_key = key;
+        self
+    }
+    /// Sets the directory to save screenshots to. Default: the current working directory.
+    pub fn with_screenshot_dir(mut self, dir: &str) -> Self {
+        self.screenshot_dir = dir.to_string();
+        self
+    }
+    /// Writes metadata with screenshots, as a JSON sidecar and/or PNG text chunks. Default: `MetadataFormat::None`.
+    ///
+    /// See module [`metadata`](../metadata/index.html).
+    pub fn with_screenshot_metadata(mut self, format: MetadataFormat) -> Self {
+        self.screenshot_metadata = format;
+        self
+    }
+
+    /// Enables camera control by keyboard: arrow keys to pan, +/- to zoom, Home to reset.
+    ///
+    /// See [`BufferWindow::draw_camera()`](struct.BufferWindow.html#method.draw_camera).
+    pub fn with_camera_keys(mut self) -> Self {
+        self.camera_keys = true;
+        self
+    }
+
+    /// Creates the window without borders, e.g. for dashboards.
+    ///
+    /// Note: always-on-top windows and window icons are not supported by the windowing backend (minifb 0.15).
+    pub fn borderless(mut self) -> Self {
+        self.borderless = true;
+        self
+    }
+    /// Creates the window without title bar. Note that the window can't be moved by the user then.
+    pub fn without_title_bar(mut self) -> Self {
+        self.title_bar = false;
+        self
+    }
+    /// Sets the pixel format of the buffer passed to the platform window. Default: [`PixelFormat::Auto`](enum.PixelFormat.html).
+    pub fn with_pixel_format(mut self, format: PixelFormat) -> Self {
+        self.pixel_format = format;
+        self
+    }
+    /// Sends all key presses in the window to a shared channel, e.g. of an [`InputGroup`](../input/struct.InputGroup.html).
+    pub fn with_key_broadcast(mut self, sender: Sender<KeyEvent>) -> Self {
+        self.key_broadcast = Some(sender);
+        self
+    }
+    /// Prepares the window for an application's own main loop: disables the FPS limit and skip rate,
+    /// overriding [`with_fps_limit()`](#method.with_fps_limit) and [`with_fps_skip()`](#method.with_fps_skip).
+    /// See the [module docs](index.html#externally-driven-windows).
+    pub fn with_external_loop(mut self) -> Self {
+        self.external_loop = true;
+        self
+    }
+    /// Adds a menu with items given as pairs of label and id. See [`BufferWindow::add_menu()`](struct.BufferWindow.html#method.add_menu).
+    pub fn add_menu(mut self, name: &str, items: &[(&str, MenuId)]) -> Self {
+        let items = items
+            .iter()
+            .map(|(label, id)| (label.to_string(), *id))
+            .collect();
+        self.menus.push((name.to_string(), items));
+        self
+    }
 
     /// Builds the window.
-    pub fn build(self) -> BufferWindow {
-        let mut win = BufferWindow::new(
+    pub fn build(mut self) -> BufferWindow {
+        if self.external_loop {
+            self.max_fps = None;
+            self.max_fps_skip = None;
+        }
+        let options = minifb::WindowOptions {
+            borderless: self.borderless,
+            title: self.title_bar,
+            ..window_options(self.scale, true)
+        };
+        let win = BufferWindow::with_options(
             &self.title,
             self.dim,
             self.max_fps,
             self.max_fps_skip,
-            self.scale,
-            true,
+            options,
         );
+        self.configure(win)
+    }
+
+    /// Builds a headless window, which draws only to its buffer, without opening a platform window.
+    ///
+    /// E.g. for rendering into other applications or images. Headless windows have no input and no FPS limit.
+    pub fn build_headless(mut self) -> BufferWindow {
+        if self.external_loop {
+            self.max_fps_skip = None;
+        }
+        let win = BufferWindow::headless(&self.title, self.dim, self.max_fps_skip);
+        self.configure(win)
+    }
+
+    fn configure(self, mut win: BufferWindow) -> BufferWindow {
         if let Some(pos) = self.position {
-            win.window.set_position(pos.0, pos.1);
+            win.set_position(pos);
+        }
+        win.set_screenshot_key(self.screenshot_key);
+        win.set_screenshot_dir(&self.screenshot_dir);
+        win.set_screenshot_metadata(self.screenshot_metadata);
+        win.set_frame_alpha(self.frame_alpha);
+        win.set_camera_keys(self.camera_keys);
+        win.set_pixel_format(self.pixel_format);
+        win.set_key_broadcast(self.key_broadcast);
+        win.set_cvd_preview(self.cvd_preview);
+        for (name, items) in &self.menus {
+            win.add_menu(name, items);
         }
         win
     }
@@ -145,11 +385,30 @@ impl WindowBuilder {
 ///
 #[allow(dead_code)]
 pub struct BufferWindow {
-    window: minifb::Window,
+    window: Option<NativeWindow>,
     pub buffer_u8: Vec<u8>,
     buffer_u32: Vec<u32>,
     dim: (usize, usize),
     fps_skip: UpdateSkip,
+    update_rate: Option<Duration>,
+    close_requested: bool,
+    on_close: Option<Box<dyn FnMut()>>,
+    screenshot_key: Option<Key>,
+    screenshot_dir: PathBuf,
+    frame_alpha: u32,
+    camera: Camera,
+    camera_keys: bool,
+    title: String,
+    position: Option<(isize, isize)>,
+    present_times: (Duration, Duration),
+    pixel_format: PixelFormat,
+    key_broadcast: Option<Sender<KeyEvent>>,
+    cvd_preview: Option<ColorVisionDeficiency>,
+    screenshot_metadata: MetadataFormat,
+    image_metadata: Option<ImageMetadata>,
+    menu_pressed: Option<MenuId>,
+    on_menu: Option<Box<dyn FnMut(MenuId)>>,
+    error: Option<image::ImageError>,
 }
 
 impl BufferWindow {
@@ -161,34 +420,149 @@ impl BufferWindow {
         scale: Scale,
         resize: bool,
     ) -> Self {
-        let buffer_u8 = vec![0 as u8; (3 * dim.0 * dim.1) as usize];
-        let buffer_u32 = vec![0 as u32; (dim.0 * dim.1) as usize];
-        let mut opt = minifb::WindowOptions::default();
-        opt.scale = scale;
-        opt.resize = resize;
-        opt.scale_mode = ScaleMode::AspectRatioStretch;
-
-        let mut window = minifb::Window::new(title, dim.0, dim.1, opt).unwrap_or_else(|e| {
+        Self::with_options(title, dim, max_fps, fps_skip, window_options(scale, resize))
+    }
+
+    fn with_options(
+        title: &str,
+        dim: (usize, usize),
+        max_fps: Option<f64>,
+        fps_skip: Option<f64>,
+        opt: minifb::WindowOptions,
+    ) -> Self {
+        let mut window = NativeWindow::new(title, dim.0, dim.1, opt).unwrap_or_else(|e| {
             panic!("{}", e);
         });
-        window.limit_update_rate(match max_fps {
-            Some(fps) => Some(Duration::from_millis((1000.0 / fps) as u64)),
-            _ => None,
-        });
+        let update_rate = max_fps.map(|fps| Duration::from_millis((1000.0 / fps) as u64));
+        window.limit_update_rate(update_rate);
+        let mut win = Self::from_window(Some(window), title, dim, fps_skip);
+        win.update_rate = update_rate;
+        win
+    }
+
+    /// Creates a window without a platform window, drawing only to its buffer.
+    pub(crate) fn headless(title: &str, dim: (usize, usize), fps_skip: Option<f64>) -> Self {
+        Self::from_window(None, title, dim, fps_skip)
+    }
+
+    fn from_window(
+        window: Option<NativeWindow>,
+        title: &str,
+        dim: (usize, usize),
+        fps_skip: Option<f64>,
+    ) -> Self {
+        let buffer_u8 = vec![0_u8; 3 * dim.0 * dim.1];
+        let buffer_u32 = vec![0_u32; dim.0 * dim.1];
         BufferWindow {
             window,
             buffer_u8,
             buffer_u32,
             dim,
             fps_skip: UpdateSkip::from(
-                fps_skip.and_then(|fps| Some(Duration::from_millis((1000.0 / fps) as u64))),
+                fps_skip.map(|fps| Duration::from_millis((1000.0 / fps) as u64)),
             ),
+            update_rate: None,
+            close_requested: false,
+            on_close: None,
+            screenshot_key: Some(Key::F12),
+            screenshot_dir: PathBuf::from("."),
+            frame_alpha: 256,
+            camera: Camera::new(),
+            camera_keys: false,
+            title: title.to_string(),
+            position: None,
+            present_times: (Duration::default(), Duration::default()),
+            pixel_format: PixelFormat::Xrgb,
+            cvd_preview: None,
+            screenshot_metadata: MetadataFormat::None,
+            image_metadata: None,
+            key_broadcast: None,
+            menu_pressed: None,
+            on_menu: None,
+            error: None,
+        }
+    }
+
+    /// Replaces the FPS limit set by [`WindowBuilder::with_fps_limit()`](struct.WindowBuilder.html#method.with_fps_limit),
+    /// which blocks in updates, by skipping updates at the same rate. For windows sharing a thread,
+    /// where blocking in one window would stall all others.
+    pub(crate) fn skip_instead_of_limit(&mut self) {
+        if let Some(rate) = self.update_rate.take() {
+            if let Some(window) = &mut self.window {
+                window.limit_update_rate(None);
+            }
+            if self.fps_skip.target_rate.map_or(true, |skip| skip < rate) {
+                self.fps_skip.set_rate(Some(rate));
+            }
         }
     }
 
     /// Returns the underlying `minifb::Window`.
+    ///
+    /// Not available with feature `gpu`, see [`gpu_window()`](#method.gpu_window).
+    ///
+    /// # Panics
+    /// Panics for headless windows, see [`WindowBuilder::build_headless()`](struct.WindowBuilder.html#method.build_headless).
+    #[cfg(not(feature = "gpu"))]
     pub fn window(&mut self) -> &mut minifb::Window {
-        &mut self.window
+        self.window
+            .as_mut()
+            .expect("Headless windows have no platform window.")
+    }
+
+    /// Returns the underlying [`GpuWindow`](../gpu/struct.GpuWindow.html).
+    ///
+    /// Requires feature `gpu`, which replaces [`window()`](#method.window).
+    ///
+    /// # Panics
+    /// Panics for headless windows, see [`WindowBuilder::build_headless()`](struct.WindowBuilder.html#method.build_headless).
+    #[cfg(feature = "gpu")]
+    pub fn gpu_window(&mut self) -> &mut crate::ui::gpu::GpuWindow {
+        self.window
+            .as_mut()
+            .expect("Headless windows have no platform window.")
+    }
+
+    /// Returns if the window is headless, i.e. draws only to its buffer.
+    pub fn is_headless(&self) -> bool {
+        self.window.is_none()
+    }
+
+    /// Returns if a key was pressed since the last update. Always `false` for headless windows.
+    pub fn is_key_pressed(&self, key: Key, repeat: KeyRepeat) -> bool {
+        self.window
+            .as_ref()
+            .map(|win| win.is_key_pressed(key, repeat))
+            .unwrap_or(false)
+    }
+
+    /// The keys pressed since the last update. Always empty for headless windows.
+    pub fn keys_pressed(&self, repeat: KeyRepeat) -> Vec<Key> {
+        self.window
+            .as_ref()
+            .and_then(|win| win.get_keys_pressed(repeat))
+            .unwrap_or_default()
+    }
+
+    /// Returns if a mouse button is down. Always `false` for headless windows.
+    pub fn is_mouse_down(&self, button: MouseButton) -> bool {
+        self.window
+            .as_ref()
+            .map(|win| win.get_mouse_down(button))
+            .unwrap_or(false)
+    }
+
+    /// Sets the window's title.
+    pub fn set_title(&mut self, title: &str) {
+        if let Some(window) = &mut self.window {
+            window.set_title(title);
+        }
+        self.title = title.to_string();
+    }
+
+    /// Returns the window's title.
+    pub fn title(&self) -> &str {
+        &self.title
     }
 
     /// Returns the unscaled size of the window in pixels.
@@ -196,13 +570,62 @@ impl BufferWindow {
         self.dim
     }
 
+    /// Returns the size of the window on screen, in pixels. Differs from [`size()`](#method.size) for scaled windows.
+    pub fn screen_size(&self) -> (usize, usize) {
+        match &self.window {
+            Some(window) => window.get_size(),
+            None => self.dim,
+        }
+    }
+
+    /// The mouse position in window pixels, if the mouse is over the window.
+    pub fn mouse_pos(&self) -> Option<(i32, i32)> {
+        self.mouse_pos_with(minifb::MouseMode::Discard)
+    }
+
+    /// The mouse position in window pixels, handling positions outside the window according to `mode`.
+    pub(crate) fn mouse_pos_with(&self, mode: minifb::MouseMode) -> Option<(i32, i32)> {
+        self.window
+            .as_ref()?
+            .get_mouse_pos(mode)
+            .map(|(x, y)| (x as i32, y as i32))
+    }
+
+    /// The number of screen pixels per buffer pixel, due to the window's [`Scale`](../enum.Scale.html) or resizing.
+    pub fn scale_factor(&self) -> f64 {
+        let (width, height) = self.screen_size();
+        let factor = (width as f64 / self.dim.0 as f64).min(height as f64 / self.dim.1 as f64);
+        if factor > 0.0 {
+            factor
+        } else {
+            1.0
+        }
+    }
+
+    /// The dimensions of the window's buffer in pixels, independent of scaling and resizing.
+    pub fn dimensions(&self) -> (usize, usize) {
+        self.dim
+    }
+
     /// Sets the position of the window's upper left corner in screen pixels.
     /// Preferably use method `with_position()` in [WindowBuilder](struct.WindowBuilder.html).
     pub fn set_position(&mut self, pos: (isize, isize)) {
-        self.window.set_position(pos.0, pos.1);
+        if let Some(window) = &mut self.window {
+            window.set_position(pos.0, pos.1);
+        }
+        self.position = Some(pos);
+    }
+
+    /// Returns the position last set by [`set_position()`](#method.set_position) or the builder, if any.
+    ///
+    /// Windows moved by the user are not tracked, as the windowing backend does not report positions.
+    pub fn position(&self) -> Option<(isize, isize)> {
+        self.position
     }
 
     /// Draws the window's content given a drawing closure.
+    ///
+    /// Returns `false` if drawing was skipped, due to the FPS skip rate or a closed window.
     /// ```
     ///# use easy_graph::ui::window::WindowBuilder;
     ///# use easy_graph::ui::drawing::IntoDrawingArea;
@@ -215,11 +638,11 @@ impl BufferWindow {
     ///     root.draw(&Circle::new((50, 50), 10, &BLACK)).unwrap();
     /// });
     /// ```
-    pub fn draw<F>(&mut self, draw: F)
+    pub fn draw<F>(&mut self, draw: F) -> bool
     where
-        F: FnOnce(BitMapBackend<RGBPixel>) -> (),
+        F: FnOnce(BitMapBackend<RGBPixel>),
     {
-        if self.window.is_open() && self.fps_skip.update() {
+        if self.is_open() && self.fps_skip.update() {
             {
                 let b = BitMapBackend::with_buffer(
                     &mut self.buffer_u8,
@@ -227,15 +650,342 @@ impl BufferWindow {
                 );
                 draw(b);
             }
-            self.transfer_buffer();
-            self.window
-                .update_with_buffer(&self.buffer_u32[..], self.dim.0, self.dim.1)
-                .unwrap();
+            self.present_buffer();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Draws the window's content given a closure writing to the raw RGB buffer, with the buffer's dimensions.
+    /// Skipped like [`draw()`](#method.draw).
+    pub(crate) fn draw_rgb<F>(&mut self, draw: F) -> bool
+    where
+        F: FnOnce(&mut [u8], (u32, u32)),
+    {
+        if self.is_open() && self.fps_skip.update() {
+            draw(&mut self.buffer_u8, (self.dim.0 as u32, self.dim.1 as u32));
+            self.present_buffer();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Draws the window's content given a drawing closure, like [`draw()`](#method.draw),
+    /// with the previous and latest state of an [`Interpolator`](../interpolate/struct.Interpolator.html),
+    /// and the interpolation factor between them.
+    ///
+    /// Does not draw if the interpolator has no states. See [`interpolate`](../interpolate/index.html) module docs for an example.
+    pub fn draw_interpolated<S, F>(&mut self, states: &Interpolator<S>, draw: F) -> bool
+    where
+        F: FnOnce(BitMapBackend<RGBPixel>, &S, &S, f64),
+    {
+        match states.states() {
+            Some((previous, current, alpha)) => self.draw(|b| draw(b, previous, current, alpha)),
+            None => false,
+        }
+    }
+
+    /// Draws the window's content given a drawing closure, like [`draw()`](#method.draw),
+    /// but with all coordinates transformed by the window's [`Camera`](../camera/struct.Camera.html).
+    ///
+    /// See [`camera`](../camera/index.html) module docs for an example.
+    pub fn draw_camera<F>(&mut self, draw: F) -> bool
+    where
+        F: FnOnce(CameraBackend<BitMapBackend<RGBPixel>>),
+    {
+        let camera = self.camera;
+        self.draw(|b| draw(CameraBackend::new(b, camera)))
+    }
+
+    /// The window's camera, used by [`draw_camera()`](#method.draw_camera).
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    /// The window's camera, for changing it programmatically.
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        &mut self.camera
+    }
+
+    /// Enables or disables camera control by keyboard.
+    /// Preferably use method `with_camera_keys()` in [WindowBuilder](struct.WindowBuilder.html).
+    pub fn set_camera_keys(&mut self, enabled: bool) {
+        self.camera_keys = enabled;
+    }
+
+    /// Processes window events without presenting a new frame: input, close requests and hotkeys.
+    ///
+    /// Not subject to the FPS skip rate. Call this from an application's own main loop on iterations
+    /// without a new frame, to keep the window responsive. See the [module docs](index.html#externally-driven-windows).
+    pub fn update_events_only(&mut self) {
+        if self.is_open() {
+            if let Some(window) = &mut self.window {
+                window.update();
+            }
+            self.handle_events();
+        }
+    }
+
+    /// Runs a computation on a worker thread and keeps the window responsive (movable, closeable)
+    /// by processing its events on the calling thread until the computation finishes.
+    /// Returns the computation's result.
+    ///
+    /// Use this for heavy compute phases between frames, to prevent the OS from reporting the window as not responding.
+    /// Windows can't process their events on a background thread themselves, as they are bound to the thread that created them.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use easy_graph::ui::window::WindowBuilder;
+    ///
+    /// let mut win = WindowBuilder::new().build();
+    /// let mut state = vec![0.0; 1000];
+    /// let sum = win.run_responsive(|| {
+    ///     for _ in 0..1000 {
+    ///         state.iter_mut().for_each(|v| *v += 1.0); // heavy work
+    ///     }
+    ///     state.iter().sum::<f64>()
+    /// });
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if the computation panics.
+    pub fn run_responsive<F, R>(&mut self, compute: F) -> R
+    where
+        F: FnOnce() -> R + Send,
+        R: Send,
+    {
+        pump_while(compute, || self.update_events_only())
+    }
+
+    /// Copies RGB pixels into a rectangular region of the window's buffer, and presents the buffer.
+    ///
+    /// Not subject to the FPS skip rate, for applications with their own main loop that render parts of the frame themselves.
+    /// `rgb` contains the region's pixels row by row, and is clipped to the window.
+    /// See the [module docs](index.html#externally-driven-windows).
+    ///
+    /// # Panics
+    /// Panics if the length of `rgb` does not equal 3 * width * height.
+    pub fn present_region(&mut self, pos: (usize, usize), size: (usize, usize), rgb: &[u8]) {
+        assert_eq!(
+            rgb.len(),
+            3 * size.0 * size.1,
+            "Length of RGB buffer must equal 3 * width * height!"
+        );
+        let width = size.0.min(self.dim.0.saturating_sub(pos.0));
+        let height = size.1.min(self.dim.1.saturating_sub(pos.1));
+        for row in 0..height {
+            let src = 3 * row * size.0;
+            let dst = 3 * ((pos.1 + row) * self.dim.0 + pos.0);
+            self.buffer_u8[dst..dst + 3 * width].copy_from_slice(&rgb[src..src + 3 * width]);
+        }
+        if self.is_open() {
+            self.present_buffer();
+        }
+    }
+
+    /// Presents a completely rendered RGB buffer, e.g. from a render thread.
+    /// Returns the previous buffer for re-use.
+    pub(crate) fn present(&mut self, buffer: Vec<u8>) -> Vec<u8> {
+        let old = std::mem::replace(&mut self.buffer_u8, buffer);
+        if self.is_open() {
+            self.present_buffer();
         }
+        old
     }
+
+    /// Handles window events after an update: close requests and hotkeys.
+    fn handle_events(&mut self) {
+        if self.window.as_ref().map(|win| !win.is_open()) == Some(true) {
+            self.request_close();
+            return;
+        }
+        if self.key_broadcast.is_some() {
+            let keys = self.keys_pressed(KeyRepeat::No);
+            self.broadcast_keys(&keys);
+        }
+        if let Some(key) = self.screenshot_key {
+            if self.is_key_pressed(key, KeyRepeat::No) {
+                if let Err(err) = self.save_screenshot() {
+                    self.error = Some(err);
+                }
+            }
+        }
+        if self.camera_keys {
+            self.handle_camera_keys();
+        }
+        if let Some(id) = self.native_menu_pressed() {
+            self.menu_pressed = Some(id);
+            if let Some(callback) = &mut self.on_menu {
+                callback(id);
+            }
+        }
+    }
+
+    /// The menu item selected in the platform window since the last update, if any.
+    #[cfg(not(feature = "gpu"))]
+    fn native_menu_pressed(&mut self) -> Option<MenuId> {
+        self.window
+            .as_mut()
+            .and_then(|window| window.is_menu_pressed())
+            .map(MenuId)
+    }
+    /// Menus are not supported by the `gpu` backend.
+    #[cfg(feature = "gpu")]
+    fn native_menu_pressed(&mut self) -> Option<MenuId> {
+        None
+    }
+
+    /// Sends key presses to the broadcast channel, if any. Stops broadcasting if the receiver was dropped.
+    fn broadcast_keys(&mut self, keys: &[Key]) {
+        if let Some(sender) = &self.key_broadcast {
+            for key in keys {
+                let event = KeyEvent {
+                    window: self.title.clone(),
+                    key: *key,
+                };
+                if sender.send(event).is_err() {
+                    self.key_broadcast = None;
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Pans and zooms the camera by keyboard.
+    fn handle_camera_keys(&mut self) {
+        let pressed = |key| self.is_key_pressed(key, KeyRepeat::Yes);
+        let (mut dx, mut dy) = (0.0, 0.0);
+        if pressed(Key::Left) {
+            dx -= CAMERA_PAN_STEP;
+        }
+        if pressed(Key::Right) {
+            dx += CAMERA_PAN_STEP;
+        }
+        if pressed(Key::Up) {
+            dy -= CAMERA_PAN_STEP;
+        }
+        if pressed(Key::Down) {
+            dy += CAMERA_PAN_STEP;
+        }
+        let mut zoom = 1.0;
+        if pressed(Key::Equal) || pressed(Key::NumPadPlus) {
+            zoom *= CAMERA_ZOOM_STEP;
+        }
+        if pressed(Key::Minus) || pressed(Key::NumPadMinus) {
+            zoom /= CAMERA_ZOOM_STEP;
+        }
+        let reset = pressed(Key::Home);
+        let center = (self.dim.0 as f64 / 2.0, self.dim.1 as f64 / 2.0);
+        if reset {
+            self.camera = Camera::new();
+        }
+        self.camera.pan(dx, dy);
+        self.camera.zoom_at(zoom, center);
+    }
+
+    /// Returns if the current frame should be skipped to achieve the FPS skip rate.
+    /// Counts as a drawn frame if it returns `false`.
+    pub(crate) fn skip_frame(&mut self) -> bool {
+        !self.fps_skip.update()
+    }
+
     /// Returns if the window is open.
+    ///
+    /// Returns `false` after the user closed the window, or after [`request_close()`](#method.request_close) was called.
     pub fn is_open(&self) -> bool {
-        self.window.is_open()
+        !self.close_requested && self.window.as_ref().map(|win| win.is_open()) != Some(false)
+    }
+
+    /// Returns if closing the window was handled: after [`request_close()`](#method.request_close) was called,
+    /// or after an update detected that the user closed the window.
+    ///
+    /// In contrast to `!is_open()`, this does not react to the native window's state before the close was handled,
+    /// so the close callback (see [`on_close()`](#method.on_close)) has run when this returns `true`.
+    /// Can be used to e.g. checkpoint and exit a simulation.
+    pub fn close_requested(&self) -> bool {
+        self.close_requested
+    }
+
+    /// Requests the window to close. The window stops drawing, and the close callback is invoked (once).
+    pub fn request_close(&mut self) {
+        if !self.close_requested {
+            self.close_requested = true;
+            if let Some(callback) = &mut self.on_close {
+                callback();
+            }
+        }
+    }
+
+    /// Sets a callback that is invoked once when the window is closed by the user or by [`request_close()`](#method.request_close).
+    ///
+    /// Closing by the user is detected on the next call to [`draw()`](#method.draw).
+    /// ```
+    ///# use easy_graph::ui::window::WindowBuilder;
+    /// let mut win = WindowBuilder::new().build();
+    /// win.on_close(|| println!("Window closed, saving checkpoint..."));
+    /// ```
+    pub fn on_close<F>(&mut self, callback: F)
+    where
+        F: FnMut() + 'static,
+    {
+        self.on_close = Some(Box::new(callback));
+    }
+
+    /// Adds a menu to the window's native menu bar, with items given as pairs of label and id.
+    ///
+    /// Selected items are reported by [`poll_menu()`](#method.poll_menu), and passed to the callback set by [`on_menu()`](#method.on_menu).
+    /// Ignored for headless windows, and with feature `gpu`.
+    /// ```no_run
+    ///# use easy_graph::ui::window::{MenuId, WindowBuilder};
+    /// const SAVE_PNG: MenuId = MenuId(1);
+    ///
+    /// let mut win = WindowBuilder::new().build();
+    /// win.add_menu("File", &[("Save PNG", SAVE_PNG)]);
+    /// while win.is_open() {
+    ///     win.update_events_only();
+    ///     if win.poll_menu() == Some(SAVE_PNG) {
+    ///         win.save_buffer("frame.png").unwrap();
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if the platform fails to create the menu.
+    pub fn add_menu<S: AsRef<str>>(&mut self, name: &str, items: &[(S, MenuId)]) {
+        #[cfg(not(feature = "gpu"))]
+        {
+            if let Some(window) = &mut self.window {
+                let mut menu = minifb::Menu::new(name).unwrap_or_else(|e| {
+                    panic!("{}", e);
+                });
+                for (label, id) in items {
+                    menu.add_item(label.as_ref(), id.0).build();
+                }
+                window.add_menu(&menu);
+            }
+        }
+        #[cfg(feature = "gpu")]
+        {
+            let _ = (name, items);
+        }
+    }
+
+    /// Returns the menu item selected since the last call, if any.
+    /// Menu selections are detected on window updates, like key presses.
+    pub fn poll_menu(&mut self) -> Option<MenuId> {
+        self.menu_pressed.take()
+    }
+
+    /// Sets a callback that is invoked with the id of each selected menu item, see [`add_menu()`](#method.add_menu).
+    ///
+    /// Selections are still reported by [`poll_menu()`](#method.poll_menu).
+    pub fn on_menu<F>(&mut self, callback: F)
+    where
+        F: FnMut(MenuId) + 'static,
+    {
+        self.on_menu = Some(Box::new(callback));
     }
 
     /// Saves the current buffer to a file at the path specified.
@@ -250,22 +1000,226 @@ impl BufferWindow {
         )
     }
 
-    fn transfer_buffer(&mut self) {
-        for (inp, outp) in self.buffer_u8.chunks(3).zip(&mut self.buffer_u32) {
-            *outp = Self::from_u8arr_rgb(inp);
+    /// Sets the hotkey for saving screenshots. Use `None` to disable.
+    /// Preferably use method `with_screenshot_key()` in [WindowBuilder](struct.WindowBuilder.html).
+    pub fn set_screenshot_key(&mut self, key: Option<Key>) {
+        self.screenshot_key = key;
+    }
+
+    /// Sets the directory to save screenshots to.
+    /// Preferably use method `with_screenshot_dir()` in [WindowBuilder](struct.WindowBuilder.html).
+    pub fn set_screenshot_dir(&mut self, dir: &str) {
+        self.screenshot_dir = PathBuf::from(dir);
+    }
+
+    /// Sets the metadata format written with screenshots.
+    /// Preferably use method `with_screenshot_metadata()` in [WindowBuilder](struct.WindowBuilder.html).
+    pub fn set_screenshot_metadata(&mut self, format: MetadataFormat) {
+        self.screenshot_metadata = format;
+    }
+    /// The metadata format written with screenshots.
+    pub fn screenshot_metadata(&self) -> MetadataFormat {
+        self.screenshot_metadata
+    }
+    /// Sets the metadata written with screenshots. If `None`, only the window title is written.
+    ///
+    /// Charts update their window's metadata automatically.
+    pub fn set_image_metadata(&mut self, metadata: Option<ImageMetadata>) {
+        self.image_metadata = metadata;
+    }
+
+    /// Saves the current buffer to a file, with metadata in the given format. See module [`metadata`](../metadata/index.html).
+    pub fn save_buffer_with_metadata<P: AsRef<Path>>(
+        &self,
+        path: P,
+        metadata: &ImageMetadata,
+        format: MetadataFormat,
+    ) -> Result<(), image::ImageError> {
+        metadata.save_image(
+            path,
+            &self.buffer_u8,
+            (self.dim.0 as u32, self.dim.1 as u32),
+            format,
+        )
+    }
+
+    /// Saves the current buffer to a PNG file named by the current time (`screenshot_<unix millis>.png`) in the screenshot directory.
+    /// Returns the path of the saved file. Writes metadata if enabled via
+    /// [`WindowBuilder::with_screenshot_metadata()`](struct.WindowBuilder.html#method.with_screenshot_metadata).
+    ///
+    /// Called when the screenshot hotkey is pressed (see [`WindowBuilder::with_screenshot_key()`](struct.WindowBuilder.html#method.with_screenshot_key)).
+    /// Errors in that case are reported by [`take_error()`](#method.take_error).
+    pub fn save_screenshot(&self) -> Result<PathBuf, image::ImageError> {
+        let path = self.screenshot_path("screenshot", "png");
+        std::fs::create_dir_all(&self.screenshot_dir)?;
+        if self.screenshot_metadata == MetadataFormat::None {
+            self.save_buffer(&path.to_string_lossy())?;
+        } else {
+            let mut metadata = self
+                .image_metadata
+                .clone()
+                .unwrap_or_else(|| ImageMetadata::new(&self.title));
+            metadata.touch();
+            self.save_buffer_with_metadata(&path, &metadata, self.screenshot_metadata)?;
         }
+        Ok(path)
     }
-    fn from_u8arr_rgb(rgb: &[u8]) -> u32 {
-        let (r, g, b) = (rgb[0] as u32, rgb[1] as u32, rgb[2] as u32);
-        (r << 16) | (g << 8) | b
+
+    /// Returns and clears the last error of saving a screenshot by the hotkey.
+    pub fn take_error(&mut self) -> Option<image::ImageError> {
+        self.error.take()
     }
-    #[allow(dead_code)]
-    fn from_u8_rgb(r: u8, g: u8, b: u8) -> u32 {
-        let (r, g, b) = (r as u32, g as u32, b as u32);
-        (r << 16) | (g << 8) | b
+
+    /// A timestamped path in the screenshot directory, e.g. `screenshot_1589808080000.png`.
+    pub(crate) fn screenshot_path(&self, prefix: &str, extension: &str) -> PathBuf {
+        let millis = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("System clock was before 1970.")
+            .as_millis();
+        self.screenshot_dir
+            .join(format!("{}_{}.{}", prefix, millis, extension))
+    }
+
+    /// Sets the opacity (0 - 1) of new frames when composited onto the previous frame. Default: 1.0.
+    /// Preferably use method `with_frame_alpha()` in [WindowBuilder](struct.WindowBuilder.html).
+    pub fn set_frame_alpha(&mut self, alpha: f64) {
+        self.frame_alpha = (alpha.clamp(0.0, 1.0) * 256.0).round() as u32;
+    }
+
+    /// Sets the pixel format of the buffer passed to the platform window. `Auto` is resolved immediately.
+    /// Preferably use method `with_pixel_format()` in [WindowBuilder](struct.WindowBuilder.html).
+    pub fn set_pixel_format(&mut self, format: PixelFormat) {
+        let (old, new) = (self.pixel_format, format.resolve());
+        #[cfg(feature = "gpu")]
+        if let Some(window) = &mut self.window {
+            window.set_pixel_format(new);
+        }
+        if old != new {
+            for pixel in &mut self.buffer_u32 {
+                let (r, g, b) = old.decode(*pixel);
+                *pixel = new.encode(r, g, b);
+            }
+            self.pixel_format = new;
+        }
+    }
+    /// The resolved pixel format of the buffer passed to the platform window.
+    pub fn pixel_format(&self) -> PixelFormat {
+        self.pixel_format
+    }
+
+    /// Sends all key presses in the window to a shared channel, e.g. of an [`InputGroup`](../input/struct.InputGroup.html).
+    /// Use `None` to stop broadcasting.
+    /// Preferably use method `with_key_broadcast()` in [WindowBuilder](struct.WindowBuilder.html).
+    pub fn set_key_broadcast(&mut self, sender: Option<Sender<KeyEvent>>) {
+        self.key_broadcast = sender;
+    }
+
+    /// Previews the window's content as seen with a color vision deficiency. Use `None` to show true colors.
+    ///
+    /// Only the displayed frames are affected. The window's buffer, and thus screenshots and saved images, keep the true colors.
+    /// With [`frame_alpha`](struct.WindowBuilder.html#method.with_frame_alpha) below 1.0, fading frames are approximated.
+    /// Preferably use method `with_cvd_preview()` in [WindowBuilder](struct.WindowBuilder.html).
+    pub fn set_cvd_preview(&mut self, cvd: Option<ColorVisionDeficiency>) {
+        self.cvd_preview = cvd;
+    }
+    /// The simulated color vision deficiency, if any.
+    pub fn cvd_preview(&self) -> Option<ColorVisionDeficiency> {
+        self.cvd_preview
+    }
+
+    /// Composites a full-size RGBA buffer onto the current frame, using per-pixel alpha, and displays the result.
+    ///
+    /// E.g. for translucent overlays from images created outside of plotters.
+    ///
+    /// # Panics
+    /// Panics if the length of `rgba` does not equal 4 * width * height of the window.
+    pub fn blend_rgba(&mut self, rgba: &[u8]) {
+        if rgba.len() != 4 * self.dim.0 * self.dim.1 {
+            panic!("Length of RGBA buffer must equal 4 * width * height of the window!");
+        }
+        if !self.is_open() {
+            return;
+        }
+        for (src, dst) in rgba.chunks(4).zip(self.buffer_u8.chunks_mut(3)) {
+            // Maps 0 - 255 to 0 - 256, so that alpha 0 is fully transparent and 255 fully opaque
+            let alpha = src[3] as u32 + (src[3] as u32 >> 7);
+            for i in 0..3 {
+                dst[i] = blend(dst[i], src[i], alpha);
+            }
+        }
+        self.present_buffer();
+    }
+
+    fn present_buffer(&mut self) {
+        let start = Instant::now();
+        self.transfer_buffer();
+        let transferred = Instant::now();
+        if let Some(window) = &mut self.window {
+            window
+                .update_with_buffer(&self.buffer_u32[..], self.dim.0, self.dim.1)
+                .unwrap();
+        }
+        self.handle_events();
+        self.present_times = (transferred - start, transferred.elapsed());
+    }
+
+    /// Time spent on converting and presenting the latest frame's buffer.
+    #[cfg(feature = "profiling")]
+    pub(crate) fn present_times(&self) -> (Duration, Duration) {
+        self.present_times
+    }
+
+    fn transfer_buffer(&mut self) {
+        let format = self.pixel_format;
+        if let Some(cvd) = self.cvd_preview {
+            let alpha = self.frame_alpha.min(256);
+            // Charts use few distinct colors, so caching the previous pixel avoids most conversions.
+            let mut cache = ((0, 0, 0), cvd.simulate((0, 0, 0)));
+            for (inp, outp) in self.buffer_u8.chunks_mut(3).zip(&mut self.buffer_u32) {
+                if alpha < 256 {
+                    let (r, g, b) = format.decode(*outp);
+                    inp[0] = blend(r, inp[0], alpha);
+                    inp[1] = blend(g, inp[1], alpha);
+                    inp[2] = blend(b, inp[2], alpha);
+                }
+                let rgb = (inp[0], inp[1], inp[2]);
+                if rgb != cache.0 {
+                    cache = (rgb, cvd.simulate(rgb));
+                }
+                let (r, g, b) = cache.1;
+                *outp = format.encode(r, g, b);
+            }
+        } else if self.frame_alpha >= 256 {
+            for (inp, outp) in self.buffer_u8.chunks(3).zip(&mut self.buffer_u32) {
+                *outp = format.encode(inp[0], inp[1], inp[2]);
+            }
+        } else {
+            let alpha = self.frame_alpha;
+            for (inp, outp) in self.buffer_u8.chunks_mut(3).zip(&mut self.buffer_u32) {
+                let (r, g, b) = format.decode(*outp);
+                inp[0] = blend(r, inp[0], alpha);
+                inp[1] = blend(g, inp[1], alpha);
+                inp[2] = blend(b, inp[2], alpha);
+                *outp = format.encode(inp[0], inp[1], inp[2]);
+            }
+        }
     }
 }
 
+/// Converts an image error to an I/O error, unwrapping I/O errors.
+pub(crate) fn into_io_error(err: image::ImageError) -> std::io::Error {
+    match err {
+        image::ImageError::IoError(err) => err,
+        err => std::io::Error::other(err.to_string()),
+    }
+}
+
+/// Blends a source onto a destination channel value, with alpha in range 0 - 256.
+#[inline]
+fn blend(dst: u8, src: u8, alpha: u32) -> u8 {
+    ((src as u32 * alpha + dst as u32 * (256 - alpha)) >> 8) as u8
+}
+
 struct UpdateSkip {
     target_rate: Option<Duration>,
     prev_time: f64,
@@ -316,12 +1270,65 @@ impl UpdateSkip {
     }
 }
 
+/// Default options for windows with the given scale.
+fn window_options(scale: Scale, resize: bool) -> minifb::WindowOptions {
+    minifb::WindowOptions {
+        scale,
+        resize,
+        scale_mode: ScaleMode::AspectRatioStretch,
+        ..minifb::WindowOptions::default()
+    }
+}
+
+/// Camera panning per key press, in window pixels.
+const CAMERA_PAN_STEP: f64 = 20.0;
+/// Camera zoom factor per key press.
+const CAMERA_ZOOM_STEP: f64 = 1.25;
+
+/// Interval for processing window events during [`BufferWindow::run_responsive()`](struct.BufferWindow.html#method.run_responsive).
+pub(crate) const EVENT_PUMP_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Runs `compute` on a scoped worker thread, calling `pump` at regular intervals until it finishes.
+pub(crate) fn pump_while<F, R, P>(compute: F, mut pump: P) -> R
+where
+    F: FnOnce() -> R + Send,
+    R: Send,
+    P: FnMut(),
+{
+    thread::scope(|scope| {
+        let (sender, receiver) = channel();
+        let handle = scope.spawn(move || {
+            let result = compute();
+            let _ = sender.send(());
+            result
+        });
+        while let Err(RecvTimeoutError::Timeout) = receiver.recv_timeout(EVENT_PUMP_INTERVAL) {
+            pump();
+        }
+        handle
+            .join()
+            .unwrap_or_else(|err| std::panic::resume_unwind(err))
+    })
+}
+
 //#[cfg(test)]
 #[allow(unused_imports)]
 mod test {
-    use crate::ui::window::BufferWindow;
+    use crate::color::ColorVisionDeficiency;
+    use crate::ui::input::InputGroup;
+    use crate::ui::interpolate::Interpolator;
+    use crate::ui::window::{blend, pump_while, BufferWindow, PixelFormat, WindowBuilder};
+    use minifb::Key;
     use plotters::prelude::*;
 
+    #[test]
+    fn blend_test() {
+        assert_eq!(blend(0, 255, 256), 255);
+        assert_eq!(blend(0, 255, 0), 0);
+        assert_eq!(blend(0, 200, 128), 100);
+        assert_eq!(blend(100, 200, 128), 150);
+    }
+
     #[test]
     fn buffer_test() {
         let mut win = BufferWindow::new("Test", (100, 100), None, None, minifb::Scale::X1, true);
@@ -333,4 +1340,136 @@ mod test {
             });
         }
     }
+
+    #[test]
+    fn blend_rgba() {
+        let mut win = WindowBuilder::new().with_dimensions(3, 1).build_headless();
+        win.buffer_u8 = vec![100; 9];
+        win.blend_rgba(&[200, 0, 50, 0, 200, 0, 50, 255, 200, 0, 50, 128]);
+        assert_eq!(&win.buffer_u8[..3], &[100, 100, 100]);
+        assert_eq!(&win.buffer_u8[3..6], &[200, 0, 50]);
+        assert_eq!(&win.buffer_u8[6..], &[150, 49, 74]);
+    }
+
+    #[test]
+    fn close_requested() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let mut win = WindowBuilder::new().build_headless();
+        let closed = Rc::new(Cell::new(0));
+        let counter = closed.clone();
+        win.on_close(move || counter.set(counter.get() + 1));
+        assert!(win.is_open() && !win.close_requested());
+        win.request_close();
+        win.request_close();
+        assert!(!win.is_open() && win.close_requested());
+        assert_eq!(closed.get(), 1);
+    }
+
+    #[test]
+    fn pump_events() {
+        let mut pumped = 0;
+        let data = [1, 2, 3];
+        let sum = pump_while(
+            || {
+                std::thread::sleep(std::time::Duration::from_millis(100));
+                data.iter().sum::<i32>()
+            },
+            || pumped += 1,
+        );
+        assert_eq!(sum, 6);
+        assert!(pumped >= 2);
+    }
+
+    #[test]
+    fn draw_interpolated() {
+        let mut win = WindowBuilder::new()
+            .with_dimensions(20, 10)
+            .build_headless();
+        let mut states = Interpolator::new();
+        assert!(!win.draw_interpolated(&states, |_, _, _, _| panic!()));
+        states.push(1);
+        states.push(2);
+        let mut drawn = None;
+        assert!(win.draw_interpolated(&states, |_, prev, curr, alpha| {
+            assert!((0.0..=1.0).contains(&alpha));
+            drawn = Some((*prev, *curr));
+        }));
+        assert_eq!(drawn, Some((1, 2)));
+    }
+
+    #[test]
+    fn pixel_format() {
+        assert_eq!(PixelFormat::Auto.encode(1, 2, 3), 0x00010203);
+        assert_eq!(PixelFormat::Bgra.encode(1, 2, 3), 0x030201ff);
+        assert_eq!(PixelFormat::Bgra.decode(0x030201ff), (1, 2, 3));
+
+        let mut win = WindowBuilder::new()
+            .with_dimensions(2, 2)
+            .with_pixel_format(PixelFormat::Bgra)
+            .build_headless();
+        assert_eq!(win.pixel_format(), PixelFormat::Bgra);
+        win.draw(|b| {
+            b.into_drawing_area().fill(&RED).unwrap();
+        });
+        assert_eq!(win.buffer_u32[0], 0x0000ffff);
+        win.set_pixel_format(PixelFormat::Auto);
+        assert_eq!(win.pixel_format(), PixelFormat::Xrgb);
+        assert_eq!(win.buffer_u32[0], 0x00ff0000);
+    }
+
+    #[test]
+    fn key_broadcast() {
+        let mut input = InputGroup::new();
+        let mut win = WindowBuilder::new()
+            .with_title("A")
+            .with_key_broadcast(input.sender())
+            .build_headless();
+        win.broadcast_keys(&[Key::Space]);
+        let events = input.poll();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].window, "A");
+        assert!(input.is_paused());
+
+        drop(input);
+        win.broadcast_keys(&[Key::Space]);
+        assert!(win.key_broadcast.is_none());
+    }
+
+    #[test]
+    fn external_loop() {
+        let mut win = WindowBuilder::new()
+            .with_dimensions(4, 3)
+            .with_fps_skip(1.0)
+            .with_external_loop()
+            .build_headless();
+        assert!(win.draw(|_| {}));
+        assert!(win.draw(|_| {}));
+
+        win.present_region((2, 1), (3, 1), &[1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(&win.buffer_u8[3 * 6..3 * 8], &[1, 2, 3, 4, 5, 6]);
+        assert_eq!(&win.buffer_u8[3 * 8..3 * 9], &[0, 0, 0]);
+        win.update_events_only();
+        assert!(win.is_open());
+    }
+
+    #[test]
+    fn cvd_preview() {
+        let cvd = ColorVisionDeficiency::Deuteranopia;
+        let mut win = WindowBuilder::new()
+            .with_dimensions(2, 1)
+            .with_cvd_preview(Some(cvd))
+            .build_headless();
+        assert_eq!(win.cvd_preview(), Some(cvd));
+        win.present_region((0, 0), (2, 1), &[255, 0, 0, 0, 0, 255]);
+        assert_eq!(&win.buffer_u8[..], &[255, 0, 0, 0, 0, 255]);
+        let format = win.pixel_format();
+        assert_eq!(format.decode(win.buffer_u32[0]), cvd.simulate((255, 0, 0)));
+        assert_eq!(format.decode(win.buffer_u32[1]), cvd.simulate((0, 0, 255)));
+
+        win.set_cvd_preview(None);
+        win.present_region((0, 0), (1, 1), &[255, 0, 0]);
+        assert_eq!(format.decode(win.buffer_u32[0]), (255, 0, 0));
+    }
 }